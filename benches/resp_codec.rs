@@ -0,0 +1,107 @@
+// RESP 编解码的吞吐基准，运行 `cargo bench --bench resp_codec`，可以用参数过滤基准名。
+// 构建环境中没有 criterion，这里用一个简单的计时循环：先预热，再在固定时间内尽量多地执行，
+// 输出每次迭代的平均耗时和吞吐。
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use bytes::BytesMut;
+use simple_redis::{
+    cmd::{Command, CommandExecutor},
+    Backend, BulkString, RespArray, RespDecoder, RespEncoder, RespFrame, SimpleString,
+};
+
+const WARMUP: Duration = Duration::from_millis(200);
+const MEASURE: Duration = Duration::from_secs(1);
+
+// 执行 routine 直到用完时间预算，返回 (迭代次数, 总耗时)
+fn run(routine: &mut impl FnMut()) -> (u64, Duration) {
+    let start = Instant::now();
+    let mut iters = 0;
+    while start.elapsed() < MEASURE {
+        // 每批执行多次再检查时间，减少 Instant::now 的开销
+        for _ in 0..64 {
+            routine();
+        }
+        iters += 64;
+    }
+    (iters, start.elapsed())
+}
+
+fn bench(filter: &[String], name: &str, bytes: usize, mut routine: impl FnMut()) {
+    if !filter.is_empty() && !filter.iter().any(|f| name.contains(f.as_str())) {
+        return;
+    }
+    let warmup = Instant::now();
+    while warmup.elapsed() < WARMUP {
+        routine();
+    }
+    let (iters, elapsed) = run(&mut routine);
+    let per_iter = elapsed.as_nanos() as f64 / iters as f64;
+    let throughput = bytes as f64 * iters as f64 / elapsed.as_secs_f64() / (1024.0 * 1024.0);
+    println!(
+        "{:<32} {:>12.1} ns/iter {:>10.1} MiB/s",
+        name, per_iter, throughput
+    );
+}
+
+// 解码基准：每次迭代从编码好的模板复制一份再解码，复制的开销相对解码很小
+fn bench_decode(filter: &[String], name: &str, frame: RespFrame) {
+    let template = BytesMut::from(&frame.encode()[..]);
+    let len = template.len();
+    bench(filter, name, len, || {
+        let mut buf = template.clone();
+        black_box(RespFrame::decode(black_box(&mut buf)).unwrap());
+    });
+}
+
+fn bench_encode(filter: &[String], name: &str, frame: RespFrame) {
+    let len = frame.clone().encode().len();
+    bench(filter, name, len, || {
+        black_box(black_box(frame.clone()).encode());
+    });
+}
+
+fn main() {
+    // cargo bench 会传入 --bench，其余参数作为过滤条件
+    let filter: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+
+    let simple: RespFrame = SimpleString::new("OK").into();
+    let bulk_1k: RespFrame = BulkString::new(vec![b'x'; 1024]).into();
+    let bulk_1m: RespFrame = BulkString::new(vec![b'x'; 1024 * 1024]).into();
+    let integers: RespFrame = RespArray::new(
+        (0..100)
+            .map(|i| RespFrame::Integer(i * 1000))
+            .collect::<Vec<_>>(),
+    )
+    .into();
+
+    bench_decode(&filter, "decode/simple_string", simple.clone());
+    bench_decode(&filter, "decode/bulk_string_1k", bulk_1k.clone());
+    bench_decode(&filter, "decode/bulk_string_1m", bulk_1m.clone());
+    bench_decode(&filter, "decode/array_100_integers", integers.clone());
+
+    bench_encode(&filter, "encode/simple_string", simple);
+    bench_encode(&filter, "encode/bulk_string_1k", bulk_1k);
+    bench_encode(&filter, "encode/bulk_string_1m", bulk_1m);
+    bench_encode(&filter, "encode/array_100_integers", integers);
+
+    // GET 命中缓存的完整流程：解码请求、执行命令、编码回复
+    let backend = Backend::new();
+    backend.set("key".to_string(), BulkString::new("value").into());
+    let request = RespArray::new(vec![
+        BulkString::new("GET").into(),
+        BulkString::new("key").into(),
+    ]);
+    let template = BytesMut::from(&RespFrame::from(request).encode()[..]);
+    bench(&filter, "roundtrip/get_hit", template.len(), || {
+        let mut buf = template.clone();
+        let frame = RespArray::decode(&mut buf).unwrap();
+        let cmd = Command::try_from(frame).unwrap();
+        black_box(cmd.execute(&backend).encode());
+    });
+}
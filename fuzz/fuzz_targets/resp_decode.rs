@@ -0,0 +1,50 @@
+// RESP 解码器的 fuzz target，运行 `cargo +nightly fuzz run resp_decode`。
+// 任意输入都不能让 decode panic；解码成功时，编码后再解码必须得到相同的帧。
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use simple_redis::{RespDecoder, RespEncoder, RespFrame};
+
+// NaN 不等于自身，逐层比较时把两个 NaN 视为相等
+fn frame_eq(a: &RespFrame, b: &RespFrame) -> bool {
+    let all_eq = |a: &[RespFrame], b: &[RespFrame]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| frame_eq(a, b))
+    };
+    match (a, b) {
+        (RespFrame::Double(a), RespFrame::Double(b)) => (a.is_nan() && b.is_nan()) || a == b,
+        (RespFrame::Array(a), RespFrame::Array(b)) => all_eq(a, b),
+        (RespFrame::Set(a), RespFrame::Set(b)) => all_eq(a, b),
+        (RespFrame::Push(a), RespFrame::Push(b)) => all_eq(a, b),
+        (RespFrame::Map(a), RespFrame::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|((ka, va), (kb, vb))| ka == kb && frame_eq(va, vb))
+        }
+        (a, b) => a == b,
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf = BytesMut::from(data);
+    let Ok(frame) = RespFrame::decode(&mut buf) else {
+        return;
+    };
+    let encoded = frame.clone().encode();
+    let mut buf = BytesMut::from(&encoded[..]);
+    let decoded = RespFrame::decode(&mut buf)
+        .unwrap_or_else(|e| panic!("failed to decode re-encoded {:?}: {}", frame, e));
+    assert!(
+        buf.is_empty(),
+        "{} trailing bytes after {:?}",
+        buf.len(),
+        frame
+    );
+    assert!(
+        frame_eq(&frame, &decoded),
+        "roundtrip mismatch: {:?} != {:?}",
+        frame,
+        decoded
+    );
+});
@@ -0,0 +1,445 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use thiserror::Error;
+
+use crate::{glob::glob_match, sha256::sha256_hex};
+
+use super::Backend;
+
+pub const DEFAULT_USER: &str = "default";
+
+// ACL 规则中可以使用的命令分类，和命令表中的 group 以及读写标记对应
+pub const ACL_CATEGORIES: &[&str] = &[
+    "all",
+    "read",
+    "write",
+    "keyspace",
+    "string",
+    "bitmap",
+    "hash",
+    "list",
+    "set",
+    "sortedset",
+    "stream",
+    "geo",
+    "connection",
+    "server",
+    "cluster",
+    "scripting",
+    "pubsub",
+    "admin",
+    "dangerous",
+];
+
+#[derive(Debug, Error, PartialEq)]
+pub enum AclError {
+    #[error("Error in ACL SETUSER modifier '{0}': Syntax error")]
+    Syntax(String),
+    #[error("Error in ACL SETUSER modifier '{0}': Unknown command or category name in ACL")]
+    UnknownCategory(String),
+}
+
+// 命令规则按顺序生效，后面的规则覆盖前面的
+#[derive(Debug, Clone, PartialEq)]
+enum CommandRule {
+    Category(String),
+    Command(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AclUser {
+    name: String,
+    enabled: bool,
+    // 设置后任意密码都可以登录
+    nopass: bool,
+    // 只保存密码的 SHA-256
+    passwords: BTreeSet<String>,
+    // (是否允许, 规则)，为空表示不能执行任何命令
+    commands: Vec<(bool, CommandRule)>,
+    key_patterns: Vec<String>,
+}
+
+impl AclUser {
+    // 新用户默认关闭，没有密码，不能执行任何命令，也不能访问任何 key
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            enabled: false,
+            nopass: false,
+            passwords: BTreeSet::new(),
+            commands: Vec::new(),
+            key_patterns: Vec::new(),
+        }
+    }
+
+    // default 用户可以执行所有命令，访问所有 key
+    fn default_user() -> Self {
+        Self {
+            enabled: true,
+            nopass: true,
+            commands: vec![(true, CommandRule::Category("all".to_string()))],
+            key_patterns: vec!["*".to_string()],
+            ..Self::new(DEFAULT_USER)
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_nopass(&self) -> bool {
+        self.nopass
+    }
+
+    pub fn password_hashes(&self) -> impl Iterator<Item = &str> {
+        self.passwords.iter().map(String::as_str)
+    }
+
+    // 依次应用 ACL SETUSER 的规则，任何一条出错时用户保持不变
+    pub fn apply_rules<S: AsRef<str>>(&mut self, rules: &[S]) -> Result<(), AclError> {
+        let mut user = self.clone();
+        for rule in rules {
+            user.apply_rule(rule.as_ref())?;
+        }
+        *self = user;
+        Ok(())
+    }
+
+    fn apply_rule(&mut self, rule: &str) -> Result<(), AclError> {
+        match rule.to_ascii_lowercase().as_str() {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            "resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            "allkeys" => self.key_patterns = vec!["*".to_string()],
+            "resetkeys" => self.key_patterns.clear(),
+            "allcommands" => return self.apply_rule("+@all"),
+            "nocommands" => return self.apply_rule("-@all"),
+            "reset" => {
+                *self = Self::new(std::mem::take(&mut self.name));
+            }
+            _ => return self.apply_value_rule(rule),
+        }
+        Ok(())
+    }
+
+    // 带参数的规则，密码和 key 模式区分大小写
+    fn apply_value_rule(&mut self, rule: &str) -> Result<(), AclError> {
+        let syntax = || AclError::Syntax(rule.to_string());
+        let (prefix, value) = rule.split_at(rule.chars().next().map_or(0, char::len_utf8));
+        match prefix {
+            ">" => {
+                self.nopass = false;
+                self.passwords.insert(sha256_hex(value.as_bytes()));
+            }
+            "<" => {
+                self.passwords.remove(&sha256_hex(value.as_bytes()));
+            }
+            "#" if is_sha256_hex(value) => {
+                self.nopass = false;
+                self.passwords.insert(value.to_ascii_lowercase());
+            }
+            "!" if is_sha256_hex(value) => {
+                self.passwords.remove(&value.to_ascii_lowercase());
+            }
+            "~" if !value.is_empty() => {
+                if !self.key_patterns.iter().any(|p| p == value) {
+                    self.key_patterns.push(value.to_string());
+                }
+            }
+            "+" | "-" if !value.is_empty() => {
+                let allow = prefix == "+";
+                let value = value.to_ascii_lowercase();
+                let rule = match value.strip_prefix('@') {
+                    Some(category) if ACL_CATEGORIES.contains(&category) => {
+                        CommandRule::Category(category.to_string())
+                    }
+                    Some(_) => return Err(AclError::UnknownCategory(rule.to_string())),
+                    None => CommandRule::Command(value),
+                };
+                // @all 覆盖之前所有的命令规则
+                if rule == CommandRule::Category("all".to_string()) {
+                    self.commands.clear();
+                }
+                self.commands.retain(|(_, r)| *r != rule);
+                self.commands.push((allow, rule));
+            }
+            _ => return Err(syntax()),
+        }
+        Ok(())
+    }
+
+    // categories 为命令所属的分类，由命令表提供
+    pub fn can_run(&self, command: &str, categories: &[&str]) -> bool {
+        self.commands
+            .iter()
+            .fold(false, |allowed, (allow, rule)| match rule {
+                CommandRule::Category(c) if c == "all" || categories.contains(&c.as_str()) => {
+                    *allow
+                }
+                CommandRule::Command(c) if c == command => *allow,
+                _ => allowed,
+            })
+    }
+
+    pub fn can_access(&self, key: &str) -> bool {
+        self.key_patterns
+            .iter()
+            .any(|p| glob_match(p.as_bytes(), key.as_bytes()))
+    }
+
+    // ACL GETUSER 中的 flags
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = vec![if self.enabled { "on" } else { "off" }];
+        if self.nopass {
+            flags.push("nopass");
+        }
+        flags
+    }
+
+    // 例如 "-@all +@read -keys"，没有以 @all 开头时补上 -@all
+    pub fn command_rules(&self) -> String {
+        let mut rules: Vec<String> = self
+            .commands
+            .iter()
+            .map(|(allow, rule)| {
+                let sign = if *allow { '+' } else { '-' };
+                match rule {
+                    CommandRule::Category(c) => format!("{}@{}", sign, c),
+                    CommandRule::Command(c) => format!("{}{}", sign, c),
+                }
+            })
+            .collect();
+        if !matches!(self.commands.first(), Some((_, CommandRule::Category(c))) if c == "all") {
+            rules.insert(0, "-@all".to_string());
+        }
+        rules.join(" ")
+    }
+
+    // 例如 "~cache:* ~user:*"
+    pub fn key_rules(&self) -> String {
+        self.key_patterns
+            .iter()
+            .map(|p| format!("~{}", p))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// ACL LIST 中的一行，格式和 ACL SETUSER 的规则一致
+impl fmt::Display for AclUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "user {}", self.name)?;
+        for flag in self.flags() {
+            write!(f, " {}", flag)?;
+        }
+        for hash in &self.passwords {
+            write!(f, " #{}", hash)?;
+        }
+        let keys = self.key_rules();
+        if !keys.is_empty() {
+            write!(f, " {}", keys)?;
+        }
+        write!(f, " {}", self.command_rules())
+    }
+}
+
+fn is_sha256_hex(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[derive(Debug)]
+pub struct AclUsers(BTreeMap<String, AclUser>);
+
+impl Default for AclUsers {
+    fn default() -> Self {
+        let default = AclUser::default_user();
+        Self(BTreeMap::from([(default.name.clone(), default)]))
+    }
+}
+
+impl Backend {
+    // 查找用户。requirepass 作为 default 用户的密码，设置后 default 用户不再是 nopass
+    pub fn acl_user(&self, name: &str) -> Option<AclUser> {
+        let mut user = self.acl_users().0.get(name).cloned()?;
+        if name == DEFAULT_USER {
+            if let Some(requirepass) = self.config().requirepass.as_deref() {
+                user.nopass = false;
+                user.passwords.insert(sha256_hex(requirepass.as_bytes()));
+            }
+        }
+        Some(user)
+    }
+
+    // 没有设置 requirepass 并且 default 用户是 nopass 时，新连接不需要认证
+    pub fn acl_default_nopass(&self) -> bool {
+        self.config().requirepass.is_none()
+            && self
+                .acl_users()
+                .0
+                .get(DEFAULT_USER)
+                .is_some_and(|user| user.enabled && user.nopass)
+    }
+
+    // 按用户名排序
+    pub fn acl_list(&self) -> Vec<AclUser> {
+        let names: Vec<String> = self.acl_users().0.keys().cloned().collect();
+        names
+            .iter()
+            .filter_map(|name| self.acl_user(name))
+            .collect()
+    }
+
+    // 用户不存在时先创建再应用规则
+    pub fn acl_setuser<S: AsRef<str>>(&self, name: &str, rules: &[S]) -> Result<(), AclError> {
+        let mut users = self.acl_users_mut();
+        let mut user = users
+            .0
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| AclUser::new(name));
+        user.apply_rules(rules)?;
+        users.0.insert(name.to_string(), user);
+        Ok(())
+    }
+
+    // 用户是否可以执行命令并访问其中所有的 key，用户不存在时拒绝
+    pub fn acl_check(
+        &self,
+        name: &str,
+        command: &str,
+        categories: &[&str],
+        keys: &[&str],
+    ) -> Result<(), AclDenied> {
+        let users = self.acl_users();
+        let Some(user) = users.0.get(name) else {
+            return Err(AclDenied::Command);
+        };
+        if !user.can_run(command, categories) {
+            return Err(AclDenied::Command);
+        }
+        if !keys.iter().all(|key| user.can_access(key)) {
+            return Err(AclDenied::Key);
+        }
+        Ok(())
+    }
+
+    fn acl_users(&self) -> RwLockReadGuard<'_, AclUsers> {
+        self.acl.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn acl_users_mut(&self) -> RwLockWriteGuard<'_, AclUsers> {
+        self.acl.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ACL 拒绝执行的原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AclDenied {
+    Command,
+    Key,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readonly_user() {
+        let backend = Backend::new();
+        backend
+            .acl_setuser("reader", &["on", ">pw", "~cache:*", "+@read"])
+            .unwrap();
+        let user = backend.acl_user("reader").unwrap();
+        assert_eq!(user.flags(), ["on"]);
+        assert_eq!(user.command_rules(), "-@all +@read");
+        assert_eq!(user.key_rules(), "~cache:*");
+        assert_eq!(
+            user.password_hashes().collect::<Vec<_>>(),
+            [sha256_hex(b"pw")]
+        );
+
+        let read = ["all", "read", "string"];
+        let write = ["all", "write", "string"];
+        assert_eq!(
+            backend.acl_check("reader", "get", &read, &["cache:1"]),
+            Ok(())
+        );
+        assert_eq!(
+            backend.acl_check("reader", "get", &read, &["other"]),
+            Err(AclDenied::Key)
+        );
+        assert_eq!(
+            backend.acl_check("reader", "set", &write, &["cache:1"]),
+            Err(AclDenied::Command)
+        );
+        assert_eq!(
+            backend.acl_check("nobody", "get", &read, &[]),
+            Err(AclDenied::Command)
+        );
+        assert_eq!(backend.acl_check("default", "set", &write, &["x"]), Ok(()));
+    }
+
+    #[test]
+    fn test_rules_apply_in_order() {
+        let mut user = AclUser::new("alice");
+        user.apply_rules(&["+@all", "-set", "+@write", "allkeys", "nopass", "on"])
+            .unwrap();
+        assert!(user.can_run("set", &["write"]));
+        assert_eq!(user.command_rules(), "+@all -set +@write");
+        assert_eq!(
+            user.to_string(),
+            "user alice on nopass ~* +@all -set +@write"
+        );
+
+        // -@all 覆盖之前的规则
+        user.apply_rules(&["-@all", "+get"]).unwrap();
+        assert_eq!(user.command_rules(), "-@all +get");
+        assert!(user.can_run("get", &["read"]));
+        assert!(!user.can_run("set", &["write"]));
+
+        // 出错时不修改用户
+        let before = user.clone();
+        assert_eq!(
+            user.apply_rules(&["off", "+@nosuch"]),
+            Err(AclError::UnknownCategory("+@nosuch".to_string()))
+        );
+        assert_eq!(
+            user.apply_rules(&["bogus"]),
+            Err(AclError::Syntax("bogus".to_string()))
+        );
+        assert_eq!(user, before);
+
+        user.apply_rules(&["reset"]).unwrap();
+        assert_eq!(user, AclUser::new("alice"));
+    }
+
+    #[test]
+    fn test_requirepass_is_default_password() {
+        let backend = Backend::with_config(crate::ServerConfig {
+            requirepass: Some("secret".to_string()),
+            ..Default::default()
+        });
+        let user = backend.acl_user(DEFAULT_USER).unwrap();
+        assert!(!user.is_nopass());
+        assert_eq!(
+            user.to_string(),
+            format!("user default on #{} ~* +@all", sha256_hex(b"secret"))
+        );
+        assert_eq!(backend.acl_list(), vec![user]);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{collections::HashMap, net::SocketAddr, sync::MutexGuard, time::Instant};
+
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+use tokio_util::sync::CancellationToken;
+
+use crate::RespFrame;
+
+use super::Backend;
+
+// 一个已连接的客户端：对端地址、用于关闭连接的 token 和推送消息的发送端
+#[derive(Debug, Clone)]
+pub struct ClientHandle {
+    pub addr: Option<SocketAddr>,
+    pub token: CancellationToken,
+    pub push: Sender<RespFrame>,
+}
+
+// 每个连接最多积压的推送帧数。客户端读得太慢导致积压超过上限时断开连接，
+// 相当于 Redis 对 pubsub 连接的 client-output-buffer-limit
+const PUSH_BUFFER_LIMIT: usize = 1024;
+
+// CLIENT PAUSE 暂缓的命令范围
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PauseMode {
+    // 只暂缓写命令
+    Write,
+    All,
+}
+
+// 当前生效的暂停：截止时间和范围
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientPause {
+    pub deadline: Instant,
+    pub mode: PauseMode,
+}
+
+impl Backend {
+    // 连接建立时注册，连接数已达到 maxclients 时返回 None。
+    // 返回的 token 被取消时连接处理循环退出，其它连接通过 push_to_client 发送的帧从返回的 receiver 中读取
+    pub fn register_client(
+        &self,
+        id: u64,
+        addr: Option<SocketAddr>,
+    ) -> Option<(CancellationToken, Receiver<RespFrame>)> {
+        let maxclients = self.config().maxclients;
+        let mut clients = self.clients();
+        if clients.len() >= maxclients {
+            return None;
+        }
+        let token = CancellationToken::new();
+        let (push, rx) = mpsc::channel(PUSH_BUFFER_LIMIT);
+        clients.insert(
+            id,
+            ClientHandle {
+                addr,
+                token: token.clone(),
+                push,
+            },
+        );
+        Some((token, rx))
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients().len()
+    }
+
+    // 向指定连接推送一帧，连接不存在或已经关闭时返回 false；积压超过上限时断开这个连接
+    pub fn push_to_client(&self, id: u64, frame: RespFrame) -> bool {
+        let clients = self.clients();
+        let Some(handle) = clients.get(&id) else {
+            return false;
+        };
+        match handle.push.try_send(frame) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                handle.token.cancel();
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    pub fn unregister_client(&self, id: u64) {
+        self.clients().remove(&id);
+        self.unsubscribe_all(id);
+    }
+
+    // 关闭满足 filter 的连接，返回关闭的数量
+    pub fn kill_clients(&self, filter: impl Fn(u64, &ClientHandle) -> bool) -> usize {
+        let clients = self.clients();
+        clients
+            .iter()
+            .filter(|(id, handle)| filter(**id, handle))
+            .inspect(|(_, handle)| handle.token.cancel())
+            .count()
+    }
+
+    // 在 deadline 之前暂缓执行命令，覆盖之前的暂停
+    pub fn client_pause(&self, deadline: Instant, mode: PauseMode) {
+        self.pause
+            .send_replace(Some(ClientPause { deadline, mode }));
+    }
+
+    // 立即解除暂停，正在等待的命令会被唤醒
+    pub fn client_unpause(&self) {
+        self.pause.send_replace(None);
+    }
+
+    // 暂停期间等待直到暂停结束或被解除，write 表示命令是否为写命令
+    pub async fn wait_if_paused(&self, write: bool) {
+        let mut pause = self.pause.subscribe();
+        loop {
+            let deadline = match *pause.borrow_and_update() {
+                Some(p) if (write || p.mode == PauseMode::All) && p.deadline > Instant::now() => {
+                    p.deadline
+                }
+                _ => return,
+            };
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline.into()) => {}
+                changed = pause.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn clients(&self) -> MutexGuard<'_, HashMap<u64, ClientHandle>> {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_kill_clients() {
+        let backend = Backend::new();
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let (first, _) = backend.register_client(1, Some(addr)).unwrap();
+        let (second, _) = backend.register_client(2, None).unwrap();
+        assert_eq!(backend.kill_clients(|id, _| id == 3), 0);
+        assert_eq!(backend.kill_clients(|_, h| h.addr == Some(addr)), 1);
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        backend.unregister_client(2);
+        assert_eq!(backend.kill_clients(|_, _| true), 1);
+        assert!(!second.is_cancelled());
+    }
+
+    #[test]
+    fn test_push_to_client() {
+        let backend = Backend::new();
+        let (_, mut rx) = backend.register_client(1, None).unwrap();
+        assert!(backend.push_to_client(1, RespFrame::Integer(1)));
+        assert!(!backend.push_to_client(2, RespFrame::Integer(2)));
+        assert_eq!(rx.try_recv().ok(), Some(RespFrame::Integer(1)));
+
+        drop(rx);
+        assert!(!backend.push_to_client(1, RespFrame::Integer(3)));
+    }
+
+    #[test]
+    fn test_push_overflow_kills_client() {
+        let backend = Backend::new();
+        let (token, _rx) = backend.register_client(1, None).unwrap();
+        for i in 0..PUSH_BUFFER_LIMIT {
+            assert!(backend.push_to_client(1, RespFrame::Integer(i as i64)));
+        }
+        assert!(!token.is_cancelled());
+        assert!(!backend.push_to_client(1, RespFrame::Integer(-1)));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_register_respects_maxclients() {
+        let backend = Backend::new();
+        backend.set_config("maxclients", "1").unwrap();
+        assert!(backend.register_client(1, None).is_some());
+        assert!(backend.register_client(2, None).is_none());
+        backend.unregister_client(1);
+        assert!(backend.register_client(2, None).is_some());
+        assert_eq!(backend.client_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unpause_wakes_waiting_commands() {
+        let backend = Backend::new();
+        backend.client_pause(Instant::now() + Duration::from_secs(10), PauseMode::Write);
+        // WRITE 模式下读命令不需要等待
+        tokio::time::timeout(Duration::from_millis(50), backend.wait_if_paused(false))
+            .await
+            .unwrap();
+
+        let waiting = tokio::spawn({
+            let backend = backend.clone();
+            async move { backend.wait_if_paused(true).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        backend.client_unpause();
+        tokio::time::timeout(Duration::from_millis(100), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
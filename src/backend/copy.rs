@@ -0,0 +1,111 @@
+use super::{stats, Backend};
+
+impl Backend {
+    // 把 src 的值（连同过期时间）复制到 dst。src 不存在，或者 dst 已存在且 replace 为 false 时返回 false。
+    // 只有一个数据库，COPY 的 DB 选项由命令层校验。
+    // 调用方需要持有 Backend::script_guard，src 和 dst 可能在同一个分片，不能同时持有两者的锁
+    pub fn copy(&self, src: &str, dst: &str, replace: bool) -> bool {
+        if !self.exists(src) {
+            return false;
+        }
+        if self.exists(dst) {
+            if !replace {
+                return false;
+            }
+            // 被覆盖的 dst 不产生 del 事件，和 Redis 一样只通知 copy_to
+            self.remove_key(dst);
+        }
+        self.touch(src);
+        self.touch_for_write(dst);
+        // 先克隆出值再写入，避免同时持有 src 和 dst 所在分片的锁
+        let dst = dst.to_string();
+        if let Some(value) = self.map.get(src).map(|v| v.clone()) {
+            self.grow_memory(dst.len() + stats::frame_size(&value));
+            self.map.insert(dst.clone(), value);
+        }
+        if let Some(value) = self.hmap.get(src).map(|v| v.clone()) {
+            self.grow_memory(dst.len() + stats::hash_size(&value));
+            self.hmap.insert(dst.clone(), value);
+        }
+        if let Some(value) = self.list_map.get(src).map(|v| v.clone()) {
+            self.grow_memory(dst.len() + stats::list_size(&value));
+            self.list_map.insert(dst.clone(), value);
+        }
+        if let Some(value) = self.set_map.get(src).map(|v| v.clone()) {
+            self.grow_memory(dst.len() + stats::set_size(&value));
+            self.set_map.insert(dst.clone(), value);
+        }
+        if let Some(value) = self.stream_map.get(src).map(|v| v.clone()) {
+            self.grow_memory(dst.len() + stats::stream_size(&value));
+            self.stream_map.insert(dst.clone(), value);
+        }
+        if let Some(value) = self.stream_groups.get(src).map(|v| v.clone()) {
+            self.stream_groups.insert(dst.clone(), value);
+        }
+        if let Some(value) = self.stream_last_ids.get(src).map(|v| *v) {
+            self.stream_last_ids.insert(dst.clone(), value);
+        }
+        if let Some(value) = self.zset_map.get(src).map(|v| v.clone()) {
+            self.grow_memory(dst.len() + stats::zset_size(&value));
+            self.zset_map.insert(dst.clone(), value);
+        }
+        match self.ttl_map.get(src).map(|v| *v) {
+            Some(deadline) => {
+                self.ttl_map.insert(dst.clone(), deadline);
+            }
+            None => {
+                self.ttl_map.remove(&dst);
+            }
+        }
+        if let Some(key_type) = self.key_type(&dst) {
+            self.emit_key_event("copy_to", &dst, key_type);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{BulkString, ZAddOptions};
+
+    use super::*;
+
+    #[test]
+    fn test_copy_is_deep() {
+        let backend = Backend::new();
+        backend.hset(
+            "src".to_string(),
+            "f".to_string(),
+            BulkString::new("1").into(),
+        );
+        assert!(backend.copy("src", "dst", false));
+        backend.hset(
+            "src".to_string(),
+            "f".to_string(),
+            BulkString::new("2").into(),
+        );
+        assert_eq!(backend.hget("dst", "f"), Some(BulkString::new("1").into()));
+    }
+
+    #[test]
+    fn test_copy_respects_replace_and_ttl() {
+        let backend = Backend::new();
+        backend.zadd(
+            "src".to_string(),
+            vec![("a".to_string(), 1.0)],
+            ZAddOptions::default(),
+        );
+        backend.expire("src", Duration::from_secs(100), None);
+        backend.set("dst".to_string(), BulkString::new("old").into());
+
+        assert!(!backend.copy("src", "dst", false));
+        assert!(!backend.copy("missing", "dst", true));
+        assert!(backend.copy("src", "dst", true));
+        // 原来的 string 值被替换成 zset
+        assert!(backend.get("dst").is_none());
+        assert_eq!(backend.zscore("dst", "a"), Some(1.0));
+        assert!(matches!(backend.ttl("dst"), Some(Some(ttl)) if ttl > Duration::from_secs(90)));
+    }
+}
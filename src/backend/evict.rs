@@ -0,0 +1,185 @@
+use dashmap::DashMap;
+
+use crate::{EvictionPolicy, Rng};
+
+use super::Backend;
+
+impl Backend {
+    // 写命令执行前调用：used_memory 超过 maxmemory 时按 maxmemory-policy 淘汰 key，直到回到上限以内。
+    // 返回 false 表示腾不出空间（noeviction 或已经没有 key 可以淘汰），命令应以 OOM 拒绝
+    pub fn evict_if_needed(&self) -> bool {
+        let (maxmemory, policy, samples) = {
+            let config = self.config();
+            (
+                config.maxmemory,
+                config.maxmemory_policy,
+                config.maxmemory_samples,
+            )
+        };
+        if maxmemory == 0 {
+            return true;
+        }
+        let mut rng = Rng::from_time();
+        while self.used_memory() > maxmemory {
+            let evicted = match policy {
+                EvictionPolicy::NoEviction => None,
+                EvictionPolicy::AllKeysLru => self.evict_lru(samples, &mut rng),
+                EvictionPolicy::AllKeysRandom => self.evict_random(&mut rng),
+            };
+            if evicted.is_none() {
+                return false;
+            }
+        }
+        true
+    }
+
+    // 随机采样 samples 个 key，淘汰其中最久没有访问的并返回它，没有 key 时返回 None；
+    // 没有访问记录的 key 最先被淘汰
+    pub fn evict_lru(&self, samples: usize, rng: &mut Rng) -> Option<String> {
+        let key = self
+            .sample_keys(samples, rng)
+            .into_iter()
+            .min_by_key(|key| self.access_map.get(key).map(|access| access.last))?;
+        self.evict(&key);
+        Some(key)
+    }
+
+    // 随机淘汰一个 key 并返回它，没有 key 时返回 None
+    pub fn evict_random(&self, rng: &mut Rng) -> Option<String> {
+        let key = self.sample_keys(1, rng).pop()?;
+        self.evict(&key);
+        Some(key)
+    }
+
+    // 从随机位置开始连续取 count 个 key，到末尾后从头继续。
+    // 跳过的部分只移动迭代器，不复制 key，也不会收集和排序整个 keyspace
+    fn sample_keys(&self, count: usize, rng: &mut Rng) -> Vec<String> {
+        let lens = [
+            self.map.len(),
+            self.hmap.len(),
+            self.list_map.len(),
+            self.set_map.len(),
+            self.stream_map.len(),
+            self.zset_map.len(),
+        ];
+        let total: usize = lens.iter().sum();
+        if total == 0 {
+            return Vec::new();
+        }
+        let mut skip = rng.below(total);
+        let mut count = count.min(total);
+        let mut keys = Vec::with_capacity(count);
+        for i in (0..lens.len()).cycle().take(lens.len() * 2) {
+            if count == 0 {
+                break;
+            }
+            if skip >= lens[i] {
+                skip -= lens[i];
+                continue;
+            }
+            let before = keys.len();
+            match i {
+                0 => take_keys(&self.map, skip, count, &mut keys),
+                1 => take_keys(&self.hmap, skip, count, &mut keys),
+                2 => take_keys(&self.list_map, skip, count, &mut keys),
+                3 => take_keys(&self.set_map, skip, count, &mut keys),
+                4 => take_keys(&self.stream_map, skip, count, &mut keys),
+                _ => take_keys(&self.zset_map, skip, count, &mut keys),
+            }
+            count -= keys.len() - before;
+            skip = 0;
+        }
+        keys
+    }
+
+    fn evict(&self, key: &str) {
+        if let Some(key_type) = self.remove_key(key) {
+            self.stats.record_eviction();
+            self.emit_key_event("evicted", key, key_type);
+        }
+    }
+}
+
+fn take_keys<V>(map: &DashMap<String, V>, skip: usize, count: usize, keys: &mut Vec<String>) {
+    keys.extend(map.iter().skip(skip).take(count).map(|e| e.key().clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use crate::BulkString;
+
+    use super::*;
+
+    fn backend(maxmemory: &str, policy: &str) -> Backend {
+        let backend = Backend::new();
+        backend.set_config("maxmemory", maxmemory).unwrap();
+        backend.set_config("maxmemory-policy", policy).unwrap();
+        backend
+    }
+
+    fn set(backend: &Backend, key: &str) {
+        backend.set(key.to_string(), BulkString::new("0123456789").into());
+        // 连续写入的访问时间可能相同，稍等一下保证先后顺序
+        thread::sleep(Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_evict_lru_removes_least_recently_used() {
+        // 每个 key 占 2 + 10 字节，最多放下 3 个
+        let backend = backend("36", "allkeys-lru");
+        for key in ["k1", "k2", "k3"] {
+            set(&backend, key);
+        }
+        assert!(backend.evict_if_needed());
+        assert_eq!(backend.dbsize(), 3);
+
+        // 读取 k1 后，最久没有访问的是 k2
+        backend.get("k1");
+        set(&backend, "k4");
+        assert!(backend.evict_if_needed());
+        assert!(!backend.exists("k2"));
+        assert!(backend.exists("k1") && backend.exists("k3") && backend.exists("k4"));
+        assert_eq!(backend.stats().evicted_keys(), 1);
+    }
+
+    #[test]
+    fn test_evict_random_stays_under_limit() {
+        let backend = backend("36", "allkeys-random");
+        for key in ["k1", "k2", "k3", "k4", "k5"] {
+            set(&backend, key);
+        }
+        assert!(backend.evict_if_needed());
+        assert_eq!(backend.dbsize(), 3);
+        assert!(backend.used_memory() <= 36);
+    }
+
+    #[test]
+    fn test_evict_lru_samples_keys() {
+        let backend = backend("36", "allkeys-lru");
+        backend.set_config("maxmemory-samples", "2").unwrap();
+        for i in 0..20 {
+            backend.set(format!("k{:02}", i), BulkString::new("0123456789").into());
+        }
+        assert!(backend.evict_if_needed());
+        assert!(backend.dbsize() <= 3);
+        assert!(backend.used_memory() <= 36);
+        assert_eq!(backend.stats().evicted_keys(), 20 - backend.dbsize() as u64);
+        assert!(backend.set_config("maxmemory-samples", "0").is_err());
+    }
+
+    #[test]
+    fn test_noeviction_reports_oom() {
+        let backend = backend("12", "noeviction");
+        set(&backend, "k1");
+        assert!(backend.evict_if_needed());
+        set(&backend, "k2");
+        assert!(!backend.evict_if_needed());
+        assert_eq!(backend.dbsize(), 2);
+
+        // maxmemory 为 0 时不限制
+        backend.set_config("maxmemory", "0").unwrap();
+        assert!(backend.evict_if_needed());
+    }
+}
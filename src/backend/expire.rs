@@ -0,0 +1,241 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::task::JoinHandle;
+
+use super::Backend;
+
+// 主动过期的默认参数，对应 Redis 的 hz 10 和每轮采样 20 个 key
+pub const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+pub const ACTIVE_EXPIRE_SAMPLE_SIZE: usize = 20;
+// 一轮采样中过期 key 超过这个比例时立即再采样一轮
+const ACTIVE_EXPIRE_REPEAT_RATIO: f64 = 0.25;
+
+// EXPIRE / PEXPIRE 的条件选项，没有过期时间的 key 在比较时视为永不过期
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExpireFlag {
+    // 只在 key 没有过期时间时设置
+    Nx,
+    // 只在 key 已有过期时间时设置
+    Xx,
+    // 只在新的过期时间晚于当前过期时间时设置
+    Gt,
+    // 只在新的过期时间早于当前过期时间时设置
+    Lt,
+}
+
+impl ExpireFlag {
+    fn allows(self, current: Option<Instant>, deadline: Instant) -> bool {
+        match self {
+            ExpireFlag::Nx => current.is_none(),
+            ExpireFlag::Xx => current.is_some(),
+            ExpireFlag::Gt => current.is_some_and(|current| deadline > current),
+            ExpireFlag::Lt => current.is_none_or(|current| deadline < current),
+        }
+    }
+}
+
+impl Backend {
+    // 设置 key 的过期时间点，key 不存在时返回 false；时间点已经过去时直接删除 key
+    pub fn expire_at(&self, key: &str, deadline: Instant) -> bool {
+        if !self.exists(key) {
+            return false;
+        }
+        if deadline <= Instant::now() {
+            self.del(key);
+        } else {
+            self.ttl_map.insert(key.to_string(), deadline);
+            if let Some(key_type) = self.key_type(key) {
+                self.emit_key_event("expire", key, key_type);
+            }
+        }
+        true
+    }
+
+    // 以 unix 时间（距 unix 纪元的时长）设置过期时间，时间已经过去时直接删除 key
+    pub fn expire_at_unix(&self, key: &str, at: Duration) -> bool {
+        let deadline = match (UNIX_EPOCH + at).duration_since(SystemTime::now()) {
+            Ok(remaining) => Instant::now() + remaining,
+            Err(_) => Instant::now(),
+        };
+        self.expire_at(key, deadline)
+    }
+
+    // 设置剩余生存时间，flag 的条件不满足时不做修改并返回 false
+    pub fn expire(&self, key: &str, ttl: Duration, flag: Option<ExpireFlag>) -> bool {
+        let deadline = Instant::now() + ttl;
+        if let Some(flag) = flag {
+            if !self.exists(key) {
+                return false;
+            }
+            let current = self.ttl_map.get(key).map(|deadline| *deadline);
+            if !flag.allows(current, deadline) {
+                return false;
+            }
+        }
+        self.expire_at(key, deadline)
+    }
+
+    // 剩余生存时间：key 不存在时返回 None，没有设置过期时间时返回 Some(None)
+    pub fn ttl(&self, key: &str) -> Option<Option<Duration>> {
+        if !self.exists(key) {
+            return None;
+        }
+        let ttl = self
+            .ttl_map
+            .get(key)
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        Some(ttl)
+    }
+
+    // 过期的绝对时间（距 unix 纪元的时长）：key 不存在时返回 None，没有设置过期时间时返回 Some(None)
+    pub fn expiretime(&self, key: &str) -> Option<Option<Duration>> {
+        let ttl = self.ttl(key)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Some(ttl.map(|ttl| now + ttl))
+    }
+
+    // 移除 key 的过期时间
+    pub fn persist(&self, key: &str) -> bool {
+        self.ttl_map.remove(key).is_some()
+    }
+
+    // key 已过期时删除它，返回是否删除
+    pub(super) fn expire_if_needed(&self, key: &str) -> bool {
+        let deadline = self.ttl_map.get(key).map(|deadline| *deadline);
+        match deadline {
+            Some(deadline) if deadline <= Instant::now() => {
+                if let Some(key_type) = self.remove_key(key) {
+                    self.emit_key_event("expired", key, key_type);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // 主动过期的一轮：从 cursor 开始采样 sample_size 个带过期时间的 key，删除其中已过期的，
+    // 返回 (采样数, 删除数)。采样时只持有单个分片的读锁，删除在遍历结束后进行。
+    pub fn active_expire_cycle(&self, cursor: &mut usize, sample_size: usize) -> (usize, usize) {
+        let len = self.ttl_map.len();
+        if len == 0 {
+            *cursor = 0;
+            return (0, 0);
+        }
+        if *cursor >= len {
+            *cursor = 0;
+        }
+        let now = Instant::now();
+        let mut sampled = 0;
+        let expired: Vec<String> = self
+            .ttl_map
+            .iter()
+            .skip(*cursor)
+            .take(sample_size)
+            .inspect(|_| sampled += 1)
+            .filter(|entry| *entry.value() <= now)
+            .map(|entry| entry.key().clone())
+            .collect();
+        *cursor += sampled - expired.len();
+
+        let mut removed = 0;
+        for key in expired {
+            if self.expire_if_needed(&key) {
+                removed += 1;
+            }
+        }
+        (sampled, removed)
+    }
+
+    // 启动后台主动过期任务，每隔 interval 执行一次采样；过期比例较高时在时间预算内继续采样
+    pub fn spawn_active_expire(&self, interval: Duration, sample_size: usize) -> JoinHandle<()> {
+        let backend = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut cursor = 0;
+            loop {
+                ticker.tick().await;
+                if !backend.active_expire() {
+                    continue;
+                }
+                let budget = Instant::now() + interval / 4;
+                loop {
+                    let (sampled, removed) = backend.active_expire_cycle(&mut cursor, sample_size);
+                    if sampled == 0
+                        || (removed as f64) <= sampled as f64 * ACTIVE_EXPIRE_REPEAT_RATIO
+                        || Instant::now() >= budget
+                    {
+                        break;
+                    }
+                    tokio::task::yield_now().await;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BulkString;
+
+    use super::*;
+
+    #[test]
+    fn test_expired_key_is_removed_lazily() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        assert!(backend.expire("key", Duration::from_millis(10), None));
+        assert!(backend.get("key").is_some());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(backend.get("key").is_none());
+        assert!(!backend.ttl_map.contains_key("key"));
+    }
+
+    #[test]
+    fn test_active_expire_cycle_walks_all_keys() {
+        let backend = Backend::new();
+        for i in 0..10 {
+            let key = format!("live{}", i);
+            backend.set(key.clone(), BulkString::new("v").into());
+            backend.expire(&key, Duration::from_secs(100), None);
+        }
+        for i in 0..5 {
+            let key = format!("dead{}", i);
+            backend.set(key.clone(), BulkString::new("v").into());
+            backend.ttl_map.insert(key, Instant::now());
+        }
+        let mut cursor = 0;
+        let mut removed = 0;
+        for _ in 0..10 {
+            removed += backend.active_expire_cycle(&mut cursor, 3).1;
+        }
+        assert_eq!(removed, 5);
+        assert_eq!(backend.map.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_active_expire_reclaims_unread_key() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        backend.expire("key", Duration::from_millis(10), None);
+        let handle = backend.spawn_active_expire(Duration::from_millis(5), 20);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+        // 直接检查底层存储，避免 get 触发惰性删除
+        assert!(!backend.map.contains_key("key"));
+        assert!(!backend.ttl_map.contains_key("key"));
+    }
+
+    #[tokio::test]
+    async fn test_active_expire_can_be_disabled() {
+        let backend = Backend::new();
+        backend.set_active_expire(false);
+        backend.set("key".to_string(), BulkString::new("value").into());
+        backend.expire("key", Duration::from_millis(10), None);
+        let handle = backend.spawn_active_expire(Duration::from_millis(5), 20);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+        assert!(backend.map.contains_key("key"));
+    }
+}
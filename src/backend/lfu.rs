@@ -0,0 +1,150 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+use crate::Rng;
+
+use super::Backend;
+
+// 新 key 的初始计数，避免刚写入的 key 因为计数为 0 被优先淘汰，和 Redis 的 LFU_INIT_VAL 一致
+pub const LFU_INIT_VAL: u8 = 5;
+// 对数增长的系数，越大计数增长越慢，和 Redis 的 lfu-log-factor 默认值一致
+const LFU_LOG_FACTOR: f64 = 10.0;
+// 每隔这么久没有访问，计数减 1，和 Redis 的 lfu-decay-time 默认值一致
+const LFU_DECAY_TIME: Duration = Duration::from_secs(60);
+
+thread_local! {
+    static LFU_RNG: RefCell<Rng> = RefCell::new(Rng::from_time());
+}
+
+// key 的访问记录：最近一次访问的时间和 8 位的对数访问频率计数
+#[derive(Debug, Clone, Copy)]
+pub(super) struct KeyAccess {
+    pub(super) last: Instant,
+    freq: u8,
+}
+
+impl KeyAccess {
+    pub(super) fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            freq: LFU_INIT_VAL,
+        }
+    }
+
+    // 按距离上次访问经过的时间衰减后的计数
+    fn decayed_freq(&self, now: Instant) -> u8 {
+        let periods = now.duration_since(self.last).as_secs() / LFU_DECAY_TIME.as_secs();
+        self.freq.saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+
+    // 先衰减再按概率加 1，r 为 [0, 1) 之间的随机数
+    fn record_access(&mut self, now: Instant, r: f64) {
+        let freq = self.decayed_freq(now);
+        self.freq = log_incr(freq, r);
+        self.last = now;
+    }
+
+    pub(super) fn touch(&mut self) {
+        let r = LFU_RNG.with(|rng| rng.borrow_mut().next_u64() as f64 / u64::MAX as f64);
+        self.record_access(Instant::now(), r);
+    }
+}
+
+// 计数越大，加 1 的概率越低：p = 1 / ((counter - LFU_INIT_VAL) * LFU_LOG_FACTOR + 1)
+fn log_incr(counter: u8, r: f64) -> u8 {
+    if counter == u8::MAX {
+        return counter;
+    }
+    let base = counter.saturating_sub(LFU_INIT_VAL) as f64;
+    let p = 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+    if r < p {
+        counter + 1
+    } else {
+        counter
+    }
+}
+
+impl Backend {
+    // OBJECT FREQ 的访问频率计数，key 不存在时返回 None；查询本身不算一次访问
+    pub fn lfu_freq(&self, key: &str) -> Option<u8> {
+        if !self.exists(key) {
+            return None;
+        }
+        let freq = self
+            .access_map
+            .get(key)
+            .map_or(LFU_INIT_VAL, |access| access.decayed_freq(Instant::now()));
+        Some(freq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BulkString, ZAddOptions};
+
+    use super::*;
+
+    #[test]
+    fn test_log_incr_slows_down() {
+        // 计数等于初始值时一定加 1
+        assert_eq!(log_incr(LFU_INIT_VAL, 0.99), LFU_INIT_VAL + 1);
+        // 计数为 6 时加 1 的概率为 1/11
+        assert_eq!(log_incr(6, 0.05), 7);
+        assert_eq!(log_incr(6, 0.2), 6);
+        assert_eq!(log_incr(u8::MAX, 0.0), u8::MAX);
+    }
+
+    #[test]
+    fn test_freq_decays_over_time() {
+        let mut access = KeyAccess::new();
+        let start = access.last;
+        for _ in 0..3 {
+            access.record_access(start, 0.0);
+        }
+        assert_eq!(access.decayed_freq(start), LFU_INIT_VAL + 3);
+        assert_eq!(
+            access.decayed_freq(start + LFU_DECAY_TIME * 2),
+            LFU_INIT_VAL + 1
+        );
+        assert_eq!(access.decayed_freq(start + LFU_DECAY_TIME * 100), 0);
+    }
+
+    #[test]
+    fn test_freq_rises_with_access() {
+        let backend = Backend::new();
+        assert_eq!(backend.lfu_freq("key"), None);
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let initial = backend.lfu_freq("key").unwrap();
+        assert_eq!(initial, LFU_INIT_VAL);
+        for _ in 0..100 {
+            backend.get("key");
+        }
+        assert!(backend.lfu_freq("key").unwrap() > initial);
+    }
+
+    #[test]
+    fn test_del_clears_last_access() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        assert!(backend.last_access("key").is_some());
+        backend.del("key");
+        assert_eq!(backend.last_access("key"), None);
+        assert!(!backend.access_map.contains_key("key"));
+        assert_eq!(backend.idletime("key"), None);
+    }
+
+    #[test]
+    fn test_missing_keys_have_no_access_record() {
+        let backend = Backend::new();
+        assert_eq!(backend.get("nosuch"), None);
+        assert!(backend.lrange("nosuch", 0, -1).is_empty());
+        let options = ZAddOptions {
+            xx: true,
+            ..Default::default()
+        };
+        assert_eq!(backend.zadd("zset".to_string(), vec![], options), 0);
+        assert!(backend.access_map.is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+
+use crate::RespFrame;
+
+use super::{stats::frame_size, Backend};
+
+impl Backend {
+    // 依次将 values 插入到列表头部，返回插入后的长度
+    pub fn lpush(&self, key: String, values: Vec<RespFrame>) -> usize {
+        self.touch_for_write(&key);
+        self.grow_memory(values.iter().map(frame_size).sum());
+        let len = {
+            let mut list = self.list_map.entry(key.clone()).or_insert_with(|| {
+                self.grow_memory(key.len());
+                VecDeque::new()
+            });
+            for value in values {
+                list.push_front(value);
+            }
+            list.len()
+        };
+        self.notify_key(&key);
+        self.emit_key_event("lpush", &key, "list");
+        len
+    }
+
+    // 依次将 values 追加到列表尾部，返回追加后的长度
+    pub fn rpush(&self, key: String, values: Vec<RespFrame>) -> usize {
+        self.touch_for_write(&key);
+        self.grow_memory(values.iter().map(frame_size).sum());
+        let len = {
+            let mut list = self.list_map.entry(key.clone()).or_insert_with(|| {
+                self.grow_memory(key.len());
+                VecDeque::new()
+            });
+            list.extend(values);
+            list.len()
+        };
+        self.notify_key(&key);
+        self.emit_key_event("rpush", &key, "list");
+        len
+    }
+
+    // 返回下标位于 [start, stop] 之间的元素，负数下标从列表尾部开始计算
+    pub fn lrange(&self, key: &str, start: i64, stop: i64) -> Vec<RespFrame> {
+        self.touch(key);
+        let Some(list) = self.list_map.get(key) else {
+            return Vec::new();
+        };
+        let len = list.len() as i64;
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let stop = if stop < 0 {
+            len + stop
+        } else {
+            stop.min(len - 1)
+        };
+        if start > stop {
+            return Vec::new();
+        }
+        list.range(start as usize..=stop as usize)
+            .cloned()
+            .collect()
+    }
+
+    // 返回等于 element 的元素下标。rank 为正数时从头部开始，跳过前 rank - 1 个匹配；
+    // 为负数时从尾部开始。count 为 0 表示返回全部匹配，maxlen 为 0 表示扫描整个列表
+    pub fn lpos(
+        &self,
+        key: &str,
+        element: &RespFrame,
+        rank: i64,
+        count: usize,
+        maxlen: usize,
+    ) -> Vec<usize> {
+        self.touch(key);
+        let Some(list) = self.list_map.get(key) else {
+            return Vec::new();
+        };
+        let len = list.len();
+        let indexes: Box<dyn Iterator<Item = usize>> = if rank > 0 {
+            Box::new(0..len)
+        } else {
+            Box::new((0..len).rev())
+        };
+        let scan = if maxlen == 0 { len } else { maxlen };
+        let count = if count == 0 { len } else { count };
+        indexes
+            .take(scan)
+            .filter(|&i| list[i] == *element)
+            .skip(rank.unsigned_abs() as usize - 1)
+            .take(count)
+            .collect()
+    }
+
+    // 从 keys 中第一个非空列表的头部（left）或尾部弹出至多 count 个元素，
+    // 返回该列表的 key 和弹出的元素；列表被弹空时删除 key
+    pub fn lmpop(
+        &self,
+        keys: &[String],
+        left: bool,
+        count: usize,
+    ) -> Option<(String, Vec<RespFrame>)> {
+        for key in keys {
+            self.touch(key);
+            let (popped, empty) = {
+                let Some(mut list) = self.list_map.get_mut(key) else {
+                    continue;
+                };
+                let len = list.len();
+                let n = count.min(len);
+                let popped: Vec<_> = if left {
+                    list.drain(..n).collect()
+                } else {
+                    list.drain(len - n..).rev().collect()
+                };
+                (popped, list.is_empty())
+            };
+            self.shrink_memory(popped.iter().map(frame_size).sum());
+            if empty
+                && self
+                    .list_map
+                    .remove_if(key, |_, list| list.is_empty())
+                    .is_some()
+            {
+                self.shrink_memory(key.len());
+                self.ttl_map.remove(key);
+                self.access_map.remove(key);
+            }
+            if !popped.is_empty() {
+                return Some((key.clone(), popped));
+            }
+        }
+        None
+    }
+
+    // 用 values 覆盖 key（无论原来是什么类型），values 为空时删除 key
+    pub fn lstore(&self, key: String, values: Vec<RespFrame>) -> usize {
+        self.del(&key);
+        let len = values.len();
+        if len > 0 {
+            self.touch_for_write(&key);
+            self.grow_memory(key.len() + values.iter().map(frame_size).sum::<usize>());
+            self.list_map.insert(key.clone(), VecDeque::from(values));
+            self.notify_key(&key);
+        }
+        len
+    }
+}
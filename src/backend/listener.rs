@@ -0,0 +1,154 @@
+use std::{fmt, sync::Arc};
+
+use super::Backend;
+
+// key 被修改时产生的事件，name 和 keyspace 通知中的事件名一致，例如 "set"、"del"、"expire"、"expired"
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyEvent {
+    pub name: &'static str,
+    pub key: String,
+    // 事件发生时 key 的类型，删除事件中是被删除的值的类型
+    pub key_type: &'static str,
+}
+
+// 观察 key 的修改。在修改完成后同步调用，调用时不持有任何数据的锁
+pub trait KeyspaceListener: fmt::Debug + Send + Sync {
+    fn on_event(&self, event: KeyEvent);
+}
+
+impl Backend {
+    // 设置或清除 listener，同一时间只有一个
+    pub fn set_keyspace_listener(&self, listener: Option<Arc<dyn KeyspaceListener>>) {
+        *self
+            .keyspace_listener
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = listener;
+    }
+
+    // 所有 key 修改事件的唯一出口：通知 listener，再按 notify-keyspace-events 发布 keyspace 通知
+    pub(super) fn emit_key_event(&self, name: &'static str, key: &str, key_type: &'static str) {
+        // 先复制出 listener 再调用，避免 listener 里设置新的 listener 时死锁
+        let listener = self
+            .keyspace_listener
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(listener) = listener {
+            listener.on_event(KeyEvent {
+                name,
+                key: key.to_string(),
+                key_type,
+            });
+        }
+        self.notify_keyspace(name, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Duration};
+
+    use crate::{BulkString, ZAddOptions};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<KeyEvent>>);
+
+    impl KeyspaceListener for Recorder {
+        fn on_event(&self, event: KeyEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    fn event(name: &'static str, key: &str, key_type: &'static str) -> KeyEvent {
+        KeyEvent {
+            name,
+            key: key.to_string(),
+            key_type,
+        }
+    }
+
+    #[test]
+    fn test_listener_records_key_events() {
+        let backend = Backend::new();
+        let recorder = Arc::new(Recorder::default());
+        backend.set_keyspace_listener(Some(recorder.clone()));
+
+        backend.set("mykey".to_string(), BulkString::new("value").into());
+        backend.expire("mykey", Duration::from_secs(10), None);
+        backend.rpush("list".to_string(), vec![BulkString::new("a").into()]);
+        backend.del("list");
+        backend.del("missing");
+        // 已经过去的时间点直接删除 key
+        backend.expire("mykey", Duration::ZERO, None);
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                event("set", "mykey", "string"),
+                event("expire", "mykey", "string"),
+                event("rpush", "list", "list"),
+                event("del", "list", "list"),
+                event("del", "mykey", "string"),
+            ]
+        );
+
+        backend.set_keyspace_listener(None);
+        backend.set("other".to_string(), BulkString::new("value").into());
+        assert_eq!(recorder.0.lock().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_mutations_emit_events_and_notifications() {
+        let backend = Backend::new();
+        let recorder = Arc::new(Recorder::default());
+        backend.set_keyspace_listener(Some(recorder.clone()));
+        backend.set_config("notify-keyspace-events", "KEA").unwrap();
+        let (_, mut rx) = backend.register_client(1, None).unwrap();
+        backend.psubscribe(1, "__keyevent@0__:*");
+
+        backend.setbit("bits".to_string(), 3, true).unwrap();
+        backend.sadd("set".to_string(), vec!["a".to_string()]);
+        backend.sadd("set".to_string(), vec!["a".to_string()]);
+        assert!(backend.copy("set", "set2", false));
+        backend.zadd(
+            "zset".to_string(),
+            vec![("m".to_string(), 1.0)],
+            ZAddOptions::default(),
+        );
+        backend.zrem("zset", &["m".to_string()]);
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                event("setbit", "bits", "string"),
+                event("sadd", "set", "set"),
+                event("copy_to", "set2", "set"),
+                event("zadd", "zset", "zset"),
+                event("zrem", "zset", "zset"),
+            ]
+        );
+        // 同一个事件也发布到 keyevent 频道
+        let mut published = Vec::new();
+        while let Ok(frame) = rx.try_recv() {
+            published.push(frame);
+        }
+        assert_eq!(published.len(), 5);
+    }
+
+    #[test]
+    fn test_lazy_expiry_fires_expired_event() {
+        let backend = Backend::new();
+        let recorder = Arc::new(Recorder::default());
+        backend.set("mykey".to_string(), BulkString::new("value").into());
+        backend.expire("mykey", Duration::from_millis(10), None);
+        backend.set_keyspace_listener(Some(recorder.clone()));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(backend.get("mykey"), None);
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![event("expired", "mykey", "string")]
+        );
+    }
+}
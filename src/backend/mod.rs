@@ -1,8 +1,61 @@
-use std::{ops::Deref, sync::Arc};
+mod acl;
+mod client;
+mod copy;
+mod evict;
+mod expire;
+mod lfu;
+mod list;
+mod listener;
+mod monitor;
+mod pubsub;
+mod scan;
+mod script;
+mod set;
+mod slowlog;
+mod snapshot;
+mod sort;
+mod stats;
+mod stream;
+mod string;
+mod zset;
 
-use dashmap::DashMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock, RwLockReadGuard,
+    },
+    time::{Duration, Instant},
+};
 
-use crate::RespFrame;
+use dashmap::{DashMap, DashSet};
+use futures::future::select_all;
+use tokio::sync::{broadcast, watch, Notify};
+
+use crate::{ConfigError, RespFrame, Rng, ServerConfig};
+
+use acl::AclUsers;
+use lfu::KeyAccess;
+use script::ScriptState;
+use snapshot::SaveState;
+
+pub use acl::{AclDenied, AclError, AclUser, ACL_CATEGORIES, DEFAULT_USER};
+pub use client::{ClientHandle, ClientPause, PauseMode};
+pub use expire::{ExpireFlag, ACTIVE_EXPIRE_INTERVAL, ACTIVE_EXPIRE_SAMPLE_SIZE};
+pub use lfu::LFU_INIT_VAL;
+pub use listener::{KeyEvent, KeyspaceListener};
+pub use scan::DEFAULT_SCAN_COUNT;
+pub use script::ScriptKill;
+pub use slowlog::{SlowLog, SlowLogEntry};
+pub use snapshot::{SnapshotError, SNAPSHOT_MAGIC};
+pub use sort::{SortError, SortOptions};
+pub use stats::ServerStats;
+pub use stream::{
+    ConsumerGroup, PendingEntry, StreamEntry, StreamGroupError, StreamId, TrimStrategy, XAddError,
+};
+pub use string::{StringError, MAX_BIT_OFFSET};
+pub use zset::{Aggregate, ScoreBound, ZAddOptions, ZSet};
 
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
@@ -11,6 +64,41 @@ pub struct Backend(Arc<BackendInner>);
 pub struct BackendInner {
     map: DashMap<String, RespFrame>,
     hmap: DashMap<String, DashMap<String, RespFrame>>,
+    list_map: DashMap<String, VecDeque<RespFrame>>,
+    set_map: DashMap<String, DashSet<String>>,
+    stream_map: DashMap<String, BTreeMap<StreamId, Vec<(String, RespFrame)>>>,
+    // 每个 stream 上的消费者组，key 为组名
+    stream_groups: DashMap<String, HashMap<String, ConsumerGroup>>,
+    // 每个 stream 最后生成的 ID。删除最大的记录之后 ID 不回退，XADD 仍然从它之后生成
+    stream_last_ids: DashMap<String, StreamId>,
+    zset_map: DashMap<String, ZSet>,
+    // 设置了过期时间的 key 及其过期时间点
+    ttl_map: DashMap<String, Instant>,
+    // 每个 key 最近一次被访问的时间和访问频率
+    access_map: DashMap<String, KeyAccess>,
+    // 数据占用的字节数，随写入和删除增量更新
+    used_memory: AtomicUsize,
+    // 阻塞命令在 key 上等待写入的通知
+    notifiers: DashMap<String, Arc<Notify>>,
+    // 是否开启主动过期，关闭后只在访问 key 时惰性删除
+    active_expire: AtomicBool,
+    config: RwLock<ServerConfig>,
+    // 当前所有连接，key 为连接 ID
+    clients: Mutex<HashMap<u64, ClientHandle>>,
+    slowlog: Mutex<SlowLog>,
+    stats: ServerStats,
+    // CLIENT PAUSE 的状态，变化时唤醒等待中的命令
+    pause: watch::Sender<Option<ClientPause>>,
+    // 每个频道的订阅者，值为连接 ID
+    channels: DashMap<String, HashSet<u64>>,
+    // 每个 glob 模式的订阅者，值为连接 ID
+    patterns: DashMap<String, HashSet<u64>>,
+    // MONITOR 连接订阅的命令流，每条命令格式化成一行
+    monitor_channel: broadcast::Sender<String>,
+    keyspace_listener: RwLock<Option<Arc<dyn KeyspaceListener>>>,
+    acl: RwLock<AclUsers>,
+    save_state: SaveState,
+    script_state: ScriptState,
 }
 
 impl Deref for Backend {
@@ -32,6 +120,29 @@ impl Default for BackendInner {
         Self {
             map: DashMap::new(),
             hmap: DashMap::new(),
+            list_map: DashMap::new(),
+            set_map: DashMap::new(),
+            stream_map: DashMap::new(),
+            stream_groups: DashMap::new(),
+            stream_last_ids: DashMap::new(),
+            zset_map: DashMap::new(),
+            ttl_map: DashMap::new(),
+            access_map: DashMap::new(),
+            used_memory: AtomicUsize::new(0),
+            notifiers: DashMap::new(),
+            active_expire: AtomicBool::new(true),
+            config: RwLock::new(ServerConfig::default()),
+            clients: Mutex::new(HashMap::new()),
+            slowlog: Mutex::new(SlowLog::default()),
+            stats: ServerStats::default(),
+            pause: watch::Sender::new(None),
+            channels: DashMap::new(),
+            patterns: DashMap::new(),
+            monitor_channel: broadcast::Sender::new(monitor::MONITOR_CAPACITY),
+            keyspace_listener: RwLock::new(None),
+            acl: RwLock::new(AclUsers::default()),
+            save_state: SaveState::default(),
+            script_state: ScriptState::default(),
         }
     }
 }
@@ -41,26 +152,352 @@ impl Backend {
         Self::default()
     }
 
+    pub fn with_config(config: ServerConfig) -> Self {
+        Self(Arc::new(BackendInner {
+            config: RwLock::new(config),
+            ..Default::default()
+        }))
+    }
+
+    pub fn config(&self) -> RwLockReadGuard<'_, ServerConfig> {
+        self.config.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // 运行时修改单个配置项
+    pub fn set_config(&self, name: &str, value: &str) -> Result<(), ConfigError> {
+        self.config
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .set(name, value)
+    }
+
+    // CONFIG SET：一次修改多个配置项，任意一项失败时都不修改；只能在启动时设置的配置项返回错误
+    pub fn set_configs(&self, pairs: &[(String, String)]) -> Result<(), ConfigError> {
+        let mut config = self.config.write().unwrap_or_else(|e| e.into_inner());
+        let mut updated = config.clone();
+        for (name, value) in pairs {
+            if ServerConfig::is_immutable(name) {
+                return Err(ConfigError::Immutable(name.to_string()));
+            }
+            updated.set(name, value)?;
+        }
+        *config = updated;
+        Ok(())
+    }
+
     pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+        self.touch(key);
+        let value = self.map.get(key).map(|v| v.value().clone());
+        self.stats.record_lookup(value.is_some());
+        value
     }
 
+    // 覆盖写入会清除 key 原有的过期时间
     pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+        self.touch_for_write(&key);
+        self.ttl_map.remove(&key);
+        self.grow_memory(key.len() + stats::frame_size(&value));
+        if let Some(old) = self.map.insert(key.clone(), value) {
+            self.shrink_memory(key.len() + stats::frame_size(&old));
+        }
+        self.emit_key_event("set", &key, "string");
     }
 
     pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
+        self.touch(key);
+        let value = self
+            .hmap
             .get(key)
-            .and_then(|m| m.get(field).map(|v| v.value().clone()))
+            .and_then(|m| m.get(field).map(|v| v.value().clone()));
+        self.stats.record_lookup(value.is_some());
+        value
     }
 
     pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let m = self.hmap.entry(key).or_default();
-        m.insert(field, value);
+        self.hset_fields(key, vec![(field, value)]);
+    }
+
+    // 一次写入多个字段，返回新增的字段数；整个过程持有 key 的写锁，其它连接看不到写了一半的结果
+    pub fn hset_fields(&self, key: String, fields: Vec<(String, RespFrame)>) -> usize {
+        self.touch_for_write(&key);
+        let key_len = key.len();
+        let m = self.hmap.entry(key.clone()).or_insert_with(|| {
+            self.grow_memory(key_len);
+            DashMap::new()
+        });
+        let mut added = 0;
+        for (field, value) in fields {
+            let size = field.len() + stats::frame_size(&value);
+            self.grow_memory(size);
+            match m.insert(field.clone(), value) {
+                Some(old) => self.shrink_memory(field.len() + stats::frame_size(&old)),
+                None => added += 1,
+            }
+        }
+        drop(m);
+        self.emit_key_event("hset", &key, "hash");
+        added
+    }
+
+    // 持有读锁调用 f 访问整个 hash，不复制其中的字段；key 不存在时返回 None
+    pub fn hgetall_with<F, R>(&self, key: &str, f: F) -> Option<R>
+    where
+        F: FnOnce(&DashMap<String, RespFrame>) -> R,
+    {
+        self.touch(key);
+        let ret = self.hmap.get(key).map(|m| f(m.value()));
+        self.stats.record_lookup(ret.is_some());
+        ret
+    }
+
+    // 随机返回 hash 中的字段：count 为正数时返回最多 count 个不重复的字段，
+    // 为负数时返回 |count| 个可能重复的字段；key 不存在时返回空列表
+    pub fn hrandfield(&self, key: &str, count: i64, rng: &mut Rng) -> Vec<(String, RespFrame)> {
+        self.touch(key);
+        let Some(hmap) = self.hmap.get(key) else {
+            return Vec::new();
+        };
+        // 先排序，保证相同的种子得到相同的结果
+        let mut fields: Vec<(String, RespFrame)> = hmap
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        drop(hmap);
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        if fields.is_empty() {
+            return fields;
+        }
+        if count < 0 {
+            return (0..count.unsigned_abs())
+                .map(|_| fields[rng.below(fields.len())].clone())
+                .collect();
+        }
+        // 部分 Fisher-Yates 洗牌，取前 count 个
+        let count = (count as usize).min(fields.len());
+        for i in 0..count {
+            let j = i + rng.below(fields.len() - i);
+            fields.swap(i, j);
+        }
+        fields.truncate(count);
+        fields
+    }
+
+    // 删除 key 及其过期时间、访问记录，返回 key 是否存在
+    pub fn del(&self, key: &str) -> bool {
+        match self.remove_key(key) {
+            Some(key_type) => {
+                self.emit_key_event("del", key, key_type);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 从所有数据结构中删除 key，返回被删除的值的类型
+    fn remove_key(&self, key: &str) -> Option<&'static str> {
+        let removed = [
+            (
+                "string",
+                self.map.remove(key).map(|(_, v)| stats::frame_size(&v)),
+            ),
+            (
+                "hash",
+                self.hmap.remove(key).map(|(_, v)| stats::hash_size(&v)),
+            ),
+            (
+                "list",
+                self.list_map.remove(key).map(|(_, v)| stats::list_size(&v)),
+            ),
+            (
+                "set",
+                self.set_map.remove(key).map(|(_, v)| stats::set_size(&v)),
+            ),
+            (
+                "stream",
+                self.stream_map
+                    .remove(key)
+                    .map(|(_, v)| stats::stream_size(&v)),
+            ),
+            (
+                "zset",
+                self.zset_map.remove(key).map(|(_, v)| stats::zset_size(&v)),
+            ),
+        ];
+        self.stream_groups.remove(key);
+        self.stream_last_ids.remove(key);
+        self.ttl_map.remove(key);
+        self.access_map.remove(key);
+        removed.into_iter().find_map(|(key_type, size)| {
+            size.map(|size| {
+                self.shrink_memory(key.len() + size);
+                key_type
+            })
+        })
+    }
+
+    // key 的类型，key 不存在时返回 None；不检查过期时间
+    fn key_type(&self, key: &str) -> Option<&'static str> {
+        [
+            ("string", self.map.contains_key(key)),
+            ("hash", self.hmap.contains_key(key)),
+            ("list", self.list_map.contains_key(key)),
+            ("set", self.set_map.contains_key(key)),
+            ("stream", self.stream_map.contains_key(key)),
+            ("zset", self.zset_map.contains_key(key)),
+        ]
+        .into_iter()
+        .find_map(|(key_type, exists)| exists.then_some(key_type))
+    }
+
+    // key 是否存在于任意一种数据结构中
+    pub fn exists(&self, key: &str) -> bool {
+        self.expire_if_needed(key);
+        self.map.contains_key(key)
+            || self.hmap.contains_key(key)
+            || self.list_map.contains_key(key)
+            || self.set_map.contains_key(key)
+            || self.stream_map.contains_key(key)
+            || self.zset_map.contains_key(key)
+    }
+
+    // key 最近一次被访问的时间点，key 不存在或还没有访问记录时返回 None
+    pub fn last_access(&self, key: &str) -> Option<Instant> {
+        if !self.exists(key) {
+            return None;
+        }
+        self.access_map.get(key).map(|access| access.last)
     }
 
-    pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|m| m.clone())
+    // 距离 key 最近一次被访问的时长，key 不存在时返回 None
+    pub fn idletime(&self, key: &str) -> Option<Duration> {
+        if !self.exists(key) {
+            return None;
+        }
+        Some(
+            self.last_access(key)
+                .map(|last| last.elapsed())
+                .unwrap_or_default(),
+        )
+    }
+
+    // OBJECT ENCODING 报告的编码。数据的存储方式不随大小变化，
+    // 这里只按 list-max-listpack-size 区分列表的 listpack 和 quicklist，方便兼容性测试
+    pub fn object_encoding(&self, key: &str) -> Option<&'static str> {
+        if !self.exists(key) {
+            return None;
+        }
+        let encoding = match self.key_type(key)? {
+            "string" => "raw",
+            "list" => {
+                let limit = self.config().list_max_listpack_size;
+                let list = self.list_map.get(key)?;
+                let packed = match limit {
+                    n if n >= 0 => list.len() as i64 <= n,
+                    n => {
+                        let bytes: usize = list.iter().map(stats::frame_size).sum();
+                        bytes <= 4096 << (n.max(-5).unsigned_abs() - 1)
+                    }
+                };
+                if packed {
+                    "listpack"
+                } else {
+                    "quicklist"
+                }
+            }
+            "zset" => "skiplist",
+            "stream" => "stream",
+            _ => "hashtable",
+        };
+        Some(encoding)
+    }
+
+    pub fn active_expire(&self) -> bool {
+        self.active_expire.load(Ordering::Relaxed)
+    }
+
+    pub fn set_active_expire(&self, enabled: bool) {
+        self.active_expire.store(enabled, Ordering::Relaxed);
+    }
+
+    // 阻塞等待直到 poll 返回 Some，或者超时返回 None；timeout 为 None 表示一直等待。
+    // 每次有 key 被写入时重新调用 poll 检查。
+    pub async fn block_on_keys<T, F>(
+        &self,
+        keys: &[String],
+        timeout: Option<Duration>,
+        mut poll: F,
+    ) -> Option<T>
+    where
+        F: FnMut(&Backend) -> Option<T>,
+    {
+        if keys.is_empty() {
+            let _guard = self.command_guard().await;
+            return poll(self);
+        }
+        let notifiers: Vec<_> = keys
+            .iter()
+            .map(|key| self.notifiers.entry(key.clone()).or_default().clone())
+            .collect();
+        // 超出 Instant 表示范围的超时和一直等待没有区别
+        let deadline = timeout.and_then(|t| tokio::time::Instant::now().checked_add(t));
+        let ret = loop {
+            // 在检查数据之前注册等待，避免错过检查之后的写入
+            let notified = notifiers.iter().map(|n| Box::pin(n.notified()));
+            let wait = select_all(notified);
+            let ret = {
+                let _guard = self.command_guard().await;
+                poll(self)
+            };
+            if let Some(ret) = ret {
+                break Some(ret);
+            }
+            match deadline {
+                Some(deadline) => {
+                    if tokio::time::timeout_at(deadline, wait).await.is_err() {
+                        break None;
+                    }
+                }
+                None => {
+                    wait.await;
+                }
+            }
+        };
+        drop(notifiers);
+        for key in keys {
+            self.notifiers
+                .remove_if(key, |_, notify| Arc::strong_count(notify) == 1);
+        }
+        ret
+    }
+
+    // 唤醒所有在 key 上阻塞的命令
+    fn notify_key(&self, key: &str) {
+        if let Some(notify) = self.notifiers.get(key) {
+            notify.notify_waiters();
+        }
+    }
+
+    // 访问 key 之前调用：惰性删除已过期的 key，并更新最近访问时间；
+    // 不存在的 key 不记录，避免读不存在的 key 时 access_map 无限增长
+    fn touch(&self, key: &str) {
+        if self.exists(key) {
+            self.record_access(key);
+        }
+    }
+
+    // 写入 key 之前调用：写入之后 key 一定存在，所以即使现在不存在也记录访问
+    fn touch_for_write(&self, key: &str) {
+        self.expire_if_needed(key);
+        self.record_access(key);
+    }
+
+    fn record_access(&self, key: &str) {
+        // 第一次访问时从初始计数开始，之后每次访问才累加频率
+        match self.access_map.get_mut(key) {
+            Some(mut access) => access.touch(),
+            None => {
+                self.access_map.insert(key.to_string(), KeyAccess::new());
+            }
+        }
     }
 }
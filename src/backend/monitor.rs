@@ -0,0 +1,73 @@
+use std::{
+    net::SocketAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tokio::sync::broadcast;
+
+use crate::resp::quote;
+
+use super::Backend;
+
+// MONITOR 连接跟不上命令流时最多缓存的行数，超过后丢弃最旧的
+pub(super) const MONITOR_CAPACITY: usize = 1024;
+
+impl Backend {
+    // 进入 MONITOR 模式的连接从返回的 receiver 中读取之后执行的每一条命令
+    pub fn monitor_subscribe(&self) -> broadcast::Receiver<String> {
+        self.monitor_channel.subscribe()
+    }
+
+    // 没有 MONITOR 连接时不需要格式化命令
+    pub fn has_monitors(&self) -> bool {
+        self.monitor_channel.receiver_count() > 0
+    }
+
+    // 把一条执行过的命令发送给所有 MONITOR 连接，格式和 Redis 相同：
+    // 1234567890.123456 [0 127.0.0.1:12345] "SET" "key" "value"
+    pub fn feed_monitors(&self, addr: Option<SocketAddr>, argv: &[String]) {
+        if !self.has_monitors() {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut line = format!(
+            "{}.{:06} [0 {}]",
+            now.as_secs(),
+            now.subsec_micros(),
+            addr.map_or_else(|| "unknown".to_string(), |addr| addr.to_string())
+        );
+        for arg in argv {
+            line.push(' ');
+            line.push_str(&quote(arg.as_bytes()));
+        }
+        // 所有 receiver 都已经关闭时发送失败，忽略即可
+        let _ = self.monitor_channel.send(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_monitors() {
+        let backend = Backend::new();
+        assert!(!backend.has_monitors());
+        backend.feed_monitors(None, &["get".to_string(), "k".to_string()]);
+
+        let mut rx = backend.monitor_subscribe();
+        assert!(backend.has_monitors());
+        let addr = "127.0.0.1:12345".parse().ok();
+        backend.feed_monitors(
+            addr,
+            &["SET".to_string(), "k".to_string(), "a b\"".to_string()],
+        );
+        let line = rx.try_recv().unwrap();
+        let (ts, rest) = line.split_once(' ').unwrap();
+        assert!(ts.parse::<f64>().is_ok(), "{}", line);
+        assert_eq!(rest, r#"[0 127.0.0.1:12345] "SET" "k" "a b\"""#);
+        assert!(rx.try_recv().is_err());
+    }
+}
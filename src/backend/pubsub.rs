@@ -0,0 +1,268 @@
+use crate::{glob::glob_match, BulkString, KeyspaceFlags, RespFrame, RespPush};
+
+use super::Backend;
+
+// 只有一个数据库，keyspace 通知的频道名中固定使用 0 号数据库
+const KEYSPACE_DB: usize = 0;
+
+impl Backend {
+    // 订阅 channel，返回是否是新的订阅
+    pub fn subscribe(&self, client_id: u64, channel: &str) -> bool {
+        self.channels
+            .entry(channel.to_string())
+            .or_default()
+            .insert(client_id)
+    }
+
+    // 取消订阅，channel 没有订阅者时删除
+    pub fn unsubscribe(&self, client_id: u64, channel: &str) -> bool {
+        let removed = self
+            .channels
+            .get_mut(channel)
+            .is_some_and(|mut subscribers| subscribers.remove(&client_id));
+        self.channels
+            .remove_if(channel, |_, subscribers| subscribers.is_empty());
+        removed
+    }
+
+    // 订阅 glob 模式，返回是否是新的订阅
+    pub fn psubscribe(&self, client_id: u64, pattern: &str) -> bool {
+        self.patterns
+            .entry(pattern.to_string())
+            .or_default()
+            .insert(client_id)
+    }
+
+    // 取消模式订阅，模式没有订阅者时删除
+    pub fn punsubscribe(&self, client_id: u64, pattern: &str) -> bool {
+        let removed = self
+            .patterns
+            .get_mut(pattern)
+            .is_some_and(|mut subscribers| subscribers.remove(&client_id));
+        self.patterns
+            .remove_if(pattern, |_, subscribers| subscribers.is_empty());
+        removed
+    }
+
+    // PUBSUB CHANNELS：至少有一个订阅者的 channel，按名字排序，pattern 为 None 时返回全部
+    pub fn pubsub_channels(&self, pattern: Option<&str>) -> Vec<String> {
+        let mut channels: Vec<String> = self
+            .channels
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|channel| {
+                pattern.is_none_or(|pattern| glob_match(pattern.as_bytes(), channel.as_bytes()))
+            })
+            .collect();
+        channels.sort();
+        channels
+    }
+
+    // PUBSUB NUMSUB：channel 的订阅者数量，不包括模式订阅
+    pub fn pubsub_numsub(&self, channel: &str) -> usize {
+        self.channels
+            .get(channel)
+            .map_or(0, |subscribers| subscribers.len())
+    }
+
+    // PUBSUB NUMPAT：至少有一个订阅者的模式的数量
+    pub fn pubsub_numpat(&self) -> usize {
+        self.patterns.len()
+    }
+
+    // 连接断开或执行 RESET 时取消它的所有订阅
+    pub fn unsubscribe_all(&self, client_id: u64) {
+        for map in [&self.channels, &self.patterns] {
+            map.retain(|_, subscribers| {
+                subscribers.remove(&client_id);
+                !subscribers.is_empty()
+            });
+        }
+    }
+
+    // 把 ["message", channel, message] 推送给 channel 的订阅者，
+    // 把 ["pmessage", pattern, channel, message] 推送给每个匹配的模式的订阅者，
+    // 返回推送成功的次数：同时订阅了 channel 和匹配的模式的连接会收到多次，也计算多次
+    pub fn publish(&self, channel: &str, message: &str) -> usize {
+        // 先复制订阅者列表，推送时不持有 channels / patterns 的锁
+        let mut deliveries: Vec<(u64, RespFrame)> = Vec::new();
+        if let Some(subscribers) = self.channels.get(channel) {
+            let frame: RespFrame = RespPush::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ])
+            .into();
+            deliveries.extend(subscribers.iter().map(|id| (*id, frame.clone())));
+        }
+        for entry in self.patterns.iter() {
+            if !glob_match(entry.key().as_bytes(), channel.as_bytes()) {
+                continue;
+            }
+            let frame: RespFrame = RespPush::new(vec![
+                BulkString::new("pmessage").into(),
+                BulkString::new(entry.key().as_str()).into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ])
+            .into();
+            deliveries.extend(entry.value().iter().map(|id| (*id, frame.clone())));
+        }
+        deliveries
+            .into_iter()
+            .filter(|(id, frame)| self.push_to_client(*id, frame.clone()))
+            .count()
+    }
+
+    // key 被修改后发布 keyspace 通知：K 发布到 __keyspace@<db>__:<key>，消息为事件名；
+    // E 发布到 __keyevent@<db>__:<event>，消息为 key。事件所属的类型没有开启时不发布
+    pub(super) fn notify_keyspace(&self, event: &str, key: &str) {
+        let flags = self.config().notify_keyspace_events;
+        if flags.is_empty() || !flags.contains(event_class(event)) {
+            return;
+        }
+        if flags.contains(KeyspaceFlags::KEYSPACE) {
+            self.publish(&format!("__keyspace@{}__:{}", KEYSPACE_DB, key), event);
+        }
+        if flags.contains(KeyspaceFlags::KEYEVENT) {
+            self.publish(&format!("__keyevent@{}__:{}", KEYSPACE_DB, event), key);
+        }
+    }
+}
+
+// 事件名对应的类型，未知的事件按通用命令处理
+fn event_class(event: &str) -> KeyspaceFlags {
+    match event {
+        "set" | "setrange" | "setbit" | "incrby" | "incrbyfloat" | "append" => {
+            KeyspaceFlags::STRING
+        }
+        "lpush" | "rpush" | "lpop" | "rpop" | "linsert" | "lset" | "lrem" | "ltrim" => {
+            KeyspaceFlags::LIST
+        }
+        "sadd" | "srem" | "spop" | "sinterstore" | "sunionstore" | "sdiffstore" => {
+            KeyspaceFlags::SET
+        }
+        "hset" | "hdel" | "hincrby" | "hincrbyfloat" => KeyspaceFlags::HASH,
+        "zadd" | "zincr" | "zrem" | "zpopmin" | "zpopmax" | "zunionstore" | "zinterstore"
+        | "zdiffstore" => KeyspaceFlags::ZSET,
+        "xadd" | "xdel" | "xtrim" | "xgroup-create" => KeyspaceFlags::STREAM,
+        "expired" => KeyspaceFlags::EXPIRED,
+        "evicted" => KeyspaceFlags::EVICTED,
+        "keymiss" => KeyspaceFlags::KEY_MISS,
+        "new" => KeyspaceFlags::NEW,
+        _ => KeyspaceFlags::GENERIC,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(channel: &str, message: &str) -> RespFrame {
+        RespPush::new(vec![
+            BulkString::new("message").into(),
+            BulkString::new(channel).into(),
+            BulkString::new(message).into(),
+        ])
+        .into()
+    }
+
+    #[test]
+    fn test_publish_reaches_subscribers() {
+        let backend = Backend::new();
+        let (_, mut rx) = backend.register_client(1, None).unwrap();
+        assert!(backend.subscribe(1, "news"));
+        assert!(!backend.subscribe(1, "news"));
+        assert_eq!(backend.publish("news", "hi"), 1);
+        assert_eq!(backend.publish("other", "hi"), 0);
+        assert_eq!(rx.try_recv().unwrap(), message("news", "hi"));
+
+        assert!(backend.unsubscribe(1, "news"));
+        assert_eq!(backend.publish("news", "hi"), 0);
+    }
+
+    #[test]
+    fn test_publish_reaches_pattern_subscribers() {
+        let backend = Backend::new();
+        let (_, mut rx) = backend.register_client(1, None).unwrap();
+        assert!(backend.psubscribe(1, "news.*"));
+        assert!(!backend.psubscribe(1, "news.*"));
+        assert_eq!(backend.publish("news.tech", "hi"), 1);
+        assert_eq!(backend.publish("weather", "hi"), 0);
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            RespPush::new(vec![
+                BulkString::new("pmessage").into(),
+                BulkString::new("news.*").into(),
+                BulkString::new("news.tech").into(),
+                BulkString::new("hi").into(),
+            ])
+            .into()
+        );
+
+        // 同时订阅 channel 和匹配的模式时收到两次
+        backend.subscribe(1, "news.tech");
+        assert_eq!(backend.publish("news.tech", "hi"), 2);
+        let messages: Vec<RespFrame> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], message("news.tech", "hi"));
+
+        assert!(backend.punsubscribe(1, "news.*"));
+        assert!(!backend.punsubscribe(1, "news.*"));
+        assert_eq!(backend.publish("news.tech", "hi"), 1);
+    }
+
+    #[test]
+    fn test_pubsub_introspection_tracks_disconnects() {
+        let backend = Backend::new();
+        backend.register_client(1, None).unwrap();
+        backend.register_client(2, None).unwrap();
+        backend.subscribe(1, "news.tech");
+        backend.subscribe(2, "news.tech");
+        backend.subscribe(2, "weather");
+        backend.psubscribe(1, "news.*");
+        backend.psubscribe(2, "news.*");
+        assert_eq!(backend.pubsub_channels(None), ["news.tech", "weather"]);
+        assert_eq!(backend.pubsub_channels(Some("news.*")), ["news.tech"]);
+        assert_eq!(backend.pubsub_numsub("news.tech"), 2);
+        assert_eq!(backend.pubsub_numpat(), 1);
+
+        // 断开的连接从订阅表中移除，没有订阅者的 channel 和模式不再列出
+        backend.unregister_client(2);
+        assert_eq!(backend.pubsub_channels(None), ["news.tech"]);
+        assert_eq!(backend.pubsub_numsub("news.tech"), 1);
+        assert_eq!(backend.pubsub_numsub("weather"), 0);
+        assert_eq!(backend.pubsub_numpat(), 1);
+        backend.unregister_client(1);
+        assert!(backend.pubsub_channels(None).is_empty());
+        assert_eq!(backend.pubsub_numpat(), 0);
+    }
+
+    #[test]
+    fn test_notify_keyspace() {
+        let backend = Backend::new();
+        let (_, mut rx) = backend.register_client(1, None).unwrap();
+        backend.subscribe(1, "__keyspace@0__:mykey");
+        backend.subscribe(1, "__keyevent@0__:set");
+        backend.subscribe(1, "__keyevent@0__:lpush");
+
+        // 默认关闭
+        backend.notify_keyspace("set", "mykey");
+        assert!(rx.try_recv().is_err());
+
+        backend.set_config("notify-keyspace-events", "KE$").unwrap();
+        backend.notify_keyspace("set", "mykey");
+        let messages: Vec<RespFrame> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                message("__keyspace@0__:mykey", "set"),
+                message("__keyevent@0__:set", "mykey"),
+            ]
+        );
+
+        // 没有开启 l，列表事件不发布
+        backend.notify_keyspace("lpush", "mykey");
+        assert!(rx.try_recv().is_err());
+    }
+}
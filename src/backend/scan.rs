@@ -0,0 +1,330 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+    time::Instant,
+};
+
+use dashmap::DashMap;
+
+use crate::{glob::glob_match, RespFrame};
+
+use super::Backend;
+
+// SCAN 系列命令默认每次检查的元素数量
+pub const DEFAULT_SCAN_COUNT: usize = 10;
+
+// 遍历 key 时假想的哈希表最少的槽位数
+const MIN_SCAN_SLOTS: u64 = 4;
+
+impl Backend {
+    // 增量遍历所有未过期的 key，返回 (下一个 cursor, keys)，cursor 为 0 表示遍历结束。
+    // 槽位的划分和遍历顺序见 SlotBatch
+    pub fn scan_cursor(&self, cursor: u64, count: usize) -> (u64, Vec<String>) {
+        let total = self.map.len()
+            + self.hmap.len()
+            + self.list_map.len()
+            + self.set_map.len()
+            + self.stream_map.len()
+            + self.zset_map.len();
+        let mut batch = SlotBatch::new(cursor, total, count);
+        let now = Instant::now();
+        let mut offer = |key: &str| {
+            batch.offer(key, || {
+                self.ttl_map
+                    .get(key)
+                    .is_none_or(|deadline| *deadline > now)
+                    .then_some(())
+            })
+        };
+        offer_keys(&self.map, &mut offer);
+        offer_keys(&self.hmap, &mut offer);
+        offer_keys(&self.list_map, &mut offer);
+        offer_keys(&self.set_map, &mut offer);
+        offer_keys(&self.stream_map, &mut offer);
+        offer_keys(&self.zset_map, &mut offer);
+        let (cursor, keys) = batch.finish();
+        (cursor, keys.into_iter().map(|(key, _)| key).collect())
+    }
+
+    // SCAN：在 scan_cursor 的基础上按 pattern 和类型过滤，过滤发生在选出本次的槽位之后
+    pub fn scan(
+        &self,
+        cursor: u64,
+        pattern: Option<&str>,
+        key_type: Option<&str>,
+        count: usize,
+    ) -> (u64, Vec<String>) {
+        let (cursor, mut keys) = self.scan_cursor(cursor, count);
+        keys.retain(|key| {
+            pattern.is_none_or(|p| glob_match(p.as_bytes(), key.as_bytes()))
+                && key_type.is_none_or(|t| {
+                    self.key_type(key)
+                        .is_some_and(|kt| kt.eq_ignore_ascii_case(t))
+                })
+        });
+        (cursor, keys)
+    }
+
+    // 增量遍历 hash 的 field，返回 (下一个 cursor, [(field, value)])，cursor 为 0 表示遍历结束。
+    // 只复制本次返回的 field
+    pub fn hscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: usize,
+    ) -> (u64, Vec<(String, RespFrame)>) {
+        self.touch(key);
+        let Some(hash) = self.hmap.get(key) else {
+            return (0, Vec::new());
+        };
+        let mut batch = SlotBatch::new(cursor, hash.len(), count);
+        for entry in hash.iter() {
+            batch.offer(entry.key(), || Some(entry.value().clone()));
+        }
+        drop(hash);
+        filter_pattern(batch.finish(), pattern)
+    }
+
+    // 增量遍历集合的成员
+    pub fn sscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: usize,
+    ) -> (u64, Vec<String>) {
+        self.touch(key);
+        let Some(set) = self.set_map.get(key) else {
+            return (0, Vec::new());
+        };
+        let mut batch = SlotBatch::new(cursor, set.len(), count);
+        for member in set.iter() {
+            batch.offer(member.key(), || Some(()));
+        }
+        drop(set);
+        let (cursor, items) = filter_pattern(batch.finish(), pattern);
+        (
+            cursor,
+            items.into_iter().map(|(member, _)| member).collect(),
+        )
+    }
+}
+
+// 每次调用必须得到相同的哈希值，DefaultHasher::new 使用固定的密钥
+fn key_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn offer_keys<V>(map: &DashMap<String, V>, offer: &mut impl FnMut(&str)) {
+    for entry in map.iter() {
+        offer(entry.key());
+    }
+}
+
+// 和 Redis 的 dictScan 一样，把元素按名字的哈希值分到 2^n 个槽位（n 随元素的数量变化），
+// cursor 是槽位号，按高位加一（reverse binary）的顺序遍历槽位：槽位数在两次调用之间翻倍或减半时，
+// 已经遍历过的槽位仍然对应已经遍历过的元素，因此整个遍历期间一直存在的元素至少返回一次，
+// 槽位数不减少时不会重复返回。每次至少返回 count 个元素（剩余的不足时返回全部），同一个槽位的元素一起返回。
+// 元素逐个传入，只保留 cursor 之后最靠前、刚好够 count 个的槽位，不需要复制和排序全部元素
+struct SlotBatch<T> {
+    mask: u64,
+    start: u64,
+    count: usize,
+    // 按遍历顺序中的位置保存选中的槽位
+    slots: BTreeMap<u64, Vec<(String, T)>>,
+    len: usize,
+    // 是否丢弃过更靠后的槽位，决定遍历是否结束
+    truncated: bool,
+}
+
+impl<T> SlotBatch<T> {
+    fn new(cursor: u64, total: usize, count: usize) -> Self {
+        let mask = (total as u64).next_power_of_two().max(MIN_SCAN_SLOTS) - 1;
+        let mut batch = Self {
+            mask,
+            start: 0,
+            count: count.max(1),
+            slots: BTreeMap::new(),
+            len: 0,
+            truncated: false,
+        };
+        batch.start = batch.position(cursor);
+        batch
+    }
+
+    // 不属于槽位号的高位置 1 后反转，得到槽位在遍历顺序中的位置
+    fn position(&self, slot: u64) -> u64 {
+        (slot | !self.mask).reverse_bits()
+    }
+
+    // 传入一个元素，只在它可能被返回时调用 item 取出它的值，item 返回 None 表示跳过
+    fn offer(&mut self, name: &str, item: impl FnOnce() -> Option<T>) {
+        let pos = self.position(key_hash(name));
+        if pos < self.start {
+            return;
+        }
+        if self.len >= self.count
+            && self
+                .slots
+                .last_key_value()
+                .is_some_and(|(&last, _)| pos > last)
+        {
+            self.truncated = true;
+            return;
+        }
+        let Some(item) = item() else {
+            return;
+        };
+        self.slots
+            .entry(pos)
+            .or_default()
+            .push((name.to_string(), item));
+        self.len += 1;
+        // 去掉最后一个槽位后仍然够 count 个时丢弃它
+        while let Some(last) = self.slots.last_entry() {
+            if self.len - last.get().len() < self.count {
+                break;
+            }
+            self.len -= last.remove().len();
+            self.truncated = true;
+        }
+    }
+
+    fn finish(self) -> (u64, Vec<(String, T)>) {
+        // 下一个 cursor 是最后一个返回的槽位之后的槽位；剩下的槽位都已经返回时为 0
+        let next = match self.slots.last_key_value() {
+            Some((&last, _)) if self.truncated => last.wrapping_add(1).reverse_bits(),
+            _ => 0,
+        };
+        (next, self.slots.into_values().flatten().collect())
+    }
+}
+
+// HSCAN / SSCAN 在选出本次的槽位之后再按 pattern 过滤
+fn filter_pattern<T>(
+    (next, mut items): (u64, Vec<(String, T)>),
+    pattern: Option<&str>,
+) -> (u64, Vec<(String, T)>) {
+    items.retain(|(name, _)| pattern.is_none_or(|p| glob_match(p.as_bytes(), name.as_bytes())));
+    (next, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::BulkString;
+
+    use super::*;
+
+    fn scan_all(backend: &Backend, count: usize) -> Vec<String> {
+        let mut cursor = 0;
+        let mut keys = Vec::new();
+        loop {
+            let (next, batch) = backend.scan_cursor(cursor, count);
+            keys.extend(batch);
+            if next == 0 {
+                return keys;
+            }
+            cursor = next;
+        }
+    }
+
+    #[test]
+    fn test_scan_cursor_returns_every_key_once() {
+        let backend = Backend::new();
+        for i in 0..100 {
+            backend.set(format!("key:{}", i), BulkString::new("v").into());
+        }
+        backend.sadd("set".to_string(), vec!["m".to_string()]);
+        let keys = scan_all(&backend, 7);
+        assert_eq!(keys.len(), 101);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 101);
+
+        assert_eq!(Backend::new().scan_cursor(0, 10), (0, Vec::new()));
+    }
+
+    #[test]
+    fn test_scan_cursor_survives_growth_and_deletes() {
+        let backend = Backend::new();
+        for i in 0..50 {
+            backend.set(format!("old:{}", i), BulkString::new("v").into());
+        }
+        let mut seen = Vec::new();
+        let (mut cursor, batch) = backend.scan_cursor(0, 10);
+        seen.extend(batch);
+
+        // 遍历中途 key 的数量变多，槽位数翻倍
+        for i in 0..200 {
+            backend.set(format!("new:{}", i), BulkString::new("v").into());
+        }
+        while cursor != 0 {
+            let (next, batch) = backend.scan_cursor(cursor, 10);
+            seen.extend(batch);
+            cursor = next;
+            // 再删除一部分新 key，槽位数减半
+            if cursor != 0 && backend.exists("new:0") {
+                for i in 0..200 {
+                    backend.del(&format!("new:{}", i));
+                }
+            }
+        }
+
+        let old: HashSet<&String> = seen.iter().filter(|k| k.starts_with("old:")).collect();
+        assert_eq!(old.len(), 50);
+    }
+
+    #[test]
+    fn test_slot_batch_keeps_only_the_next_slots() {
+        let names: Vec<String> = (0..1000).map(|i| format!("key:{}", i)).collect();
+        let mut batch = SlotBatch::new(0, names.len(), 5);
+        let mut kept = 0;
+        for name in &names {
+            batch.offer(name, || Some(()));
+            kept = kept.max(batch.len);
+        }
+        // 逐个传入时只保留够 count 个的槽位
+        assert!(kept < 10);
+
+        // 和对全部元素排序后取最靠前的槽位的结果相同
+        let mut positions: Vec<(u64, &String)> = names
+            .iter()
+            .map(|name| (batch.position(key_hash(name)), name))
+            .collect();
+        positions.sort();
+        let last = positions[4].0;
+        let expected: HashSet<&String> = positions
+            .iter()
+            .take_while(|(pos, _)| *pos <= last)
+            .map(|(_, name)| *name)
+            .collect();
+        let (next, items) = batch.finish();
+        assert_eq!(next, last.wrapping_add(1).reverse_bits());
+        assert_eq!(
+            items.iter().map(|(name, _)| name).collect::<HashSet<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_hscan_uses_slot_cursor() {
+        let backend = Backend::new();
+        let field = |i: usize| (format!("f{}", i), RespFrame::from(BulkString::new("v")));
+        backend.hset_fields("h".to_string(), (0..20).map(field).collect());
+        let (mut cursor, batch) = backend.hscan("h", 0, None, 5);
+        let mut seen: Vec<String> = batch.into_iter().map(|(f, _)| f).collect();
+        // 遍历中途 field 变多，原有的 field 仍然都会返回，且不会重复
+        backend.hset_fields("h".to_string(), (20..100).map(field).collect());
+        while cursor != 0 {
+            let (next, batch) = backend.hscan("h", cursor, None, 5);
+            seen.extend(batch.into_iter().map(|(f, _)| f));
+            cursor = next;
+        }
+        let unique: HashSet<&String> = seen.iter().collect();
+        assert_eq!(unique.len(), seen.len());
+        assert!((0..20).all(|i| unique.contains(&format!("f{}", i))));
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[cfg(feature = "scripting")]
+use dashmap::DashMap;
+use tokio::{
+    sync::{watch, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::Instant,
+};
+
+use super::Backend;
+#[cfg(feature = "scripting")]
+use crate::sha1::sha1_hex;
+#[cfg(feature = "scripting")]
+use crate::RespFrame;
+
+#[derive(Debug)]
+pub(super) struct ScriptState {
+    // 脚本执行时持有写锁，普通命令执行时持有读锁，保证脚本执行期间没有其它命令穿插。
+    // 用 tokio 的锁，等待的连接不会阻塞运行时的工作线程
+    lock: Arc<RwLock<()>>,
+    // 正在执行的脚本开始的时间，没有脚本执行时为 None
+    running: watch::Sender<Option<Instant>>,
+    // SCRIPT KILL 请求终止正在执行的脚本
+    kill: AtomicBool,
+    // 正在执行的脚本已经执行过写命令，不能再被 SCRIPT KILL 终止
+    wrote: AtomicBool,
+    // SCRIPT LOAD 缓存的脚本，key 为小写十六进制的 SHA1
+    #[cfg(feature = "scripting")]
+    scripts: DashMap<String, String>,
+}
+
+impl Default for ScriptState {
+    fn default() -> Self {
+        Self {
+            lock: Arc::default(),
+            running: watch::Sender::new(None),
+            kill: AtomicBool::new(false),
+            wrote: AtomicBool::new(false),
+            #[cfg(feature = "scripting")]
+            scripts: DashMap::new(),
+        }
+    }
+}
+
+// SCRIPT KILL 的结果
+#[derive(Debug, PartialEq)]
+pub enum ScriptKill {
+    Killed,
+    NotBusy,
+    Unkillable,
+}
+
+impl Backend {
+    // 等待正在执行的脚本结束
+    pub async fn command_guard(&self) -> RwLockReadGuard<'_, ()> {
+        self.script_state.lock.read().await
+    }
+
+    // 独占执行，和脚本一样等待其它命令结束，期间没有其它命令穿插
+    pub async fn script_guard(&self) -> RwLockWriteGuard<'_, ()> {
+        self.script_state.lock.write().await
+    }
+
+    // 脚本执行超过 busy-reply-threshold 之后不再等待，返回 None，调用方回复 BUSY
+    pub async fn command_guard_unless_busy(&self) -> Option<RwLockReadGuard<'_, ()>> {
+        self.unless_busy(self.command_guard()).await
+    }
+
+    // 独占执行的命令使用，同样在脚本忙时返回 None
+    pub async fn script_guard_unless_busy(&self) -> Option<RwLockWriteGuard<'_, ()>> {
+        self.unless_busy(self.script_guard()).await
+    }
+
+    async fn unless_busy<F: Future>(&self, fut: F) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            ret = fut => Some(ret),
+            _ = self.script_busy() => None,
+        }
+    }
+
+    // 有脚本执行超过 busy-reply-threshold 时返回
+    async fn script_busy(&self) {
+        let mut running = self.script_state.running.subscribe();
+        loop {
+            let started = *running.borrow_and_update();
+            match started {
+                Some(started) => {
+                    let threshold = Duration::from_millis(self.config().busy_reply_threshold);
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(started + threshold) => return,
+                        _ = running.changed() => {}
+                    }
+                }
+                None => {
+                    // sender 和 Backend 一起释放，不会出错
+                    let _ = running.changed().await;
+                }
+            }
+        }
+    }
+
+    pub fn script_kill(&self) -> ScriptKill {
+        let state = &self.script_state;
+        if state.running.borrow().is_none() {
+            return ScriptKill::NotBusy;
+        }
+        if state.wrote.load(Ordering::Relaxed) {
+            return ScriptKill::Unkillable;
+        }
+        state.kill.store(true, Ordering::Relaxed);
+        ScriptKill::Killed
+    }
+
+    // 解释器定期检查，返回 true 时终止脚本
+    pub fn script_kill_requested(&self) -> bool {
+        self.script_state.kill.load(Ordering::Relaxed)
+    }
+
+    // 脚本执行了写命令
+    pub fn script_mark_wrote(&self) {
+        self.script_state.wrote.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl Backend {
+    // 持有独占锁，在 blocking 线程上执行脚本，执行期间不占用运行时的工作线程。
+    // 已经有脚本执行超过 busy-reply-threshold 时不等待，返回 None
+    pub async fn run_script<F>(&self, f: F) -> Option<RespFrame>
+    where
+        F: FnOnce(&Backend) -> RespFrame + Send + 'static,
+    {
+        let guard = self
+            .unless_busy(self.script_state.lock.clone().write_owned())
+            .await?;
+        let backend = self.clone();
+        let ret = tokio::task::spawn_blocking(move || {
+            let _guard = guard;
+            let _running = RunningScript::start(&backend);
+            f(&backend)
+        })
+        .await;
+        Some(ret.unwrap_or_else(|e| RespFrame::error(format!("ERR script failed: {}", e))))
+    }
+}
+
+// 记录脚本的执行状态，脚本结束（包括 panic）时清除
+#[cfg(feature = "scripting")]
+struct RunningScript<'a>(&'a ScriptState);
+
+#[cfg(feature = "scripting")]
+impl<'a> RunningScript<'a> {
+    fn start(backend: &'a Backend) -> Self {
+        let state = &backend.script_state;
+        state.kill.store(false, Ordering::Relaxed);
+        state.wrote.store(false, Ordering::Relaxed);
+        state.running.send_replace(Some(Instant::now()));
+        Self(state)
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl Drop for RunningScript<'_> {
+    fn drop(&mut self) {
+        self.0.running.send_replace(None);
+        self.0.kill.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl Backend {
+    // 缓存脚本并返回它的 SHA1，EVAL 执行过的脚本也会被缓存
+    pub fn script_load(&self, script: &str) -> String {
+        let sha = sha1_hex(script.as_bytes());
+        self.script_state
+            .scripts
+            .entry(sha.clone())
+            .or_insert_with(|| script.to_string());
+        sha
+    }
+
+    pub fn script_get(&self, sha: &str) -> Option<String> {
+        self.script_state
+            .scripts
+            .get(&sha.to_ascii_lowercase())
+            .map(|s| s.clone())
+    }
+
+    pub fn script_exists(&self, sha: &str) -> bool {
+        self.script_state
+            .scripts
+            .contains_key(&sha.to_ascii_lowercase())
+    }
+
+    pub fn script_flush(&self) {
+        self.script_state.scripts.clear();
+    }
+}
@@ -0,0 +1,70 @@
+use dashmap::DashSet;
+
+use super::Backend;
+
+impl Backend {
+    // 添加成员，返回新增的数量
+    pub fn sadd(&self, key: String, members: Vec<String>) -> usize {
+        self.touch_for_write(&key);
+        let key_len = key.len();
+        let set = self.set_map.entry(key.clone()).or_insert_with(|| {
+            self.grow_memory(key_len);
+            DashSet::new()
+        });
+        let added = members
+            .into_iter()
+            .filter(|member| set.insert(member.clone()))
+            .inspect(|member| self.grow_memory(member.len()))
+            .count();
+        drop(set);
+        if added > 0 {
+            self.emit_key_event("sadd", &key, "set");
+        }
+        added
+    }
+
+    pub fn smembers(&self, key: &str) -> Option<DashSet<String>> {
+        self.touch(key);
+        self.set_map.get(key).map(|set| set.clone())
+    }
+
+    // 所有 keys 的交集的大小，不存在的 key 视为空集合；limit 大于 0 时数到 limit 就停止
+    pub fn sintercard(&self, keys: &[&str], limit: usize) -> usize {
+        let mut smallest: Option<(&str, usize)> = None;
+        for &key in keys {
+            self.touch(key);
+            let Some(len) = self.set_map.get(key).map(|set| set.len()) else {
+                return 0;
+            };
+            if smallest.is_none_or(|(_, min)| len < min) {
+                smallest = Some((key, len));
+            }
+        }
+        let Some((first, _)) = smallest else {
+            return 0;
+        };
+        // 持有所有集合的读锁，直接遍历最小集合的成员，不复制。DashMap 的读锁不会被等待中的写锁阻塞，
+        // 同时持有同一个分片的多个读锁不会死锁
+        let Some(first_set) = self.set_map.get(first) else {
+            return 0;
+        };
+        let Some(others) = keys
+            .iter()
+            .filter(|&&key| key != first)
+            .map(|key| self.set_map.get(*key))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return 0;
+        };
+        let mut count = 0;
+        for member in first_set.iter() {
+            if others.iter().all(|set| set.contains(member.key())) {
+                count += 1;
+                if count == limit {
+                    break;
+                }
+            }
+        }
+        count
+    }
+}
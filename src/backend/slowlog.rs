@@ -0,0 +1,160 @@
+use std::{
+    collections::VecDeque,
+    sync::MutexGuard,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::Backend;
+
+// 慢日志中最多记录的参数个数和单个参数的最大长度，和 Redis 一致
+const SLOWLOG_MAX_ARGC: usize = 32;
+const SLOWLOG_MAX_ARGLEN: usize = 128;
+
+// 慢日志的一条记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowLogEntry {
+    pub id: u64,
+    // 命令开始执行的 unix 时间戳（秒）
+    pub timestamp: u64,
+    pub duration_us: u64,
+    pub argv: Vec<String>,
+    pub client_addr: String,
+    pub client_name: String,
+}
+
+// 最新的记录在最前面，超过 slowlog-max-len 时丢弃最旧的记录
+#[derive(Debug, Default)]
+pub struct SlowLog {
+    next_id: u64,
+    entries: VecDeque<SlowLogEntry>,
+}
+
+impl Backend {
+    // 命令执行时间超过 slowlog-log-slower-than 时记入慢日志，返回是否记录
+    pub fn slowlog_record(
+        &self,
+        duration: Duration,
+        argv: impl FnOnce() -> Vec<String>,
+        client_addr: String,
+        client_name: String,
+    ) -> bool {
+        let (threshold, max_len) = {
+            let config = self.config();
+            (config.slowlog_log_slower_than, config.slowlog_max_len)
+        };
+        let duration_us = duration.as_micros() as u64;
+        if threshold < 0 || duration_us < threshold as u64 {
+            return false;
+        }
+        let timestamp = (SystemTime::now() - duration)
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut slowlog = self.slowlog();
+        let id = slowlog.next_id;
+        slowlog.next_id += 1;
+        slowlog.entries.push_front(SlowLogEntry {
+            id,
+            timestamp,
+            duration_us,
+            argv: truncate_argv(argv()),
+            client_addr,
+            client_name,
+        });
+        slowlog.entries.truncate(max_len);
+        true
+    }
+
+    // 最新的 count 条记录，count 为 None 时返回全部
+    pub fn slowlog_get(&self, count: Option<usize>) -> Vec<SlowLogEntry> {
+        self.slowlog()
+            .entries
+            .iter()
+            .take(count.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    pub fn slowlog_len(&self) -> usize {
+        self.slowlog().entries.len()
+    }
+
+    // 清空记录，ID 继续递增
+    pub fn slowlog_reset(&self) {
+        self.slowlog().entries.clear();
+    }
+
+    fn slowlog(&self) -> MutexGuard<'_, SlowLog> {
+        self.slowlog.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// 参数过多或过长时截断，避免慢日志占用过多内存
+fn truncate_argv(mut argv: Vec<String>) -> Vec<String> {
+    if argv.len() > SLOWLOG_MAX_ARGC {
+        let more = argv.len() - SLOWLOG_MAX_ARGC + 1;
+        argv.truncate(SLOWLOG_MAX_ARGC - 1);
+        argv.push(format!("... ({} more arguments)", more));
+    }
+    for arg in argv.iter_mut() {
+        if arg.len() > SLOWLOG_MAX_ARGLEN {
+            let mut end = SLOWLOG_MAX_ARGLEN;
+            while !arg.is_char_boundary(end) {
+                end -= 1;
+            }
+            let more = arg.len() - end;
+            arg.truncate(end);
+            arg.push_str(&format!("... ({} more bytes)", more));
+        }
+    }
+    argv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(backend: &Backend, us: u64, cmd: &str) -> bool {
+        backend.slowlog_record(
+            Duration::from_micros(us),
+            || vec![cmd.to_string()],
+            "127.0.0.1:1234".to_string(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_slowlog_threshold_and_max_len() {
+        let backend = Backend::new();
+        backend
+            .set_config("slowlog-log-slower-than", "100")
+            .unwrap();
+        backend.set_config("slowlog-max-len", "2").unwrap();
+        assert!(!record(&backend, 99, "fast"));
+        assert!(record(&backend, 100, "a"));
+        assert!(record(&backend, 200, "b"));
+        assert!(record(&backend, 300, "c"));
+
+        let entries = backend.slowlog_get(None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].id, entries[0].argv[0].as_str()), (2, "c"));
+        assert_eq!((entries[1].id, entries[1].argv[0].as_str()), (1, "b"));
+        assert_eq!(backend.slowlog_get(Some(1)).len(), 1);
+
+        backend.slowlog_reset();
+        assert_eq!(backend.slowlog_len(), 0);
+        backend.set_config("slowlog-log-slower-than", "-1").unwrap();
+        assert!(!record(&backend, 1_000_000, "disabled"));
+    }
+
+    #[test]
+    fn test_truncate_argv() {
+        let argv: Vec<String> = (0..40).map(|i| i.to_string()).collect();
+        let argv = truncate_argv(argv);
+        assert_eq!(argv.len(), 32);
+        assert_eq!(argv[31], "... (9 more arguments)");
+
+        let argv = truncate_argv(vec!["x".repeat(130)]);
+        assert_eq!(argv[0], format!("{}... (2 more bytes)", "x".repeat(128)));
+    }
+}
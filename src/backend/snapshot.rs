@@ -0,0 +1,753 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::BytesMut;
+use dashmap::{DashMap, DashSet};
+use thiserror::Error;
+
+use crate::{BulkString, RespArray, RespDecoder, RespEncoder, RespFrame};
+
+use super::{stream::last_id, Backend, ConsumerGroup, PendingEntry, StreamId, ZSet};
+
+// 快照文件的格式：SNAPSHOT_MAGIC + payload + payload 的 CRC64（小端序 8 字节）。
+// payload 由一串 RESP 数组组成，每个 key 一条：[type, key, 过期的 unix 毫秒数（-1 表示不过期）, value]。
+// stream 的 value 为 [entries, groups, 最后生成的 ID]，旧的快照没有最后一项
+pub const SNAPSHOT_MAGIC: &[u8] = b"SREDIS001";
+const CHECKSUM_LEN: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error(
+        "Bad snapshot header, expecting {:?}",
+        String::from_utf8_lossy(SNAPSHOT_MAGIC)
+    )]
+    BadHeader,
+    #[error("Snapshot file is truncated")]
+    Truncated,
+    #[error("Snapshot checksum mismatch: expected {expected:016x}, got {actual:016x}")]
+    Checksum { expected: u64, actual: u64 },
+    #[error("Corrupt snapshot record: {0}")]
+    Corrupt(String),
+    #[error("Background save already in progress")]
+    InProgress,
+}
+
+// SAVE / BGSAVE 的状态，last_save 是最近一次保存成功的 unix 秒数，启动时为启动时间。
+// save_in_progress 同时用于 SAVE 和 BGSAVE，两者都写同一个临时文件，不能同时进行
+#[derive(Debug)]
+pub(super) struct SaveState {
+    last_save: AtomicU64,
+    save_in_progress: AtomicBool,
+}
+
+impl Default for SaveState {
+    fn default() -> Self {
+        Self {
+            last_save: AtomicU64::new(unix_secs()),
+            save_in_progress: AtomicBool::new(false),
+        }
+    }
+}
+
+// 刷新 path 所在的目录，让重命名落盘；Windows 上不能打开目录，跳过
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+// 解析出来的一个 key 的值
+enum Value {
+    String(RespFrame),
+    Hash(DashMap<String, RespFrame>),
+    List(VecDeque<RespFrame>),
+    Set(DashSet<String>),
+    ZSet(ZSet),
+    Stream(
+        BTreeMap<StreamId, Vec<(String, RespFrame)>>,
+        HashMap<String, ConsumerGroup>,
+        Option<StreamId>,
+    ),
+}
+
+// CRC-64/Jones（反射多项式 0x95ac9329ac4bc9b5），和 Redis RDB 文件使用的算法一致
+const CRC64_TABLE: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x95ac9329ac4bc9b5
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc64(data: &[u8]) -> u64 {
+    data.iter().fold(0, |crc, &b| {
+        CRC64_TABLE[((crc ^ b as u64) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+impl Backend {
+    // 把所有未过期的 key 写入 path，先写临时文件再重命名，写入中途失败不会破坏已有的快照。
+    // 重命名之前把临时文件刷到磁盘，重命名之后再刷新所在的目录，掉电后不会留下空的或者不完整的快照
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&self.snapshot_bytes())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp, path)?;
+        sync_dir(path)?;
+        Ok(())
+    }
+
+    // 快照文件的完整内容，SYNC 直接把它发送给副本
+    pub fn snapshot_bytes(&self) -> Vec<u8> {
+        let payload = self.dump_payload();
+        let mut data = Vec::with_capacity(SNAPSHOT_MAGIC.len() + payload.len() + CHECKSUM_LEN);
+        data.extend_from_slice(SNAPSHOT_MAGIC);
+        data.extend_from_slice(&payload);
+        data.extend_from_slice(&crc64(&payload).to_le_bytes());
+        data
+    }
+
+    // SAVE：在当前线程写入配置的快照文件，另一个保存进行中时返回错误
+    pub fn save(&self) -> Result<(), SnapshotError> {
+        self.begin_save()?;
+        let ret = self.save_snapshot();
+        self.end_save();
+        ret
+    }
+
+    // BGSAVE：在后台线程写入快照，同一时间只允许一个保存
+    pub fn bgsave(&self) -> Result<JoinHandle<Result<(), SnapshotError>>, SnapshotError> {
+        self.begin_save()?;
+        let backend = self.clone();
+        Ok(thread::spawn(move || {
+            let ret = backend.save_snapshot();
+            if let Err(e) = &ret {
+                tracing::warn!("Background saving error: {}", e);
+            }
+            backend.end_save();
+            ret
+        }))
+    }
+
+    fn begin_save(&self) -> Result<(), SnapshotError> {
+        self.save_state
+            .save_in_progress
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| ())
+            .map_err(|_| SnapshotError::InProgress)
+    }
+
+    fn end_save(&self) {
+        self.save_state
+            .save_in_progress
+            .store(false, Ordering::Release);
+    }
+
+    // DEBUG RELOAD：保存快照后清空数据并重新加载，用来检验每种类型都能完整地保存和恢复。
+    // 调用方需要持有 Backend::script_guard，保证保存和加载之间没有其它命令修改数据
+    pub fn reload(&self) -> Result<(), SnapshotError> {
+        self.save()?;
+        let path = self.config().snapshot_path();
+        self.load_from(path)
+    }
+
+    // LASTSAVE：最近一次保存成功的 unix 秒数
+    pub fn lastsave(&self) -> u64 {
+        self.save_state.last_save.load(Ordering::Relaxed)
+    }
+
+    fn save_snapshot(&self) -> Result<(), SnapshotError> {
+        let path = self.config().snapshot_path();
+        self.save_to(path)?;
+        self.save_state
+            .last_save
+            .store(unix_secs(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    // 从 path 加载快照，替换当前所有的 key。文件头、校验和或记录有误时返回错误，且不修改当前数据
+    pub fn load_from(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        self.load_bytes(&fs::read(path)?)
+    }
+
+    // 和 load_from 相同，快照内容来自内存，例如副本从主节点拉取的 SYNC 回复
+    pub fn load_bytes(&self, data: &[u8]) -> Result<(), SnapshotError> {
+        if data.len() < SNAPSHOT_MAGIC.len() {
+            return Err(if SNAPSHOT_MAGIC.starts_with(data) {
+                SnapshotError::Truncated
+            } else {
+                SnapshotError::BadHeader
+            });
+        }
+        let (header, rest) = data.split_at(SNAPSHOT_MAGIC.len());
+        if header != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::BadHeader);
+        }
+        if rest.len() < CHECKSUM_LEN {
+            return Err(SnapshotError::Truncated);
+        }
+        let (payload, checksum) = rest.split_at(rest.len() - CHECKSUM_LEN);
+        let expected = u64::from_le_bytes(checksum.try_into().expect("checksum is 8 bytes"));
+        let actual = crc64(payload);
+        if expected != actual {
+            return Err(SnapshotError::Checksum { expected, actual });
+        }
+
+        // 先解析出所有记录，全部成功后再替换数据
+        let mut buf = BytesMut::from(payload);
+        let mut records = Vec::new();
+        while !buf.is_empty() {
+            let frame =
+                RespArray::decode(&mut buf).map_err(|e| SnapshotError::Corrupt(e.to_string()))?;
+            records.push(parse_record(frame)?);
+        }
+
+        self.flush();
+        let now = SystemTime::now();
+        for (key, expire_at, value) in records {
+            let deadline = match expire_at {
+                Some(at) => match (UNIX_EPOCH + at).duration_since(now) {
+                    Ok(remaining) => Some(Instant::now() + remaining),
+                    // 保存之后已经过期的 key 不再加载
+                    Err(_) => continue,
+                },
+                None => None,
+            };
+            match value {
+                Value::String(v) => {
+                    self.map.insert(key.clone(), v);
+                }
+                Value::Hash(v) => {
+                    self.hmap.insert(key.clone(), v);
+                }
+                Value::List(v) => {
+                    self.list_map.insert(key.clone(), v);
+                }
+                Value::Set(v) => {
+                    self.set_map.insert(key.clone(), v);
+                }
+                Value::ZSet(v) => {
+                    self.zset_map.insert(key.clone(), v);
+                }
+                Value::Stream(entries, groups, last_id) => {
+                    self.stream_map.insert(key.clone(), entries);
+                    if !groups.is_empty() {
+                        self.stream_groups.insert(key.clone(), groups);
+                    }
+                    if let Some(last_id) = last_id {
+                        self.stream_last_ids.insert(key.clone(), last_id);
+                    }
+                }
+            }
+            if let Some(deadline) = deadline {
+                self.ttl_map.insert(key, deadline);
+            }
+        }
+        self.recount_memory();
+        Ok(())
+    }
+
+    // 删除所有 key
+    fn flush(&self) {
+        self.map.clear();
+        self.hmap.clear();
+        self.list_map.clear();
+        self.set_map.clear();
+        self.stream_map.clear();
+        self.stream_groups.clear();
+        self.stream_last_ids.clear();
+        self.zset_map.clear();
+        self.ttl_map.clear();
+        self.access_map.clear();
+        self.recount_memory();
+    }
+
+    // key 在快照中的类型名和编码后的值，key 不存在时返回 None
+    pub fn snapshot_value(&self, key: &str) -> Option<(&'static str, RespFrame)> {
+        if !self.exists(key) {
+            return None;
+        }
+        if let Some(v) = self.map.get(key) {
+            return Some(("string", v.clone()));
+        }
+        if let Some(v) = self.hmap.get(key) {
+            return Some(("hash", hash_frame(&v)));
+        }
+        if let Some(v) = self.list_map.get(key) {
+            return Some(("list", list_frame(&v)));
+        }
+        if let Some(v) = self.set_map.get(key) {
+            return Some(("set", set_frame(&v)));
+        }
+        if let Some(v) = self.zset_map.get(key) {
+            return Some(("zset", zset_frame(&v)));
+        }
+        let stream = self.stream_map.get(key)?;
+        let groups = self.stream_groups.get(key);
+        let last_id = last_id(&stream, self.stream_last_ids.get(key).map(|id| *id));
+        Some(("stream", stream_frame(&stream, groups.as_deref(), last_id)))
+    }
+
+    // 按数据类型依次编码所有未过期的 key
+    fn dump_payload(&self) -> Vec<u8> {
+        let now = Instant::now();
+        let now_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        // 已过期的 key 返回 None，不过期的 key 返回 Some(-1)
+        let expire_at = |key: &str| match self.ttl_map.get(key).map(|deadline| *deadline) {
+            Some(deadline) if deadline <= now => None,
+            Some(deadline) => Some((now_unix + (deadline - now)).as_millis() as i64),
+            None => Some(-1),
+        };
+        let mut payload = Vec::new();
+        let mut push = |kind: &str, key: &str, value: RespFrame| {
+            if let Some(expire_at) = expire_at(key) {
+                let record = RespArray::new(vec![
+                    BulkString::new(kind).into(),
+                    BulkString::new(key).into(),
+                    RespFrame::Integer(expire_at),
+                    value,
+                ]);
+                payload.extend_from_slice(&record.encode());
+            }
+        };
+
+        for entry in self.map.iter() {
+            push("string", entry.key(), entry.value().clone());
+        }
+        for entry in self.hmap.iter() {
+            push("hash", entry.key(), hash_frame(entry.value()));
+        }
+        for entry in self.list_map.iter() {
+            push("list", entry.key(), list_frame(entry.value()));
+        }
+        for entry in self.set_map.iter() {
+            push("set", entry.key(), set_frame(entry.value()));
+        }
+        for entry in self.zset_map.iter() {
+            push("zset", entry.key(), zset_frame(entry.value()));
+        }
+        for entry in self.stream_map.iter() {
+            let groups = self.stream_groups.get(entry.key());
+            let recorded = self.stream_last_ids.get(entry.key()).map(|id| *id);
+            let value = stream_frame(
+                entry.value(),
+                groups.as_deref(),
+                last_id(entry.value(), recorded),
+            );
+            push("stream", entry.key(), value);
+        }
+        payload
+    }
+}
+
+fn hash_frame(hash: &DashMap<String, RespFrame>) -> RespFrame {
+    let fields = hash
+        .iter()
+        .flat_map(|field| [bulk(field.key()), field.value().clone()])
+        .collect();
+    array(fields)
+}
+
+fn list_frame(list: &VecDeque<RespFrame>) -> RespFrame {
+    array(list.iter().cloned().collect())
+}
+
+fn set_frame(set: &DashSet<String>) -> RespFrame {
+    array(set.iter().map(|m| bulk(&m)).collect())
+}
+
+fn zset_frame(zset: &ZSet) -> RespFrame {
+    let members = zset
+        .iter()
+        .flat_map(|(member, score)| [bulk(member), RespFrame::Double(score)])
+        .collect();
+    array(members)
+}
+
+// [[id, [field, value, ...]], ...] 和 [[group, last_delivered, [[id, consumer, count], ...]], ...]
+fn stream_frame(
+    stream: &BTreeMap<StreamId, Vec<(String, RespFrame)>>,
+    groups: Option<&HashMap<String, ConsumerGroup>>,
+    last_id: StreamId,
+) -> RespFrame {
+    let entries = stream
+        .iter()
+        .map(|(id, fields)| {
+            let fields = fields
+                .iter()
+                .flat_map(|(field, value)| [bulk(field), value.clone()])
+                .collect();
+            array(vec![bulk(&id.to_string()), array(fields)])
+        })
+        .collect();
+    let groups = groups
+        .into_iter()
+        .flatten()
+        .map(|(name, group)| {
+            let pending = group
+                .pending
+                .iter()
+                .map(|(id, pending)| {
+                    array(vec![
+                        bulk(&id.to_string()),
+                        bulk(&pending.consumer),
+                        RespFrame::Integer(pending.delivery_count as i64),
+                    ])
+                })
+                .collect();
+            array(vec![
+                bulk(name),
+                bulk(&group.last_delivered.to_string()),
+                array(pending),
+            ])
+        })
+        .collect();
+    array(vec![
+        array(entries),
+        array(groups),
+        bulk(&last_id.to_string()),
+    ])
+}
+
+fn bulk(s: &str) -> RespFrame {
+    BulkString::new(s).into()
+}
+
+fn array(frames: Vec<RespFrame>) -> RespFrame {
+    RespArray::new(frames).into()
+}
+
+fn corrupt(msg: &str) -> SnapshotError {
+    SnapshotError::Corrupt(msg.to_string())
+}
+
+fn expect_string(frame: RespFrame) -> Result<String, SnapshotError> {
+    match frame {
+        RespFrame::BulkString(s) => {
+            String::from_utf8(s.0).map_err(|_| corrupt("string is not valid UTF-8"))
+        }
+        _ => Err(corrupt("expecting a bulk string")),
+    }
+}
+
+fn expect_array(frame: RespFrame) -> Result<Vec<RespFrame>, SnapshotError> {
+    match frame {
+        RespFrame::Array(array) => Ok(array.0),
+        _ => Err(corrupt("expecting an array")),
+    }
+}
+
+fn expect_integer(frame: RespFrame) -> Result<i64, SnapshotError> {
+    match frame {
+        RespFrame::Integer(n) => Ok(n),
+        _ => Err(corrupt("expecting an integer")),
+    }
+}
+
+fn expect_stream_id(frame: RespFrame) -> Result<StreamId, SnapshotError> {
+    expect_string(frame)?
+        .parse()
+        .map_err(|_| corrupt("invalid stream ID"))
+}
+
+// 把 [k1, v1, k2, v2, ...] 拆成 (k, v) 对
+fn pairs(frames: Vec<RespFrame>) -> Result<Vec<(RespFrame, RespFrame)>, SnapshotError> {
+    if !frames.len().is_multiple_of(2) {
+        return Err(corrupt("odd number of elements in a pair list"));
+    }
+    let mut iter = frames.into_iter();
+    let mut pairs = Vec::new();
+    while let (Some(k), Some(v)) = (iter.next(), iter.next()) {
+        pairs.push((k, v));
+    }
+    Ok(pairs)
+}
+
+fn parse_record(record: RespArray) -> Result<(String, Option<Duration>, Value), SnapshotError> {
+    let [kind, key, expire_at, value]: [RespFrame; 4] = record
+        .0
+        .try_into()
+        .map_err(|_| corrupt("record must have 4 elements"))?;
+    let kind = expect_string(kind)?;
+    let key = expect_string(key)?;
+    let expire_at = match expect_integer(expire_at)? {
+        -1 => None,
+        ms if ms >= 0 => Some(Duration::from_millis(ms as u64)),
+        _ => return Err(corrupt("invalid expire time")),
+    };
+    let value = match kind.as_str() {
+        "string" => Value::String(value),
+        "hash" => {
+            let hash = DashMap::new();
+            for (field, value) in pairs(expect_array(value)?)? {
+                hash.insert(expect_string(field)?, value);
+            }
+            Value::Hash(hash)
+        }
+        "list" => Value::List(expect_array(value)?.into()),
+        "set" => Value::Set(
+            expect_array(value)?
+                .into_iter()
+                .map(expect_string)
+                .collect::<Result<_, _>>()?,
+        ),
+        "zset" => {
+            let mut zset = ZSet::new();
+            for (member, score) in pairs(expect_array(value)?)? {
+                let RespFrame::Double(score) = score else {
+                    return Err(corrupt("expecting a double score"));
+                };
+                zset.insert(expect_string(member)?, score);
+            }
+            Value::ZSet(zset)
+        }
+        "stream" => {
+            let mut items = expect_array(value)?.into_iter();
+            let (Some(entries), Some(groups), last_id, None) =
+                (items.next(), items.next(), items.next(), items.next())
+            else {
+                return Err(corrupt("stream must have entries, groups and the last ID"));
+            };
+            let last_id = last_id.map(expect_stream_id).transpose()?;
+            let mut stream = BTreeMap::new();
+            for entry in expect_array(entries)? {
+                let [id, fields]: [RespFrame; 2] = expect_array(entry)?
+                    .try_into()
+                    .map_err(|_| corrupt("stream entry must have an ID and fields"))?;
+                let fields = pairs(expect_array(fields)?)?
+                    .into_iter()
+                    .map(|(field, value)| Ok((expect_string(field)?, value)))
+                    .collect::<Result<_, SnapshotError>>()?;
+                stream.insert(expect_stream_id(id)?, fields);
+            }
+            let mut consumer_groups = HashMap::new();
+            for group in expect_array(groups)? {
+                let [name, last_delivered, pending]: [RespFrame; 3] = expect_array(group)?
+                    .try_into()
+                    .map_err(|_| corrupt("consumer group must have 3 elements"))?;
+                let mut group = ConsumerGroup {
+                    last_delivered: expect_stream_id(last_delivered)?,
+                    ..Default::default()
+                };
+                for entry in expect_array(pending)? {
+                    let [id, consumer, count]: [RespFrame; 3] = expect_array(entry)?
+                        .try_into()
+                        .map_err(|_| corrupt("pending entry must have 3 elements"))?;
+                    // 投递时间不保存，加载后从当前时间开始计算
+                    group.pending.insert(
+                        expect_stream_id(id)?,
+                        PendingEntry {
+                            consumer: expect_string(consumer)?,
+                            delivered_at: Instant::now(),
+                            delivery_count: expect_integer(count)?.max(0) as u64,
+                        },
+                    );
+                }
+                consumer_groups.insert(expect_string(name)?, group);
+            }
+            Value::Stream(stream, consumer_groups, last_id)
+        }
+        _ => return Err(SnapshotError::Corrupt(format!("unknown type '{}'", kind))),
+    };
+    Ok((key, expire_at, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{XAddError, ZAddOptions};
+
+    use super::*;
+
+    // 每个测试使用独立的临时文件
+    fn temp_path() -> std::path::PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        std::env::temp_dir().join(format!(
+            "simple-redis-snapshot-{}-{}.db",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    fn sample_backend() -> Backend {
+        let backend = Backend::new();
+        backend.set("string".to_string(), BulkString::new("value").into());
+        backend.hset(
+            "hash".to_string(),
+            "field".to_string(),
+            BulkString::new("1").into(),
+        );
+        backend.rpush("list".to_string(), vec![bulk("a"), bulk("b")]);
+        backend.sadd("set".to_string(), vec!["x".to_string(), "y".to_string()]);
+        backend.zadd(
+            "zset".to_string(),
+            vec![("m".to_string(), 1.5)],
+            ZAddOptions::default(),
+        );
+        backend
+            .xadd(
+                "stream".to_string(),
+                Some(StreamId(1, 0)),
+                vec![("f".to_string(), bulk("v"))],
+            )
+            .unwrap();
+        // 删除最大的记录之后，最后生成的 ID 仍然是 2-0
+        backend
+            .xadd("stream".to_string(), Some(StreamId(2, 0)), vec![])
+            .unwrap();
+        backend.xdel("stream", &[StreamId(2, 0)]);
+        backend
+            .xgroup_create("stream", "group", Some(StreamId::MIN), false)
+            .unwrap();
+        backend.expire("string", Duration::from_secs(100), None);
+        backend
+    }
+
+    #[test]
+    fn test_crc64() {
+        // Redis crc64 的测试向量
+        assert_eq!(crc64(b"123456789"), 0xe9c6d914c4b8d9ca);
+        assert_eq!(crc64(b""), 0);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() -> anyhow::Result<()> {
+        let path = temp_path();
+        sample_backend().save_to(&path)?;
+        let data = fs::read(&path)?;
+        assert!(data.starts_with(SNAPSHOT_MAGIC));
+
+        let backend = Backend::new();
+        backend.set("stale".to_string(), bulk("gone"));
+        backend.load_from(&path)?;
+        fs::remove_file(&path)?;
+
+        assert!(!backend.exists("stale"));
+        assert_eq!(backend.get("string"), Some(bulk("value")));
+        assert!(backend.ttl("string").unwrap().unwrap() > Duration::from_secs(90));
+        assert_eq!(backend.hget("hash", "field"), Some(bulk("1")));
+        assert_eq!(backend.lrange("list", 0, -1), vec![bulk("a"), bulk("b")]);
+        assert_eq!(backend.smembers("set").unwrap().len(), 2);
+        assert_eq!(backend.zset_map.get("zset").unwrap().score("m"), Some(1.5));
+        assert_eq!(backend.xlast_id("stream"), StreamId(2, 0));
+        assert_eq!(
+            backend.xadd("stream".to_string(), Some(StreamId(2, 0)), vec![]),
+            Err(XAddError::IdTooSmall)
+        );
+        assert!(backend.xgroup("stream", "group").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_rejects_corrupt_files() -> anyhow::Result<()> {
+        let path = temp_path();
+        sample_backend().save_to(&path)?;
+        let data = fs::read(&path)?;
+        let backend = Backend::new();
+        backend.set("key".to_string(), bulk("kept"));
+
+        // payload 中的任意一个字节被修改
+        let mut corrupted = data.clone();
+        corrupted[SNAPSHOT_MAGIC.len() + 10] ^= 0xff;
+        fs::write(&path, &corrupted)?;
+        assert!(matches!(
+            backend.load_from(&path),
+            Err(SnapshotError::Checksum { .. })
+        ));
+
+        // 文件被截断，尾部的校验和对不上
+        fs::write(&path, &data[..data.len() - 3])?;
+        assert!(matches!(
+            backend.load_from(&path),
+            Err(SnapshotError::Checksum { .. })
+        ));
+        fs::write(&path, &data[..4])?;
+        assert!(matches!(
+            backend.load_from(&path),
+            Err(SnapshotError::Truncated)
+        ));
+
+        fs::write(&path, b"REDIS0011garbage")?;
+        assert!(matches!(
+            backend.load_from(&path),
+            Err(SnapshotError::BadHeader)
+        ));
+        fs::remove_file(&path)?;
+
+        // 加载失败时保留原有的数据
+        assert_eq!(backend.get("key"), Some(bulk("kept")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bgsave_rejects_concurrent_save() -> anyhow::Result<()> {
+        let path = temp_path();
+        let backend = Backend::new();
+        let dir = path.parent().unwrap().to_string_lossy().into_owned();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        backend.set_config("dir", &dir)?;
+        backend.set_config("dbfilename", &name)?;
+
+        // 模拟正在进行的后台保存
+        backend
+            .save_state
+            .save_in_progress
+            .store(true, Ordering::Release);
+        assert!(matches!(backend.bgsave(), Err(SnapshotError::InProgress)));
+        assert!(matches!(backend.save(), Err(SnapshotError::InProgress)));
+        assert!(!path.exists());
+
+        backend
+            .save_state
+            .save_in_progress
+            .store(false, Ordering::Release);
+        // SAVE 结束后释放标记，之后的 BGSAVE 可以继续
+        backend.save()?;
+        assert!(path.exists());
+        backend.bgsave()?.join().unwrap()?;
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
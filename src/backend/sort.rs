@@ -0,0 +1,96 @@
+use thiserror::Error;
+
+use crate::{BulkString, RespFrame};
+
+use super::Backend;
+
+// SORT 的选项
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SortOptions {
+    pub desc: bool,
+    // 按字典序而不是数值排序
+    pub alpha: bool,
+    // (offset, count)
+    pub limit: Option<(usize, usize)>,
+    // 将结果写入该列表而不是直接返回
+    pub store: Option<String>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SortError {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR One or more scores can't be converted into double")]
+    NotANumber,
+}
+
+impl Backend {
+    // 对列表或集合的元素排序。指定了 store 时同时写入目标列表
+    pub fn sort(&self, key: &str, options: &SortOptions) -> Result<Vec<RespFrame>, SortError> {
+        let elements = self.sort_elements(key)?;
+        let mut sorted = if options.alpha {
+            let mut elements = elements;
+            elements.sort();
+            elements
+        } else {
+            let mut scored = elements
+                .into_iter()
+                .map(|element| {
+                    let score = String::from_utf8_lossy(&element)
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|score| !score.is_nan())
+                        .ok_or(SortError::NotANumber)?;
+                    Ok((score, element))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            // score 相同时按字典序排列，保证结果稳定
+            scored.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+            scored.into_iter().map(|(_, element)| element).collect()
+        };
+        if options.desc {
+            sorted.reverse();
+        }
+        if let Some((offset, count)) = options.limit {
+            sorted = sorted.into_iter().skip(offset).take(count).collect();
+        }
+
+        let frames: Vec<RespFrame> = sorted
+            .into_iter()
+            .map(|element| BulkString::new(element).into())
+            .collect();
+        if let Some(dest) = &options.store {
+            self.lstore(dest.clone(), frames.clone());
+        }
+        Ok(frames)
+    }
+
+    // 取出列表或集合中所有元素的字节内容，key 不存在时返回空
+    fn sort_elements(&self, key: &str) -> Result<Vec<Vec<u8>>, SortError> {
+        self.touch(key);
+        if let Some(list) = self.list_map.get(key) {
+            return Ok(list.iter().map(frame_bytes).collect());
+        }
+        if let Some(set) = self.set_map.get(key) {
+            return Ok(set
+                .iter()
+                .map(|member| member.as_bytes().to_vec())
+                .collect());
+        }
+        if self.exists(key) {
+            return Err(SortError::WrongType);
+        }
+        Ok(Vec::new())
+    }
+}
+
+// 列表元素都是 bulk string，其余类型按编码后的内容比较
+fn frame_bytes(frame: &RespFrame) -> Vec<u8> {
+    match frame {
+        RespFrame::BulkString(s) => s.to_vec(),
+        RespFrame::SimpleString(s) => s.as_bytes().to_vec(),
+        RespFrame::Integer(n) => n.to_string().into_bytes(),
+        other => format!("{:?}", other).into_bytes(),
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use dashmap::{DashMap, DashSet};
+
+use crate::{RespFrame, StreamId, ZSet};
+
+use super::Backend;
+
+// INFO 使用的统计计数
+#[derive(Debug)]
+pub struct ServerStats {
+    started_at: Instant,
+    total_connections_received: AtomicU64,
+    total_commands_processed: AtomicU64,
+    keyspace_hits: AtomicU64,
+    keyspace_misses: AtomicU64,
+    evicted_keys: AtomicU64,
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            total_connections_received: AtomicU64::new(0),
+            total_commands_processed: AtomicU64::new(0),
+            keyspace_hits: AtomicU64::new(0),
+            keyspace_misses: AtomicU64::new(0),
+            evicted_keys: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ServerStats {
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    pub fn total_connections_received(&self) -> u64 {
+        self.total_connections_received.load(Ordering::Relaxed)
+    }
+
+    pub fn total_commands_processed(&self) -> u64 {
+        self.total_commands_processed.load(Ordering::Relaxed)
+    }
+
+    pub fn keyspace_hits(&self) -> u64 {
+        self.keyspace_hits.load(Ordering::Relaxed)
+    }
+
+    pub fn keyspace_misses(&self) -> u64 {
+        self.keyspace_misses.load(Ordering::Relaxed)
+    }
+
+    pub fn evicted_keys(&self) -> u64 {
+        self.evicted_keys.load(Ordering::Relaxed)
+    }
+
+    pub fn record_connection(&self) {
+        self.total_connections_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_command(&self) {
+        self.total_commands_processed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    // 读取 key 时记录命中或未命中
+    pub(super) fn record_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.keyspace_hits
+        } else {
+            &self.keyspace_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn record_eviction(&self) {
+        self.evicted_keys.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Backend {
+    pub fn stats(&self) -> &ServerStats {
+        &self.stats
+    }
+
+    // 所有 key 的数量
+    pub fn dbsize(&self) -> usize {
+        self.map.len()
+            + self.hmap.len()
+            + self.list_map.len()
+            + self.set_map.len()
+            + self.stream_map.len()
+            + self.zset_map.len()
+    }
+
+    // 设置了过期时间的 key 的数量
+    pub fn expires_count(&self) -> usize {
+        self.ttl_map.len()
+    }
+
+    // 粗略估算数据占用的字节数：key 和 value 的长度之和，不包括容器本身的开销。
+    // 计数在写入和删除时增量更新，检查 maxmemory 时不需要遍历整个数据集
+    pub fn used_memory(&self) -> usize {
+        self.used_memory.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn grow_memory(&self, n: usize) {
+        self.used_memory.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub(super) fn shrink_memory(&self, n: usize) {
+        let _ = self
+            .used_memory
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                Some(used.saturating_sub(n))
+            });
+    }
+
+    // 遍历整个数据集重新计算占用的字节数，只在整体替换数据（加载快照）之后调用
+    pub(super) fn recount_memory(&self) {
+        self.used_memory
+            .store(self.dataset_memory(), Ordering::Relaxed);
+    }
+
+    fn dataset_memory(&self) -> usize {
+        let map: usize = self
+            .map
+            .iter()
+            .map(|e| e.key().len() + frame_size(e.value()))
+            .sum();
+        let hmap: usize = self
+            .hmap
+            .iter()
+            .map(|e| e.key().len() + hash_size(e.value()))
+            .sum();
+        let list: usize = self
+            .list_map
+            .iter()
+            .map(|e| e.key().len() + list_size(e.value()))
+            .sum();
+        let set: usize = self
+            .set_map
+            .iter()
+            .map(|e| e.key().len() + set_size(e.value()))
+            .sum();
+        let stream: usize = self
+            .stream_map
+            .iter()
+            .map(|e| e.key().len() + stream_size(e.value()))
+            .sum();
+        let zset: usize = self
+            .zset_map
+            .iter()
+            .map(|e| e.key().len() + zset_size(e.value()))
+            .sum();
+        map + hmap + list + set + stream + zset
+    }
+}
+
+pub(super) fn hash_size(hash: &DashMap<String, RespFrame>) -> usize {
+    hash.iter()
+        .map(|f| f.key().len() + frame_size(f.value()))
+        .sum()
+}
+
+pub(super) fn list_size(list: &VecDeque<RespFrame>) -> usize {
+    list.iter().map(frame_size).sum()
+}
+
+pub(super) fn set_size(set: &DashSet<String>) -> usize {
+    set.iter().map(|m| m.len()).sum()
+}
+
+pub(super) fn stream_size(stream: &BTreeMap<StreamId, Vec<(String, RespFrame)>>) -> usize {
+    stream.values().map(|f| stream_entry_size(f)).sum()
+}
+
+// 每个字段额外按 16 字节计算记录 ID 的开销
+pub(super) fn stream_entry_size(fields: &[(String, RespFrame)]) -> usize {
+    fields
+        .iter()
+        .map(|(field, value)| 16 + field.len() + frame_size(value))
+        .sum()
+}
+
+pub(super) fn zset_size(zset: &ZSet) -> usize {
+    zset.iter()
+        .map(|(member, _)| zset_member_size(member))
+        .sum()
+}
+
+// 每个成员额外按 8 字节计算 score
+pub(super) fn zset_member_size(member: &str) -> usize {
+    member.len() + 8
+}
+
+pub(super) fn frame_size(frame: &RespFrame) -> usize {
+    match frame {
+        RespFrame::BulkString(s) => s.len(),
+        RespFrame::SimpleString(s) => s.len(),
+        RespFrame::Array(array) => array.iter().map(frame_size).sum(),
+        _ => 8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BulkString;
+
+    use super::*;
+
+    #[test]
+    fn test_keyspace_hits_and_misses() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        backend.get("key");
+        backend.get("missing");
+        backend.get("missing");
+        assert_eq!(backend.stats().keyspace_hits(), 1);
+        assert_eq!(backend.stats().keyspace_misses(), 2);
+    }
+
+    #[test]
+    fn test_dbsize_and_used_memory() {
+        let backend = Backend::new();
+        assert_eq!(backend.used_memory(), 0);
+        backend.set("key".to_string(), BulkString::new("value").into());
+        backend.sadd("set".to_string(), vec!["a".to_string(), "bc".to_string()]);
+        assert_eq!(backend.dbsize(), 2);
+        assert_eq!(backend.used_memory(), 3 + 5 + 3 + 3);
+    }
+
+    #[test]
+    fn test_used_memory_tracks_mutations() {
+        let backend = Backend::new();
+        let check = |backend: &Backend| assert_eq!(backend.used_memory(), backend.dataset_memory());
+        backend.set("s".to_string(), BulkString::new("value").into());
+        backend.set("s".to_string(), BulkString::new("v").into());
+        backend.setbit("bits".to_string(), 100, true).unwrap();
+        backend.hset_fields(
+            "h".to_string(),
+            vec![
+                ("f1".to_string(), BulkString::new("a").into()),
+                ("f2".to_string(), BulkString::new("bb").into()),
+            ],
+        );
+        backend.hset(
+            "h".to_string(),
+            "f1".to_string(),
+            BulkString::new("ccc").into(),
+        );
+        backend.rpush("l".to_string(), vec![BulkString::new("x").into(); 3]);
+        backend.lmpop(&["l".to_string()], true, 2);
+        backend.sadd("set".to_string(), vec!["a".to_string(), "a".to_string()]);
+        backend.zadd(
+            "z".to_string(),
+            vec![("m1".to_string(), 1.0), ("m2".to_string(), 2.0)],
+            crate::ZAddOptions::default(),
+        );
+        backend.zrem("z", &["m1".to_string(), "m1".to_string()]);
+        backend.zincrby("z".to_string(), "m3".to_string(), 1.0);
+        let id = backend
+            .xadd(
+                "x".to_string(),
+                None,
+                vec![("f".to_string(), BulkString::new("v").into())],
+            )
+            .unwrap();
+        backend
+            .xadd(
+                "x".to_string(),
+                None,
+                vec![("g".to_string(), BulkString::new("w").into())],
+            )
+            .unwrap();
+        backend.xdel("x", &[id]);
+        backend.copy("h", "h2", false);
+        check(&backend);
+
+        backend.xtrim("x", crate::TrimStrategy::MaxLen(0, false));
+        backend.zmpop(&["z".to_string()], true, 10);
+        backend.lmpop(&["l".to_string()], true, 10);
+        backend.del("h");
+        check(&backend);
+        for key in ["s", "bits", "h2", "set", "x"] {
+            backend.del(key);
+        }
+        assert_eq!(backend.used_memory(), 0);
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    ops::Bound,
+    str::FromStr,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+use crate::RespFrame;
+
+use super::{
+    stats::{stream_entry_size, stream_size},
+    Backend,
+};
+
+// stream 条目 ID，格式为 <ms>-<seq>
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StreamId(pub u64, pub u64);
+
+// 一条 stream 记录：ID 和 field/value 列表
+pub type StreamEntry = (StreamId, Vec<(String, RespFrame)>);
+
+// XTRIM 的裁剪策略，bool 表示是否为近似裁剪（`~`）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrimStrategy {
+    MaxLen(usize, bool),
+    MinId(StreamId, bool),
+}
+
+// 消费者组：最后投递的 ID 和待确认列表（PEL）
+#[derive(Debug, Default, Clone)]
+pub struct ConsumerGroup {
+    pub last_delivered: StreamId,
+    pub pending: BTreeMap<StreamId, PendingEntry>,
+}
+
+// PEL 中的一条记录：投递给哪个消费者、最近投递时间和投递次数
+#[derive(Debug, Clone)]
+pub struct PendingEntry {
+    pub consumer: String,
+    pub delivered_at: Instant,
+    pub delivery_count: u64,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StreamGroupError {
+    #[error("ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.")]
+    NoSuchKey,
+    #[error("BUSYGROUP Consumer Group name already exists")]
+    BusyGroup,
+    #[error("NOGROUP No such key '{0}' or consumer group '{1}'")]
+    NoGroup(String, String),
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum XAddError {
+    #[error("ERR The ID specified in XADD is equal or smaller than the target stream top item")]
+    IdTooSmall,
+    #[error("ERR The stream has exhausted the last possible ID, unable to add more items")]
+    Exhausted,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId(0, 0);
+    pub const MAX: StreamId = StreamId(u64::MAX, u64::MAX);
+
+    // 基于当前时间和上一个 ID 生成新的自增 ID；seq 用完时进位到下一毫秒，ID 已经用尽时返回 None
+    fn next_after(last: Option<StreamId>) -> Option<StreamId> {
+        let ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        match last {
+            Some(StreamId(last_ms, seq)) if last_ms >= ms => match seq.checked_add(1) {
+                Some(seq) => Some(StreamId(last_ms, seq)),
+                None => last_ms.checked_add(1).map(|ms| StreamId(ms, 0)),
+            },
+            _ => Some(StreamId(ms, 0)),
+        }
+    }
+}
+
+// stream 最后生成的 ID：记录下来的 ID 和当前最大的 ID 中较大的一个
+pub(super) fn last_id(
+    stream: &BTreeMap<StreamId, Vec<(String, RespFrame)>>,
+    recorded: Option<StreamId>,
+) -> StreamId {
+    stream
+        .keys()
+        .next_back()
+        .copied()
+        .max(recorded)
+        .unwrap_or(StreamId::MIN)
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.0, self.1)
+    }
+}
+
+// 解析 "<ms>-<seq>" 或 "<ms>"（seq 默认为 0）
+impl FromStr for StreamId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('-') {
+            Some((ms, seq)) => Ok(StreamId(ms.parse()?, seq.parse()?)),
+            None => Ok(StreamId(s.parse()?, 0)),
+        }
+    }
+}
+
+impl Backend {
+    // 追加一条记录，id 为 None 时自动生成；显式 id 不大于当前最大 ID，或者 ID 已经用尽时返回错误
+    pub fn xadd(
+        &self,
+        key: String,
+        id: Option<StreamId>,
+        fields: Vec<(String, RespFrame)>,
+    ) -> Result<StreamId, XAddError> {
+        self.touch_for_write(&key);
+        let id = {
+            let mut stream = self.stream_map.entry(key.clone()).or_insert_with(|| {
+                self.grow_memory(key.len());
+                BTreeMap::new()
+            });
+            let last = stream
+                .keys()
+                .next_back()
+                .copied()
+                .max(self.stream_last_ids.get(&key).map(|id| *id));
+            let id = match id {
+                Some(id) if id == StreamId::MIN || last.is_some_and(|last| id <= last) => {
+                    return Err(XAddError::IdTooSmall)
+                }
+                Some(id) => id,
+                None => StreamId::next_after(last).ok_or(XAddError::Exhausted)?,
+            };
+            self.grow_memory(stream_entry_size(&fields));
+            stream.insert(id, fields);
+            self.stream_last_ids.insert(key.clone(), id);
+            id
+        };
+        self.notify_key(&key);
+        self.emit_key_event("xadd", &key, "stream");
+        Ok(id)
+    }
+
+    // stream 最后生成的 ID，删除记录之后不回退；stream 不存在时为 0-0
+    pub fn xlast_id(&self, key: &str) -> StreamId {
+        self.expire_if_needed(key);
+        let Some(stream) = self.stream_map.get(key) else {
+            return StreamId::MIN;
+        };
+        last_id(&stream, self.stream_last_ids.get(key).map(|id| *id))
+    }
+
+    // 读取 ID 位于 [start, end] 之间的记录
+    pub fn xrange(
+        &self,
+        key: &str,
+        start: StreamId,
+        end: StreamId,
+        count: Option<usize>,
+    ) -> Vec<StreamEntry> {
+        self.touch(key);
+        if start > end {
+            return Vec::new();
+        }
+        self.stream_map
+            .get(key)
+            .map(|stream| {
+                stream
+                    .range(start..=end)
+                    .take(count.unwrap_or(usize::MAX))
+                    .map(|(id, fields)| (*id, fields.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn xlen(&self, key: &str) -> usize {
+        self.touch(key);
+        self.stream_map
+            .get(key)
+            .map(|stream| stream.len())
+            .unwrap_or_default()
+    }
+
+    // 删除指定 ID 的记录，返回实际删除的数量
+    pub fn xdel(&self, key: &str, ids: &[StreamId]) -> usize {
+        self.touch(key);
+        let Some(mut stream) = self.stream_map.get_mut(key) else {
+            return 0;
+        };
+        ids.iter()
+            .filter_map(|id| stream.remove(id))
+            .map(|fields| self.shrink_memory(stream_entry_size(&fields)))
+            .count()
+    }
+
+    // 从最旧的记录开始裁剪，返回删除的数量。近似裁剪目前也按精确方式处理
+    pub fn xtrim(&self, key: &str, strategy: TrimStrategy) -> usize {
+        self.touch(key);
+        let Some(mut stream) = self.stream_map.get_mut(key) else {
+            return 0;
+        };
+        let before = stream.len();
+        let mut freed = 0;
+        match strategy {
+            TrimStrategy::MaxLen(max_len, _) => {
+                while stream.len() > max_len {
+                    if let Some((_, fields)) = stream.pop_first() {
+                        freed += stream_entry_size(&fields);
+                    }
+                }
+            }
+            TrimStrategy::MinId(min_id, _) => {
+                let kept = stream.split_off(&min_id);
+                freed = stream_size(&stream);
+                *stream = kept;
+            }
+        }
+        self.shrink_memory(freed);
+        before - stream.len()
+    }
+
+    // 读取 ID 大于 after 的记录
+    pub fn xread(&self, key: &str, after: StreamId, count: Option<usize>) -> Vec<StreamEntry> {
+        self.touch(key);
+        self.stream_map
+            .get(key)
+            .map(|stream| {
+                stream
+                    .range((Bound::Excluded(after), Bound::Unbounded))
+                    .take(count.unwrap_or(usize::MAX))
+                    .map(|(id, fields)| (*id, fields.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // 创建消费者组，id 为 None 表示 `$`；mkstream 为 true 时 stream 不存在则创建空 stream
+    pub fn xgroup_create(
+        &self,
+        key: &str,
+        group: &str,
+        id: Option<StreamId>,
+        mkstream: bool,
+    ) -> Result<(), StreamGroupError> {
+        self.touch(key);
+        if !self.stream_map.contains_key(key) {
+            if !mkstream {
+                return Err(StreamGroupError::NoSuchKey);
+            }
+            self.touch_for_write(key);
+            self.stream_map.entry(key.to_string()).or_insert_with(|| {
+                self.grow_memory(key.len());
+                BTreeMap::new()
+            });
+        }
+        let last_delivered = id.unwrap_or_else(|| self.xlast_id(key));
+        let mut groups = self.stream_groups.entry(key.to_string()).or_default();
+        if groups.contains_key(group) {
+            return Err(StreamGroupError::BusyGroup);
+        }
+        groups.insert(
+            group.to_string(),
+            ConsumerGroup {
+                last_delivered,
+                ..Default::default()
+            },
+        );
+        Ok(())
+    }
+
+    // 以消费者组的方式读取：id 为 None（`>`）时投递新记录并加入 PEL，
+    // 否则重放该消费者在 PEL 中 ID 大于 id 的记录
+    pub fn xreadgroup(
+        &self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+        id: Option<StreamId>,
+        count: Option<usize>,
+    ) -> Result<Vec<StreamEntry>, StreamGroupError> {
+        self.touch(key);
+        let no_group = || StreamGroupError::NoGroup(key.to_string(), group.to_string());
+        let mut groups = self.stream_groups.get_mut(key).ok_or_else(no_group)?;
+        let group = groups.get_mut(group).ok_or_else(no_group)?;
+        let count = count.unwrap_or(usize::MAX);
+        // 持有组的锁时不能再调用 touch，否则可能在惰性删除时死锁
+        let stream = self.stream_map.get(key);
+        match id {
+            None => {
+                let entries: Vec<StreamEntry> = stream
+                    .map(|stream| {
+                        stream
+                            .range((Bound::Excluded(group.last_delivered), Bound::Unbounded))
+                            .take(count)
+                            .map(|(id, fields)| (*id, fields.clone()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let now = Instant::now();
+                for (id, _) in &entries {
+                    group.pending.insert(
+                        *id,
+                        PendingEntry {
+                            consumer: consumer.to_string(),
+                            delivered_at: now,
+                            delivery_count: 1,
+                        },
+                    );
+                }
+                if let Some((id, _)) = entries.last() {
+                    group.last_delivered = *id;
+                }
+                Ok(entries)
+            }
+            Some(after) => {
+                let entries = group
+                    .pending
+                    .range((Bound::Excluded(after), Bound::Unbounded))
+                    .filter(|(_, pending)| pending.consumer == consumer)
+                    .take(count)
+                    .map(|(id, _)| {
+                        // 已经被 XDEL 删除的记录只返回 ID
+                        let fields = stream
+                            .as_ref()
+                            .and_then(|stream| stream.get(id).cloned())
+                            .unwrap_or_default();
+                        (*id, fields)
+                    })
+                    .collect();
+                Ok(entries)
+            }
+        }
+    }
+
+    // 确认记录已处理，从 PEL 中移除，返回实际移除的数量
+    pub fn xack(&self, key: &str, group: &str, ids: &[StreamId]) -> usize {
+        self.touch(key);
+        self.stream_groups
+            .get_mut(key)
+            .and_then(|mut groups| {
+                groups.get_mut(group).map(|group| {
+                    ids.iter()
+                        .filter(|id| group.pending.remove(id).is_some())
+                        .count()
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn xgroup(&self, key: &str, group: &str) -> Option<ConsumerGroup> {
+        self.stream_groups
+            .get(key)
+            .and_then(|groups| groups.get(group).cloned())
+    }
+}
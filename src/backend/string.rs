@@ -0,0 +1,150 @@
+use thiserror::Error;
+
+use crate::{BulkString, RespFrame};
+
+use super::Backend;
+
+// SETBIT 允许的最大偏移量，和 Redis 一样把字符串限制在 512MB 以内
+pub const MAX_BIT_OFFSET: u64 = 512 * 1024 * 1024 * 8 - 1;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StringError {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+}
+
+impl Backend {
+    // 设置字符串第 offset 位（高位在前）的值，字符串不够长时补 0，返回原来的值
+    pub fn setbit(&self, key: String, offset: u64, bit: bool) -> Result<bool, StringError> {
+        self.touch_for_write(&key);
+        if self.holds_non_string(&key) {
+            return Err(StringError::WrongType);
+        }
+        let key_len = key.len();
+        let mut entry = self.map.entry(key.clone()).or_insert_with(|| {
+            self.grow_memory(key_len);
+            BulkString::new(Vec::new()).into()
+        });
+        if let RespFrame::SimpleString(s) = entry.value() {
+            let value = BulkString::new(s.as_bytes()).into();
+            *entry.value_mut() = value;
+        }
+        let RespFrame::BulkString(value) = entry.value_mut() else {
+            return Err(StringError::WrongType);
+        };
+        let byte = (offset / 8) as usize;
+        let mask = 0x80 >> (offset % 8);
+        if value.0.len() <= byte {
+            self.grow_memory(byte + 1 - value.0.len());
+            value.0.resize(byte + 1, 0);
+        }
+        let old = value.0[byte] & mask != 0;
+        if bit {
+            value.0[byte] |= mask;
+        } else {
+            value.0[byte] &= !mask;
+        }
+        drop(entry);
+        self.emit_key_event("setbit", &key, "string");
+        Ok(old)
+    }
+
+    // 读取字符串第 offset 位的值，超出字符串长度或 key 不存在时为 0
+    pub fn getbit(&self, key: &str, offset: u64) -> Result<bool, StringError> {
+        let byte = (offset / 8) as usize;
+        let mask = 0x80 >> (offset % 8);
+        self.with_string_bytes(key, |bytes| bytes.get(byte).is_some_and(|b| b & mask != 0))
+    }
+
+    // 统计字节区间 [start, end] 内为 1 的位数，负数表示从末尾倒数，range 为 None 时统计整个字符串
+    pub fn bitcount(&self, key: &str, range: Option<(i64, i64)>) -> Result<u64, StringError> {
+        self.with_string_bytes(key, |bytes| {
+            let len = bytes.len() as i64;
+            let (start, end) = range.unwrap_or((0, -1));
+            let start = if start < 0 {
+                (len + start).max(0)
+            } else {
+                start
+            };
+            let end = if end < 0 { len + end } else { end.min(len - 1) };
+            if start > end || start >= len {
+                return 0;
+            }
+            bytes[start as usize..=end as usize]
+                .iter()
+                .map(|b| b.count_ones() as u64)
+                .sum()
+        })
+    }
+
+    // 持有读锁调用 f 访问字符串 key 的原始字节，不复制整个字符串；key 不存在时为空
+    fn with_string_bytes<F, R>(&self, key: &str, f: F) -> Result<R, StringError>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        self.touch(key);
+        if self.holds_non_string(key) {
+            return Err(StringError::WrongType);
+        }
+        match self.map.get(key).as_deref() {
+            Some(RespFrame::BulkString(value)) => Ok(f(&value.0)),
+            Some(RespFrame::SimpleString(value)) => Ok(f(value.as_bytes())),
+            Some(_) => Err(StringError::WrongType),
+            None => Ok(f(&[])),
+        }
+    }
+
+    // key 是否保存着字符串以外的类型
+    fn holds_non_string(&self, key: &str) -> bool {
+        self.hmap.contains_key(key)
+            || self.list_map.contains_key(key)
+            || self.set_map.contains_key(key)
+            || self.stream_map.contains_key(key)
+            || self.zset_map.contains_key(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setbit_grows_string() {
+        let backend = Backend::new();
+        assert_eq!(backend.setbit("key".to_string(), 7, true), Ok(false));
+        assert_eq!(backend.setbit("key".to_string(), 7, true), Ok(true));
+        assert_eq!(backend.setbit("key".to_string(), 20, true), Ok(false));
+        assert_eq!(
+            backend.get("key"),
+            Some(BulkString::new(vec![0x01, 0x00, 0x08]).into())
+        );
+        assert_eq!(backend.getbit("key", 20), Ok(true));
+        assert_eq!(backend.getbit("key", 21), Ok(false));
+        assert_eq!(backend.getbit("key", 1000), Ok(false));
+    }
+
+    #[test]
+    fn test_bitcount_range() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("foobar").into());
+        assert_eq!(backend.bitcount("key", None), Ok(26));
+        assert_eq!(backend.bitcount("key", Some((0, 0))), Ok(4));
+        assert_eq!(backend.bitcount("key", Some((1, 1))), Ok(6));
+        assert_eq!(backend.bitcount("key", Some((-2, -1))), Ok(7));
+        assert_eq!(backend.bitcount("key", Some((5, 100))), Ok(4));
+        assert_eq!(backend.bitcount("key", Some((3, 1))), Ok(0));
+        assert_eq!(backend.bitcount("missing", None), Ok(0));
+    }
+
+    #[test]
+    fn test_bit_ops_on_wrong_type() {
+        let backend = Backend::new();
+        backend.lpush("list".to_string(), vec![BulkString::new("a").into()]);
+        assert_eq!(
+            backend.setbit("list".to_string(), 0, true),
+            Err(StringError::WrongType)
+        );
+        assert_eq!(backend.getbit("list", 0), Err(StringError::WrongType));
+        assert_eq!(backend.bitcount("list", None), Err(StringError::WrongType));
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
+
+use super::{
+    stats::{zset_member_size, zset_size},
+    Backend,
+};
+
+// 有序集合：member -> score 的映射，加上按 (score, member) 排序的索引
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ZSet {
+    scores: HashMap<String, f64>,
+    ordered: BTreeSet<(Score, String)>,
+}
+
+// ZUNIONSTORE / ZINTERSTORE 合并 score 的方式
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Min,
+    Max,
+}
+
+// ZRANGEBYSCORE 的区间边界，exclusive 对应 `(` 前缀
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreBound {
+    pub value: f64,
+    pub exclusive: bool,
+}
+
+// 为 f64 提供全序，用作有序索引的 key
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Score(f64);
+
+// ZADD / GEOADD 的条件选项
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ZAddOptions {
+    // 只添加新成员
+    pub nx: bool,
+    // 只更新已有成员
+    pub xx: bool,
+    // 只在新 score 大于旧 score 时更新，不影响新成员的添加
+    pub gt: bool,
+    // 只在新 score 小于旧 score 时更新，不影响新成员的添加
+    pub lt: bool,
+    // 返回变更（新增 + 更新）的数量，而不是新增的数量
+    pub ch: bool,
+}
+
+impl ZAddOptions {
+    // 按 NX / XX / GT / LT 判断成员能否从 old 变成 score，old 为 None 表示新成员
+    fn allows(&self, old: Option<f64>, score: f64) -> bool {
+        match old {
+            Some(_) if self.nx => false,
+            None => !self.xx,
+            Some(old) if self.gt && score <= old => false,
+            Some(old) if self.lt && score >= old => false,
+            Some(_) => true,
+        }
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Aggregate {
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            // inf + -inf 的结果按 0 处理，和 Redis 一致
+            Aggregate::Sum => match a + b {
+                sum if sum.is_nan() => 0.0,
+                sum => sum,
+            },
+            Aggregate::Min => a.min(b),
+            Aggregate::Max => a.max(b),
+        }
+    }
+}
+
+impl ScoreBound {
+    pub fn inclusive(value: f64) -> Self {
+        Self {
+            value,
+            exclusive: false,
+        }
+    }
+
+    // score 是否满足下界
+    fn below(&self, score: f64) -> bool {
+        if self.exclusive {
+            self.value < score
+        } else {
+            self.value <= score
+        }
+    }
+
+    // score 是否满足上界
+    fn above(&self, score: f64) -> bool {
+        if self.exclusive {
+            score < self.value
+        } else {
+            score <= self.value
+        }
+    }
+}
+
+// 带权重的 score，0 * inf 按 0 处理
+fn weighted(score: f64, weight: f64) -> f64 {
+    match score * weight {
+        v if v.is_nan() => 0.0,
+        v => v,
+    }
+}
+
+impl ZSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 插入或更新成员，返回是否为新成员
+    pub fn insert(&mut self, member: String, score: f64) -> bool {
+        match self.scores.insert(member.clone(), score) {
+            Some(old) => {
+                self.ordered.remove(&(Score(old), member.clone()));
+                self.ordered.insert((Score(score), member));
+                false
+            }
+            None => {
+                self.ordered.insert((Score(score), member));
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, member: &str) -> bool {
+        match self.scores.remove(member) {
+            Some(score) => {
+                self.ordered.remove(&(Score(score), member.to_string()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    // 弹出 score 最小（min 为 true）或最大的成员
+    pub fn pop(&mut self, min: bool) -> Option<(String, f64)> {
+        let (score, member) = if min {
+            self.ordered.pop_first()?
+        } else {
+            self.ordered.pop_last()?
+        };
+        self.scores.remove(&member);
+        Some((member, score.0))
+    }
+
+    // 按 score 升序的排名，从 0 开始
+    pub fn rank(&self, member: &str) -> Option<usize> {
+        let score = self.score(member)?;
+        Some(
+            self.ordered
+                .range(..(Score(score), member.to_string()))
+                .count(),
+        )
+    }
+
+    // 按 score 升序遍历 (member, score)
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.ordered
+            .iter()
+            .map(|(score, member)| (member.as_str(), score.0))
+    }
+}
+
+impl Backend {
+    // 从 keys 中第一个非空有序集合弹出至多 count 个 score 最小（min）或最大的成员，
+    // 返回该集合的 key 和弹出的 (member, score)；集合被弹空时删除 key
+    pub fn zmpop(
+        &self,
+        keys: &[String],
+        min: bool,
+        count: usize,
+    ) -> Option<(String, Vec<(String, f64)>)> {
+        for key in keys {
+            self.touch(key);
+            let (popped, empty) = {
+                let Some(mut zset) = self.zset_map.get_mut(key) else {
+                    continue;
+                };
+                let popped: Vec<_> = (0..count).map_while(|_| zset.pop(min)).collect();
+                (popped, zset.is_empty())
+            };
+            self.shrink_memory(popped.iter().map(|(m, _)| zset_member_size(m)).sum());
+            if empty
+                && self
+                    .zset_map
+                    .remove_if(key, |_, zset| zset.is_empty())
+                    .is_some()
+            {
+                self.shrink_memory(key.len());
+                self.ttl_map.remove(key);
+                self.access_map.remove(key);
+            }
+            if !popped.is_empty() {
+                return Some((key.clone(), popped));
+            }
+        }
+        None
+    }
+
+    // 批量添加成员，返回新增的数量（ch 为 true 时返回新增和更新的数量）
+    pub fn zadd(&self, key: String, members: Vec<(String, f64)>, options: ZAddOptions) -> usize {
+        self.touch_for_write(&key);
+        let key_len = key.len();
+        let mut zset = self.zset_map.entry(key.clone()).or_insert_with(|| {
+            self.grow_memory(key_len);
+            ZSet::new()
+        });
+        let mut added = 0;
+        let mut changed = 0;
+        for (member, score) in members {
+            let old = zset.score(&member);
+            if !options.allows(old, score) {
+                continue;
+            }
+            match old {
+                Some(old) => {
+                    if old != score {
+                        zset.insert(member, score);
+                        changed += 1;
+                    }
+                }
+                None => {
+                    self.grow_memory(zset_member_size(&member));
+                    zset.insert(member, score);
+                    added += 1;
+                }
+            }
+        }
+        let empty = zset.is_empty();
+        drop(zset);
+        if empty
+            && self
+                .zset_map
+                .remove_if(&key, |_, zset| zset.is_empty())
+                .is_some()
+        {
+            self.shrink_memory(key_len);
+            self.access_map.remove(&key);
+        }
+        if added + changed > 0 {
+            self.emit_key_event("zadd", &key, "zset");
+        }
+        if options.ch {
+            added + changed
+        } else {
+            added
+        }
+    }
+
+    // 给成员的 score 加上 increment，成员不存在时从 0 开始，返回新的 score；
+    // 结果为 NaN（例如 inf 加 -inf）时不修改并返回 None
+    pub fn zincrby(&self, key: String, member: String, increment: f64) -> Option<f64> {
+        self.zadd_incr(key, member, increment, ZAddOptions::default())
+            .filter(|score| !score.is_nan())
+    }
+
+    // ZADD INCR：在 NX / XX / GT / LT 允许时给成员的 score 加上 increment 并返回新的 score，
+    // 条件不满足时返回 None；结果为 NaN 时不修改，原样返回 NaN 由调用方报错
+    pub fn zadd_incr(
+        &self,
+        key: String,
+        member: String,
+        increment: f64,
+        options: ZAddOptions,
+    ) -> Option<f64> {
+        self.touch_for_write(&key);
+        let key_len = key.len();
+        let mut zset = self.zset_map.entry(key.clone()).or_insert_with(|| {
+            self.grow_memory(key_len);
+            ZSet::new()
+        });
+        let old = zset.score(&member);
+        let score = old.unwrap_or(0.0) + increment;
+        let allowed = score.is_nan() || options.allows(old, score);
+        if allowed && !score.is_nan() {
+            if old.is_none() {
+                self.grow_memory(zset_member_size(&member));
+            }
+            zset.insert(member, score);
+        }
+        let empty = zset.is_empty();
+        drop(zset);
+        if empty
+            && self
+                .zset_map
+                .remove_if(&key, |_, zset| zset.is_empty())
+                .is_some()
+        {
+            self.shrink_memory(key_len);
+            self.access_map.remove(&key);
+        }
+        if allowed && !score.is_nan() {
+            self.emit_key_event("zincr", &key, "zset");
+        }
+        allowed.then_some(score)
+    }
+
+    pub fn zrank(&self, key: &str, member: &str) -> Option<usize> {
+        self.touch(key);
+        self.zset_map.get(key).and_then(|zset| zset.rank(member))
+    }
+
+    pub fn zcard(&self, key: &str) -> usize {
+        self.touch(key);
+        self.zset_map.get(key).map_or(0, |zset| zset.len())
+    }
+
+    // 删除成员，返回实际删除的数量；集合被删空时删除 key
+    pub fn zrem(&self, key: &str, members: &[String]) -> usize {
+        self.touch(key);
+        let (removed, empty) = {
+            let Some(mut zset) = self.zset_map.get_mut(key) else {
+                return 0;
+            };
+            let removed = members
+                .iter()
+                .filter(|m| zset.remove(m))
+                .map(|m| self.shrink_memory(zset_member_size(m)))
+                .count();
+            (removed, zset.is_empty())
+        };
+        if empty
+            && self
+                .zset_map
+                .remove_if(key, |_, zset| zset.is_empty())
+                .is_some()
+        {
+            self.shrink_memory(key.len());
+            self.ttl_map.remove(key);
+            self.access_map.remove(key);
+        }
+        if removed > 0 {
+            self.emit_key_event("zrem", key, "zset");
+        }
+        removed
+    }
+
+    pub fn zscore(&self, key: &str, member: &str) -> Option<f64> {
+        self.touch(key);
+        self.zset_map.get(key).and_then(|zset| zset.score(member))
+    }
+
+    // 按 score 升序返回所有成员
+    pub fn zmembers(&self, key: &str) -> Vec<(String, f64)> {
+        self.touch(key);
+        self.zset_map
+            .get(key)
+            .map(|zset| {
+                zset.iter()
+                    .map(|(member, score)| (member.to_string(), score))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // 返回 score 位于 [min, max] 之间的成员，跳过前 offset 个，最多返回 count 个
+    pub fn zrange_by_score(
+        &self,
+        key: &str,
+        min: ScoreBound,
+        max: ScoreBound,
+        offset: usize,
+        count: Option<usize>,
+    ) -> Vec<(String, f64)> {
+        self.touch(key);
+        self.zset_map
+            .get(key)
+            .map(|zset| {
+                zset.iter()
+                    .skip_while(|(_, score)| !min.below(*score))
+                    .take_while(|(_, score)| max.above(*score))
+                    .skip(offset)
+                    .take(count.unwrap_or(usize::MAX))
+                    .map(|(member, score)| (member.to_string(), score))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // 所有 key 的并集，每个 key 的 score 先乘以对应的权重再按 aggregate 合并
+    pub fn zunion(&self, keys: &[String], weights: &[f64], aggregate: Aggregate) -> ZSet {
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        for (key, weight) in keys.iter().zip(weights) {
+            self.touch(key);
+            let Some(zset) = self.zset_map.get(key) else {
+                continue;
+            };
+            for (member, score) in zset.iter() {
+                let score = weighted(score, *weight);
+                scores
+                    .entry(member.to_string())
+                    .and_modify(|v| *v = aggregate.apply(*v, score))
+                    .or_insert(score);
+            }
+        }
+        scores.into_iter().collect()
+    }
+
+    // 所有 key 的交集，score 的计算方式与 zunion 相同
+    pub fn zinter(&self, keys: &[String], weights: &[f64], aggregate: Aggregate) -> ZSet {
+        let mut scores: Option<HashMap<String, f64>> = None;
+        for (key, weight) in keys.iter().zip(weights) {
+            self.touch(key);
+            let Some(zset) = self.zset_map.get(key) else {
+                return ZSet::new();
+            };
+            scores = Some(match scores {
+                None => zset
+                    .iter()
+                    .map(|(member, score)| (member.to_string(), weighted(score, *weight)))
+                    .collect(),
+                Some(mut scores) => {
+                    scores.retain(|member, v| match zset.score(member) {
+                        Some(score) => {
+                            *v = aggregate.apply(*v, weighted(score, *weight));
+                            true
+                        }
+                        None => false,
+                    });
+                    scores
+                }
+            });
+        }
+        scores.unwrap_or_default().into_iter().collect()
+    }
+
+    // 第一个 key 中不属于其余任何 key 的成员，保留第一个 key 中的 score
+    pub fn zdiff(&self, keys: &[String]) -> ZSet {
+        let Some((first, rest)) = keys.split_first() else {
+            return ZSet::new();
+        };
+        self.touch(first);
+        let Some(mut ret) = self.zset_map.get(first).map(|zset| zset.clone()) else {
+            return ZSet::new();
+        };
+        for key in rest {
+            self.touch(key);
+            if let Some(zset) = self.zset_map.get(key) {
+                for (member, _) in zset.iter() {
+                    ret.remove(member);
+                }
+            }
+        }
+        ret
+    }
+
+    // 用 zset 覆盖 dst（无论 dst 原来是什么类型），返回成员数量；zset 为空时删除 dst
+    pub fn zstore(&self, dst: String, zset: ZSet) -> usize {
+        self.del(&dst);
+        let len = zset.len();
+        if len > 0 {
+            self.touch_for_write(&dst);
+            self.grow_memory(dst.len() + zset_size(&zset));
+            self.zset_map.insert(dst, zset);
+        }
+        len
+    }
+}
+
+impl FromIterator<(String, f64)> for ZSet {
+    fn from_iter<T: IntoIterator<Item = (String, f64)>>(iter: T) -> Self {
+        let mut zset = ZSet::new();
+        for (member, score) in iter {
+            zset.insert(member, score);
+        }
+        zset
+    }
+}
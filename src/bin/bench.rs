@@ -0,0 +1,206 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use simple_redis::{Client, RespFrame};
+
+// 压测参数，例如 `--addr 127.0.0.1:6379 --requests 100000 --clients 50 --payload-size 3`
+#[derive(Debug, Clone)]
+struct BenchOptions {
+    addr: String,
+    requests: usize,
+    clients: usize,
+    payload_size: usize,
+}
+
+// 一轮压测的结果
+#[derive(Debug)]
+struct BenchReport {
+    name: &'static str,
+    requests: usize,
+    elapsed: Duration,
+    p50: Duration,
+    p99: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BenchCommand {
+    Set,
+    Get,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            addr: "127.0.0.1:6379".to_string(),
+            requests: 100_000,
+            clients: 50,
+            payload_size: 3,
+        }
+    }
+}
+
+impl BenchOptions {
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut opts = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let name = arg.trim_start_matches("--").to_string();
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("missing value for option '{}'", name))?;
+            let invalid = || anyhow!("invalid value '{}' for option '{}'", value, name);
+            match name.as_str() {
+                "addr" => opts.addr = value.clone(),
+                "requests" => opts.requests = value.parse().map_err(|_| invalid())?,
+                "clients" => match value.parse() {
+                    Ok(n) if n > 0 => opts.clients = n,
+                    _ => return Err(invalid()),
+                },
+                "payload-size" => opts.payload_size = value.parse().map_err(|_| invalid())?,
+                _ => bail!("unknown option '{}'", name),
+            }
+        }
+        Ok(opts)
+    }
+}
+
+impl BenchCommand {
+    fn name(self) -> &'static str {
+        match self {
+            BenchCommand::Set => "SET",
+            BenchCommand::Get => "GET",
+        }
+    }
+}
+
+impl BenchReport {
+    fn ops_per_sec(&self) -> f64 {
+        self.requests as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+// 用 clients 个连接并发执行 requests 条命令，每个连接依次发送，记录每条命令的延迟
+async fn run_bench(opts: &BenchOptions, cmd: BenchCommand) -> Result<BenchReport> {
+    let payload = vec![b'x'; opts.payload_size];
+    let start = Instant::now();
+    let mut tasks = Vec::with_capacity(opts.clients);
+    for i in 0..opts.clients {
+        // 请求平均分给每个连接，余数分给前面的连接
+        let n = opts.requests / opts.clients + usize::from(i < opts.requests % opts.clients);
+        let addr = opts.addr.clone();
+        let payload = payload.clone();
+        tasks.push(tokio::spawn(async move {
+            let mut client = Client::connect(addr).await?;
+            let mut latencies = Vec::with_capacity(n);
+            for j in 0..n {
+                let key = format!("bench:{}:{}", i, j);
+                let begin = Instant::now();
+                let ret = match cmd {
+                    BenchCommand::Set => client.set(&key, payload.clone()).await?,
+                    BenchCommand::Get => client.get(&key).await?,
+                };
+                latencies.push(begin.elapsed());
+                if let RespFrame::Error(e) = ret {
+                    bail!("{} failed: {:?}", cmd.name(), e);
+                }
+            }
+            Ok::<_, anyhow::Error>(latencies)
+        }));
+    }
+    let mut latencies = Vec::with_capacity(opts.requests);
+    for task in tasks {
+        latencies.extend(task.await??);
+    }
+    let elapsed = start.elapsed();
+    latencies.sort_unstable();
+    Ok(BenchReport {
+        name: cmd.name(),
+        requests: latencies.len(),
+        elapsed,
+        p50: percentile(&latencies, 50.0),
+        p99: percentile(&latencies, 99.0),
+    })
+}
+
+// latencies 需要已经升序排列
+fn percentile(latencies: &[Duration], p: f64) -> Duration {
+    if latencies.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p / 100.0 * latencies.len() as f64).ceil() as usize;
+    latencies[rank.clamp(1, latencies.len()) - 1]
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let opts = BenchOptions::from_args(std::env::args().skip(1))?;
+    println!(
+        "{} requests, {} parallel clients, {} bytes payload",
+        opts.requests, opts.clients, opts.payload_size
+    );
+    for cmd in [BenchCommand::Set, BenchCommand::Get] {
+        let report = run_bench(&opts, cmd).await?;
+        println!(
+            "{}: {:.2} requests per second, p50={:.3} msec, p99={:.3} msec",
+            report.name,
+            report.ops_per_sec(),
+            report.p50.as_secs_f64() * 1000.0,
+            report.p99.as_secs_f64() * 1000.0,
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use simple_redis::{network, Backend};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_bench_options_from_args() -> Result<()> {
+        let args = ["--requests", "10", "--clients", "2", "--payload-size", "16"];
+        let opts = BenchOptions::from_args(args.map(String::from))?;
+        assert_eq!(opts.requests, 10);
+        assert_eq!(opts.clients, 2);
+        assert_eq!(opts.payload_size, 16);
+        assert!(BenchOptions::from_args(["--clients", "0"].map(String::from)).is_err());
+        assert!(BenchOptions::from_args(["--requests"].map(String::from)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<_> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&latencies, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&latencies, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&[], 99.0), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_bench_against_in_process_server() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(network::stream_handler(socket, backend.clone()));
+            }
+        });
+
+        let opts = BenchOptions {
+            addr: addr.to_string(),
+            requests: 25,
+            clients: 4,
+            payload_size: 8,
+        };
+        let set = run_bench(&opts, BenchCommand::Set).await?;
+        assert_eq!(set.requests, 25);
+        assert!(set.p50 <= set.p99);
+        let get = run_bench(&opts, BenchCommand::Get).await?;
+        assert_eq!(get.requests, 25);
+        assert!(get.ops_per_sec() > 0.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result};
+use futures::SinkExt;
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio_stream::StreamExt;
+use tokio_util::codec::Framed;
+
+use crate::{network::RespFrameCodec, BulkString, RespArray, RespFrame};
+
+// 简单的 RESP 客户端：一次发送一条命令并等待回复
+#[derive(Debug)]
+pub struct Client {
+    framed: Framed<TcpStream, RespFrameCodec>,
+}
+
+impl Client {
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            framed: Framed::new(stream, RespFrameCodec),
+        })
+    }
+
+    // 以 bulk string 数组的形式发送命令，返回服务端的回复
+    pub async fn command<I, A>(&mut self, args: I) -> Result<RespFrame>
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<Vec<u8>>,
+    {
+        let args: Vec<RespFrame> = args
+            .into_iter()
+            .map(|arg| BulkString::new(arg).into())
+            .collect();
+        self.framed.send(RespArray::new(args).into()).await?;
+        self.framed
+            .next()
+            .await
+            .ok_or_else(|| anyhow!("connection closed by server"))?
+    }
+
+    pub async fn get(&mut self, key: &str) -> Result<RespFrame> {
+        self.command([b"GET".to_vec(), key.into()]).await
+    }
+
+    pub async fn set(&mut self, key: &str, value: impl Into<Vec<u8>>) -> Result<RespFrame> {
+        self.command([b"SET".to_vec(), key.into(), value.into()])
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use crate::{network, Backend, SimpleString};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_client_set_and_get() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await?;
+            network::stream_handler(socket, Backend::new()).await
+        });
+
+        let mut client = Client::connect(addr).await?;
+        assert_eq!(
+            client.set("key", "value").await?,
+            SimpleString::new("OK").into()
+        );
+        assert_eq!(client.get("key").await?, BulkString::new("value").into());
+        Ok(())
+    }
+}
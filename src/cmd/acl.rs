@@ -0,0 +1,238 @@
+use crate::{
+    cmd::{extract_args, extract_string, help_reply, validate_command_at_least},
+    Backend, BulkString, RespArray, RespFrame, RespNull, RespOrderedMap,
+};
+
+use super::{Acl, AclSubcommand, ClientState, CommandError, CommandExecutor};
+
+const ACL_HELP: &[&str] = &[
+    "GETUSER <username>",
+    "    Get the user's details.",
+    "LIST",
+    "    Show users details in config file format.",
+    "SETUSER <username> <attribute> [<attribute> ...]",
+    "    Create or modify a user with the specified attributes.",
+    "WHOAMI",
+    "    Return the current connection username.",
+];
+
+// 不带连接状态执行时按 default 用户处理
+impl CommandExecutor for Acl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_with_client(backend, &ClientState::default())
+    }
+}
+
+impl Acl {
+    pub fn execute_with_client(self, backend: &Backend, client: &ClientState) -> RespFrame {
+        match self.subcommand {
+            AclSubcommand::WhoAmI => BulkString::new(client.user.as_str()).into(),
+            AclSubcommand::List => RespArray::new(
+                backend
+                    .acl_list()
+                    .iter()
+                    .map(|user| BulkString::new(user.to_string()).into())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            AclSubcommand::SetUser(name, rules) => match backend.acl_setuser(&name, &rules) {
+                Ok(()) => RespFrame::ok(),
+                Err(e) => RespFrame::error(format!("ERR {}", e)),
+            },
+            AclSubcommand::GetUser(name) => match backend.acl_user(&name) {
+                Some(user) => {
+                    let mut map = RespOrderedMap::new();
+                    let flags: Vec<RespFrame> = user
+                        .flags()
+                        .into_iter()
+                        .map(|flag| BulkString::new(flag).into())
+                        .collect();
+                    let passwords: Vec<RespFrame> = user
+                        .password_hashes()
+                        .map(|hash| BulkString::new(hash).into())
+                        .collect();
+                    map.insert("flags".to_string(), RespArray::new(flags).into());
+                    map.insert("passwords".to_string(), RespArray::new(passwords).into());
+                    map.insert(
+                        "commands".to_string(),
+                        BulkString::new(user.command_rules()).into(),
+                    );
+                    map.insert("keys".to_string(), BulkString::new(user.key_rules()).into());
+                    map.into()
+                }
+                None => RespFrame::Null(RespNull),
+            },
+            AclSubcommand::Help => help_reply("ACL", ACL_HELP),
+        }
+    }
+}
+
+// ACL WHOAMI | ACL LIST | ACL SETUSER username [rule ...] | ACL GETUSER username
+impl TryFrom<RespArray> for Acl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["acl"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let sub = extract_string(args.next())?.to_ascii_lowercase();
+        let subcommand = match sub.as_str() {
+            "whoami" | "list" | "help" if args.next().is_some() => {
+                return Err(CommandError::WrongArity(format!("acl|{}", sub)))
+            }
+            "whoami" => AclSubcommand::WhoAmI,
+            "list" => AclSubcommand::List,
+            "help" => AclSubcommand::Help,
+            "setuser" => {
+                let name = extract_string(args.next())
+                    .map_err(|_| CommandError::WrongArity("acl|setuser".to_string()))?;
+                let rules = args
+                    .map(|rule| extract_string(Some(rule)))
+                    .collect::<Result<_, _>>()?;
+                AclSubcommand::SetUser(name, rules)
+            }
+            "getuser" => match (args.next(), args.next()) {
+                (Some(name), None) => AclSubcommand::GetUser(extract_string(Some(name))?),
+                _ => return Err(CommandError::WrongArity("acl|getuser".to_string())),
+            },
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try ACL HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(Acl { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Command, Response};
+
+    use super::*;
+
+    fn command(args: &[&str]) -> anyhow::Result<Command> {
+        let frames: Vec<RespFrame> = args
+            .iter()
+            .map(|arg| BulkString::new(*arg).into())
+            .collect();
+        Ok(Command::try_from(RespArray::new(frames))?)
+    }
+
+    async fn run(
+        backend: &Backend,
+        client: &mut ClientState,
+        args: &[&str],
+    ) -> anyhow::Result<RespFrame> {
+        match command(args)?.dispatch(backend, client).await {
+            Response::Reply(frame) | Response::ReplyAndClose(frame) => Ok(frame),
+            Response::Replies(frames) => Ok(RespArray::new(frames).into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readonly_user_cannot_write() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut admin = ClientState::default();
+        let ret = run(
+            &backend,
+            &mut admin,
+            &["acl", "setuser", "reader", "on", ">pw", "~*", "+@read"],
+        )
+        .await?;
+        assert_eq!(ret, RespFrame::ok());
+        run(&backend, &mut admin, &["set", "key", "value"]).await?;
+
+        let mut client = ClientState::default();
+        let ret = run(&backend, &mut client, &["auth", "reader", "wrong"]).await?;
+        assert!(matches!(ret, RespFrame::Error(ref e) if e.starts_with("WRONGPASS")));
+        let ret = run(&backend, &mut client, &["auth", "reader", "pw"]).await?;
+        assert_eq!(ret, RespFrame::ok());
+        assert_eq!(client.user, "reader");
+
+        let ret = run(&backend, &mut client, &["get", "key"]).await?;
+        assert_eq!(ret, BulkString::new("value").into());
+        let ret = run(&backend, &mut client, &["set", "key", "other"]).await?;
+        assert_eq!(
+            ret,
+            RespFrame::error("NOPERM User reader has no permissions to run the 'set' command")
+        );
+        assert_eq!(backend.get("key"), Some(BulkString::new("value").into()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_key_patterns() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend
+            .acl_setuser("cache", &["on", "nopass", "~cache:*", "+@all"])
+            .unwrap();
+        let mut client = ClientState::default();
+        run(&backend, &mut client, &["auth", "cache", "any"]).await?;
+        let ret = run(&backend, &mut client, &["set", "cache:1", "v"]).await?;
+        assert_eq!(ret, RespFrame::ok());
+        let ret = run(&backend, &mut client, &["del", "cache:1", "other"]).await?;
+        assert_eq!(
+            ret,
+            RespFrame::error("NOPERM No permissions to access a key")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_whoami_list_getuser() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut client = ClientState::default();
+        let ret = run(&backend, &mut client, &["acl", "whoami"]).await?;
+        assert_eq!(ret, BulkString::new("default").into());
+
+        let ret = run(
+            &backend,
+            &mut client,
+            &["acl", "setuser", "alice", "on", "nopass", "allkeys", "+get"],
+        )
+        .await?;
+        assert_eq!(ret, RespFrame::ok());
+        let ret = run(&backend, &mut client, &["acl", "list"]).await?;
+        assert_eq!(
+            ret,
+            RespArray::new(vec![
+                BulkString::new("user alice on nopass ~* -@all +get").into(),
+                BulkString::new("user default on nopass ~* +@all").into(),
+            ])
+            .into()
+        );
+
+        let RespFrame::OrderedMap(map) =
+            run(&backend, &mut client, &["acl", "getuser", "alice"]).await?
+        else {
+            panic!("ACL GETUSER should reply with a map");
+        };
+        assert_eq!(
+            map.get("flags"),
+            Some(
+                &RespArray::new(vec![
+                    BulkString::new("on").into(),
+                    BulkString::new("nopass").into()
+                ])
+                .into()
+            )
+        );
+        assert_eq!(
+            map.get("commands"),
+            Some(&BulkString::new("-@all +get").into())
+        );
+        let ret = run(&backend, &mut client, &["acl", "getuser", "nobody"]).await?;
+        assert_eq!(ret, RespFrame::Null(RespNull));
+
+        let ret = run(
+            &backend,
+            &mut client,
+            &["acl", "setuser", "alice", "+@nosuch"],
+        )
+        .await?;
+        assert!(
+            matches!(ret, RespFrame::Error(ref e) if e.contains("Unknown command or category"))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    cmd::{extract_args, extract_string, help_reply, validate_command_at_least},
+    glob::glob_match,
+    Backend, BulkString, RespArray, RespFrame, RespOrderedMap,
+};
+
+use super::{CommandError, CommandExecutor, Config, ConfigSubcommand};
+
+const CONFIG_HELP: &[&str] = &[
+    "GET <pattern>",
+    "    Return parameters matching the glob-like <pattern> and their values.",
+    "SET <directive> <value>",
+    "    Set the configuration <directive> to <value>.",
+];
+
+impl CommandExecutor for Config {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            // RESP3 回复 map，RESP2 下展开成 [name, value, name, value, ...]
+            ConfigSubcommand::Get(patterns) => {
+                let mut map = RespOrderedMap::new();
+                for (name, value) in backend.config().params() {
+                    if patterns
+                        .iter()
+                        .any(|pattern| glob_match(pattern.as_bytes(), name.as_bytes()))
+                    {
+                        map.insert(name.to_string(), BulkString::new(value).into());
+                    }
+                }
+                map.into()
+            }
+            ConfigSubcommand::Set(pairs) => match backend.set_configs(&pairs) {
+                Ok(()) => RespFrame::ok(),
+                Err(e) => RespFrame::error(format!("ERR {}", e)),
+            },
+            ConfigSubcommand::Help => help_reply("CONFIG", CONFIG_HELP),
+        }
+    }
+}
+
+// CONFIG GET parameter [parameter ...] | CONFIG SET parameter value [parameter value ...]
+impl TryFrom<RespArray> for Config {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["config"], 1)?;
+        let mut args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        let sub = args.next().unwrap_or_default().to_ascii_lowercase();
+        let rest: Vec<String> = args.collect();
+        let subcommand = match sub.as_str() {
+            "get" if !rest.is_empty() => ConfigSubcommand::Get(
+                rest.into_iter()
+                    .map(|pattern| pattern.to_ascii_lowercase())
+                    .collect(),
+            ),
+            "set" if !rest.is_empty() && rest.len().is_multiple_of(2) => ConfigSubcommand::Set(
+                rest.chunks(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect(),
+            ),
+            "get" | "set" => return Err(CommandError::WrongArity(format!("config|{}", sub))),
+            "help" if rest.is_empty() => ConfigSubcommand::Help,
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand or wrong number of arguments for '{}'. Try CONFIG HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(Config { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(backend: &Backend, args: &[&str]) -> anyhow::Result<RespFrame> {
+        let frames: Vec<RespFrame> = ["config"]
+            .iter()
+            .chain(args)
+            .map(|arg| BulkString::new(*arg).into())
+            .collect();
+        Ok(Config::try_from(RespArray::new(frames))?.execute(backend))
+    }
+
+    #[test]
+    fn test_config_get_patterns() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let RespFrame::OrderedMap(map) = config(&backend, &["get", "MAX*", "hz"])? else {
+            panic!("CONFIG GET should reply with a map");
+        };
+        let names: Vec<&str> = map.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "maxclients",
+                "maxmemory",
+                "maxmemory-policy",
+                "maxmemory-samples",
+                "hz"
+            ]
+        );
+        assert_eq!(map.get("hz"), Some(&BulkString::new("10").into()));
+
+        // RESP2 下是扁平的 [name, value] 数组
+        let ret = config(&backend, &["get", "slowlog-max-len"])?.into_resp2();
+        assert_eq!(
+            ret,
+            RespArray::new(vec![
+                BulkString::new("slowlog-max-len").into(),
+                BulkString::new("128").into(),
+            ])
+            .into()
+        );
+        assert!(config(&backend, &["get"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_set() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let ret = config(
+            &backend,
+            &["set", "maxmemory", "1mb", "maxmemory-policy", "allkeys-lru"],
+        )?;
+        assert_eq!(ret, RespFrame::ok());
+        assert_eq!(backend.config().maxmemory, 1 << 20);
+        assert_eq!(backend.config().maxmemory_policy.to_string(), "allkeys-lru");
+
+        // 任意一项失败时整条命令都不生效
+        let ret = config(
+            &backend,
+            &["set", "maxmemory-samples", "10", "maxmemory", "lots"],
+        )?;
+        assert_eq!(
+            ret,
+            RespFrame::error("ERR Invalid argument 'lots' for CONFIG SET 'maxmemory'")
+        );
+        assert_eq!(backend.config().maxmemory_samples, 5);
+
+        let ret = config(&backend, &["set", "nosuch", "1"])?;
+        assert!(matches!(ret, RespFrame::Error(ref e) if e.contains("Unknown option")));
+        for (name, value) in [("port", "6380"), ("hz", "50"), ("dir", "/tmp")] {
+            let ret = config(&backend, &["set", name, value])?;
+            assert!(matches!(ret, RespFrame::Error(ref e) if e.contains("immutable config")));
+        }
+        assert_eq!(backend.config().hz, 10);
+        assert!(config(&backend, &["set", "hz"]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,817 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    cmd::{
+        extract_args, extract_integer, extract_string, help_reply, validate_command,
+        validate_command_at_least,
+    },
+    sha256::sha256_hex,
+    Backend, BulkString, PauseMode, RespArray, RespFrame, RespOrderedMap, SimpleString,
+    DEFAULT_USER,
+};
+
+use super::{
+    Auth, Client, ClientKill, ClientKillFilter, ClientPauseArgs, ClientState, ClientSubcommand,
+    CommandError, CommandExecutor, Hello, Monitor, Ping, Quit, Reset,
+};
+
+const CLIENT_HELP: &[&str] = &[
+    "GETNAME",
+    "    Return the name of the current connection.",
+    "ID",
+    "    Return the ID of the current connection.",
+    "KILL <ip:port>",
+    "    Kill connection made from <ip:port>.",
+    "KILL <option> <value> [<option> <value> [...]]",
+    "    Kill connections. Options are:",
+    "    * ADDR (<ip:port>|<unixsocket>:0)",
+    "      Kill connections made from the specified address",
+    "    * ID <client-id>",
+    "      Kill connections by client id.",
+    "    * SKIPME (YES|NO)",
+    "      Skip killing current connection (default: yes).",
+    "PAUSE <timeout> [WRITE|ALL]",
+    "    Suspend all, or just write, clients for <timeout> milliseconds.",
+    "SETNAME <name>",
+    "    Assign the name <name> to the current connection.",
+    "UNPAUSE",
+    "    Stop the current client pause, resuming traffic.",
+];
+
+// 关闭连接由 Command::dispatch 返回的 Response::ReplyAndClose 通知连接处理循环
+impl CommandExecutor for Quit {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespFrame::ok()
+    }
+}
+
+// 不带连接状态执行时没有连接可以切换，只回复 OK
+impl CommandExecutor for Monitor {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespFrame::ok()
+    }
+}
+
+// 不带连接状态执行时没有连接可以重置，只回复 RESET
+impl CommandExecutor for Reset {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleString::new("RESET").into()
+    }
+}
+
+impl Reset {
+    // 连接回到刚建立时的状态：取消订阅、退出 MONITOR、恢复 RESP2 和 default 用户、清除连接名
+    pub fn execute_with_client(self, backend: &Backend, client: &mut ClientState) -> RespFrame {
+        backend.unsubscribe_all(client.id);
+        client.subscriptions.clear();
+        client.patterns.clear();
+        client.in_monitor_mode = false;
+        client.protocol = 2;
+        client.name = None;
+        client.user = DEFAULT_USER.to_string();
+        client.authenticated = false;
+        self.execute(backend)
+    }
+}
+
+impl Monitor {
+    // 连接处理循环看到 in_monitor_mode 后开始转发命令流
+    pub fn execute_with_client(self, client: &mut ClientState) -> RespFrame {
+        client.in_monitor_mode = true;
+        RespFrame::ok()
+    }
+}
+
+impl CommandExecutor for Ping {
+    fn execute(self, _: &Backend) -> RespFrame {
+        match self.message {
+            Some(message) => BulkString::new(message).into(),
+            None => SimpleString::new("PONG").into(),
+        }
+    }
+}
+
+impl Ping {
+    // RESP2 的连接处于订阅状态时回复 ["pong", message]，和订阅消息的格式一致
+    pub fn execute_with_client(self, backend: &Backend, client: &ClientState) -> RespFrame {
+        if client.protocol == 2 && client.is_subscribed() {
+            return RespArray::new(vec![
+                BulkString::new("pong").into(),
+                BulkString::new(self.message.unwrap_or_default()).into(),
+            ])
+            .into();
+        }
+        self.execute(backend)
+    }
+}
+
+// 不带连接状态执行时只校验密码
+impl CommandExecutor for Auth {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.check(backend) {
+            Ok(()) => RespFrame::ok(),
+            Err(e) => e,
+        }
+    }
+}
+
+impl Auth {
+    // 校验通过后将连接标记为已认证，并切换到对应的 ACL 用户
+    pub fn execute_with_client(self, backend: &Backend, client: &mut ClientState) -> RespFrame {
+        match self.check(backend) {
+            Ok(()) => {
+                self.login(client);
+                RespFrame::ok()
+            }
+            Err(e) => e,
+        }
+    }
+
+    fn login(&self, client: &mut ClientState) {
+        client.authenticated = true;
+        client.user = self.username.as_deref().unwrap_or(DEFAULT_USER).to_string();
+    }
+
+    fn check(&self, backend: &Backend) -> Result<(), RespFrame> {
+        let username = self.username.as_deref().unwrap_or(DEFAULT_USER);
+        let Some(user) = backend.acl_user(username) else {
+            return Err(wrong_pass());
+        };
+        if self.username.is_none() && user.is_nopass() {
+            return Err(RespFrame::error(
+                "ERR AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?",
+            ));
+        }
+        // 比较密码的摘要，nopass 的用户可以使用任意密码登录
+        let digest = sha256_hex(self.password.as_bytes());
+        let pass_ok = user.is_nopass()
+            || user
+                .password_hashes()
+                .any(|hash| constant_time_eq(hash.as_bytes(), digest.as_bytes()));
+        if user.is_enabled() && pass_ok {
+            Ok(())
+        } else {
+            Err(wrong_pass())
+        }
+    }
+}
+
+// 不带连接状态执行时按默认连接返回服务端信息
+impl CommandExecutor for Hello {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_with_client(backend, &mut ClientState::default())
+    }
+}
+
+impl Hello {
+    // 依次校验协议版本和认证信息，全部通过之后才修改连接状态
+    pub fn execute_with_client(self, backend: &Backend, client: &mut ClientState) -> RespFrame {
+        let protocol = match self.protover {
+            None => client.protocol,
+            Some(v @ (2 | 3)) => v as u8,
+            Some(_) => return RespFrame::error("NOPROTO unsupported protocol version"),
+        };
+        match &self.auth {
+            Some(auth) => {
+                if let Err(e) = auth.check(backend) {
+                    return e;
+                }
+                auth.login(client);
+            }
+            None if !client.is_authenticated(backend) => {
+                return RespFrame::error(
+                    "NOAUTH HELLO must be called with the client already authenticated, otherwise the HELLO <proto> AUTH <user> <pass> option can be used to authenticate the client and select the RESP protocol version at the same time",
+                );
+            }
+            None => {}
+        }
+        if let Some(name) = &self.setname {
+            if let Err(e) = validate_client_name(name) {
+                return e;
+            }
+        }
+        client.protocol = protocol;
+        if let Some(name) = self.setname {
+            client.name = (!name.is_empty()).then_some(name);
+        }
+        server_info(client)
+    }
+}
+
+// 不带连接状态执行时没有“自身”连接可以跳过，其余子命令按默认连接处理
+impl CommandExecutor for Client {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            ClientSubcommand::Kill(kill) => kill.execute_for(backend, None),
+            _ => self.execute_with_client(backend, &mut ClientState::default()),
+        }
+    }
+}
+
+impl Client {
+    pub fn execute_with_client(self, backend: &Backend, client: &mut ClientState) -> RespFrame {
+        match self.subcommand {
+            ClientSubcommand::Kill(kill) => kill.execute_for(backend, Some(client.id)),
+            ClientSubcommand::SetName(name) => match validate_client_name(&name) {
+                Ok(()) => {
+                    client.name = (!name.is_empty()).then_some(name);
+                    RespFrame::ok()
+                }
+                Err(e) => e,
+            },
+            ClientSubcommand::GetName => {
+                BulkString::new(client.name.clone().unwrap_or_default()).into()
+            }
+            ClientSubcommand::Id => RespFrame::Integer(client.id as i64),
+            ClientSubcommand::Pause(pause) => {
+                let deadline = Instant::now() + Duration::from_millis(pause.timeout_ms);
+                backend.client_pause(deadline, pause.mode);
+                RespFrame::ok()
+            }
+            ClientSubcommand::Unpause => {
+                backend.client_unpause();
+                RespFrame::ok()
+            }
+            ClientSubcommand::Help => help_reply("CLIENT", CLIENT_HELP),
+        }
+    }
+}
+
+impl ClientKill {
+    fn execute_for(self, backend: &Backend, me: Option<u64>) -> RespFrame {
+        let skip = if self.skip_me { me } else { None };
+        let killed = backend.kill_clients(|id, handle| {
+            Some(id) != skip
+                && self.filters.iter().all(|filter| match filter {
+                    ClientKillFilter::Id(target) => id == *target,
+                    ClientKillFilter::Addr(addr) => {
+                        handle.addr.is_some_and(|a| a.to_string() == *addr)
+                    }
+                })
+        });
+        match (self.legacy, killed) {
+            (true, 0) => RespFrame::error("ERR No such client"),
+            (true, _) => RespFrame::ok(),
+            (false, n) => RespFrame::Integer(n as i64),
+        }
+    }
+}
+
+fn server_info(client: &ClientState) -> RespFrame {
+    // 字段顺序和 Redis 保持一致
+    let mut map = RespOrderedMap::new();
+    let fields: [(&str, RespFrame); 7] = [
+        ("server", BulkString::new("redis").into()),
+        ("version", BulkString::new(env!("CARGO_PKG_VERSION")).into()),
+        ("proto", RespFrame::Integer(client.protocol as i64)),
+        ("id", RespFrame::Integer(client.id as i64)),
+        ("mode", BulkString::new("standalone").into()),
+        ("role", BulkString::new("master").into()),
+        ("modules", RespArray::new(vec![]).into()),
+    ];
+    for (k, v) in fields {
+        map.insert(k.to_string(), v);
+    }
+    map.into()
+}
+
+fn wrong_pass() -> RespFrame {
+    RespFrame::error("WRONGPASS invalid username-password pair or user is disabled.")
+}
+
+// 比较耗时只取决于两者中较长的长度，不会因为提前返回泄露匹配的前缀
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let len = a.len().max(b.len());
+    let mut diff = (a.len() != b.len()) as u8;
+    for i in 0..len {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+// AUTH password 或 AUTH username password
+impl TryFrom<RespArray> for Auth {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["auth"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (username, password) = match (args.next(), args.next(), args.next()) {
+            (password, None, None) => (None, extract_string(password)?),
+            (username, password, None) => {
+                (Some(extract_string(username)?), extract_string(password)?)
+            }
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(Auth { username, password })
+    }
+}
+
+// HELLO [protover [AUTH username password] [SETNAME clientname]]
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hello"], 0)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut hello = Hello {
+            protover: None,
+            auth: None,
+            setname: None,
+        };
+        let Some(protover) = args.next() else {
+            return Ok(hello);
+        };
+        hello.protover = Some(extract_integer(Some(protover)).map_err(|_| {
+            CommandError::InvalidArgument(
+                "Protocol version is not an integer or out of range".to_string(),
+            )
+        })?);
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+                "auth" => {
+                    hello.auth = Some(Auth {
+                        username: Some(extract_string(args.next())?),
+                        password: extract_string(args.next())?,
+                    })
+                }
+                "setname" => hello.setname = Some(extract_string(args.next())?),
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(hello)
+    }
+}
+
+impl TryFrom<RespArray> for Client {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["client"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let sub = extract_string(args.next())?.to_ascii_lowercase();
+        let subcommand = match sub.as_str() {
+            "kill" => ClientSubcommand::Kill(parse_client_kill(args)?),
+            "setname" => match (args.next(), args.next()) {
+                (Some(name), None) => ClientSubcommand::SetName(extract_string(Some(name))?),
+                _ => return Err(CommandError::WrongArity("client|setname".to_string())),
+            },
+            "pause" => ClientSubcommand::Pause(parse_client_pause(args)?),
+            "getname" | "id" | "unpause" | "help" if args.next().is_some() => {
+                return Err(CommandError::WrongArity(format!("client|{}", sub)))
+            }
+            "getname" => ClientSubcommand::GetName,
+            "id" => ClientSubcommand::Id,
+            "unpause" => ClientSubcommand::Unpause,
+            "help" => ClientSubcommand::Help,
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try CLIENT HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(Client { subcommand })
+    }
+}
+
+// 连接名不能包含空格、换行等不可见字符，和 Redis 一样只允许 `!` 到 `~` 之间的字符
+fn validate_client_name(name: &str) -> Result<(), RespFrame> {
+    if name.bytes().all(|b| (b'!'..=b'~').contains(&b)) {
+        Ok(())
+    } else {
+        Err(RespFrame::error(
+            "ERR Client names cannot contain spaces, newlines or special characters.",
+        ))
+    }
+}
+
+fn parse_client_pause(
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<ClientPauseArgs, CommandError> {
+    let timeout_ms = match extract_integer(args.next()) {
+        Ok(ms) if ms >= 0 => ms as u64,
+        _ => {
+            return Err(CommandError::InvalidArgument(
+                "timeout is not an integer or out of range".to_string(),
+            ))
+        }
+    };
+    let mode = match args.next() {
+        None => PauseMode::All,
+        Some(arg) => match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+            "write" => PauseMode::Write,
+            "all" => PauseMode::All,
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        },
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+    Ok(ClientPauseArgs { timeout_ms, mode })
+}
+
+// CLIENT KILL addr:port 或 CLIENT KILL [ID id] [ADDR addr:port] [SKIPME yes|no]
+fn parse_client_kill(args: impl Iterator<Item = RespFrame>) -> Result<ClientKill, CommandError> {
+    let args = args
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let [addr] = args.as_slice() {
+        return Ok(ClientKill {
+            filters: vec![ClientKillFilter::Addr(addr.clone())],
+            skip_me: false,
+            legacy: true,
+        });
+    }
+    if args.is_empty() || !args.len().is_multiple_of(2) {
+        return Err(CommandError::InvalidArgument("syntax error".to_string()));
+    }
+    let mut kill = ClientKill {
+        filters: Vec::new(),
+        skip_me: true,
+        legacy: false,
+    };
+    for pair in args.chunks(2) {
+        let value = &pair[1];
+        match pair[0].to_ascii_lowercase().as_str() {
+            "id" => {
+                let id = value.parse().map_err(|_| {
+                    CommandError::InvalidArgument("client-id should be greater than 0".to_string())
+                })?;
+                kill.filters.push(ClientKillFilter::Id(id));
+            }
+            "addr" => kill.filters.push(ClientKillFilter::Addr(value.clone())),
+            "skipme" => match value.to_ascii_lowercase().as_str() {
+                "yes" => kill.skip_me = true,
+                "no" => kill.skip_me = false,
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            },
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        }
+    }
+    Ok(kill)
+}
+
+// 和 Redis 一样忽略 QUIT 之后的多余参数
+// PING [message]
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["ping"], 0)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let message = args
+            .next()
+            .map(|arg| extract_string(Some(arg)))
+            .transpose()?;
+        if args.next().is_some() {
+            return Err(CommandError::WrongArity("ping".to_string()));
+        }
+        Ok(Ping { message })
+    }
+}
+
+impl TryFrom<RespArray> for Quit {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["quit"], 0)?;
+        Ok(Quit)
+    }
+}
+
+// RESET
+impl TryFrom<RespArray> for Reset {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["reset"], 0)?;
+        Ok(Reset)
+    }
+}
+
+// MONITOR
+impl TryFrom<RespArray> for Monitor {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["monitor"], 0)?;
+        Ok(Monitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cmd::Command, cmd::Response, BulkString};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_quit_replies_and_closes() -> anyhow::Result<()> {
+        let cmd = Command::try_from(RespArray::new(vec![BulkString::new("QUIT").into()]))?;
+        let ret = cmd
+            .dispatch(&Backend::new(), &mut ClientState::default())
+            .await;
+        assert_eq!(ret, Response::ReplyAndClose(RespFrame::ok()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_restores_client_state() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut client = ClientState {
+            id: 1,
+            ..Default::default()
+        };
+        let run = |args: &[&str]| {
+            Command::try_from(RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::new(*arg).into())
+                    .collect::<Vec<_>>(),
+            ))
+        };
+        run(&["hello", "3", "setname", "conn"])?
+            .dispatch(&backend, &mut client)
+            .await;
+        run(&["subscribe", "news"])?
+            .dispatch(&backend, &mut client)
+            .await;
+        run(&["psubscribe", "n*"])?
+            .dispatch(&backend, &mut client)
+            .await;
+        run(&["monitor"])?.dispatch(&backend, &mut client).await;
+        assert_eq!(backend.pubsub_numpat(), 1);
+
+        let ret = run(&["reset"])?.dispatch(&backend, &mut client).await;
+        assert_eq!(ret, Response::Reply(SimpleString::new("RESET").into()));
+        assert!(!client.is_subscribed() && !client.in_monitor_mode);
+        assert_eq!((client.protocol, client.name.clone()), (2, None));
+        assert_eq!(client.user, DEFAULT_USER);
+        assert!(backend.pubsub_channels(None).is_empty());
+        assert_eq!(backend.pubsub_numpat(), 0);
+
+        assert!(run(&["reset", "now"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret\0"));
+        assert!(!constant_time_eq(b"", b"x"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[tokio::test]
+    async fn test_auth_gates_commands() -> anyhow::Result<()> {
+        let backend = Backend::with_config(crate::ServerConfig {
+            requirepass: Some("secret".to_string()),
+            ..Default::default()
+        });
+        let mut client = ClientState::default();
+        let get = || {
+            Command::try_from(RespArray::new(vec![
+                BulkString::new("get").into(),
+                BulkString::new("key").into(),
+            ]))
+        };
+        let auth = |args: &[&str]| Auth {
+            username: (args.len() == 2).then(|| args[0].to_string()),
+            password: args[args.len() - 1].to_string(),
+        };
+
+        let ret = get()?.dispatch(&backend, &mut client).await;
+        assert_eq!(
+            ret,
+            Response::Reply(RespFrame::error("NOAUTH Authentication required."))
+        );
+        // 认证之前未知命令也回复 NOAUTH，不暴露命令表
+        let nosuch = || Command::try_from(RespArray::new(vec![BulkString::new("nosuch").into()]));
+        let ret = nosuch()?.dispatch(&backend, &mut client).await;
+        assert_eq!(
+            ret,
+            Response::Reply(RespFrame::error("NOAUTH Authentication required."))
+        );
+        let ret = Command::Auth(auth(&["wrong"]))
+            .dispatch(&backend, &mut client)
+            .await;
+        assert!(
+            matches!(ret, Response::Reply(RespFrame::Error(ref e)) if e.starts_with("WRONGPASS"))
+        );
+        let ret = Command::Auth(auth(&["alice", "secret"]))
+            .dispatch(&backend, &mut client)
+            .await;
+        assert!(
+            matches!(ret, Response::Reply(RespFrame::Error(ref e)) if e.starts_with("WRONGPASS"))
+        );
+        assert!(!client.authenticated);
+
+        let ret = Command::Auth(auth(&["default", "secret"]))
+            .dispatch(&backend, &mut client)
+            .await;
+        assert_eq!(ret, Response::Reply(RespFrame::ok()));
+        let ret = get()?.dispatch(&backend, &mut client).await;
+        assert_eq!(ret, Response::Reply(RespFrame::Null(crate::RespNull)));
+        let ret = nosuch()?.dispatch(&backend, &mut client).await;
+        assert!(
+            matches!(ret, Response::Reply(RespFrame::Error(ref e)) if e.starts_with("ERR unknown command"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_auth_without_requirepass() {
+        let backend = Backend::new();
+        let auth = Auth {
+            username: None,
+            password: "any".to_string(),
+        };
+        assert!(
+            matches!(auth.execute(&backend), RespFrame::Error(ref e) if e.starts_with("ERR AUTH"))
+        );
+    }
+
+    #[test]
+    fn test_hello_switches_protocol() {
+        let backend = Backend::new();
+        let mut client = ClientState::default();
+        let hello = |protover| Hello {
+            protover,
+            auth: None,
+            setname: Some("conn".to_string()),
+        };
+        let ret = hello(Some(4)).execute_with_client(&backend, &mut client);
+        assert!(matches!(ret, RespFrame::Error(ref e) if e.starts_with("NOPROTO")));
+        assert_eq!(client.protocol, 2);
+        assert!(client.name.is_none());
+
+        let RespFrame::OrderedMap(map) = hello(Some(3)).execute_with_client(&backend, &mut client)
+        else {
+            panic!("HELLO should reply with a map");
+        };
+        // 字段按 Redis 的顺序输出
+        let keys: Vec<&str> = map.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            ["server", "version", "proto", "id", "mode", "role", "modules"]
+        );
+        assert_eq!(map.get("proto"), Some(&RespFrame::Integer(3)));
+        assert_eq!(map.get("id"), Some(&RespFrame::Integer(client.id as i64)));
+        assert_eq!(client.protocol, 3);
+        assert_eq!(client.name.as_deref(), Some("conn"));
+    }
+
+    #[test]
+    fn test_hello_with_auth() {
+        let backend = Backend::with_config(crate::ServerConfig {
+            requirepass: Some("secret".to_string()),
+            ..Default::default()
+        });
+        let mut client = ClientState::default();
+        let hello = |password: Option<&str>| Hello {
+            protover: Some(3),
+            auth: password.map(|password| Auth {
+                username: Some("default".to_string()),
+                password: password.to_string(),
+            }),
+            setname: None,
+        };
+        let ret = hello(None).execute_with_client(&backend, &mut client);
+        assert!(matches!(ret, RespFrame::Error(ref e) if e.starts_with("NOAUTH")));
+        let ret = hello(Some("wrong")).execute_with_client(&backend, &mut client);
+        assert!(matches!(ret, RespFrame::Error(ref e) if e.starts_with("WRONGPASS")));
+        assert_eq!(client.protocol, 2);
+        let ret = hello(Some("secret")).execute_with_client(&backend, &mut client);
+        assert!(matches!(ret, RespFrame::OrderedMap(_)));
+        assert!(client.authenticated);
+        assert_eq!(client.protocol, 3);
+    }
+
+    #[test]
+    fn test_client_kill_try_from_resp_array() -> anyhow::Result<()> {
+        let frame = RespArray::new(vec![
+            BulkString::new("client").into(),
+            BulkString::new("kill").into(),
+            BulkString::new("ID").into(),
+            BulkString::new("7").into(),
+            BulkString::new("SKIPME").into(),
+            BulkString::new("no").into(),
+        ]);
+        let cmd: Client = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            ClientSubcommand::Kill(ClientKill {
+                filters: vec![ClientKillFilter::Id(7)],
+                skip_me: false,
+                legacy: false,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_kill_execute() {
+        let backend = Backend::new();
+        let mut me = ClientState::default();
+        let (my_token, _) = backend.register_client(me.id, None).unwrap();
+        let other = ClientState::default();
+        let addr = "127.0.0.1:6000".parse().unwrap();
+        let (other_token, _) = backend.register_client(other.id, Some(addr)).unwrap();
+
+        let kill = |filters, legacy| Client {
+            subcommand: ClientSubcommand::Kill(ClientKill {
+                filters,
+                skip_me: true,
+                legacy,
+            }),
+        };
+        // 默认跳过自身
+        let ret =
+            kill(vec![ClientKillFilter::Id(me.id)], false).execute_with_client(&backend, &mut me);
+        assert_eq!(ret, RespFrame::Integer(0));
+        assert!(!my_token.is_cancelled());
+
+        let ret = kill(
+            vec![ClientKillFilter::Addr("127.0.0.1:1".to_string())],
+            true,
+        )
+        .execute_with_client(&backend, &mut me);
+        assert!(matches!(ret, RespFrame::Error(_)));
+        let ret = kill(
+            vec![ClientKillFilter::Addr("127.0.0.1:6000".to_string())],
+            true,
+        )
+        .execute_with_client(&backend, &mut me);
+        assert_eq!(ret, RespFrame::ok());
+        assert!(other_token.is_cancelled());
+    }
+
+    #[test]
+    fn test_client_setname_getname_id() {
+        let backend = Backend::new();
+        let mut client = ClientState::default();
+        let mut run = |subcommand| Client { subcommand }.execute_with_client(&backend, &mut client);
+        assert_eq!(run(ClientSubcommand::GetName), BulkString::new("").into());
+        assert_eq!(
+            run(ClientSubcommand::SetName("worker-1".to_string())),
+            RespFrame::ok()
+        );
+        assert_eq!(
+            run(ClientSubcommand::GetName),
+            BulkString::new("worker-1").into()
+        );
+        // 非法的名字不会覆盖原来的名字
+        let ret = run(ClientSubcommand::SetName("bad name".to_string()));
+        assert!(matches!(ret, RespFrame::Error(_)));
+        let ret = run(ClientSubcommand::SetName("bad\nname".to_string()));
+        assert!(matches!(ret, RespFrame::Error(_)));
+        assert_eq!(
+            run(ClientSubcommand::GetName),
+            BulkString::new("worker-1").into()
+        );
+        // 空字符串清除连接名
+        run(ClientSubcommand::SetName(String::new()));
+        assert_eq!(run(ClientSubcommand::GetName), BulkString::new("").into());
+        let id = client.id;
+        let ret = Client {
+            subcommand: ClientSubcommand::Id,
+        }
+        .execute_with_client(&backend, &mut client);
+        assert_eq!(ret, RespFrame::Integer(id as i64));
+    }
+
+    #[test]
+    fn test_client_pause_try_from_resp_array() -> anyhow::Result<()> {
+        let parse = |args: &[&str]| {
+            let args: Vec<RespFrame> = args.iter().map(|a| BulkString::new(*a).into()).collect();
+            Client::try_from(RespArray::new(args))
+        };
+        assert_eq!(
+            parse(&["client", "pause", "100"])?.subcommand,
+            ClientSubcommand::Pause(ClientPauseArgs {
+                timeout_ms: 100,
+                mode: PauseMode::All,
+            })
+        );
+        assert_eq!(
+            parse(&["client", "pause", "0", "write"])?.subcommand,
+            ClientSubcommand::Pause(ClientPauseArgs {
+                timeout_ms: 0,
+                mode: PauseMode::Write,
+            })
+        );
+        assert!(parse(&["client", "pause", "-1"]).is_err());
+        assert!(parse(&["client", "pause", "100", "read"]).is_err());
+        assert_eq!(
+            parse(&["client", "unpause"])?.subcommand,
+            ClientSubcommand::Unpause
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_setname_arity() {
+        let frame = RespArray::new(vec![
+            BulkString::new("client").into(),
+            BulkString::new("setname").into(),
+        ]);
+        let ret: Result<Client, _> = frame.try_into();
+        assert!(matches!(ret, Err(CommandError::WrongArity(_))));
+    }
+}
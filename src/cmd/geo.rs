@@ -0,0 +1,1049 @@
+use crate::{
+    cmd::{extract_args, extract_string, validate_command, validate_command_at_least},
+    Backend, BulkString, RespArray, RespFrame, RespNull, ZAddOptions,
+};
+
+use super::{
+    CommandError, CommandExecutor, GeoAdd, GeoCenter, GeoDist, GeoPos, GeoRadius,
+    GeoRadiusByMember, GeoRadiusOptions, GeoSearch, GeoSearchOptions, GeoShape, GeoUnit, SortOrder,
+};
+
+const GEO_LON_MIN: f64 = -180.0;
+const GEO_LON_MAX: f64 = 180.0;
+const GEO_LAT_MIN: f64 = -85.05112878;
+const GEO_LAT_MAX: f64 = 85.05112878;
+// 经度、纬度各占 26 位，交织后共 52 位，可以无损地存为 f64 score
+const GEO_STEP: u32 = 26;
+const EARTH_RADIUS_IN_METERS: f64 = 6372797.560856;
+
+impl CommandExecutor for GeoAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let members = self
+            .members
+            .into_iter()
+            .map(|(lon, lat, member)| (member, encode_geohash(lon, lat) as f64))
+            .collect();
+        RespFrame::Integer(backend.zadd(self.key, members, self.options) as i64)
+    }
+}
+
+impl CommandExecutor for GeoPos {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let frames = self
+            .members
+            .iter()
+            .map(|member| match backend.zscore(&self.key, member) {
+                Some(score) => {
+                    let (lon, lat) = decode_geohash(score as u64);
+                    RespArray::new(vec![lon.into(), lat.into()]).into()
+                }
+                None => RespFrame::Null(RespNull),
+            })
+            .collect::<Vec<_>>();
+        RespArray::new(frames).into()
+    }
+}
+
+impl CommandExecutor for GeoDist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let pos1 = backend.zscore(&self.key, &self.member1);
+        let pos2 = backend.zscore(&self.key, &self.member2);
+        match (pos1, pos2) {
+            (Some(score1), Some(score2)) => {
+                let (lon1, lat1) = decode_geohash(score1 as u64);
+                let (lon2, lat2) = decode_geohash(score2 as u64);
+                let dist = haversine_m(lon1, lat1, lon2, lat2) / self.unit.to_meters();
+                BulkString::new(format!("{:.4}", dist)).into()
+            }
+            _ => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+// 目前直接遍历整个有序集合过滤，而不是像 Redis 那样只查找中心点附近的 geohash 网格
+impl CommandExecutor for GeoSearch {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let options = self.options;
+        let center = match options.center {
+            GeoCenter::Member(ref member) => match member_position(backend, &self.key, member) {
+                Ok(pos) => pos,
+                Err(e) => return e,
+            },
+            GeoCenter::LonLat(lon, lat) => (lon, lat),
+        };
+        let hits = search(
+            backend,
+            &self.key,
+            center,
+            &options.shape,
+            options.order,
+            options.count,
+        );
+        hits_reply(
+            hits,
+            options.shape.unit(),
+            options.with_dist,
+            options.with_coord,
+        )
+    }
+}
+
+impl CommandExecutor for GeoRadius {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        georadius(backend, &self.key, (self.lon, self.lat), self.options)
+    }
+}
+
+impl CommandExecutor for GeoRadiusByMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match member_position(backend, &self.key, &self.member) {
+            Ok(center) => georadius(backend, &self.key, center, self.options),
+            Err(e) => e,
+        }
+    }
+}
+
+// 查询命中的成员
+struct GeoHit {
+    member: String,
+    score: f64,
+    // 到中心点的距离（米）
+    dist: f64,
+    // (经度, 纬度)
+    pos: (f64, f64),
+}
+
+fn member_position(backend: &Backend, key: &str, member: &str) -> Result<(f64, f64), RespFrame> {
+    backend
+        .zscore(key, member)
+        .map(|score| decode_geohash(score as u64))
+        .ok_or_else(|| RespFrame::error("ERR could not decode requested zset member"))
+}
+
+fn search(
+    backend: &Backend,
+    key: &str,
+    (lon, lat): (f64, f64),
+    shape: &GeoShape,
+    order: Option<SortOrder>,
+    count: Option<(usize, bool)>,
+) -> Vec<GeoHit> {
+    let mut hits = Vec::new();
+    for (member, score) in backend.zmembers(key) {
+        let pos = decode_geohash(score as u64);
+        if let Some(dist) = shape.distance_within(lon, lat, pos.0, pos.1) {
+            hits.push(GeoHit {
+                member,
+                score,
+                dist,
+                pos,
+            });
+            // ANY：找到足够数量的结果后立即停止
+            if matches!(count, Some((n, true)) if hits.len() >= n) {
+                break;
+            }
+        }
+    }
+
+    // 指定了 COUNT 但没有 ANY 和排序方式时，按距离升序返回最近的结果
+    let order = match (order, count) {
+        (None, Some((_, false))) => Some(SortOrder::Asc),
+        (order, _) => order,
+    };
+    match order {
+        Some(SortOrder::Asc) => hits.sort_by(|a, b| a.dist.total_cmp(&b.dist)),
+        Some(SortOrder::Desc) => hits.sort_by(|a, b| b.dist.total_cmp(&a.dist)),
+        None => {}
+    }
+    if let Some((n, _)) = count {
+        hits.truncate(n);
+    }
+    hits
+}
+
+fn hits_reply(hits: Vec<GeoHit>, unit: GeoUnit, with_dist: bool, with_coord: bool) -> RespFrame {
+    let unit = unit.to_meters();
+    let frames = hits
+        .into_iter()
+        .map(|hit| {
+            if !with_dist && !with_coord {
+                return BulkString::new(hit.member).into();
+            }
+            let mut item = vec![BulkString::new(hit.member).into()];
+            if with_dist {
+                item.push(BulkString::new(format!("{:.4}", hit.dist / unit)).into());
+            }
+            if with_coord {
+                item.push(RespArray::new(vec![hit.pos.0.into(), hit.pos.1.into()]).into());
+            }
+            RespArray::new(item).into()
+        })
+        .collect::<Vec<RespFrame>>();
+    RespArray::new(frames).into()
+}
+
+fn georadius(
+    backend: &Backend,
+    key: &str,
+    center: (f64, f64),
+    options: GeoRadiusOptions,
+) -> RespFrame {
+    let shape = GeoShape::Radius(options.radius, options.unit);
+    let hits = search(backend, key, center, &shape, options.order, options.count);
+    match options.store {
+        // 保留原来的 geohash 作为 score，结果仍然可以用 GEO 命令查询
+        Some(dest) => {
+            let zset = hits
+                .into_iter()
+                .map(|hit| (hit.member, hit.score))
+                .collect();
+            RespFrame::Integer(backend.zstore(dest, zset) as i64)
+        }
+        None => hits_reply(hits, options.unit, options.with_dist, options.with_coord),
+    }
+}
+
+impl GeoShape {
+    pub fn unit(&self) -> GeoUnit {
+        match self {
+            GeoShape::Radius(_, unit) | GeoShape::Box(_, _, unit) => *unit,
+        }
+    }
+
+    // 点 (lon2, lat2) 位于以 (lon1, lat1) 为中心的区域内时返回两点距离（米）
+    fn distance_within(&self, lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> Option<f64> {
+        match *self {
+            GeoShape::Radius(radius, unit) => {
+                let dist = haversine_m(lon1, lat1, lon2, lat2);
+                (dist <= radius * unit.to_meters()).then_some(dist)
+            }
+            GeoShape::Box(width, height, unit) => {
+                let (width, height) = (width * unit.to_meters(), height * unit.to_meters());
+                // 分别比较南北方向和东西方向的距离
+                if haversine_m(0.0, lat1, 0.0, lat2) > height / 2.0
+                    || haversine_m(lon2, lat2, lon1, lat2) > width / 2.0
+                {
+                    return None;
+                }
+                Some(haversine_m(lon1, lat1, lon2, lat2))
+            }
+        }
+    }
+}
+
+impl GeoUnit {
+    pub fn to_meters(self) -> f64 {
+        match self {
+            GeoUnit::Meters => 1.0,
+            GeoUnit::Kilometers => 1000.0,
+            GeoUnit::Miles => 1609.34,
+            GeoUnit::Feet => 0.3048,
+        }
+    }
+}
+
+impl TryFrom<&str> for GeoUnit {
+    type Error = CommandError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "m" => Ok(GeoUnit::Meters),
+            "km" => Ok(GeoUnit::Kilometers),
+            "mi" => Ok(GeoUnit::Miles),
+            "ft" => Ok(GeoUnit::Feet),
+            _ => Err(CommandError::InvalidArgument(
+                "unsupported unit provided. please use M, KM, FT, MI".to_string(),
+            )),
+        }
+    }
+}
+
+// 将经纬度编码为 52 位的 geohash：经度的第 i 位放在 2i+1，纬度的第 i 位放在 2i
+pub fn encode_geohash(lon: f64, lat: f64) -> u64 {
+    let scale = (1u64 << GEO_STEP) as f64;
+    let lon_offset = (lon - GEO_LON_MIN) / (GEO_LON_MAX - GEO_LON_MIN);
+    let lat_offset = (lat - GEO_LAT_MIN) / (GEO_LAT_MAX - GEO_LAT_MIN);
+    let max = (1u64 << GEO_STEP) - 1;
+    let lon_bits = ((lon_offset * scale) as u64).min(max);
+    let lat_bits = ((lat_offset * scale) as u64).min(max);
+    (spread_bits(lon_bits) << 1) | spread_bits(lat_bits)
+}
+
+// 解码 geohash，返回所在网格中心点的 (经度, 纬度)
+pub fn decode_geohash(hash: u64) -> (f64, f64) {
+    let scale = (1u64 << GEO_STEP) as f64;
+    let lon_bits = squash_bits(hash >> 1) as f64;
+    let lat_bits = squash_bits(hash) as f64;
+    let lon = GEO_LON_MIN + (lon_bits + 0.5) / scale * (GEO_LON_MAX - GEO_LON_MIN);
+    let lat = GEO_LAT_MIN + (lat_bits + 0.5) / scale * (GEO_LAT_MAX - GEO_LAT_MIN);
+    (
+        lon.clamp(GEO_LON_MIN, GEO_LON_MAX),
+        lat.clamp(GEO_LAT_MIN, GEO_LAT_MAX),
+    )
+}
+
+// 两点之间的球面距离（米）
+pub fn haversine_m(lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((lon2 - lon1).to_radians() / 2.0).sin();
+    let a = u * u + lat1.cos() * lat2.cos() * v * v;
+    2.0 * EARTH_RADIUS_IN_METERS * a.sqrt().asin()
+}
+
+pub(crate) fn validate_coordinates(lon: f64, lat: f64) -> Result<(), CommandError> {
+    if !(GEO_LON_MIN..=GEO_LON_MAX).contains(&lon) || !(GEO_LAT_MIN..=GEO_LAT_MAX).contains(&lat) {
+        return Err(CommandError::InvalidArgument(format!(
+            "invalid longitude,latitude pair {:.6},{:.6}",
+            lon, lat
+        )));
+    }
+    Ok(())
+}
+
+// 将低 32 位的每一位 i 移动到 2i
+fn spread_bits(v: u64) -> u64 {
+    let mut v = v & 0xFFFF_FFFF;
+    v = (v | (v << 16)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v << 8)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    v = (v | (v << 1)) & 0x5555_5555_5555_5555;
+    v
+}
+
+// spread_bits 的逆操作，取出所有偶数位
+fn squash_bits(v: u64) -> u64 {
+    let mut v = v & 0x5555_5555_5555_5555;
+    v = (v | (v >> 1)) & 0x3333_3333_3333_3333;
+    v = (v | (v >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v >> 4)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v >> 8)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v >> 16)) & 0x0000_0000_FFFF_FFFF;
+    v
+}
+
+pub(crate) fn parse_float(s: &str) -> Result<f64, CommandError> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|v| !v.is_nan())
+        .ok_or_else(|| CommandError::InvalidArgument("value is not a valid float".to_string()))
+}
+
+impl TryFrom<RespArray> for GeoAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["geoadd"], 4)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = args[0].clone();
+
+        let mut options = ZAddOptions::default();
+        let mut i = 1;
+        while let Some(arg) = args.get(i) {
+            match arg.to_ascii_lowercase().as_str() {
+                "nx" => options.nx = true,
+                "xx" => options.xx = true,
+                "ch" => options.ch = true,
+                _ => break,
+            }
+            i += 1;
+        }
+        if options.nx && options.xx {
+            return Err(CommandError::InvalidArgument(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+
+        let rest = &args[i..];
+        if rest.is_empty() || rest.len() % 3 != 0 {
+            return Err(CommandError::InvalidArgument(
+                "wrong number of arguments for 'geoadd' command".to_string(),
+            ));
+        }
+        let members = rest
+            .chunks(3)
+            .map(|chunk| {
+                let (lon, lat) = (parse_float(&chunk[0])?, parse_float(&chunk[1])?);
+                validate_coordinates(lon, lat)?;
+                Ok((lon, lat, chunk[2].clone()))
+            })
+            .collect::<Result<Vec<_>, CommandError>>()?;
+        Ok(GeoAdd {
+            key,
+            options,
+            members,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for GeoPos {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["geopos"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let members = args
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(GeoPos { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for GeoDist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let unit = match value.len() {
+            4 => {
+                validate_command(&value, &["geodist"], 3)?;
+                GeoUnit::Meters
+            }
+            _ => {
+                validate_command(&value, &["geodist"], 4)?;
+                match value[4] {
+                    RespFrame::BulkString(ref unit) => {
+                        GeoUnit::try_from(String::from_utf8_lossy(unit).as_ref())?
+                    }
+                    _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+                }
+            }
+        };
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GeoDist {
+            key: extract_string(args.next())?,
+            member1: extract_string(args.next())?,
+            member2: extract_string(args.next())?,
+            unit,
+        })
+    }
+}
+
+// GEOSEARCH key FROMMEMBER member|FROMLONLAT lon lat BYRADIUS r unit|BYBOX w h unit
+//   [ASC|DESC] [COUNT n [ANY]] [WITHCOORD] [WITHDIST]
+impl TryFrom<RespArray> for GeoSearch {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["geosearch"], 5)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = args[0].clone();
+
+        let mut center = None;
+        let mut shape = None;
+        let mut order = None;
+        let mut count = None;
+        let mut with_coord = false;
+        let mut with_dist = false;
+        let mut i = 1;
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        let arg = |i: usize| args.get(i).ok_or_else(syntax_error);
+        while i < args.len() {
+            match args[i].to_ascii_lowercase().as_str() {
+                "frommember" if center.is_none() => {
+                    center = Some(GeoCenter::Member(arg(i + 1)?.clone()));
+                    i += 2;
+                }
+                "fromlonlat" if center.is_none() => {
+                    let lon = parse_float(arg(i + 1)?)?;
+                    let lat = parse_float(arg(i + 2)?)?;
+                    validate_coordinates(lon, lat)?;
+                    center = Some(GeoCenter::LonLat(lon, lat));
+                    i += 3;
+                }
+                "frommember" | "fromlonlat" => {
+                    return Err(CommandError::InvalidArgument(
+                        "exactly one of FROMMEMBER or FROMLONLAT can be specified for GEOSEARCH"
+                            .to_string(),
+                    ))
+                }
+                "byradius" if shape.is_none() => {
+                    let radius = parse_distance(arg(i + 1)?)?;
+                    let unit = GeoUnit::try_from(arg(i + 2)?.as_str())?;
+                    shape = Some(GeoShape::Radius(radius, unit));
+                    i += 3;
+                }
+                "bybox" if shape.is_none() => {
+                    let width = parse_distance(arg(i + 1)?)?;
+                    let height = parse_distance(arg(i + 2)?)?;
+                    let unit = GeoUnit::try_from(arg(i + 3)?.as_str())?;
+                    shape = Some(GeoShape::Box(width, height, unit));
+                    i += 4;
+                }
+                "byradius" | "bybox" => {
+                    return Err(CommandError::InvalidArgument(
+                        "exactly one of BYRADIUS and BYBOX can be specified for GEOSEARCH"
+                            .to_string(),
+                    ))
+                }
+                "asc" => {
+                    order = Some(SortOrder::Asc);
+                    i += 1;
+                }
+                "desc" => {
+                    order = Some(SortOrder::Desc);
+                    i += 1;
+                }
+                "count" => {
+                    let (n, any) = parse_count(&args, i)?;
+                    count = Some((n, any));
+                    i += if any { 3 } else { 2 };
+                }
+                "withcoord" => {
+                    with_coord = true;
+                    i += 1;
+                }
+                "withdist" => {
+                    with_dist = true;
+                    i += 1;
+                }
+                "any" => {
+                    return Err(CommandError::InvalidArgument(
+                        "the ANY argument requires COUNT argument".to_string(),
+                    ))
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+
+        let center = center.ok_or_else(|| {
+            CommandError::InvalidArgument(
+                "exactly one of FROMMEMBER or FROMLONLAT can be specified for GEOSEARCH"
+                    .to_string(),
+            )
+        })?;
+        let shape = shape.ok_or_else(|| {
+            CommandError::InvalidArgument(
+                "exactly one of BYRADIUS and BYBOX can be specified for GEOSEARCH".to_string(),
+            )
+        })?;
+        Ok(GeoSearch {
+            key,
+            options: GeoSearchOptions {
+                center,
+                shape,
+                order,
+                count,
+                with_coord,
+                with_dist,
+            },
+        })
+    }
+}
+
+// GEORADIUS key lon lat radius unit [WITHCOORD] [WITHDIST] [COUNT n [ANY]] [ASC|DESC] [STORE dest]
+impl TryFrom<RespArray> for GeoRadius {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["georadius"], 5)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (lon, lat) = (parse_float(&args[1])?, parse_float(&args[2])?);
+        validate_coordinates(lon, lat)?;
+        Ok(GeoRadius {
+            key: args[0].clone(),
+            lon,
+            lat,
+            options: parse_radius_options(&args[3..])?,
+        })
+    }
+}
+
+// GEORADIUSBYMEMBER key member radius unit [和 GEORADIUS 相同的选项]
+impl TryFrom<RespArray> for GeoRadiusByMember {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["georadiusbymember"], 4)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(GeoRadiusByMember {
+            key: args[0].clone(),
+            member: args[1].clone(),
+            options: parse_radius_options(&args[2..])?,
+        })
+    }
+}
+
+// args 从 radius 开始
+fn parse_radius_options(args: &[String]) -> Result<GeoRadiusOptions, CommandError> {
+    let mut options = GeoRadiusOptions {
+        radius: parse_distance(&args[0])?,
+        unit: GeoUnit::try_from(args[1].as_str())?,
+        order: None,
+        count: None,
+        with_coord: false,
+        with_dist: false,
+        store: None,
+    };
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].to_ascii_lowercase().as_str() {
+            "withcoord" => options.with_coord = true,
+            "withdist" => options.with_dist = true,
+            "asc" => options.order = Some(SortOrder::Asc),
+            "desc" => options.order = Some(SortOrder::Desc),
+            "count" => {
+                let (n, any) = parse_count(args, i)?;
+                options.count = Some((n, any));
+                i += if any { 2 } else { 1 };
+            }
+            "store" => {
+                options.store = Some(args.get(i + 1).ok_or_else(syntax_error)?.clone());
+                i += 1;
+            }
+            "any" => {
+                return Err(CommandError::InvalidArgument(
+                    "the ANY argument requires COUNT argument".to_string(),
+                ))
+            }
+            _ => return Err(syntax_error()),
+        }
+        i += 1;
+    }
+    if options.store.is_some() && (options.with_coord || options.with_dist) {
+        return Err(CommandError::InvalidArgument(
+            "STORE option in GEORADIUS is not compatible with WITHDIST, WITHHASH and WITHCOORD options"
+                .to_string(),
+        ));
+    }
+    Ok(options)
+}
+
+// args[i] 是 COUNT，返回 (数量, 是否跟着 ANY)
+fn parse_count(args: &[String], i: usize) -> Result<(usize, bool), CommandError> {
+    let n = args
+        .get(i + 1)
+        .ok_or_else(|| CommandError::InvalidArgument("syntax error".to_string()))?
+        .parse::<i64>()
+        .map_err(|_| {
+            CommandError::InvalidArgument("value is not an integer or out of range".to_string())
+        })?;
+    if n <= 0 {
+        return Err(CommandError::InvalidArgument(
+            "COUNT must be > 0".to_string(),
+        ));
+    }
+    let any = args
+        .get(i + 2)
+        .is_some_and(|arg| arg.eq_ignore_ascii_case("any"));
+    Ok((n as usize, any))
+}
+
+// 半径、宽高必须是非负数
+fn parse_distance(s: &str) -> Result<f64, CommandError> {
+    let v = parse_float(s)?;
+    if v < 0.0 {
+        return Err(CommandError::InvalidArgument(
+            "radius cannot be negative".to_string(),
+        ));
+    }
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::RespDecoder;
+
+    use super::*;
+
+    fn palermo_catania() -> GeoAdd {
+        GeoAdd {
+            key: "Sicily".to_string(),
+            options: ZAddOptions::default(),
+            members: vec![
+                (13.361389, 38.115556, "Palermo".to_string()),
+                (15.087269, 37.502669, "Catania".to_string()),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_geohash_roundtrip() {
+        let coords = [
+            (13.361389, 38.115556),
+            (-122.419416, 37.774929),
+            (-58.381592, -34.603722),
+            (151.209290, -33.868820),
+            (0.0, 0.0),
+            (-179.999999, -85.0),
+            (179.999999, 85.0),
+        ];
+        for (lon, lat) in coords {
+            let hash = encode_geohash(lon, lat);
+            assert!(hash < 1 << 52, "{} is wider than 52 bits", hash);
+            let (lon2, lat2) = decode_geohash(hash);
+            // 26 位精度下网格边长约 0.6 米，中心点到原始坐标的距离不会超过它
+            let dist = haversine_m(lon, lat, lon2, lat2);
+            assert!(dist < 0.6, "({}, {}) decoded {} m away", lon, lat, dist);
+        }
+    }
+
+    #[test]
+    fn test_geohash_bit_layout() {
+        // 和 Redis 的 score 一致
+        assert_eq!(encode_geohash(13.361389, 38.115556), 3479099956230698);
+        assert_eq!(encode_geohash(GEO_LON_MIN, GEO_LAT_MIN), 0);
+        // 只有经度在最高位：经度的位在奇数位置
+        assert_eq!(encode_geohash(0.0, GEO_LAT_MIN), 1 << 51);
+        assert_eq!(encode_geohash(GEO_LON_MIN, 0.0), 1 << 50);
+    }
+
+    #[test]
+    fn test_geoadd_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$6\r\ngeoadd\r\n$6\r\nSicily\r\n$2\r\nCH\r\n$9\r\n13.361389\r\n$9\r\n38.115556\r\n$7\r\nPalermo\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let geoadd: GeoAdd = cmd.try_into()?;
+        assert_eq!(geoadd.key, "Sicily");
+        assert!(geoadd.options.ch);
+        assert_eq!(
+            geoadd.members,
+            vec![(13.361389, 38.115556, "Palermo".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_geoadd_rejects_invalid_coordinates() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\ngeoadd\r\n$1\r\nk\r\n$3\r\n200\r\n$2\r\n10\r\n$1\r\nm\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<GeoAdd, _> = cmd.try_into();
+        assert!(ret.is_err());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\ngeoadd\r\n$1\r\nk\r\n$2\r\n10\r\n$2\r\n86\r\n$1\r\nm\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<GeoAdd, _> = cmd.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_geoadd_geopos_execute() {
+        let backend = Backend::new();
+        assert_eq!(palermo_catania().execute(&backend), RespFrame::Integer(2));
+        assert_eq!(palermo_catania().execute(&backend), RespFrame::Integer(0));
+
+        let geopos = GeoPos {
+            key: "Sicily".to_string(),
+            members: vec!["Palermo".to_string(), "NonExisting".to_string()],
+        };
+        let RespFrame::Array(ret) = geopos.execute(&backend) else {
+            panic!("GEOPOS should return an array");
+        };
+        assert_eq!(ret[1], RespFrame::Null(RespNull));
+        let RespFrame::Array(ref pos) = ret[0] else {
+            panic!("GEOPOS entry should be an array");
+        };
+        match (&pos[0], &pos[1]) {
+            (RespFrame::Double(lon), RespFrame::Double(lat)) => {
+                assert!((lon - 13.361389).abs() < 1e-5);
+                assert!((lat - 38.115556).abs() < 1e-5);
+            }
+            _ => panic!("GEOPOS coordinates should be doubles"),
+        }
+    }
+
+    #[test]
+    fn test_geodist_execute() {
+        let backend = Backend::new();
+        palermo_catania().execute(&backend);
+        let geodist = GeoDist {
+            key: "Sicily".to_string(),
+            member1: "Palermo".to_string(),
+            member2: "Catania".to_string(),
+            unit: GeoUnit::Kilometers,
+        };
+        assert_eq!(
+            geodist.execute(&backend),
+            BulkString::new("166.2742").into()
+        );
+
+        let geodist = GeoDist {
+            key: "Sicily".to_string(),
+            member1: "Palermo".to_string(),
+            member2: "Rome".to_string(),
+            unit: GeoUnit::Meters,
+        };
+        assert_eq!(geodist.execute(&backend), RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_geosearch_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*12\r\n$9\r\ngeosearch\r\n$6\r\nSicily\r\n$10\r\nFROMLONLAT\r\n$2\r\n15\r\n$2\r\n37\r\n$5\r\nBYBOX\r\n$3\r\n400\r\n$3\r\n400\r\n$2\r\nkm\r\n$4\r\nDESC\r\n$5\r\nCOUNT\r\n$1\r\n1\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let geosearch: GeoSearch = cmd.try_into()?;
+        assert_eq!(geosearch.key, "Sicily");
+        assert_eq!(
+            geosearch.options,
+            GeoSearchOptions {
+                center: GeoCenter::LonLat(15.0, 37.0),
+                shape: GeoShape::Box(400.0, 400.0, GeoUnit::Kilometers),
+                order: Some(SortOrder::Desc),
+                count: Some((1, false)),
+                with_coord: false,
+                with_dist: false,
+            }
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*9\r\n$9\r\ngeosearch\r\n$1\r\nk\r\n$10\r\nFROMMEMBER\r\n$1\r\nm\r\n$8\r\nBYRADIUS\r\n$2\r\n10\r\n$1\r\nm\r\n$8\r\nWITHDIST\r\n$3\r\nANY\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<GeoSearch, _> = cmd.try_into();
+        assert!(ret.is_err());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$9\r\ngeosearch\r\n$1\r\nk\r\n$10\r\nFROMMEMBER\r\n$1\r\nm\r\n$10\r\nFROMMEMBER\r\n$1\r\nn\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<GeoSearch, _> = cmd.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    fn geosearch(center: GeoCenter, shape: GeoShape) -> GeoSearchOptions {
+        GeoSearchOptions {
+            center,
+            shape,
+            order: None,
+            count: None,
+            with_coord: false,
+            with_dist: false,
+        }
+    }
+
+    #[test]
+    fn test_geosearch_by_radius_execute() {
+        let backend = Backend::new();
+        palermo_catania().execute(&backend);
+        let options = GeoSearchOptions {
+            order: Some(SortOrder::Asc),
+            with_dist: true,
+            ..geosearch(
+                GeoCenter::LonLat(15.0, 37.0),
+                GeoShape::Radius(200.0, GeoUnit::Kilometers),
+            )
+        };
+        let ret = GeoSearch {
+            key: "Sicily".to_string(),
+            options,
+        }
+        .execute(&backend);
+        let expected: RespFrame = RespArray::new(vec![
+            RespArray::new(vec![
+                BulkString::new("Catania").into(),
+                BulkString::new("56.4413").into(),
+            ])
+            .into(),
+            RespArray::new(vec![
+                BulkString::new("Palermo").into(),
+                BulkString::new("190.4424").into(),
+            ])
+            .into(),
+        ])
+        .into();
+        assert_eq!(ret, expected);
+
+        // COUNT 不带 ANY 时返回距离最近的结果
+        let options = GeoSearchOptions {
+            count: Some((1, false)),
+            ..geosearch(
+                GeoCenter::Member("Palermo".to_string()),
+                GeoShape::Radius(500.0, GeoUnit::Kilometers),
+            )
+        };
+        let ret = GeoSearch {
+            key: "Sicily".to_string(),
+            options,
+        }
+        .execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new(vec![BulkString::new("Palermo").into()]).into()
+        );
+    }
+
+    #[test]
+    fn test_geosearch_by_box_execute() {
+        let backend = Backend::new();
+        palermo_catania().execute(&backend);
+        let options = GeoSearchOptions {
+            order: Some(SortOrder::Desc),
+            with_coord: true,
+            ..geosearch(
+                GeoCenter::LonLat(15.0, 37.0),
+                GeoShape::Box(400.0, 400.0, GeoUnit::Kilometers),
+            )
+        };
+        let RespFrame::Array(ret) = (GeoSearch {
+            key: "Sicily".to_string(),
+            options,
+        })
+        .execute(&backend) else {
+            panic!("GEOSEARCH should return an array");
+        };
+        assert_eq!(ret.len(), 2);
+        let RespFrame::Array(ref first) = ret[0] else {
+            panic!("GEOSEARCH entry should be an array");
+        };
+        assert_eq!(first[0], BulkString::new("Palermo").into());
+        assert!(matches!(first[1], RespFrame::Array(ref pos) if pos.len() == 2));
+
+        // 宽度不足以覆盖 Palermo
+        let options = geosearch(
+            GeoCenter::LonLat(15.0, 37.0),
+            GeoShape::Box(100.0, 400.0, GeoUnit::Kilometers),
+        );
+        let ret = GeoSearch {
+            key: "Sicily".to_string(),
+            options,
+        }
+        .execute(&backend);
+        assert_eq!(
+            ret,
+            RespArray::new(vec![BulkString::new("Catania").into()]).into()
+        );
+    }
+
+    #[test]
+    fn test_geosearch_missing_member() {
+        let backend = Backend::new();
+        palermo_catania().execute(&backend);
+        let options = geosearch(
+            GeoCenter::Member("Rome".to_string()),
+            GeoShape::Radius(10.0, GeoUnit::Meters),
+        );
+        let ret = GeoSearch {
+            key: "Sicily".to_string(),
+            options,
+        }
+        .execute(&backend);
+        assert!(matches!(ret, RespFrame::Error(_)));
+    }
+
+    #[test]
+    fn test_georadius_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*11\r\n$9\r\ngeoradius\r\n$6\r\nSicily\r\n$2\r\n15\r\n$2\r\n37\r\n$3\r\n200\r\n$2\r\nkm\r\n$8\r\nWITHDIST\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n$3\r\nANY\r\n$4\r\nDESC\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let georadius: GeoRadius = cmd.try_into()?;
+        assert_eq!((georadius.lon, georadius.lat), (15.0, 37.0));
+        assert_eq!(
+            georadius.options,
+            GeoRadiusOptions {
+                radius: 200.0,
+                unit: GeoUnit::Kilometers,
+                order: Some(SortOrder::Desc),
+                count: Some((2, true)),
+                with_coord: false,
+                with_dist: true,
+                store: None,
+            }
+        );
+
+        // STORE 不能和 WITHDIST、WITHCOORD 一起使用
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*8\r\n$17\r\ngeoradiusbymember\r\n$6\r\nSicily\r\n$7\r\nPalermo\r\n$3\r\n200\r\n$2\r\nkm\r\n$5\r\nSTORE\r\n$4\r\ndest\r\n$9\r\nWITHCOORD\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<GeoRadiusByMember, _> = cmd.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    fn georadius(radius: f64, unit: GeoUnit) -> GeoRadiusOptions {
+        GeoRadiusOptions {
+            radius,
+            unit,
+            order: None,
+            count: None,
+            with_coord: false,
+            with_dist: false,
+            store: None,
+        }
+    }
+
+    #[test]
+    fn test_georadius_execute() {
+        let backend = Backend::new();
+        palermo_catania().execute(&backend);
+        let ret = GeoRadius {
+            key: "Sicily".to_string(),
+            lon: 15.0,
+            lat: 37.0,
+            options: GeoRadiusOptions {
+                order: Some(SortOrder::Asc),
+                with_dist: true,
+                with_coord: true,
+                ..georadius(100.0, GeoUnit::Kilometers)
+            },
+        }
+        .execute(&backend);
+        let RespFrame::Array(ret) = ret else {
+            panic!("GEORADIUS should return an array");
+        };
+        assert_eq!(ret.len(), 1);
+        let RespFrame::Array(ref item) = ret[0] else {
+            panic!("GEORADIUS entry should be an array");
+        };
+        assert_eq!(item[0], BulkString::new("Catania").into());
+        assert_eq!(item[1], BulkString::new("56.4413").into());
+        let RespFrame::Array(ref pos) = item[2] else {
+            panic!("WITHCOORD should add coordinates");
+        };
+        assert!(matches!(pos[0], RespFrame::Double(lon) if (lon - 15.087269).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_georadiusbymember_store_execute() {
+        let backend = Backend::new();
+        palermo_catania().execute(&backend);
+        let ret = GeoRadiusByMember {
+            key: "Sicily".to_string(),
+            member: "Palermo".to_string(),
+            options: GeoRadiusOptions {
+                store: Some("nearby".to_string()),
+                ..georadius(200.0, GeoUnit::Kilometers)
+            },
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        assert_eq!(
+            backend.zscore("nearby", "Catania"),
+            backend.zscore("Sicily", "Catania")
+        );
+
+        let ret = GeoRadiusByMember {
+            key: "Sicily".to_string(),
+            member: "Rome".to_string(),
+            options: georadius(200.0, GeoUnit::Kilometers),
+        }
+        .execute(&backend);
+        assert!(matches!(ret, RespFrame::Error(_)));
+    }
+}
@@ -1,9 +1,16 @@
 use crate::{
-    cmd::{extract_args, validate_command},
-    RespArray, RespFrame, RespMap,
+    cmd::{
+        extract_args, extract_integer, extract_string, parse_scan_args, validate_command,
+        validate_command_at_least, wrong_arity,
+    },
+    Backend, BulkString, RespArray, RespFrame, RespMap, Rng,
 };
 
-use super::{CommandError, CommandExecutor, HGet, HGetAll, HSet, RESP_OK};
+use super::{CommandError, CommandExecutor, HGet, HGetAll, HRandField, HScan, HSet};
+
+// HRANDFIELD 负数 count 允许重复，回复长度只由 count 决定，所有结果都要先放进回复数组，
+// 超过这个数量的 count 直接报错，避免耗尽内存
+const MAX_RANDOM_FIELDS: i64 = 1 << 24;
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
@@ -16,29 +23,70 @@ impl CommandExecutor for HGet {
 
 impl CommandExecutor for HSet {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value.clone());
-        RESP_OK.clone()
+        let added = backend.hset_fields(self.key, self.fields);
+        RespFrame::Integer(added as i64)
     }
 }
 
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &crate::Backend) -> RespFrame {
-        let hmap = backend.hgetall(&self.key);
-        match hmap {
-            Some(m) => {
-                let mut frames = RespMap::new();
-                for v in m.iter() {
-                    let key = v.key().to_string();
-                    let value = v.value().clone();
-                    frames.insert(key, value);
-                }
-                frames.into()
+        let frames = backend.hgetall_with(&self.key, |m| {
+            let mut frames = RespMap::new();
+            for v in m.iter() {
+                frames.insert(v.key().to_string(), v.value().clone());
             }
+            frames
+        });
+        match frames {
+            Some(frames) => frames.into(),
             None => RespFrame::Array(crate::RespArray::new(Vec::new())),
         }
     }
 }
 
+impl CommandExecutor for HScan {
+    fn execute(self, backend: &crate::Backend) -> RespFrame {
+        let (cursor, items) =
+            backend.hscan(&self.key, self.cursor, self.pattern.as_deref(), self.count);
+        let mut frames = Vec::with_capacity(items.len() * 2);
+        for (field, value) in items {
+            frames.push(BulkString::new(field).into());
+            frames.push(value);
+        }
+        RespArray::new(vec![
+            BulkString::new(cursor.to_string()).into(),
+            RespArray::new(frames).into(),
+        ])
+        .into()
+    }
+}
+
+impl CommandExecutor for HRandField {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_with_rng(backend, &mut Rng::from_time())
+    }
+}
+
+impl HRandField {
+    pub fn execute_with_rng(self, backend: &Backend, rng: &mut Rng) -> RespFrame {
+        let Some(count) = self.count else {
+            return match backend.hrandfield(&self.key, 1, rng).pop() {
+                Some((field, _)) => BulkString::new(field).into(),
+                None => RespFrame::Null(crate::RespNull),
+            };
+        };
+        // WITHVALUES 时字段和值交替出现在同一个数组中
+        let mut frames = Vec::new();
+        for (field, value) in backend.hrandfield(&self.key, count, rng) {
+            frames.push(BulkString::new(field).into());
+            if self.with_values {
+                frames.push(value);
+            }
+        }
+        RespArray::new(frames).into()
+    }
+}
+
 impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
@@ -56,23 +104,29 @@ impl TryFrom<RespArray> for HGet {
     }
 }
 
+// HSET key field value [field value ...]
 impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hset"], 3)?;
+        validate_command_at_least(&value, &["hset"], 3)?;
+        if !value.len().is_multiple_of(2) {
+            return Err(wrong_arity(&["hset"]));
+        }
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(HSet {
-                    key: String::from_utf8(key.0)?,
-                    field: String::from_utf8(field.0)?,
-                    value,
-                })
-            }
-            _ => Err(CommandError::InvalidArgument(
-                "Invalid key, field or value".to_string(),
-            )),
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        };
+        let mut fields = Vec::new();
+        while let (Some(field), Some(value)) = (args.next(), args.next()) {
+            let RespFrame::BulkString(field) = field else {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid key, field or value".to_string(),
+                ));
+            };
+            fields.push((String::from_utf8(field.0)?, value));
         }
+        Ok(HSet { key, fields })
     }
 }
 
@@ -90,6 +144,64 @@ impl TryFrom<RespArray> for HGetAll {
     }
 }
 
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hscan"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (cursor, pattern, count, _) = parse_scan_args(args, false)?;
+        Ok(HScan {
+            key,
+            cursor,
+            pattern,
+            count,
+        })
+    }
+}
+
+// HRANDFIELD key [count [WITHVALUES]]
+impl TryFrom<RespArray> for HRandField {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["hrandfield"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = args
+            .next()
+            .map(|arg| extract_integer(Some(arg)))
+            .transpose()?;
+        let with_values = match args
+            .next()
+            .map(|arg| extract_string(Some(arg)))
+            .transpose()?
+        {
+            None => false,
+            Some(arg) if arg.eq_ignore_ascii_case("withvalues") => true,
+            Some(_) => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        // WITHVALUES 时回复长度是 count 的两倍
+        let limit = if with_values {
+            MAX_RANDOM_FIELDS / 2
+        } else {
+            MAX_RANDOM_FIELDS
+        };
+        if count.is_some_and(|count| count < -limit) {
+            return Err(CommandError::InvalidArgument(
+                "value is out of range".to_string(),
+            ));
+        }
+        Ok(HRandField {
+            key,
+            count,
+            with_values,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -120,8 +232,40 @@ mod tests {
         let hset: HSet = cmd.try_into()?;
         let value = BulkString::new("value".to_string());
         assert_eq!(hset.key, "key");
-        assert_eq!(hset.field, "field");
-        assert_eq!(hset.value, RespFrame::BulkString(value));
+        assert_eq!(hset.fields, vec![("field".to_string(), value.into())]);
+
+        // 字段和值必须成对出现
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nhset\r\n$3\r\nkey\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n",
+        );
+        let cmd: RespArray = RespArray::decode(&mut buf)?;
+        let ret: Result<HSet, _> = cmd.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hset_multiple_fields_execute() -> anyhow::Result<()> {
+        let backend = crate::Backend::new();
+        backend.hset(
+            "key".to_string(),
+            "a".to_string(),
+            BulkString::new("old").into(),
+        );
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*8\r\n$4\r\nhset\r\n$3\r\nkey\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n$1\r\nc\r\n$1\r\n3\r\n",
+        );
+        let hset: HSet = RespArray::decode(&mut buf)?.try_into()?;
+        // a 已经存在，只新增了 b 和 c
+        assert_eq!(hset.execute(&backend), RespFrame::Integer(2));
+        for (field, value) in [("a", "1"), ("b", "2"), ("c", "3")] {
+            assert_eq!(
+                backend.hget("key", field),
+                Some(BulkString::new(value).into())
+            );
+        }
         Ok(())
     }
 
@@ -135,4 +279,212 @@ mod tests {
         assert_eq!(hgetall.key, "key");
         Ok(())
     }
+
+    #[test]
+    fn test_hgetall_execute() {
+        let backend = Backend::new();
+        backend.hset(
+            "key".to_string(),
+            "f".to_string(),
+            BulkString::new("v").into(),
+        );
+        let ret = HGetAll {
+            key: "key".to_string(),
+        }
+        .execute(&backend);
+        let mut expected = RespMap::new();
+        expected.insert("f".to_string(), BulkString::new("v").into());
+        assert_eq!(ret, expected.into());
+
+        let ret = HGetAll {
+            key: "missing".to_string(),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespArray::new(Vec::new()).into());
+    }
+
+    #[test]
+    fn test_hscan_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$5\r\nhscan\r\n$3\r\nkey\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$2\r\nf*\r\n$5\r\nCOUNT\r\n$2\r\n20\r\n",
+        );
+        let cmd: RespArray = RespArray::decode(&mut buf)?;
+        let hscan: HScan = cmd.try_into()?;
+        assert_eq!(hscan.key, "key");
+        assert_eq!(hscan.cursor, 0);
+        assert_eq!(hscan.pattern.as_deref(), Some("f*"));
+        assert_eq!(hscan.count, 20);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_iterates_all_fields() {
+        let backend = crate::Backend::new();
+        for i in 0..100 {
+            backend.hset(
+                "key".to_string(),
+                format!("field{}", i),
+                BulkString::new(i.to_string()).into(),
+            );
+        }
+        let mut fields = std::collections::HashSet::new();
+        let mut cursor = 0;
+        let mut calls = 0;
+        loop {
+            let hscan = HScan {
+                key: "key".to_string(),
+                cursor,
+                pattern: None,
+                count: 15,
+            };
+            let RespFrame::Array(ret) = hscan.execute(&backend) else {
+                panic!("HSCAN should return an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(items)) = (&ret[0], &ret[1]) else {
+                panic!("HSCAN should return [cursor, items]");
+            };
+            for pair in items.chunks(2) {
+                let RespFrame::BulkString(field) = &pair[0] else {
+                    panic!("field should be a bulk string");
+                };
+                fields.insert(String::from_utf8(field.to_vec()).unwrap());
+            }
+            calls += 1;
+            cursor = String::from_utf8_lossy(next).parse().unwrap();
+            if cursor == 0 {
+                break;
+            }
+        }
+        assert_eq!(fields.len(), 100);
+        assert_eq!(calls, 7);
+    }
+
+    #[test]
+    fn test_hscan_match_filters_fields() {
+        let backend = crate::Backend::new();
+        for field in ["name", "age", "nickname"] {
+            backend.hset(
+                "key".to_string(),
+                field.to_string(),
+                BulkString::new("v").into(),
+            );
+        }
+        let hscan = HScan {
+            key: "key".to_string(),
+            cursor: 0,
+            pattern: Some("*name".to_string()),
+            count: 10,
+        };
+        let expected: RespFrame = RespArray::new(vec![
+            BulkString::new("0").into(),
+            RespArray::new(vec![
+                BulkString::new("name").into(),
+                BulkString::new("v").into(),
+                BulkString::new("nickname").into(),
+                BulkString::new("v").into(),
+            ])
+            .into(),
+        ])
+        .into();
+        assert_eq!(hscan.execute(&backend), expected);
+    }
+
+    fn hash_with_fields(backend: &crate::Backend, n: usize) {
+        for i in 0..n {
+            backend.hset(
+                "key".to_string(),
+                format!("f{}", i),
+                BulkString::new(format!("v{}", i)).into(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_hrandfield_single_field() {
+        let backend = crate::Backend::new();
+        let hrandfield = |key: &str| HRandField {
+            key: key.to_string(),
+            count: None,
+            with_values: false,
+        };
+        assert_eq!(
+            hrandfield("key").execute_with_rng(&backend, &mut Rng::new(1)),
+            RespFrame::Null(crate::RespNull)
+        );
+
+        hash_with_fields(&backend, 3);
+        let ret = hrandfield("key").execute_with_rng(&backend, &mut Rng::new(1));
+        let RespFrame::BulkString(field) = &ret else {
+            panic!("HRANDFIELD should return a bulk string");
+        };
+        assert!(backend
+            .hget("key", &String::from_utf8_lossy(field))
+            .is_some());
+        // 相同的种子得到相同的结果
+        assert_eq!(
+            hrandfield("key").execute_with_rng(&backend, &mut Rng::new(1)),
+            ret
+        );
+    }
+
+    #[test]
+    fn test_hrandfield_rejects_huge_negative_count() {
+        let parse = |args: &[&str]| {
+            let frames: Vec<RespFrame> = args.iter().map(|a| BulkString::new(*a).into()).collect();
+            HRandField::try_from(RespArray::new(frames))
+        };
+        for args in [
+            &["hrandfield", "h", "-9223372036854775808"][..],
+            &["hrandfield", "h", "-100000000"],
+            &["hrandfield", "h", "-10000000", "withvalues"],
+        ] {
+            let err = parse(args).unwrap_err();
+            assert_eq!(
+                RespFrame::from(err),
+                RespFrame::error("ERR value is out of range")
+            );
+        }
+        assert!(parse(&["hrandfield", "h", "-1000"]).is_ok());
+        assert!(parse(&["hrandfield", "h", "9223372036854775807"]).is_ok());
+    }
+
+    #[test]
+    fn test_hrandfield_count_with_values() {
+        let backend = crate::Backend::new();
+        hash_with_fields(&backend, 5);
+        let hrandfield = |count| HRandField {
+            key: "key".to_string(),
+            count: Some(count),
+            with_values: true,
+        };
+
+        let RespFrame::Array(ret) = hrandfield(3).execute_with_rng(&backend, &mut Rng::new(7))
+        else {
+            panic!("HRANDFIELD should return an array");
+        };
+        assert_eq!(ret.len(), 6);
+        let mut fields = std::collections::HashSet::new();
+        for pair in ret.chunks(2) {
+            let RespFrame::BulkString(field) = &pair[0] else {
+                panic!("field should be a bulk string");
+            };
+            let field = String::from_utf8_lossy(field).into_owned();
+            assert_eq!(backend.hget("key", &field), Some(pair[1].clone()));
+            fields.insert(field);
+        }
+        assert_eq!(fields.len(), 3);
+
+        // 正数 count 超过字段数时返回全部字段，负数允许重复
+        let RespFrame::Array(ret) = hrandfield(10).execute_with_rng(&backend, &mut Rng::new(7))
+        else {
+            panic!("HRANDFIELD should return an array");
+        };
+        assert_eq!(ret.len(), 10);
+        let RespFrame::Array(ret) = hrandfield(-8).execute_with_rng(&backend, &mut Rng::new(7))
+        else {
+            panic!("HRANDFIELD should return an array");
+        };
+        assert_eq!(ret.len(), 16);
+    }
 }
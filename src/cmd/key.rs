@@ -0,0 +1,654 @@
+use std::time::Duration;
+
+use crate::{
+    cmd::{extract_args, extract_integer, extract_string, parse_scan_args, validate_command},
+    Backend, BulkString, ExpireFlag, RespArray, RespFrame,
+};
+
+use super::{
+    validate_command_at_least, CommandError, CommandExecutor, Copy, Del, Expire, ExpireAt,
+    ExpireTime, PExpire, PExpireAt, PExpireTime, PTtl, Scan, Ttl,
+};
+
+impl CommandExecutor for Del {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let removed = self.keys.iter().filter(|key| backend.del(key)).count();
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl CommandExecutor for Copy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // 只有 0 号数据库，和 Redis 配置 databases 1 时一样拒绝其它编号
+        if self.db != 0 {
+            return RespFrame::error("ERR DB index is out of range");
+        }
+        if self.source == self.destination {
+            return RespFrame::error("ERR source and destination objects are the same");
+        }
+        let copied = backend.copy(&self.source, &self.destination, self.replace);
+        RespFrame::Integer(copied as i64)
+    }
+}
+
+impl CommandExecutor for Scan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, keys) = backend.scan(
+            self.cursor,
+            self.pattern.as_deref(),
+            self.key_type.as_deref(),
+            self.count,
+        );
+        let keys: Vec<RespFrame> = keys
+            .into_iter()
+            .map(|key| BulkString::new(key).into())
+            .collect();
+        RespArray::new(vec![
+            BulkString::new(cursor.to_string()).into(),
+            RespArray::new(keys).into(),
+        ])
+        .into()
+    }
+}
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_millis(
+            backend,
+            &self.key,
+            self.seconds.saturating_mul(1000),
+            self.flag,
+        )
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_millis(backend, &self.key, self.milliseconds, self.flag)
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_at_millis(backend, &self.key, self.timestamp.saturating_mul(1000))
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_at_millis(backend, &self.key, self.timestamp_ms)
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // 不足一秒的部分向上取整，和 Redis 保持一致
+        ttl_reply(backend, &self.key, |ttl| {
+            ttl.as_millis().div_ceil(1000) as i64
+        })
+    }
+}
+
+impl CommandExecutor for PTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        ttl_reply(backend, &self.key, |ttl| ttl.as_millis() as i64)
+    }
+}
+
+impl CommandExecutor for ExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expiretime_reply(backend, &self.key, |t| t.as_secs() as i64)
+    }
+}
+
+impl CommandExecutor for PExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expiretime_reply(backend, &self.key, |t| t.as_millis() as i64)
+    }
+}
+
+// 设置过期时间，非正数时直接删除 key；返回 1 表示设置成功，0 表示 key 不存在或 flag 的条件不满足
+fn expire_millis(backend: &Backend, key: &str, millis: i64, flag: Option<ExpireFlag>) -> RespFrame {
+    let ttl = Duration::from_millis(millis.max(0) as u64);
+    let updated = backend.expire(key, ttl, flag);
+    RespFrame::Integer(updated as i64)
+}
+
+// 以 unix 毫秒时间戳设置过期时间，负数和已经过去的时间都会直接删除 key
+fn expire_at_millis(backend: &Backend, key: &str, millis: i64) -> RespFrame {
+    let at = Duration::from_millis(millis.max(0) as u64);
+    let updated = backend.expire_at_unix(key, at);
+    RespFrame::Integer(updated as i64)
+}
+
+// key 不存在返回 -2，没有过期时间返回 -1
+fn ttl_reply(backend: &Backend, key: &str, convert: impl Fn(Duration) -> i64) -> RespFrame {
+    match backend.ttl(key) {
+        None => RespFrame::Integer(-2),
+        Some(None) => RespFrame::Integer(-1),
+        Some(Some(ttl)) => RespFrame::Integer(convert(ttl)),
+    }
+}
+
+// 和 ttl_reply 一样，key 不存在返回 -2，没有过期时间返回 -1
+fn expiretime_reply(backend: &Backend, key: &str, convert: impl Fn(Duration) -> i64) -> RespFrame {
+    match backend.expiretime(key) {
+        None => RespFrame::Integer(-2),
+        Some(None) => RespFrame::Integer(-1),
+        Some(Some(t)) => RespFrame::Integer(convert(t)),
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["del"], 1)?;
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|frame| extract_string(Some(frame)))
+            .collect::<Result<_, _>>()?;
+        Ok(Del { keys })
+    }
+}
+
+// 解析 `[NX | XX | GT | LT]`，同一个选项可以重复，不同的选项不能同时出现
+fn parse_expire_flag(
+    args: impl Iterator<Item = RespFrame>,
+) -> Result<Option<ExpireFlag>, CommandError> {
+    let mut flag = None;
+    for arg in args {
+        let next = match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+            "nx" => ExpireFlag::Nx,
+            "xx" => ExpireFlag::Xx,
+            "gt" => ExpireFlag::Gt,
+            "lt" => ExpireFlag::Lt,
+            other => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "Unsupported option {}",
+                    other
+                )))
+            }
+        };
+        match flag {
+            Some(flag) if flag != next => {
+                let msg = if flag == ExpireFlag::Nx || next == ExpireFlag::Nx {
+                    "NX and XX, GT or LT options at the same time are not compatible"
+                } else {
+                    "GT and LT options at the same time are not compatible"
+                };
+                return Err(CommandError::InvalidArgument(msg.to_string()));
+            }
+            _ => flag = Some(next),
+        }
+    }
+    Ok(flag)
+}
+
+// COPY source destination [DB destination-db] [REPLACE]
+impl TryFrom<RespArray> for Copy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["copy"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let source = extract_string(args.next())?;
+        let destination = extract_string(args.next())?;
+        let mut db = 0;
+        let mut replace = false;
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+                "db" => {
+                    db = extract_integer(args.next())?.try_into().map_err(|_| {
+                        CommandError::InvalidArgument("DB index is out of range".to_string())
+                    })?
+                }
+                "replace" => replace = true,
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(Copy {
+            source,
+            destination,
+            db,
+            replace,
+        })
+    }
+}
+
+// SCAN cursor [MATCH pattern] [COUNT count] [TYPE type]
+impl TryFrom<RespArray> for Scan {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["scan"], 1)?;
+        let args = extract_args(value, 1)?.into_iter();
+        let (cursor, pattern, count, key_type) = parse_scan_args(args, true)?;
+        Ok(Scan {
+            cursor,
+            pattern,
+            count,
+            key_type,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["expire"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Expire {
+            key: extract_string(args.next())?,
+            seconds: extract_integer(args.next())?,
+            flag: parse_expire_flag(args)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["pexpire"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(PExpire {
+            key: extract_string(args.next())?,
+            milliseconds: extract_integer(args.next())?,
+            flag: parse_expire_flag(args)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["expireat"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ExpireAt {
+            key: extract_string(args.next())?,
+            timestamp: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pexpireat"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(PExpireAt {
+            key: extract_string(args.next())?,
+            timestamp_ms: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ttl"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Ttl {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pttl"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(PTtl {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["expiretime"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ExpireTime {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireTime {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pexpiretime"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(PExpireTime {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::{BulkString, RespDecoder};
+
+    use super::*;
+
+    #[test]
+    fn test_expire_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$2\r\n10\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Expire = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.seconds, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_rejects_non_integer() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$3\r\nabc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let ret: Result<Expire, _> = frame.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_and_ttl_execute() {
+        let backend = Backend::new();
+        let ttl = || {
+            Ttl {
+                key: "key".to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(ttl(), RespFrame::Integer(-2));
+
+        backend.set("key".to_string(), BulkString::new("value").into());
+        assert_eq!(ttl(), RespFrame::Integer(-1));
+
+        let ret = Expire {
+            key: "key".to_string(),
+            seconds: 10,
+            flag: None,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(ttl(), RespFrame::Integer(10));
+
+        let ret = PTtl {
+            key: "key".to_string(),
+        }
+        .execute(&backend);
+        assert!(matches!(ret, RespFrame::Integer(ms) if ms > 9000 && ms <= 10000));
+
+        // 重新 SET 会清除过期时间
+        backend.set("key".to_string(), BulkString::new("value").into());
+        assert_eq!(ttl(), RespFrame::Integer(-1));
+    }
+
+    #[test]
+    fn test_expire_non_positive_deletes_key() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let ret = PExpire {
+            key: "key".to_string(),
+            milliseconds: -1,
+            flag: None,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert!(backend.get("key").is_none());
+
+        let ret = Expire {
+            key: "missing".to_string(),
+            seconds: 10,
+            flag: None,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+    }
+
+    #[test]
+    fn test_expire_flags() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let expire = |seconds, flag| {
+            Expire {
+                key: "key".to_string(),
+                seconds,
+                flag: Some(flag),
+            }
+            .execute(&backend)
+        };
+        let ttl = || {
+            Ttl {
+                key: "key".to_string(),
+            }
+            .execute(&backend)
+        };
+        // 没有过期时间时 XX 和 GT 不生效，LT 视为比永不过期更早
+        assert_eq!(expire(100, ExpireFlag::Xx), RespFrame::Integer(0));
+        assert_eq!(expire(100, ExpireFlag::Gt), RespFrame::Integer(0));
+        assert_eq!(ttl(), RespFrame::Integer(-1));
+        assert_eq!(expire(100, ExpireFlag::Nx), RespFrame::Integer(1));
+        assert_eq!(expire(200, ExpireFlag::Nx), RespFrame::Integer(0));
+        assert_eq!(ttl(), RespFrame::Integer(100));
+
+        assert_eq!(expire(50, ExpireFlag::Gt), RespFrame::Integer(0));
+        assert_eq!(expire(200, ExpireFlag::Gt), RespFrame::Integer(1));
+        assert_eq!(expire(300, ExpireFlag::Lt), RespFrame::Integer(0));
+        assert_eq!(expire(50, ExpireFlag::Lt), RespFrame::Integer(1));
+        assert_eq!(expire(60, ExpireFlag::Xx), RespFrame::Integer(1));
+        assert_eq!(ttl(), RespFrame::Integer(60));
+
+        // 条件不满足时非正数也不会删除 key
+        assert_eq!(expire(-1, ExpireFlag::Nx), RespFrame::Integer(0));
+        assert!(backend.exists("key"));
+        assert_eq!(expire(-1, ExpireFlag::Lt), RespFrame::Integer(1));
+        assert!(!backend.exists("key"));
+        assert_eq!(expire(100, ExpireFlag::Lt), RespFrame::Integer(0));
+    }
+
+    #[test]
+    fn test_expire_flag_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\npexpire\r\n$3\r\nkey\r\n$3\r\n100\r\n$2\r\ngt\r\n");
+        let cmd: PExpire = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.flag, Some(ExpireFlag::Gt));
+
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$2\r\n10\r\n$2\r\nNX\r\n$2\r\nXX\r\n",
+        );
+        let ret: Result<Expire, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+
+        buf.extend_from_slice(b"*4\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$2\r\n10\r\n$2\r\nzz\r\n");
+        let ret: Result<Expire, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_del_execute() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::new("1").into());
+        backend.hset(
+            "b".to_string(),
+            "f".to_string(),
+            BulkString::new("2").into(),
+        );
+        let ret = Del {
+            keys: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        assert!(!backend.exists("a"));
+        assert!(!backend.exists("b"));
+    }
+
+    #[test]
+    fn test_copy_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$2\r\nDB\r\n$1\r\n0\r\n$7\r\nREPLACE\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Copy = frame.try_into()?;
+        assert_eq!(cmd.source, "a");
+        assert_eq!(cmd.destination, "b");
+        assert_eq!(cmd.db, 0);
+        assert!(cmd.replace);
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_execute() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::new("1").into());
+        let copy = |destination: &str, db: usize, replace: bool| {
+            Copy {
+                source: "a".to_string(),
+                destination: destination.to_string(),
+                db,
+                replace,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(copy("b", 0, false), RespFrame::Integer(1));
+        assert_eq!(copy("b", 0, false), RespFrame::Integer(0));
+        assert_eq!(copy("b", 0, true), RespFrame::Integer(1));
+        assert_eq!(backend.get("b"), Some(BulkString::new("1").into()));
+        assert!(matches!(copy("b", 1, false), RespFrame::Error(_)));
+        assert!(matches!(copy("a", 0, true), RespFrame::Error(_)));
+    }
+
+    #[test]
+    fn test_scan_execute() {
+        let backend = Backend::new();
+        for i in 0..30 {
+            backend.set(format!("user:{}", i), BulkString::new("v").into());
+        }
+        backend.sadd("user:set".to_string(), vec!["m".to_string()]);
+        backend.set("other".to_string(), BulkString::new("v").into());
+        let scan = |cursor: u64, pattern: Option<&str>, key_type: Option<&str>| {
+            let RespFrame::Array(reply) = Scan {
+                cursor,
+                pattern: pattern.map(str::to_string),
+                count: 5,
+                key_type: key_type.map(str::to_string),
+            }
+            .execute(&backend) else {
+                panic!("SCAN should reply with an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(keys)) = (&reply[0], &reply[1])
+            else {
+                panic!("SCAN should reply with [cursor, keys]");
+            };
+            let next: u64 = String::from_utf8_lossy(next).parse().unwrap();
+            (next, keys.len())
+        };
+        let scan_all = |pattern, key_type| {
+            let (mut cursor, mut total) = scan(0, pattern, key_type);
+            while cursor != 0 {
+                let (next, n) = scan(cursor, pattern, key_type);
+                (cursor, total) = (next, total + n);
+            }
+            total
+        };
+        assert_eq!(scan_all(None, None), 32);
+        assert_eq!(scan_all(Some("user:*"), None), 31);
+        assert_eq!(scan_all(Some("user:*"), Some("SET")), 1);
+        assert_eq!(scan_all(None, Some("hash")), 0);
+    }
+
+    #[test]
+    fn test_expiretime_execute() {
+        let backend = Backend::new();
+        let expiretime = || {
+            ExpireTime {
+                key: "key".to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(expiretime(), RespFrame::Integer(-2));
+        backend.set("key".to_string(), BulkString::new("value").into());
+        assert_eq!(expiretime(), RespFrame::Integer(-1));
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap();
+        Expire {
+            key: "key".to_string(),
+            seconds: 100,
+            flag: None,
+        }
+        .execute(&backend);
+        let RespFrame::Integer(secs) = expiretime() else {
+            panic!("EXPIRETIME should return an integer");
+        };
+        assert!((secs - (now.as_secs() as i64 + 100)).abs() <= 1);
+
+        let ret = PExpireTime {
+            key: "key".to_string(),
+        }
+        .execute(&backend);
+        let RespFrame::Integer(ms) = ret else {
+            panic!("PEXPIRETIME should return an integer");
+        };
+        assert!((ms - (now.as_millis() as i64 + 100_000)).abs() <= 1000);
+
+        // 去掉过期时间后回到 -1
+        backend.persist("key");
+        assert_eq!(expiretime(), RespFrame::Integer(-1));
+    }
+
+    #[test]
+    fn test_expireat_execute() {
+        let backend = Backend::new();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap();
+        let ret = ExpireAt {
+            key: "key".to_string(),
+            timestamp: now.as_secs() as i64 + 100,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let ret = PExpireAt {
+            key: "key".to_string(),
+            timestamp_ms: now.as_millis() as i64 + 100_000,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ttl = Ttl {
+            key: "key".to_string(),
+        }
+        .execute(&backend);
+        assert!(matches!(ttl, RespFrame::Integer(secs) if (99..=100).contains(&secs)));
+
+        // 过去的时间戳直接删除 key
+        let ret = ExpireAt {
+            key: "key".to_string(),
+            timestamp: now.as_secs() as i64 - 10,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert!(!backend.exists("key"));
+    }
+
+    #[test]
+    fn test_pexpireat_past_timestamp_deletes_key() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$9\r\npexpireat\r\n$3\r\nkey\r\n$1\r\n1\r\n");
+        let cmd: PExpireAt = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.timestamp_ms, 1);
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(!backend.exists("key"));
+        Ok(())
+    }
+}
@@ -0,0 +1,543 @@
+use std::time::Duration;
+
+use crate::{
+    cmd::{
+        extract_args, extract_integer, extract_string, parse_mpop, validate_command,
+        validate_command_at_least,
+    },
+    Backend, BulkString, RespArray, RespFrame, RespNull, RespNullArray,
+};
+
+use super::{BLPop, BRPop, CommandError, CommandExecutor, LMPop, LPos, LPush, LRange, RPush};
+
+impl CommandExecutor for LPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let len = backend.lpush(self.key, self.values);
+        RespFrame::Integer(len as i64)
+    }
+}
+
+impl CommandExecutor for RPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let len = backend.rpush(self.key, self.values);
+        RespFrame::Integer(len as i64)
+    }
+}
+
+impl CommandExecutor for LRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespArray::new(backend.lrange(&self.key, self.start, self.stop)).into()
+    }
+}
+
+impl CommandExecutor for LPos {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let positions = backend.lpos(
+            &self.key,
+            &self.element,
+            self.rank,
+            self.count.unwrap_or(1),
+            self.maxlen,
+        );
+        let mut positions = positions.into_iter().map(|i| RespFrame::Integer(i as i64));
+        match self.count {
+            Some(_) => RespArray::new(positions.collect::<Vec<_>>()).into(),
+            None => positions.next().unwrap_or(RespFrame::Null(RespNull)),
+        }
+    }
+}
+
+impl CommandExecutor for LMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lmpop(&self.keys, self.left, self.count) {
+            Some((key, values)) => RespArray::new(vec![
+                BulkString::new(key).into(),
+                RespArray::new(values).into(),
+            ])
+            .into(),
+            None => RespFrame::NullArray(RespNullArray),
+        }
+    }
+}
+
+impl CommandExecutor for BLPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_one(backend, &self.keys, true).unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl CommandExecutor for BRPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_one(backend, &self.keys, false).unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl BLPop {
+    // 所有列表都为空时等待 LPUSH / RPUSH 唤醒，超时返回 null array
+    pub async fn execute_blocking(self, backend: &Backend) -> RespFrame {
+        blocking_pop(backend, &self.keys, true, self.timeout).await
+    }
+}
+
+impl BRPop {
+    pub async fn execute_blocking(self, backend: &Backend) -> RespFrame {
+        blocking_pop(backend, &self.keys, false, self.timeout).await
+    }
+}
+
+// 从第一个非空列表弹出一个元素，返回 [key, element]
+fn pop_one(backend: &Backend, keys: &[String], left: bool) -> Option<RespFrame> {
+    let (key, mut values) = backend.lmpop(keys, left, 1)?;
+    Some(RespArray::new(vec![BulkString::new(key).into(), values.remove(0)]).into())
+}
+
+async fn blocking_pop(backend: &Backend, keys: &[String], left: bool, timeout: f64) -> RespFrame {
+    let timeout = match Duration::try_from_secs_f64(timeout) {
+        Ok(duration) => (timeout > 0.0).then_some(duration),
+        Err(_) => return RespFrame::error("ERR timeout is out of range"),
+    };
+    backend
+        .block_on_keys(keys, timeout, |backend| pop_one(backend, keys, left))
+        .await
+        .unwrap_or(RespFrame::NullArray(RespNullArray))
+}
+
+// 解析 `key value [value ...]`
+fn parse_push(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<RespFrame>), CommandError> {
+    validate_command_at_least(&value, &[name], 2)?;
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    Ok((key, args.collect()))
+}
+
+// 解析 `key [key ...] timeout`，timeout 为秒数，可以是小数
+fn parse_blocking_pop(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(Vec<String>, f64), CommandError> {
+    validate_command_at_least(&value, &[name], 2)?;
+    let mut keys = extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let timeout = keys.pop().unwrap_or_default();
+    let timeout = match timeout.parse::<f64>() {
+        Ok(t) if t < 0.0 => {
+            return Err(CommandError::InvalidArgument(
+                "timeout is negative".to_string(),
+            ))
+        }
+        Ok(t) if t.is_finite() => t,
+        _ => {
+            return Err(CommandError::InvalidArgument(
+                "timeout is not a float or out of range".to_string(),
+            ))
+        }
+    };
+    Ok((keys, timeout))
+}
+
+impl TryFrom<RespArray> for BLPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop(value, "blpop")?;
+        Ok(BLPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BRPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop(value, "brpop")?;
+        Ok(BRPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push(value, "lpush")?;
+        Ok(LPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push(value, "rpush")?;
+        Ok(RPush { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for LRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lrange"], 3)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LRange {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            stop: extract_integer(args.next())?,
+        })
+    }
+}
+
+// LPOS key element [RANK rank] [COUNT num-matches] [MAXLEN len]
+impl TryFrom<RespArray> for LPos {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["lpos"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let element = args.next().unwrap_or(RespFrame::Null(RespNull));
+        let mut lpos = LPos {
+            key,
+            element,
+            rank: 1,
+            count: None,
+            maxlen: 0,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_lowercase();
+            let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+            let value = match args.next() {
+                Some(value) => extract_integer(Some(value))?,
+                None => return Err(syntax_error()),
+            };
+            match option.as_str() {
+                "rank" if value == 0 => {
+                    return Err(CommandError::InvalidArgument(
+                        "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".to_string(),
+                    ))
+                }
+                "rank" if value == i64::MIN => {
+                    return Err(CommandError::InvalidArgument(
+                        "value is out of range".to_string(),
+                    ))
+                }
+                "rank" => lpos.rank = value,
+                "count" if value < 0 => {
+                    return Err(CommandError::InvalidArgument(
+                        "COUNT can't be negative".to_string(),
+                    ))
+                }
+                "count" => lpos.count = Some(value as usize),
+                "maxlen" if value < 0 => {
+                    return Err(CommandError::InvalidArgument(
+                        "MAXLEN can't be negative".to_string(),
+                    ))
+                }
+                "maxlen" => lpos.maxlen = value as usize,
+                _ => return Err(syntax_error()),
+            }
+        }
+        Ok(lpos)
+    }
+}
+
+impl TryFrom<RespArray> for LMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, left, count) = parse_mpop(value, "lmpop", ["left", "right"])?;
+        Ok(LMPop { keys, left, count })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::{BulkString, RespDecoder};
+
+    use super::*;
+
+    fn bulk(values: &[&str]) -> Vec<RespFrame> {
+        values.iter().map(|v| BulkString::new(*v).into()).collect()
+    }
+
+    #[test]
+    fn test_lpush_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nlpush\r\n$4\r\nlist\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let lpush: LPush = cmd.try_into()?;
+        assert_eq!(lpush.key, "list");
+        assert_eq!(lpush.values, bulk(&["a", "b"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_and_lrange_execute() {
+        let backend = Backend::new();
+        let rpush = RPush {
+            key: "list".to_string(),
+            values: bulk(&["b", "c"]),
+        };
+        assert_eq!(rpush.execute(&backend), RespFrame::Integer(2));
+        let lpush = LPush {
+            key: "list".to_string(),
+            values: bulk(&["a", "z"]),
+        };
+        assert_eq!(lpush.execute(&backend), RespFrame::Integer(4));
+
+        let lrange = |start, stop| {
+            LRange {
+                key: "list".to_string(),
+                start,
+                stop,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(
+            lrange(0, -1),
+            RespArray::new(bulk(&["z", "a", "b", "c"])).into()
+        );
+        assert_eq!(lrange(-2, 10), RespArray::new(bulk(&["b", "c"])).into());
+        assert_eq!(lrange(3, 1), RespArray::new(vec![]).into());
+    }
+
+    #[test]
+    fn test_lmpop_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$5\r\nlmpop\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nRIGHT\r\n$5\r\ncount\r\n$1\r\n3\r\n",
+        );
+        let lmpop: LMPop = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(lmpop.keys, vec!["a", "b"]);
+        assert!(!lmpop.left);
+        assert_eq!(lmpop.count, 3);
+
+        let parse = |args: &[&str]| -> Result<LMPop, CommandError> {
+            let mut frames = bulk(&["lmpop"]);
+            frames.extend(bulk(args));
+            RespArray::new(frames).try_into()
+        };
+        assert_eq!(parse(&["1", "a", "left"])?.count, 1);
+        let err = |args: &[&str]| parse(args).unwrap_err().to_string();
+        assert_eq!(
+            err(&["0", "a", "left"]),
+            "Invalid argument: numkeys should be greater than 0"
+        );
+        // numkeys 大于实际的 key 数量
+        assert_eq!(err(&["3", "a", "left"]), "Invalid argument: syntax error");
+        assert_eq!(err(&["1", "a", "middle"]), "Invalid argument: syntax error");
+        assert_eq!(
+            err(&["1", "a", "left", "count", "0"]),
+            "Invalid argument: count should be greater than 0"
+        );
+        assert_eq!(
+            err(&["1", "a", "left", "count"]),
+            "Invalid argument: syntax error"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmpop_pops_from_first_non_empty_list() {
+        let backend = Backend::new();
+        backend.rpush("second".to_string(), bulk(&["a", "b", "c"]));
+        let lmpop = |left, count| {
+            LMPop {
+                keys: vec!["first".to_string(), "second".to_string()],
+                left,
+                count,
+            }
+            .execute(&backend)
+        };
+        let reply = |values: &[&str]| -> RespFrame {
+            RespArray::new(vec![
+                BulkString::new("second").into(),
+                RespArray::new(bulk(values)).into(),
+            ])
+            .into()
+        };
+        assert_eq!(lmpop(true, 1), reply(&["a"]));
+        // 从尾部弹出时按弹出的顺序返回，count 超过列表长度时弹出全部元素
+        assert_eq!(lmpop(false, 5), reply(&["c", "b"]));
+        assert!(!backend.exists("second"));
+        assert_eq!(lmpop(true, 1), RespFrame::NullArray(RespNullArray));
+    }
+
+    #[test]
+    fn test_blpop_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nBLPOP\r\n$1\r\na\r\n$1\r\nb\r\n$3\r\n0.5\r\n");
+        let blpop: BLPop = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(blpop.keys, vec!["a", "b"]);
+        assert_eq!(blpop.timeout, 0.5);
+
+        let parse = |timeout: &str| -> Result<BRPop, CommandError> {
+            RespArray::new(bulk(&["brpop", "a", timeout])).try_into()
+        };
+        assert_eq!(parse("0")?.timeout, 0.0);
+        assert_eq!(
+            parse("-1").unwrap_err().to_string(),
+            "Invalid argument: timeout is negative"
+        );
+        assert_eq!(
+            parse("soon").unwrap_err().to_string(),
+            "Invalid argument: timeout is not a float or out of range"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blpop_returns_immediately_when_list_has_elements() {
+        let backend = Backend::new();
+        backend.rpush("b".to_string(), bulk(&["x", "y"]));
+        let brpop = BRPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            timeout: 1.0,
+        };
+        assert_eq!(
+            brpop.execute_blocking(&backend).await,
+            RespArray::new(bulk(&["b", "y"])).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blpop_timeout_out_of_range() {
+        let backend = Backend::new();
+        let blpop = |timeout| BLPop {
+            keys: vec!["a".to_string()],
+            timeout,
+        };
+        assert_eq!(
+            blpop(1e300).execute_blocking(&backend).await,
+            RespFrame::error("ERR timeout is out of range")
+        );
+        // 超时很长但在 Duration 范围内时不会溢出
+        backend.rpush("a".to_string(), bulk(&["x"]));
+        assert_eq!(
+            blpop(1.8e19).execute_blocking(&backend).await,
+            RespArray::new(bulk(&["a", "x"])).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blpop_woken_by_push() {
+        let backend = Backend::new();
+        let blpop = BLPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            timeout: 0.0,
+        };
+        let reader = {
+            let backend = backend.clone();
+            tokio::spawn(async move { blpop.execute_blocking(&backend).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!reader.is_finished());
+        backend.lpush("b".to_string(), bulk(&["v"]));
+        let ret = tokio::time::timeout(Duration::from_secs(1), reader)
+            .await
+            .expect("BLPOP 0 should be woken by LPUSH")
+            .unwrap();
+        assert_eq!(ret, RespArray::new(bulk(&["b", "v"])).into());
+        // 弹出最后一个元素后删除列表
+        assert!(!backend.exists("b"));
+    }
+
+    #[tokio::test]
+    async fn test_blpop_times_out() {
+        let backend = Backend::new();
+        let blpop = BLPop {
+            keys: vec!["a".to_string()],
+            timeout: 0.01,
+        };
+        assert_eq!(
+            blpop.execute_blocking(&backend).await,
+            RespFrame::NullArray(RespNullArray)
+        );
+    }
+
+    fn lpos(rank: i64, count: Option<usize>, maxlen: usize) -> LPos {
+        LPos {
+            key: "list".to_string(),
+            element: BulkString::new("a").into(),
+            rank,
+            count,
+            maxlen,
+        }
+    }
+
+    #[test]
+    fn test_lpos_execute() {
+        let backend = Backend::new();
+        backend.rpush("list".to_string(), bulk(&["a", "b", "c", "a", "b", "a"]));
+
+        assert_eq!(lpos(1, None, 0).execute(&backend), RespFrame::Integer(0));
+        assert_eq!(lpos(2, None, 0).execute(&backend), RespFrame::Integer(3));
+        assert_eq!(
+            lpos(4, None, 0).execute(&backend),
+            RespFrame::Null(RespNull)
+        );
+
+        let indexes = |values: &[i64]| -> RespFrame {
+            RespArray::new(
+                values
+                    .iter()
+                    .map(|&i| RespFrame::Integer(i))
+                    .collect::<Vec<_>>(),
+            )
+            .into()
+        };
+        assert_eq!(lpos(1, Some(2), 0).execute(&backend), indexes(&[0, 3]));
+        // COUNT 0 返回全部匹配
+        assert_eq!(lpos(1, Some(0), 0).execute(&backend), indexes(&[0, 3, 5]));
+        // MAXLEN 只比较前 4 个元素
+        assert_eq!(lpos(1, Some(0), 4).execute(&backend), indexes(&[0, 3]));
+        assert_eq!(
+            LPos {
+                key: "missing".to_string(),
+                ..lpos(1, Some(0), 0)
+            }
+            .execute(&backend),
+            indexes(&[])
+        );
+    }
+
+    #[test]
+    fn test_lpos_negative_rank_execute() {
+        let backend = Backend::new();
+        backend.rpush("list".to_string(), bulk(&["a", "b", "c", "a", "b", "a"]));
+        assert_eq!(lpos(-1, None, 0).execute(&backend), RespFrame::Integer(5));
+        assert_eq!(lpos(-2, None, 0).execute(&backend), RespFrame::Integer(3));
+        assert_eq!(
+            lpos(-1, Some(0), 0).execute(&backend),
+            RespArray::new(vec![
+                RespFrame::Integer(5),
+                RespFrame::Integer(3),
+                RespFrame::Integer(0)
+            ])
+            .into()
+        );
+        // 从尾部开始只比较 2 个元素
+        assert_eq!(
+            lpos(-2, None, 2).execute(&backend),
+            RespFrame::Null(RespNull)
+        );
+    }
+
+    #[test]
+    fn test_lpos_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*9\r\n$4\r\nlpos\r\n$4\r\nlist\r\n$1\r\na\r\n$4\r\nRANK\r\n$2\r\n-2\r\n$5\r\nCOUNT\r\n$1\r\n0\r\n$6\r\nMAXLEN\r\n$2\r\n10\r\n",
+        );
+        let lpos: LPos = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(lpos.key, "list");
+        assert_eq!(lpos.element, BulkString::new("a").into());
+        assert_eq!((lpos.rank, lpos.count, lpos.maxlen), (-2, Some(0), 10));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nlpos\r\n$4\r\nlist\r\n$1\r\na\r\n$4\r\nRANK\r\n$1\r\n0\r\n",
+        );
+        let ret: Result<LPos, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{
-    cmd::{extract_args, validate_command},
+    cmd::{extract_args, extract_string, validate_command, validate_command_at_least},
     Backend, RespArray, RespFrame, RespNull,
 };
 
-use super::{CommandError, CommandExecutor, Get, Set, RESP_OK};
+use super::{CommandError, CommandExecutor, Get, MSet, Set};
 
 impl CommandExecutor for Get {
     fn execute(self, backend: &Backend) -> RespFrame {
@@ -16,8 +16,17 @@ impl CommandExecutor for Get {
 
 impl CommandExecutor for Set {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.set(self.key, self.value.clone());
-        RESP_OK.clone()
+        backend.set(self.key, self.value);
+        RespFrame::ok()
+    }
+}
+
+impl CommandExecutor for MSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        for (key, value) in self.pairs {
+            backend.set(key, value);
+        }
+        RespFrame::ok()
     }
 }
 
@@ -52,6 +61,23 @@ impl TryFrom<RespArray> for Set {
     }
 }
 
+// MSET key value [key value ...]：key 从第 1 个参数开始，每隔一个参数一个
+impl TryFrom<RespArray> for MSet {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["mset"], 2)?;
+        if value.len().is_multiple_of(2) {
+            return Err(CommandError::WrongArity("mset".to_string()));
+        }
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut pairs = Vec::new();
+        while let (Some(key), Some(value)) = (args.next(), args.next()) {
+            pairs.push((extract_string(Some(key))?, value));
+        }
+        Ok(MSet { pairs })
+    }
+}
+
 mod tests {
 
     #[allow(unused_imports)]
@@ -104,7 +130,7 @@ mod tests {
             value: RespFrame::BulkString(BulkString::new("value")),
         };
         let resp = set.execute(&backend);
-        assert_eq!(resp, RESP_OK.clone());
+        assert_eq!(resp, RespFrame::ok());
         let resp = backend.get("key").unwrap();
         assert_eq!(resp, RespFrame::BulkString(BulkString::new("value")));
     }
@@ -117,11 +143,51 @@ mod tests {
             value: RespFrame::BulkString(BulkString::new("value")),
         };
         let resp = set.execute(&backend);
-        assert_eq!(resp, RESP_OK.clone());
+        assert_eq!(resp, RespFrame::ok());
         let get = Get {
             key: "key".to_string(),
         };
         let resp = get.execute(&backend);
         assert_eq!(resp, RespFrame::BulkString(BulkString::new("value")));
     }
+
+    #[test]
+    fn test_mset_execute() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nmset\r\n$2\r\nk1\r\n$2\r\nv1\r\n$2\r\nk2\r\n$2\r\nv2\r\n",
+        );
+        let mset: MSet = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(mset.execute(&backend), RespFrame::ok());
+        assert_eq!(backend.get("k1"), Some(BulkString::new("v1").into()));
+        assert_eq!(backend.get("k2"), Some(BulkString::new("v2").into()));
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nmset\r\n$2\r\nk1\r\n$2\r\nv1\r\n$2\r\nk2\r\n");
+        let ret: Result<MSet, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(matches!(ret, Err(CommandError::WrongArity(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_publishes_keyspace_events() {
+        let backend = Backend::new();
+        backend.set_config("notify-keyspace-events", "KEA").unwrap();
+        let (_, mut rx) = backend.register_client(1, None).unwrap();
+        backend.subscribe(1, "__keyevent@0__:set");
+        let set = Set {
+            key: "mykey".to_string(),
+            value: RespFrame::BulkString(BulkString::new("value")),
+        };
+        set.execute(&backend);
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            resp::RespPush::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new("__keyevent@0__:set").into(),
+                BulkString::new("mykey").into(),
+            ])
+            .into()
+        );
+    }
 }
@@ -1,28 +1,72 @@
+mod acl;
+mod config;
+mod connection;
+mod geo;
 mod hmap;
+mod key;
+mod list;
 mod map;
+mod object;
+mod pubsub;
+#[cfg(feature = "scripting")]
+mod script;
+mod server;
+mod set;
+mod sort;
+mod stream;
+mod string;
+mod table;
+mod zset;
 
-use crate::{Backend, RespArray, RespError, RespFrame, SimpleString};
+use crate::{
+    AclDenied, Aggregate, Backend, ExpireFlag, PauseMode, RespArray, RespError, RespFrame,
+    ScoreBound, SimpleString, SortOptions, StreamId, TrimStrategy, ZAddOptions, DEFAULT_SCAN_COUNT,
+    DEFAULT_USER,
+};
 use enum_dispatch::enum_dispatch;
-use lazy_static::lazy_static;
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+pub use table::{lookup_command, CommandSpec, COMMAND_TABLE};
 use thiserror::Error;
 use tracing::info;
 
-lazy_static! {
-    static ref RESP_OK: RespFrame = SimpleString::new("OK").into();
-}
-
 #[derive(Debug, Error)]
 pub enum CommandError {
     #[error("Invalid command: {0}")]
     InvalidCommand(String),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
     #[error(" {0}")]
     RespError(#[from] RespError),
     #[error("UTF-8 error: {0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
 
+// 解析失败时回复给客户端的错误，和 Redis 一样以 ERR 开头
+impl From<CommandError> for RespFrame {
+    fn from(e: CommandError) -> Self {
+        match e {
+            CommandError::WrongArity(_) => RespFrame::error(e.to_string()),
+            CommandError::InvalidCommand(msg) | CommandError::InvalidArgument(msg) => {
+                RespFrame::error(format!("ERR {}", msg))
+            }
+            CommandError::RespError(e) => RespFrame::error(format!("ERR {}", e)),
+            CommandError::Utf8Error(e) => RespFrame::error(format!("ERR {}", e)),
+        }
+    }
+}
+
+// 脚本执行超过 busy-reply-threshold 时，其它命令收到的错误
+pub const BUSY_ERROR: &str =
+    "BUSY Redis is busy running a script. You can only call SCRIPT KILL or SHUTDOWN NOSAVE.";
+
+// 只读写数据的命令实现 execute；需要读取或修改连接状态的命令（AUTH、HELLO、CLIENT、SUBSCRIBE、
+// MONITOR、RESET 等）另外提供 execute_with_client，由 Command::dispatch 传入 ClientState
 #[enum_dispatch]
 pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> RespFrame;
@@ -33,16 +77,143 @@ pub trait CommandExecutor {
 pub enum Command {
     Get(Get),
     Set(Set),
+    MSet(MSet),
+    SetBit(SetBit),
+    GetBit(GetBit),
+    BitCount(BitCount),
     HGet(HGet),
     HSet(HSet),
     HGetAll(HGetAll),
+    HScan(HScan),
+    HRandField(HRandField),
+    LPush(LPush),
+    RPush(RPush),
+    LRange(LRange),
+    LPos(LPos),
+    LMPop(LMPop),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    SAdd(SAdd),
+    SMembers(SMembers),
+    SScan(SScan),
+    SInterCard(SInterCard),
+    Del(Del),
+    Copy(Copy),
+    Scan(Scan),
+    Expire(Expire),
+    PExpire(PExpire),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    ExpireTime(ExpireTime),
+    PExpireTime(PExpireTime),
+    Sort(Sort),
+    XAdd(XAdd),
+    XRead(XRead),
+    XRange(XRange),
+    XLen(XLen),
+    XDel(XDel),
+    XTrim(XTrim),
+    XGroup(XGroup),
+    XReadGroup(XReadGroup),
+    XAck(XAck),
+    ZAdd(ZAdd),
+    ZIncrBy(ZIncrBy),
+    ZRank(ZRank),
+    ZCard(ZCard),
+    ZRem(ZRem),
+    ZRangeByScore(ZRangeByScore),
+    ZUnionStore(ZUnionStore),
+    ZInterStore(ZInterStore),
+    ZDiffStore(ZDiffStore),
+    ZUnion(ZUnion),
+    ZInter(ZInter),
+    ZDiff(ZDiff),
+    ZMPop(ZMPop),
+    GeoAdd(GeoAdd),
+    GeoPos(GeoPos),
+    GeoDist(GeoDist),
+    GeoSearch(GeoSearch),
+    GeoRadius(GeoRadius),
+    GeoRadiusByMember(GeoRadiusByMember),
+    Object(Object),
+    Debug(Debug),
+    Wait(Wait),
+    ReadOnly(ReadOnly),
+    ReadWrite(ReadWrite),
+    Asking(Asking),
+    Cluster(Cluster),
+    Quit(Quit),
+    Reset(Reset),
+    Ping(Ping),
+    Auth(Auth),
+    Hello(Hello),
+    Client(Client),
+    Acl(Acl),
+    Slowlog(Slowlog),
+    Config(Config),
+    Save(Save),
+    Sync(Sync),
+    BgSave(BgSave),
+    LastSave(LastSave),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    Publish(Publish),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    PubSub(PubSub),
+    Monitor(Monitor),
+    #[cfg(feature = "scripting")]
+    Eval(Eval),
+    #[cfg(feature = "scripting")]
+    EvalSha(EvalSha),
+    #[cfg(feature = "scripting")]
+    Script(Script),
+    Info(Info),
+    CommandMeta(CommandMeta),
 
     // Unrecognized command
     Unrecognized(Unrecognized),
 }
 
+// 命令执行的结果：回复客户端，或者回复之后关闭连接
+#[derive(Debug, PartialEq)]
+pub enum Response {
+    Reply(RespFrame),
+    ReplyAndClose(RespFrame),
+    // 依次回复多帧，例如 SUBSCRIBE 对每个 channel 各回复一帧
+    Replies(Vec<RespFrame>),
+}
+
+// 每个连接各自的状态
+#[derive(Debug)]
+pub struct ClientState {
+    // 连接的唯一 ID，从 1 开始递增
+    pub id: u64,
+    // 是否已经通过 AUTH 认证，没有设置 requirepass 时不需要认证
+    pub authenticated: bool,
+    // 当前登录的 ACL 用户，默认为 default
+    pub user: String,
+    // HELLO 协商的协议版本，默认为 RESP2
+    pub protocol: u8,
+    // HELLO SETNAME 设置的连接名
+    pub name: Option<String>,
+    // 对端地址，不是来自 TCP 连接时为 None
+    pub addr: Option<SocketAddr>,
+    // SUBSCRIBE 订阅的 channel
+    pub subscriptions: HashSet<String>,
+    // PSUBSCRIBE 订阅的模式
+    pub patterns: HashSet<String>,
+    // 执行过 MONITOR，连接只接收命令流，除 QUIT 和 RESET 以外不再执行命令
+    pub in_monitor_mode: bool,
+}
+
 #[derive(Debug)]
-pub struct Unrecognized;
+pub struct Unrecognized {
+    pub name: String,
+    pub args: Vec<String>,
+}
 
 #[derive(Debug)]
 pub struct Get {
@@ -55,6 +226,31 @@ pub struct Set {
     pub value: RespFrame,
 }
 
+#[derive(Debug)]
+pub struct MSet {
+    pub pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct SetBit {
+    pub key: String,
+    pub offset: u64,
+    pub value: bool,
+}
+
+#[derive(Debug)]
+pub struct GetBit {
+    pub key: String,
+    pub offset: u64,
+}
+
+#[derive(Debug)]
+pub struct BitCount {
+    pub key: String,
+    // 字节区间 [start, end]，None 表示整个字符串
+    pub range: Option<(i64, i64)>,
+}
+
 #[derive(Debug)]
 pub struct HGet {
     pub key: String,
@@ -64,8 +260,8 @@ pub struct HGet {
 #[derive(Debug)]
 pub struct HSet {
     pub key: String,
-    pub field: String,
-    pub value: RespFrame,
+    // (field, value)
+    pub fields: Vec<(String, RespFrame)>,
 }
 
 #[derive(Debug)]
@@ -73,10 +269,1229 @@ pub struct HGetAll {
     pub key: String,
 }
 
+#[derive(Debug)]
+pub struct HScan {
+    pub key: String,
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct HRandField {
+    pub key: String,
+    // None 表示只返回一个字段
+    pub count: Option<i64>,
+    pub with_values: bool,
+}
+
+#[derive(Debug)]
+pub struct LPush {
+    pub key: String,
+    pub values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    pub key: String,
+    pub values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct LRange {
+    pub key: String,
+    pub start: i64,
+    pub stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LPos {
+    pub key: String,
+    pub element: RespFrame,
+    // 返回第 rank 个匹配，负数从尾部开始计数
+    pub rank: i64,
+    // None 表示只返回第一个匹配的下标，Some(0) 表示返回全部匹配
+    pub count: Option<usize>,
+    // 最多比较的元素个数，0 表示不限制
+    pub maxlen: usize,
+}
+
+// BLPOP key [key ...] timeout
+#[derive(Debug)]
+pub struct BLPop {
+    pub keys: Vec<String>,
+    // 阻塞的秒数，0 表示一直等待
+    pub timeout: f64,
+}
+
+// BRPOP key [key ...] timeout
+#[derive(Debug)]
+pub struct BRPop {
+    pub keys: Vec<String>,
+    pub timeout: f64,
+}
+
+// LMPOP numkeys key [key ...] LEFT|RIGHT [COUNT count]
+#[derive(Debug)]
+pub struct LMPop {
+    pub keys: Vec<String>,
+    pub left: bool,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct SAdd {
+    pub key: String,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SMembers {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct SScan {
+    pub key: String,
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: usize,
+}
+
+// SINTERCARD numkeys key [key ...] [LIMIT limit]，limit 为 0 表示不限制
+#[derive(Debug)]
+pub struct SInterCard {
+    pub keys: Vec<String>,
+    pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct Del {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Copy {
+    pub source: String,
+    pub destination: String,
+    // DB 选项指定的目标数据库，默认为 0
+    pub db: usize,
+    pub replace: bool,
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: usize,
+    // TYPE 选项，只返回这种类型的 key
+    pub key_type: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Expire {
+    pub key: String,
+    // 非正数表示立即删除
+    pub seconds: i64,
+    pub flag: Option<ExpireFlag>,
+}
+
+#[derive(Debug)]
+pub struct PExpire {
+    pub key: String,
+    pub milliseconds: i64,
+    pub flag: Option<ExpireFlag>,
+}
+
+#[derive(Debug)]
+pub struct ExpireAt {
+    pub key: String,
+    // unix 时间戳（秒），已经过去的时间表示立即删除
+    pub timestamp: i64,
+}
+
+#[derive(Debug)]
+pub struct PExpireAt {
+    pub key: String,
+    pub timestamp_ms: i64,
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct PTtl {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct ExpireTime {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct PExpireTime {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct Sort {
+    pub key: String,
+    pub options: SortOptions,
+}
+
+#[derive(Debug)]
+pub struct ZAdd {
+    pub key: String,
+    pub options: ZAddOptions,
+    // INCR：像 ZINCRBY 一样累加，回复新的 score
+    pub incr: bool,
+    // (score, member)
+    pub members: Vec<(f64, String)>,
+}
+
+#[derive(Debug)]
+pub struct ZIncrBy {
+    pub key: String,
+    pub increment: f64,
+    pub member: String,
+}
+
+#[derive(Debug)]
+pub struct ZRank {
+    pub key: String,
+    pub member: String,
+}
+
+#[derive(Debug)]
+pub struct ZCard {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct ZRem {
+    pub key: String,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByScore {
+    pub key: String,
+    pub min: ScoreBound,
+    pub max: ScoreBound,
+    pub with_scores: bool,
+    // (offset, count)，count 为 None 表示不限制数量
+    pub limit: Option<(usize, Option<usize>)>,
+}
+
+#[derive(Debug)]
+pub struct ZUnionStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+    // 与 keys 一一对应，默认都是 1
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+}
+
+#[derive(Debug)]
+pub struct ZInterStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+}
+
+#[derive(Debug)]
+pub struct ZDiffStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ZUnion {
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZInter {
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZDiff {
+    pub keys: Vec<String>,
+    pub with_scores: bool,
+}
+
+// ZMPOP numkeys key [key ...] MIN|MAX [COUNT count]
+#[derive(Debug)]
+pub struct ZMPop {
+    pub keys: Vec<String>,
+    pub min: bool,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct GeoAdd {
+    pub key: String,
+    pub options: ZAddOptions,
+    // (longitude, latitude, member)
+    pub members: Vec<(f64, f64, String)>,
+}
+
+#[derive(Debug)]
+pub struct GeoPos {
+    pub key: String,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct GeoDist {
+    pub key: String,
+    pub member1: String,
+    pub member2: String,
+    pub unit: GeoUnit,
+}
+
+#[derive(Debug)]
+pub struct GeoSearch {
+    pub key: String,
+    pub options: GeoSearchOptions,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GeoSearchOptions {
+    pub center: GeoCenter,
+    pub shape: GeoShape,
+    pub order: Option<SortOrder>,
+    // (数量, 是否为 ANY)
+    pub count: Option<(usize, bool)>,
+    pub with_coord: bool,
+    pub with_dist: bool,
+}
+
+#[derive(Debug)]
+pub struct GeoRadius {
+    pub key: String,
+    pub lon: f64,
+    pub lat: f64,
+    pub options: GeoRadiusOptions,
+}
+
+#[derive(Debug)]
+pub struct GeoRadiusByMember {
+    pub key: String,
+    pub member: String,
+    pub options: GeoRadiusOptions,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GeoRadiusOptions {
+    pub radius: f64,
+    pub unit: GeoUnit,
+    pub order: Option<SortOrder>,
+    // (数量, 是否为 ANY)
+    pub count: Option<(usize, bool)>,
+    pub with_coord: bool,
+    pub with_dist: bool,
+    // STORE dest：结果写入 dest，score 为 geohash
+    pub store: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GeoCenter {
+    Member(String),
+    // (longitude, latitude)
+    LonLat(f64, f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoShape {
+    Radius(f64, GeoUnit),
+    // (width, height, unit)
+    Box(f64, f64, GeoUnit),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoUnit {
+    Meters,
+    Kilometers,
+    Miles,
+    Feet,
+}
+
+#[derive(Debug)]
+pub struct Object {
+    pub subcommand: ObjectSubcommand,
+    // OBJECT HELP 没有 key
+    pub key: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ObjectSubcommand {
+    IdleTime,
+    RefCount,
+    Freq,
+    Encoding,
+    Help,
+}
+
+#[derive(Debug)]
+pub struct Quit;
+
+#[derive(Debug)]
+pub struct Reset;
+
+#[derive(Debug)]
+pub struct Monitor;
+
+#[derive(Debug)]
+pub struct Ping {
+    pub message: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Auth {
+    // None 表示只传了密码，对应 default 用户
+    pub username: Option<String>,
+    pub password: String,
+}
+
+#[derive(Debug)]
+pub struct Hello {
+    // None 表示不切换协议，只返回服务端信息
+    pub protover: Option<i64>,
+    pub auth: Option<Auth>,
+    pub setname: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Client {
+    pub subcommand: ClientSubcommand,
+}
+
+#[derive(Debug)]
+pub struct Acl {
+    pub subcommand: AclSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AclSubcommand {
+    WhoAmI,
+    List,
+    // 用户名和按顺序应用的规则
+    SetUser(String, Vec<String>),
+    GetUser(String),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClientSubcommand {
+    Kill(ClientKill),
+    // 空字符串表示清除连接名
+    SetName(String),
+    GetName,
+    Id,
+    Pause(ClientPauseArgs),
+    Unpause,
+    Help,
+}
+
+// CLIENT PAUSE timeout [WRITE | ALL]
+#[derive(Debug, PartialEq)]
+pub struct ClientPauseArgs {
+    pub timeout_ms: u64,
+    pub mode: PauseMode,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ClientKill {
+    // 多个过滤条件需要同时满足
+    pub filters: Vec<ClientKillFilter>,
+    // 是否跳过执行命令的连接自身，默认为 true
+    pub skip_me: bool,
+    // 旧格式 CLIENT KILL addr:port，回复 OK 而不是关闭的数量
+    pub legacy: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClientKillFilter {
+    Id(u64),
+    // ip:port
+    Addr(String),
+}
+
+#[derive(Debug)]
+pub struct Info {
+    // 小写的 section 名，为空表示默认的所有 section
+    pub sections: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Slowlog {
+    pub subcommand: SlowlogSubcommand,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub subcommand: ConfigSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigSubcommand {
+    // glob 模式，已转换成小写
+    Get(Vec<String>),
+    // (name, value)
+    Set(Vec<(String, String)>),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SlowlogSubcommand {
+    // None 表示返回全部记录
+    Get(Option<usize>),
+    Len,
+    Reset,
+    Help,
+}
+
+// COMMAND 命令，命名为 CommandMeta 以免和 Command 枚举冲突
+#[derive(Debug)]
+pub struct CommandMeta {
+    pub subcommand: CommandMetaSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandMetaSubcommand {
+    // 为空表示返回所有命令的文档
+    Docs(Vec<String>),
+    // 完整的命令数组，回复其中的 key
+    GetKeys(RespArray),
+    Help,
+}
+
+#[derive(Debug)]
+pub struct Debug {
+    pub subcommand: DebugSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DebugSubcommand {
+    // 休眠的秒数
+    Sleep(f64),
+    SetActiveExpire(bool),
+    // 输出 key 的编码和序列化后的长度
+    Object(String),
+    // 保存快照后清空数据并重新加载
+    Reload,
+    Help,
+}
+
+// 集群模式下的命令，单机服务器上什么也不做
+#[derive(Debug)]
+pub struct ReadOnly;
+
+#[derive(Debug)]
+pub struct ReadWrite;
+
+#[derive(Debug)]
+pub struct Asking;
+
+#[derive(Debug)]
+pub struct Cluster {
+    pub subcommand: ClusterSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClusterSubcommand {
+    Info,
+    MyId,
+    Help,
+}
+
+#[derive(Debug)]
+pub struct Save;
+
+#[derive(Debug)]
+pub struct Sync;
+
+#[derive(Debug)]
+pub struct BgSave;
+
+#[derive(Debug)]
+pub struct LastSave;
+
+#[derive(Debug)]
+pub struct Subscribe {
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unsubscribe {
+    // 为空表示取消所有订阅
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Publish {
+    pub channel: String,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct PSubscribe {
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    // 为空表示取消所有模式订阅
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PubSub {
+    pub subcommand: PubSubSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PubSubSubcommand {
+    // 可选的 glob 模式
+    Channels(Option<String>),
+    NumSub(Vec<String>),
+    NumPat,
+    Help,
+}
+
+#[cfg(feature = "scripting")]
+#[derive(Debug)]
+pub struct Eval {
+    pub script: String,
+    pub keys: Vec<String>,
+    pub args: Vec<String>,
+}
+
+#[cfg(feature = "scripting")]
+#[derive(Debug)]
+pub struct EvalSha {
+    pub sha: String,
+    pub keys: Vec<String>,
+    pub args: Vec<String>,
+}
+
+#[cfg(feature = "scripting")]
+#[derive(Debug)]
+pub struct Script {
+    pub subcommand: ScriptSubcommand,
+}
+
+#[cfg(feature = "scripting")]
+#[derive(Debug, PartialEq)]
+pub enum ScriptSubcommand {
+    Load(String),
+    Exists(Vec<String>),
+    Flush,
+    Kill,
+    Help,
+}
+
+#[derive(Debug)]
+pub struct Wait {
+    pub numreplicas: i64,
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug)]
+pub struct XAdd {
+    pub key: String,
+    // None 表示由服务端自动生成 ID
+    pub id: Option<StreamId>,
+    pub fields: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct XRead {
+    pub count: Option<usize>,
+    // 阻塞的毫秒数，0 表示一直等待
+    pub block: Option<u64>,
+    // None 表示 `$`，即只读取命令执行之后新加入的记录
+    pub streams: Vec<(String, Option<StreamId>)>,
+}
+
+#[derive(Debug)]
+pub struct XRange {
+    pub key: String,
+    pub start: StreamId,
+    pub end: StreamId,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct XLen {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct XDel {
+    pub key: String,
+    pub ids: Vec<StreamId>,
+}
+
+#[derive(Debug)]
+pub struct XTrim {
+    pub key: String,
+    pub strategy: TrimStrategy,
+}
+
+#[derive(Debug)]
+pub struct XGroup {
+    pub subcommand: XGroupSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum XGroupSubcommand {
+    // id 为 None 表示 `$`
+    Create {
+        key: String,
+        group: String,
+        id: Option<StreamId>,
+        mkstream: bool,
+    },
+    Help,
+}
+
+#[derive(Debug)]
+pub struct XReadGroup {
+    pub group: String,
+    pub consumer: String,
+    pub count: Option<usize>,
+    pub block: Option<u64>,
+    // None 表示 `>`，即读取从未投递给组内消费者的新记录
+    pub streams: Vec<(String, Option<StreamId>)>,
+}
+
+#[derive(Debug)]
+pub struct XAck {
+    pub key: String,
+    pub group: String,
+    pub ids: Vec<StreamId>,
+}
+
 impl CommandExecutor for Unrecognized {
     fn execute(self, _: &Backend) -> RespFrame {
-        info!("Unrecognized command");
-        RESP_OK.clone()
+        self.error()
+    }
+}
+
+impl Unrecognized {
+    fn error(&self) -> RespFrame {
+        info!("Unrecognized command: {}", self.name);
+        let args: String = self.args.iter().map(|arg| format!("'{}' ", arg)).collect();
+        RespFrame::error(format!(
+            "ERR unknown command '{}', with args beginning with: {}",
+            self.name, args
+        ))
+    }
+}
+
+impl From<RespArray> for Unrecognized {
+    fn from(value: RespArray) -> Self {
+        let mut iter = value.0.into_iter().map(|frame| match frame {
+            RespFrame::BulkString(s) => String::from_utf8_lossy(&s).into_owned(),
+            RespFrame::SimpleString(s) => s.to_string(),
+            other => format!("{:?}", other),
+        });
+        let name = iter.next().unwrap_or_default();
+        Unrecognized {
+            name,
+            args: iter.collect(),
+        }
+    }
+}
+
+impl Command {
+    // 检查并执行命令，被拒绝时回复检查的错误
+    pub async fn dispatch(self, backend: &Backend, client: &mut ClientState) -> Response {
+        match self.check(backend, client).await {
+            Ok(()) => self.run(backend, client).await,
+            Err(e) => Response::Reply(e),
+        }
+    }
+
+    // 执行之前的检查：认证、ACL、CLIENT PAUSE、订阅和 MONITOR 模式的限制、只读副本和 maxmemory。
+    // 被拒绝的命令没有执行，不记入 MONITOR 和慢日志
+    pub async fn check(&self, backend: &Backend, client: &ClientState) -> Result<(), RespFrame> {
+        if !self.allowed_without_auth() && !client.is_authenticated(backend) {
+            return Err(RespFrame::error("NOAUTH Authentication required."));
+        }
+        // 未知命令在认证之后直接回复 unknown command，不做 ACL 检查
+        if let Command::Unrecognized(cmd) = self {
+            return Err(cmd.error());
+        }
+        if !self.allowed_without_auth() {
+            self.acl_check(backend, &client.user)?;
+        }
+        if self.pausable() {
+            backend.wait_if_paused(self.is_write()).await;
+        }
+        // RESP2 的连接订阅之后只能收发订阅相关的消息，RESP3 可以用 push 帧区分，不受限制
+        if client.protocol == 2 && client.is_subscribed() && !self.allowed_when_subscribed() {
+            return Err(RespFrame::error(format!(
+                "ERR Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+                self.name()
+            )));
+        }
+        if client.in_monitor_mode && !matches!(self, Command::Quit(_) | Command::Reset(_)) {
+            return Err(RespFrame::error(format!(
+                "ERR Can't execute '{}': only QUIT / RESET are allowed in MONITOR mode",
+                self.name()
+            )));
+        }
+        if self.is_write() {
+            let Some(_guard) = backend.command_guard_unless_busy().await else {
+                return Err(RespFrame::error(BUSY_ERROR));
+            };
+            self.write_check(backend)?;
+        }
+        Ok(())
+    }
+
+    // 执行通过检查的命令，阻塞类命令在这里异步等待，需要连接状态的命令在这里处理，其余命令直接同步执行
+    pub async fn run(self, backend: &Backend, client: &mut ClientState) -> Response {
+        let frame = match self {
+            Command::Auth(cmd) => cmd.execute_with_client(backend, client),
+            Command::Hello(cmd) => cmd.execute_with_client(backend, client),
+            Command::Client(cmd) => cmd.execute_with_client(backend, client),
+            Command::Acl(cmd) => cmd.execute_with_client(backend, client),
+            Command::Ping(cmd) => cmd.execute_with_client(backend, client),
+            Command::Monitor(cmd) => cmd.execute_with_client(client),
+            Command::Reset(cmd) => cmd.execute_with_client(backend, client),
+            Command::Subscribe(cmd) => {
+                return Response::Replies(cmd.execute_with_client(backend, client))
+            }
+            Command::Unsubscribe(cmd) => {
+                return Response::Replies(cmd.execute_with_client(backend, client))
+            }
+            Command::PSubscribe(cmd) => {
+                return Response::Replies(cmd.execute_with_client(backend, client))
+            }
+            Command::PUnsubscribe(cmd) => {
+                return Response::Replies(cmd.execute_with_client(backend, client))
+            }
+            Command::XRead(cmd) if cmd.block.is_some() => cmd.execute_blocking(backend).await,
+            Command::XReadGroup(cmd) if cmd.block.is_some() => cmd.execute_blocking(backend).await,
+            Command::BLPop(cmd) => cmd.execute_blocking(backend).await,
+            Command::BRPop(cmd) => cmd.execute_blocking(backend).await,
+            Command::Debug(cmd) => cmd.execute_async(backend).await,
+            Command::Wait(cmd) => cmd.execute_async(backend).await,
+            Command::Save(cmd) => {
+                let Some(_guard) = backend.command_guard_unless_busy().await else {
+                    return Response::Reply(RespFrame::error(BUSY_ERROR));
+                };
+                cmd.execute_async(backend).await
+            }
+            // 读取源 key 和写入目标 key 之间不能有其它命令穿插
+            Command::Copy(cmd) => {
+                let Some(_guard) = backend.script_guard_unless_busy().await else {
+                    return Response::Reply(RespFrame::error(BUSY_ERROR));
+                };
+                cmd.execute(backend)
+            }
+            Command::Quit(cmd) => return Response::ReplyAndClose(cmd.execute(backend)),
+            #[cfg(feature = "scripting")]
+            Command::Eval(cmd) => cmd.execute_with_client(backend, client).await,
+            #[cfg(feature = "scripting")]
+            Command::EvalSha(cmd) => cmd.execute_with_client(backend, client).await,
+            // SCRIPT KILL 在脚本执行期间调用，不能等待脚本结束
+            #[cfg(feature = "scripting")]
+            Command::Script(cmd) if cmd.subcommand == ScriptSubcommand::Kill => {
+                cmd.execute(backend)
+            }
+            cmd => {
+                let Some(_guard) = backend.command_guard_unless_busy().await else {
+                    return Response::Reply(RespFrame::error(BUSY_ERROR));
+                };
+                cmd.execute(backend)
+            }
+        };
+        Response::Reply(frame)
+    }
+}
+
+impl Command {
+    // 命令名，用于日志
+    pub fn name(&self) -> &str {
+        match self {
+            Command::Get(_) => "get",
+            Command::Set(_) => "set",
+            Command::MSet(_) => "mset",
+            Command::SetBit(_) => "setbit",
+            Command::GetBit(_) => "getbit",
+            Command::BitCount(_) => "bitcount",
+            Command::HGet(_) => "hget",
+            Command::HSet(_) => "hset",
+            Command::HGetAll(_) => "hgetall",
+            Command::HScan(_) => "hscan",
+            Command::HRandField(_) => "hrandfield",
+            Command::LPush(_) => "lpush",
+            Command::RPush(_) => "rpush",
+            Command::LRange(_) => "lrange",
+            Command::LPos(_) => "lpos",
+            Command::LMPop(_) => "lmpop",
+            Command::BLPop(_) => "blpop",
+            Command::BRPop(_) => "brpop",
+            Command::SAdd(_) => "sadd",
+            Command::SMembers(_) => "smembers",
+            Command::SScan(_) => "sscan",
+            Command::SInterCard(_) => "sintercard",
+            Command::Del(_) => "del",
+            Command::Copy(_) => "copy",
+            Command::Scan(_) => "scan",
+            Command::Expire(_) => "expire",
+            Command::PExpire(_) => "pexpire",
+            Command::ExpireAt(_) => "expireat",
+            Command::PExpireAt(_) => "pexpireat",
+            Command::Ttl(_) => "ttl",
+            Command::PTtl(_) => "pttl",
+            Command::ExpireTime(_) => "expiretime",
+            Command::PExpireTime(_) => "pexpiretime",
+            Command::Sort(_) => "sort",
+            Command::XAdd(_) => "xadd",
+            Command::XRead(_) => "xread",
+            Command::XRange(_) => "xrange",
+            Command::XLen(_) => "xlen",
+            Command::XDel(_) => "xdel",
+            Command::XTrim(_) => "xtrim",
+            Command::XGroup(_) => "xgroup",
+            Command::XReadGroup(_) => "xreadgroup",
+            Command::XAck(_) => "xack",
+            Command::ZAdd(_) => "zadd",
+            Command::ZIncrBy(_) => "zincrby",
+            Command::ZRank(_) => "zrank",
+            Command::ZCard(_) => "zcard",
+            Command::ZRem(_) => "zrem",
+            Command::ZRangeByScore(_) => "zrangebyscore",
+            Command::ZUnionStore(_) => "zunionstore",
+            Command::ZInterStore(_) => "zinterstore",
+            Command::ZDiffStore(_) => "zdiffstore",
+            Command::ZUnion(_) => "zunion",
+            Command::ZInter(_) => "zinter",
+            Command::ZDiff(_) => "zdiff",
+            Command::ZMPop(_) => "zmpop",
+            Command::GeoAdd(_) => "geoadd",
+            Command::GeoPos(_) => "geopos",
+            Command::GeoDist(_) => "geodist",
+            Command::GeoSearch(_) => "geosearch",
+            Command::GeoRadius(_) => "georadius",
+            Command::GeoRadiusByMember(_) => "georadiusbymember",
+            Command::Object(_) => "object",
+            Command::Debug(_) => "debug",
+            Command::Wait(_) => "wait",
+            Command::ReadOnly(_) => "readonly",
+            Command::ReadWrite(_) => "readwrite",
+            Command::Asking(_) => "asking",
+            Command::Cluster(_) => "cluster",
+            Command::Slowlog(_) => "slowlog",
+            Command::Config(_) => "config",
+            Command::Save(_) => "save",
+            Command::Sync(_) => "sync",
+            Command::BgSave(_) => "bgsave",
+            Command::LastSave(_) => "lastsave",
+            Command::Subscribe(_) => "subscribe",
+            Command::Unsubscribe(_) => "unsubscribe",
+            Command::Publish(_) => "publish",
+            Command::PSubscribe(_) => "psubscribe",
+            Command::PUnsubscribe(_) => "punsubscribe",
+            Command::PubSub(_) => "pubsub",
+            Command::Monitor(_) => "monitor",
+            #[cfg(feature = "scripting")]
+            Command::Eval(_) => "eval",
+            #[cfg(feature = "scripting")]
+            Command::EvalSha(_) => "evalsha",
+            #[cfg(feature = "scripting")]
+            Command::Script(_) => "script",
+            Command::Info(_) => "info",
+            Command::CommandMeta(_) => "command",
+            Command::Quit(_) => "quit",
+            Command::Reset(_) => "reset",
+            Command::Ping(_) => "ping",
+            Command::Auth(_) => "auth",
+            Command::Hello(_) => "hello",
+            Command::Client(_) => "client",
+            Command::Acl(_) => "acl",
+            Command::Unrecognized(cmd) => &cmd.name,
+        }
+    }
+
+    // 命令操作的第一个 key，没有 key 的命令返回 None，用于日志
+    pub fn key(&self) -> Option<&str> {
+        self.keys().first().copied()
+    }
+
+    // 命令操作的所有 key，按它们在参数中出现的顺序，用于 COMMAND GETKEYS 和 ACL 的 key 检查。
+    // 不使用通配分支，新增命令时必须在这里说明它有哪些 key
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            Command::Get(Get { key, .. })
+            | Command::Set(Set { key, .. })
+            | Command::SetBit(SetBit { key, .. })
+            | Command::GetBit(GetBit { key, .. })
+            | Command::BitCount(BitCount { key, .. })
+            | Command::HGet(HGet { key, .. })
+            | Command::HSet(HSet { key, .. })
+            | Command::HGetAll(HGetAll { key, .. })
+            | Command::HScan(HScan { key, .. })
+            | Command::HRandField(HRandField { key, .. })
+            | Command::LPush(LPush { key, .. })
+            | Command::RPush(RPush { key, .. })
+            | Command::LRange(LRange { key, .. })
+            | Command::LPos(LPos { key, .. })
+            | Command::SAdd(SAdd { key, .. })
+            | Command::SMembers(SMembers { key, .. })
+            | Command::SScan(SScan { key, .. })
+            | Command::Expire(Expire { key, .. })
+            | Command::PExpire(PExpire { key, .. })
+            | Command::ExpireAt(ExpireAt { key, .. })
+            | Command::PExpireAt(PExpireAt { key, .. })
+            | Command::Ttl(Ttl { key, .. })
+            | Command::PTtl(PTtl { key, .. })
+            | Command::ExpireTime(ExpireTime { key, .. })
+            | Command::PExpireTime(PExpireTime { key, .. })
+            | Command::XAdd(XAdd { key, .. })
+            | Command::XRange(XRange { key, .. })
+            | Command::XLen(XLen { key, .. })
+            | Command::XDel(XDel { key, .. })
+            | Command::XTrim(XTrim { key, .. })
+            | Command::XAck(XAck { key, .. })
+            | Command::ZAdd(ZAdd { key, .. })
+            | Command::ZIncrBy(ZIncrBy { key, .. })
+            | Command::ZRank(ZRank { key, .. })
+            | Command::ZCard(ZCard { key, .. })
+            | Command::ZRem(ZRem { key, .. })
+            | Command::ZRangeByScore(ZRangeByScore { key, .. })
+            | Command::GeoAdd(GeoAdd { key, .. })
+            | Command::GeoPos(GeoPos { key, .. })
+            | Command::GeoDist(GeoDist { key, .. })
+            | Command::GeoSearch(GeoSearch { key, .. })
+            | Command::XGroup(XGroup {
+                subcommand: XGroupSubcommand::Create { key, .. },
+            })
+            | Command::Debug(Debug {
+                subcommand: DebugSubcommand::Object(key),
+            }) => vec![key],
+            Command::Object(cmd) => cmd.key.iter().map(String::as_str).collect(),
+            Command::Del(Del { keys, .. })
+            | Command::LMPop(LMPop { keys, .. })
+            | Command::BLPop(BLPop { keys, .. })
+            | Command::BRPop(BRPop { keys, .. })
+            | Command::SInterCard(SInterCard { keys, .. })
+            | Command::ZUnion(ZUnion { keys, .. })
+            | Command::ZInter(ZInter { keys, .. })
+            | Command::ZDiff(ZDiff { keys, .. })
+            | Command::ZMPop(ZMPop { keys, .. }) => keys.iter().map(String::as_str).collect(),
+            #[cfg(feature = "scripting")]
+            Command::Eval(Eval { keys, .. }) | Command::EvalSha(EvalSha { keys, .. }) => {
+                keys.iter().map(String::as_str).collect()
+            }
+            Command::MSet(cmd) => cmd.pairs.iter().map(|(key, _)| key.as_str()).collect(),
+            Command::ZUnionStore(ZUnionStore {
+                destination, keys, ..
+            })
+            | Command::ZInterStore(ZInterStore {
+                destination, keys, ..
+            })
+            | Command::ZDiffStore(ZDiffStore {
+                destination, keys, ..
+            }) => std::iter::once(destination.as_str())
+                .chain(keys.iter().map(String::as_str))
+                .collect(),
+            Command::Copy(cmd) => vec![&cmd.source, &cmd.destination],
+            Command::Sort(cmd) => std::iter::once(cmd.key.as_str())
+                .chain(cmd.options.store.as_deref())
+                .collect(),
+            Command::GeoRadius(cmd) => std::iter::once(cmd.key.as_str())
+                .chain(cmd.options.store.as_deref())
+                .collect(),
+            Command::GeoRadiusByMember(cmd) => std::iter::once(cmd.key.as_str())
+                .chain(cmd.options.store.as_deref())
+                .collect(),
+            Command::XRead(XRead { streams, .. })
+            | Command::XReadGroup(XReadGroup { streams, .. }) => {
+                streams.iter().map(|(key, _)| key.as_str()).collect()
+            }
+            Command::Debug(_)
+            | Command::XGroup(_)
+            | Command::Scan(_)
+            | Command::Wait(_)
+            | Command::ReadOnly(_)
+            | Command::ReadWrite(_)
+            | Command::Asking(_)
+            | Command::Cluster(_)
+            | Command::Quit(_)
+            | Command::Reset(_)
+            | Command::Ping(_)
+            | Command::Auth(_)
+            | Command::Hello(_)
+            | Command::Client(_)
+            | Command::Acl(_)
+            | Command::Slowlog(_)
+            | Command::Config(_)
+            | Command::Save(_)
+            | Command::Sync(_)
+            | Command::BgSave(_)
+            | Command::LastSave(_)
+            | Command::Subscribe(_)
+            | Command::Unsubscribe(_)
+            | Command::Publish(_)
+            | Command::PSubscribe(_)
+            | Command::PUnsubscribe(_)
+            | Command::PubSub(_)
+            | Command::Monitor(_)
+            | Command::Info(_)
+            | Command::CommandMeta(_)
+            | Command::Unrecognized(_) => Vec::new(),
+            #[cfg(feature = "scripting")]
+            Command::Script(_) => Vec::new(),
+        }
+    }
+
+    // 未认证的连接也可以执行的命令
+    fn allowed_without_auth(&self) -> bool {
+        matches!(
+            self,
+            Command::Auth(_) | Command::Hello(_) | Command::Quit(_) | Command::Reset(_)
+        )
+    }
+
+    // CLIENT PAUSE 期间需要等待的命令，连接管理类的命令不受影响，否则无法执行 CLIENT UNPAUSE
+    fn pausable(&self) -> bool {
+        !matches!(
+            self,
+            Command::Auth(_)
+                | Command::Hello(_)
+                | Command::Quit(_)
+                | Command::Reset(_)
+                | Command::Client(_)
+                | Command::Unrecognized(_)
+        )
+    }
+
+    // 命令表中标记为写命令
+    pub(crate) fn is_write(&self) -> bool {
+        lookup_command(self.name()).is_some_and(|spec| spec.write)
+    }
+
+    // RESP2 连接处于订阅状态时仍然可以执行的命令
+    fn allowed_when_subscribed(&self) -> bool {
+        matches!(
+            self,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::Ping(_)
+                | Command::Quit(_)
+                | Command::Reset(_)
+        )
+    }
+
+    // 内存超过 maxmemory 又无法淘汰时拒绝的命令，对应 Redis 的 denyoom 标记：
+    // 只删除数据的写命令不受影响，否则内存满了以后无法释放
+    fn denied_on_oom(&self) -> bool {
+        self.is_write()
+            && !matches!(
+                self,
+                Command::Del(_)
+                    | Command::LMPop(_)
+                    | Command::BLPop(_)
+                    | Command::BRPop(_)
+                    | Command::ZRem(_)
+                    | Command::ZMPop(_)
+                    | Command::XDel(_)
+                    | Command::XTrim(_)
+                    | Command::XAck(_)
+                    | Command::Expire(_)
+                    | Command::PExpire(_)
+                    | Command::ExpireAt(_)
+                    | Command::PExpireAt(_)
+            )
+    }
+
+    // 按命令表中的分类和命令的 key 检查当前用户的 ACL 权限
+    // 检查用户是否可以执行这条命令、访问它的 key，脚本中的 redis.call 也要经过这个检查
+    pub(crate) fn acl_check(&self, backend: &Backend, user: &str) -> Result<(), RespFrame> {
+        let categories = lookup_command(self.name())
+            .map(CommandSpec::acl_categories)
+            .unwrap_or_default();
+        match backend.acl_check(user, self.name(), &categories, &self.keys()) {
+            Ok(()) => Ok(()),
+            Err(AclDenied::Command) => Err(RespFrame::error(format!(
+                "NOPERM User {} has no permissions to run the '{}' command",
+                user,
+                self.name()
+            ))),
+            Err(AclDenied::Key) => Err(RespFrame::error("NOPERM No permissions to access a key")),
+        }
+    }
+
+    // 只读副本拒绝写命令；内存超过 maxmemory 并且淘汰不出空间时拒绝会占用更多内存的命令。
+    // 会淘汰 key，调用方需要持有 command_guard 或 script_guard
+    pub(crate) fn write_check(&self, backend: &Backend) -> Result<(), RespFrame> {
+        if self.is_write() && backend.config().replica_of.is_some() {
+            return Err(RespFrame::error(
+                "READONLY You can't write against a read only replica.",
+            ));
+        }
+        if self.denied_on_oom() && !backend.evict_if_needed() {
+            return Err(RespFrame::error(
+                "OOM command not allowed when used memory > 'maxmemory'.",
+            ));
+        }
+        Ok(())
+    }
+}
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+impl Default for ClientState {
+    fn default() -> Self {
+        Self {
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            authenticated: false,
+            user: DEFAULT_USER.to_string(),
+            protocol: 2,
+            name: None,
+            addr: None,
+            subscriptions: HashSet::new(),
+            patterns: HashSet::new(),
+            in_monitor_mode: false,
+        }
+    }
+}
+
+impl ClientState {
+    // 订阅了任意 channel 或模式
+    pub fn is_subscribed(&self) -> bool {
+        !self.subscriptions.is_empty() || !self.patterns.is_empty()
+    }
+
+    // default 用户是 nopass 时不需要认证
+    pub fn is_authenticated(&self, backend: &Backend) -> bool {
+        self.authenticated || backend.acl_default_nopass()
     }
 }
 
@@ -95,18 +1510,20 @@ impl TryFrom<RespFrame> for Command {
 impl TryFrom<RespArray> for Command {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        match value.first() {
-            Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
-                "get" => value.try_into().map(Command::Get),
-                "set" => value.try_into().map(Command::Set),
-                "hget" => value.try_into().map(Command::HGet),
-                "hset" => value.try_into().map(Command::HSet),
-                "hgetall" => value.try_into().map(Command::HGetAll),
-                _ => Ok(Command::Unrecognized(Unrecognized)),
-            },
-            _ => Err(CommandError::InvalidCommand(
-                "Command must be a bulk string".to_string(),
-            )),
+        let name = match value.first() {
+            Some(RespFrame::BulkString(ref cmd)) => {
+                String::from_utf8_lossy(cmd).to_ascii_lowercase()
+            }
+            _ => {
+                return Err(CommandError::InvalidCommand(
+                    "Command must be a bulk string".to_string(),
+                ))
+            }
+        };
+        match lookup_command(&name) {
+            Some(spec) if !spec.accepts(value.len()) => Err(CommandError::WrongArity(name)),
+            Some(spec) => (spec.parse)(value),
+            None => Ok(Command::Unrecognized(value.into())),
         }
     }
 }
@@ -117,21 +1534,51 @@ fn validate_command(
     n_args: usize,
 ) -> Result<(), CommandError> {
     if value.len() != n_args + names.len() {
-        return Err(CommandError::InvalidArgument(format!(
-            "{} command must have {} arguments ",
-            names.join(" "),
-            n_args
-        )));
+        return Err(wrong_arity(names));
+    }
+    validate_names(value, names)
+}
+
+// 校验可变参数命令，至少需要 min_args 个参数
+fn validate_command_at_least(
+    value: &RespArray,
+    names: &[&'static str],
+    min_args: usize,
+) -> Result<(), CommandError> {
+    if value.len() < min_args + names.len() {
+        return Err(wrong_arity(names));
     }
+    validate_names(value, names)
+}
 
+// 子命令 HELP 的回复，和 Redis 一样首行说明用法，末尾附上 HELP 自身的说明，每一行都是 simple string
+fn help_reply(command: &str, lines: &[&str]) -> RespFrame {
+    let header = format!(
+        "{} <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+        command
+    );
+    let frames: Vec<RespFrame> = std::iter::once(header.as_str())
+        .chain(lines.iter().copied())
+        .chain(["HELP", "    Print this help."])
+        .map(|line| SimpleString::new(line).into())
+        .collect();
+    RespArray::new(frames).into()
+}
+
+// 子命令的错误信息和 Redis 一样写作 `client|kill`
+fn wrong_arity(names: &[&'static str]) -> CommandError {
+    CommandError::WrongArity(names.join("|"))
+}
+
+fn validate_names(value: &RespArray, names: &[&'static str]) -> Result<(), CommandError> {
     for (i, name) in names.iter().enumerate() {
         match value[i] {
             RespFrame::BulkString(ref cmd) => {
-                if cmd.as_ref().to_ascii_lowercase().as_bytes() != name.as_bytes() {
+                if !cmd.eq_ignore_ascii_case(name.as_bytes()) {
                     return Err(CommandError::InvalidCommand(format!(
                         "Invalid command: expected {}, got {}",
                         name,
-                        cmd.as_ref()
+                        String::from_utf8_lossy(cmd)
                     )));
                 }
             }
@@ -148,3 +1595,406 @@ fn validate_command(
 fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     Ok(value.0.into_iter().skip(start).collect())
 }
+
+// 将 bulk string 参数转换为 String
+fn extract_string(frame: Option<RespFrame>) -> Result<String, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(s)) => Ok(String::from_utf8(s.0)?),
+        _ => Err(CommandError::InvalidArgument(
+            "Argument must be a bulk string".to_string(),
+        )),
+    }
+}
+
+// 将 bulk string 参数解析为整数
+fn extract_integer(frame: Option<RespFrame>) -> Result<i64, CommandError> {
+    extract_string(frame)?.parse().map_err(|_| {
+        CommandError::InvalidArgument("value is not an integer or out of range".to_string())
+    })
+}
+
+// 解析 LMPOP / ZMPOP 共用的 `numkeys key [key ...] <where> [COUNT count]`，
+// where 取 wheres 中的一个，返回 (keys, 是否为 wheres[0], count)
+fn parse_mpop(
+    value: RespArray,
+    name: &'static str,
+    wheres: [&str; 2],
+) -> Result<(Vec<String>, bool, usize), CommandError> {
+    validate_command_at_least(&value, &[name], 3)?;
+    let args = extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let numkeys = match args[0].parse::<i64>() {
+        Ok(n) if n > 0 => n as usize,
+        _ => {
+            return Err(CommandError::InvalidArgument(
+                "numkeys should be greater than 0".to_string(),
+            ))
+        }
+    };
+    // numkeys 个 key 之后至少还要有 where
+    if args.len() < numkeys + 2 {
+        return Err(syntax_error());
+    }
+    let first = match args[numkeys + 1].to_ascii_lowercase() {
+        w if w == wheres[0] => true,
+        w if w == wheres[1] => false,
+        _ => return Err(syntax_error()),
+    };
+    let count = match &args[numkeys + 2..] {
+        [] => 1,
+        [opt, n] if opt.eq_ignore_ascii_case("count") => match n.parse::<i64>() {
+            Ok(n) if n > 0 => n as usize,
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "count should be greater than 0".to_string(),
+                ))
+            }
+        },
+        _ => return Err(syntax_error()),
+    };
+    Ok((args[1..=numkeys].to_vec(), first, count))
+}
+
+// 解析 `cursor [MATCH pattern] [COUNT count]`，返回 (cursor, pattern, count, type)；
+// with_type 为 true 时（SCAN）还接受 `TYPE type`
+fn parse_scan_args(
+    mut args: impl Iterator<Item = RespFrame>,
+    with_type: bool,
+) -> Result<(u64, Option<String>, usize, Option<String>), CommandError> {
+    let cursor = extract_string(args.next())?
+        .parse()
+        .map_err(|_| CommandError::InvalidArgument("invalid cursor".to_string()))?;
+    let mut pattern = None;
+    let mut count = DEFAULT_SCAN_COUNT;
+    let mut key_type = None;
+    while let Some(arg) = args.next() {
+        match extract_string(Some(arg))?.to_ascii_lowercase().as_str() {
+            "match" => pattern = Some(extract_string(args.next())?),
+            "type" if with_type => key_type = Some(extract_string(args.next())?),
+            "count" => {
+                count = match extract_integer(args.next())? {
+                    n if n >= 1 => n as usize,
+                    _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+                }
+            }
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        }
+    }
+    Ok((cursor, pattern, count, key_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::{BulkString, RespDecoder};
+
+    use super::*;
+
+    #[test]
+    fn test_unrecognized_command_returns_error() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nfoob\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        let cmd = Command::try_from(frame)?;
+        let ret = cmd.execute(&Backend::new());
+        assert_eq!(
+            ret,
+            RespFrame::error(
+                "ERR unknown command 'foob', with args beginning with: 'key' 'value' "
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_utf8_command_name_does_not_panic() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$2\r\n\xff\xfe\r\n$3\r\nkey\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        let cmd = Command::try_from(frame)?;
+        let ret = cmd.execute(&Backend::new());
+        assert!(matches!(ret, RespFrame::Error(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_command_rejects_non_utf8_name() {
+        let array = RespArray::new(vec![
+            BulkString::new(b"\xffet".to_vec()).into(),
+            BulkString::new("key").into(),
+        ]);
+        let ret = validate_command(&array, &["get"], 1);
+        assert!(matches!(ret, Err(CommandError::InvalidCommand(_))));
+    }
+
+    #[test]
+    fn test_command_name_is_case_insensitive() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nGeT\r\n$3\r\nkey\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        let cmd = Command::try_from(frame)?;
+        assert!(matches!(cmd, Command::Get(_)));
+        Ok(())
+    }
+
+    // 每个命令一条示例，名字形如 k1、k2 的参数是 key。新增命令时需要在这里补上示例
+    const KEY_EXAMPLES: &[&str] = &[
+        "get k1",
+        "set k1 v",
+        "mset k1 v k2 v",
+        "setbit k1 7 1",
+        "getbit k1 7",
+        "bitcount k1",
+        "hget k1 f",
+        "hset k1 f v",
+        "hgetall k1",
+        "hscan k1 0",
+        "hrandfield k1",
+        "lpush k1 a",
+        "rpush k1 a",
+        "lrange k1 0 -1",
+        "lpos k1 a",
+        "lmpop 2 k1 k2 LEFT",
+        "blpop k1 k2 0",
+        "brpop k1 k2 0",
+        "sadd k1 a",
+        "smembers k1",
+        "sscan k1 0",
+        "sintercard 2 k1 k2",
+        "del k1 k2",
+        "copy k1 k2",
+        "scan 0",
+        "expire k1 10",
+        "pexpire k1 10",
+        "expireat k1 10",
+        "pexpireat k1 10",
+        "ttl k1",
+        "pttl k1",
+        "expiretime k1",
+        "pexpiretime k1",
+        "sort k1 STORE k2",
+        "xadd k1 * f v",
+        "xread STREAMS k1 k2 0 0",
+        "xrange k1 - +",
+        "xlen k1",
+        "xdel k1 1-1",
+        "xtrim k1 MAXLEN 10",
+        "xgroup CREATE k1 g $",
+        "xreadgroup GROUP g c STREAMS k1 >",
+        "xack k1 g 1-1",
+        "zadd k1 1 m",
+        "zincrby k1 1 m",
+        "zrank k1 m",
+        "zcard k1",
+        "zrem k1 m",
+        "zrangebyscore k1 0 1",
+        "zunionstore k1 2 k2 k3",
+        "zinterstore k1 2 k2 k3",
+        "zdiffstore k1 2 k2 k3",
+        "zunion 2 k1 k2",
+        "zinter 2 k1 k2",
+        "zdiff 2 k1 k2",
+        "zmpop 2 k1 k2 MIN",
+        "geoadd k1 1 1 m",
+        "geopos k1 m",
+        "geodist k1 a b",
+        "geosearch k1 FROMMEMBER m BYRADIUS 1 m",
+        "georadius k1 1 1 1 m STORE k2",
+        "georadiusbymember k1 m 1 m STORE k2",
+        "object encoding k1",
+        "debug object k1",
+        "wait 0 0",
+        "readonly",
+        "readwrite",
+        "asking",
+        "cluster info",
+        "save",
+        "sync",
+        "bgsave",
+        "lastsave",
+        "subscribe c",
+        "unsubscribe",
+        "publish c m",
+        "psubscribe p",
+        "punsubscribe",
+        "pubsub channels",
+        "monitor",
+        "eval s 2 k1 k2 a",
+        "evalsha 0000000000000000000000000000000000000000 1 k1",
+        "script flush",
+        "info",
+        "slowlog len",
+        "config get maxmemory",
+        "command docs get",
+        "quit",
+        "reset",
+        "ping",
+        "auth pass",
+        "hello",
+        "client id",
+        "acl whoami",
+    ];
+
+    #[test]
+    fn test_keys_cover_command_table() {
+        for spec in COMMAND_TABLE {
+            let example = KEY_EXAMPLES
+                .iter()
+                .find(|example| example.split(' ').next() == Some(spec.name))
+                .unwrap_or_else(|| panic!("no example for {}", spec.name));
+            let args: Vec<RespFrame> = example
+                .split(' ')
+                .map(|arg| BulkString::new(arg).into())
+                .collect();
+            let cmd = Command::try_from(RespArray::new(args))
+                .unwrap_or_else(|e| panic!("{}: {:?}", example, e));
+            assert_eq!(cmd.name(), spec.name);
+            let expected: Vec<&str> = example
+                .split(' ')
+                .skip(1)
+                .filter(|arg| {
+                    arg.strip_prefix('k')
+                        .is_some_and(|n| n.parse::<u32>().is_ok())
+                })
+                .collect();
+            assert_eq!(cmd.keys(), expected, "{}", example);
+            assert_eq!(cmd.key(), expected.first().copied(), "{}", example);
+        }
+    }
+
+    #[test]
+    fn test_acl_check_uses_keys_and_admin_category() {
+        let backend = Backend::new();
+        backend
+            .acl_setuser("ops", &["on", "nopass", "~cache:*", "+@server", "+@stream"])
+            .unwrap();
+        let parse = |args: &str| {
+            let array: Vec<RespFrame> =
+                args.split(' ').map(|a| BulkString::new(a).into()).collect();
+            Command::try_from(RespArray::new(array)).unwrap()
+        };
+        assert!(parse("info").acl_check(&backend, "ops").is_ok());
+        assert!(parse("config get maxmemory")
+            .acl_check(&backend, "ops")
+            .is_err());
+        assert!(parse("monitor").acl_check(&backend, "ops").is_err());
+        assert!(parse("xgroup CREATE cache:1 g $")
+            .acl_check(&backend, "ops")
+            .is_ok());
+        assert_eq!(
+            parse("xgroup CREATE other g $").acl_check(&backend, "ops"),
+            Err(RespFrame::error("NOPERM No permissions to access a key"))
+        );
+    }
+
+    #[test]
+    fn test_wrong_number_of_arguments() {
+        let parse = |args: &[&str]| {
+            let array: Vec<RespFrame> = args.iter().map(|a| BulkString::new(*a).into()).collect();
+            Command::try_from(RespArray::new(array))
+        };
+        // 参数不足
+        let err = parse(&["get"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'get' command"
+        );
+        // 参数过多
+        let err = parse(&["GET", "a", "b"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'get' command"
+        );
+        // 可变参数命令只检查下限
+        assert!(matches!(parse(&["del"]), Err(CommandError::WrongArity(_))));
+        assert!(matches!(
+            parse(&["del", "a", "b", "c"]),
+            Ok(Command::Del(_))
+        ));
+    }
+
+    async fn run(
+        backend: &Backend,
+        client: &mut ClientState,
+        args: &[&str],
+    ) -> anyhow::Result<RespFrame> {
+        let frames: Vec<RespFrame> = args.iter().map(|a| BulkString::new(*a).into()).collect();
+        match Command::try_from(RespArray::new(frames))?
+            .dispatch(backend, client)
+            .await
+        {
+            Response::Reply(frame) | Response::ReplyAndClose(frame) => Ok(frame),
+            Response::Replies(frames) => Ok(RespArray::new(frames).into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_rejected_when_out_of_memory() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        backend.set_config("maxmemory", "10")?;
+        let client = &mut ClientState::default();
+        let ret = run(&backend, client, &["set", "a", "0123456789"]).await?;
+        assert_eq!(ret, RespFrame::ok());
+        let oom = RespFrame::error("OOM command not allowed when used memory > 'maxmemory'.");
+        assert_eq!(run(&backend, client, &["set", "b", "x"]).await?, oom);
+        // 读命令和删除命令不受影响
+        let ret = run(&backend, client, &["get", "a"]).await?;
+        assert_eq!(ret, BulkString::new("0123456789").into());
+        let ret = run(&backend, client, &["del", "a"]).await?;
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(
+            run(&backend, client, &["set", "b", "x"]).await?,
+            RespFrame::ok()
+        );
+
+        // 淘汰发生在写命令执行之前，写入 c 之后超出上限，下一次写入时淘汰最久没有访问的 b
+        backend.set_config("maxmemory-policy", "allkeys-lru")?;
+        let ret = run(&backend, client, &["set", "c", "01234567"]).await?;
+        assert_eq!(ret, RespFrame::ok());
+        assert_eq!(
+            run(&backend, client, &["set", "d", "x"]).await?,
+            RespFrame::ok()
+        );
+        assert!(!backend.exists("b"));
+        assert!(backend.exists("c") && backend.exists("d"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_runs_exclusively() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let client = &mut ClientState::default();
+        run(&backend, client, &["set", "src", "v"]).await?;
+        // 其它命令执行期间 COPY 一直等待
+        let guard = backend.command_guard().await;
+        let pending = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            run(&backend, client, &["copy", "src", "dst"]),
+        )
+        .await;
+        assert!(pending.is_err());
+        drop(guard);
+        let ret = run(&backend, client, &["copy", "src", "dst"]).await?;
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(backend.get("dst"), Some(BulkString::new("v").into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_command_reports_subcommand() {
+        let array = RespArray::new(vec![
+            BulkString::new("slowlog").into(),
+            BulkString::new("len").into(),
+            BulkString::new("extra").into(),
+        ]);
+        let ret = validate_command(&array, &["slowlog", "len"], 0);
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR wrong number of arguments for 'slowlog|len' command"
+        );
+    }
+}
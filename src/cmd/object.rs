@@ -0,0 +1,221 @@
+use crate::{
+    cmd::{extract_args, extract_string, help_reply, validate_command_at_least},
+    Backend, BulkString, RespArray, RespFrame, RespNull,
+};
+
+use super::{CommandError, CommandExecutor, Object, ObjectSubcommand};
+
+const OBJECT_HELP: &[&str] = &[
+    "ENCODING <key>",
+    "    Return the kind of internal representation used in order to store the value",
+    "    associated with a <key>.",
+    "FREQ <key>",
+    "    Return the access frequency index of the <key>. The returned integer is",
+    "    proportional to the logarithm of the recent access frequency of the key.",
+    "IDLETIME <key>",
+    "    Return the idle time of the <key>, that is the approximated number of",
+    "    seconds elapsed since the last access to the key.",
+    "REFCOUNT <key>",
+    "    Return the number of references of the value associated with the specified",
+    "    <key>.",
+];
+
+impl CommandExecutor for Object {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let key = self.key.unwrap_or_default();
+        match self.subcommand {
+            ObjectSubcommand::Help => help_reply("OBJECT", OBJECT_HELP),
+            ObjectSubcommand::IdleTime => match backend.idletime(&key) {
+                Some(idle) => RespFrame::Integer(idle.as_secs() as i64),
+                None => RespFrame::Null(RespNull),
+            },
+            // value 不在多个 key 之间共享，引用计数恒为 1
+            ObjectSubcommand::RefCount => match backend.exists(&key) {
+                true => RespFrame::Integer(1),
+                false => RespFrame::Null(RespNull),
+            },
+            // 还没有淘汰策略，访问频率总是被记录
+            ObjectSubcommand::Freq => match backend.lfu_freq(&key) {
+                Some(freq) => RespFrame::Integer(freq as i64),
+                None => RespFrame::Null(RespNull),
+            },
+            ObjectSubcommand::Encoding => match backend.object_encoding(&key) {
+                Some(encoding) => BulkString::new(encoding).into(),
+                None => RespFrame::Null(RespNull),
+            },
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Object {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["object"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match extract_string(args.next())?.to_ascii_lowercase().as_str() {
+            "help" if args.len() == 0 => {
+                return Ok(Object {
+                    subcommand: ObjectSubcommand::Help,
+                    key: None,
+                })
+            }
+            "idletime" => ObjectSubcommand::IdleTime,
+            "refcount" => ObjectSubcommand::RefCount,
+            "freq" => ObjectSubcommand::Freq,
+            "encoding" => ObjectSubcommand::Encoding,
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try OBJECT HELP.",
+                    sub
+                )))
+            }
+        };
+        let key = match args.next() {
+            Some(key) => extract_string(Some(key))?,
+            None => return Err(CommandError::WrongArity("object".to_string())),
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument(
+                "wrong number of arguments for 'object' command".to_string(),
+            ));
+        }
+        Ok(Object {
+            subcommand,
+            key: Some(key),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use bytes::BytesMut;
+
+    use crate::RespDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_object_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nobject\r\n$8\r\nIDLETIME\r\n$3\r\nkey\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let object: Object = cmd.try_into()?;
+        assert_eq!(object.subcommand, ObjectSubcommand::IdleTime);
+        assert_eq!(object.key.as_deref(), Some("key"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_help() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\nobject\r\n$4\r\nhelp\r\n");
+        let object: Object = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(object.subcommand, ObjectSubcommand::Help);
+        assert_eq!(object.key, None);
+
+        let RespFrame::Array(lines) = object.execute(&Backend::new()) else {
+            panic!("expected array");
+        };
+        assert!(!lines.is_empty());
+        assert!(lines
+            .iter()
+            .all(|line| matches!(line, RespFrame::SimpleString(_))));
+
+        // HELP 不接受额外的参数
+        buf.extend_from_slice(b"*3\r\n$6\r\nobject\r\n$4\r\nhelp\r\n$3\r\nkey\r\n");
+        let ret: Result<Object, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_refcount_execute() {
+        let backend = Backend::new();
+        let object = Object {
+            subcommand: ObjectSubcommand::RefCount,
+            key: Some("key".to_string()),
+        };
+        assert_eq!(object.execute(&backend), RespFrame::Null(RespNull));
+
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let object = Object {
+            subcommand: ObjectSubcommand::RefCount,
+            key: Some("key".to_string()),
+        };
+        assert_eq!(object.execute(&backend), RespFrame::Integer(1));
+    }
+
+    #[test]
+    fn test_object_freq_execute() {
+        let backend = Backend::new();
+        let freq = || {
+            Object {
+                subcommand: ObjectSubcommand::Freq,
+                key: Some("key".to_string()),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(freq(), RespFrame::Null(RespNull));
+        backend.set("key".to_string(), BulkString::new("value").into());
+        assert_eq!(freq(), RespFrame::Integer(crate::LFU_INIT_VAL as i64));
+        // OBJECT FREQ 本身不算访问，计数只在读写 key 时增长
+        assert_eq!(freq(), RespFrame::Integer(crate::LFU_INIT_VAL as i64));
+        for _ in 0..10 {
+            backend.get("key");
+        }
+        assert!(matches!(freq(), RespFrame::Integer(n) if n > crate::LFU_INIT_VAL as i64));
+    }
+
+    #[test]
+    fn test_object_encoding_follows_listpack_size() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let encoding = |key: &str| {
+            Object {
+                subcommand: ObjectSubcommand::Encoding,
+                key: Some(key.to_string()),
+            }
+            .execute(&backend)
+        };
+        backend.set_config("list-max-listpack-size", "3")?;
+        let items = |n: usize| (0..n).map(|_| BulkString::new("x").into()).collect();
+        backend.rpush("list".to_string(), items(3));
+        assert_eq!(encoding("list"), BulkString::new("listpack").into());
+        backend.rpush("list".to_string(), items(1));
+        assert_eq!(encoding("list"), BulkString::new("quicklist").into());
+
+        // 负数按字节数计算，-1 为 4KB
+        backend.set_config("list-max-listpack-size", "-1")?;
+        assert_eq!(encoding("list"), BulkString::new("listpack").into());
+        backend.rpush(
+            "list".to_string(),
+            vec![BulkString::new("x".repeat(4096)).into()],
+        );
+        assert_eq!(encoding("list"), BulkString::new("quicklist").into());
+
+        assert_eq!(encoding("missing"), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_idletime_increases_after_read() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        backend.get("key");
+        let first = backend.idletime("key").unwrap();
+        thread::sleep(Duration::from_millis(20));
+        let second = backend.idletime("key").unwrap();
+        assert!(second > first);
+        assert!(second >= Duration::from_millis(20));
+
+        backend.get("key");
+        assert!(backend.idletime("key").unwrap() < second);
+
+        let object = Object {
+            subcommand: ObjectSubcommand::IdleTime,
+            key: Some("key".to_string()),
+        };
+        assert_eq!(object.execute(&backend), RespFrame::Integer(0));
+    }
+}
@@ -0,0 +1,499 @@
+use crate::{
+    cmd::{extract_args, extract_string, help_reply, validate_command, validate_command_at_least},
+    Backend, BulkString, RespArray, RespFrame, RespNull, RespPush,
+};
+
+use super::{
+    ClientState, CommandError, CommandExecutor, PSubscribe, PUnsubscribe, PubSub, PubSubSubcommand,
+    Publish, Subscribe, Unsubscribe,
+};
+
+const PUBSUB_HELP: &[&str] = &[
+    "CHANNELS [<pattern>]",
+    "    Return the currently active channels matching a <pattern> (default: '*').",
+    "NUMPAT",
+    "    Return number of subscriptions to patterns.",
+    "NUMSUB [<channel> ...]",
+    "    Return the number of subscribers for the specified channels, excluding",
+    "    pattern subscriptions(default: no channels).",
+];
+
+// 不带连接状态执行时，订阅记在一个临时的连接上，回复合并成一个数组
+impl CommandExecutor for Subscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespArray::new(self.execute_with_client(backend, &mut ClientState::default())).into()
+    }
+}
+
+impl CommandExecutor for Unsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespArray::new(self.execute_with_client(backend, &mut ClientState::default())).into()
+    }
+}
+
+impl CommandExecutor for PSubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespArray::new(self.execute_with_client(backend, &mut ClientState::default())).into()
+    }
+}
+
+impl CommandExecutor for PUnsubscribe {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespArray::new(self.execute_with_client(backend, &mut ClientState::default())).into()
+    }
+}
+
+impl CommandExecutor for Publish {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.publish(&self.channel, &self.message) as i64)
+    }
+}
+
+impl CommandExecutor for PubSub {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            PubSubSubcommand::Channels(pattern) => RespArray::new(
+                backend
+                    .pubsub_channels(pattern.as_deref())
+                    .into_iter()
+                    .map(|channel| BulkString::new(channel).into())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            // [channel, count, channel, count, ...]
+            PubSubSubcommand::NumSub(channels) => RespArray::new(
+                channels
+                    .into_iter()
+                    .flat_map(|channel| {
+                        let count = backend.pubsub_numsub(&channel) as i64;
+                        [BulkString::new(channel).into(), RespFrame::Integer(count)]
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            PubSubSubcommand::NumPat => RespFrame::Integer(backend.pubsub_numpat() as i64),
+            PubSubSubcommand::Help => help_reply("PUBSUB", PUBSUB_HELP),
+        }
+    }
+}
+
+impl Subscribe {
+    // 每个 channel 回复一帧 ["subscribe", channel, 当前订阅数]
+    pub fn execute_with_client(
+        self,
+        backend: &Backend,
+        client: &mut ClientState,
+    ) -> Vec<RespFrame> {
+        self.channels
+            .into_iter()
+            .map(|channel| {
+                backend.subscribe(client.id, &channel);
+                client.subscriptions.insert(channel.clone());
+                subscription_reply("subscribe", Some(channel), client)
+            })
+            .collect()
+    }
+}
+
+impl Unsubscribe {
+    // 每个 channel 回复一帧 ["unsubscribe", channel, 剩余订阅数]；
+    // 不带参数时取消所有订阅，没有任何订阅时回复 ["unsubscribe", nil, 0]
+    pub fn execute_with_client(
+        self,
+        backend: &Backend,
+        client: &mut ClientState,
+    ) -> Vec<RespFrame> {
+        let mut channels = self.channels;
+        if channels.is_empty() {
+            channels = client.subscriptions.iter().cloned().collect();
+            channels.sort();
+            if channels.is_empty() {
+                return vec![subscription_reply("unsubscribe", None, client)];
+            }
+        }
+        channels
+            .into_iter()
+            .map(|channel| {
+                backend.unsubscribe(client.id, &channel);
+                client.subscriptions.remove(&channel);
+                subscription_reply("unsubscribe", Some(channel), client)
+            })
+            .collect()
+    }
+}
+
+impl PSubscribe {
+    // 每个模式回复一帧 ["psubscribe", pattern, 当前订阅数]
+    pub fn execute_with_client(
+        self,
+        backend: &Backend,
+        client: &mut ClientState,
+    ) -> Vec<RespFrame> {
+        self.patterns
+            .into_iter()
+            .map(|pattern| {
+                backend.psubscribe(client.id, &pattern);
+                client.patterns.insert(pattern.clone());
+                subscription_reply("psubscribe", Some(pattern), client)
+            })
+            .collect()
+    }
+}
+
+impl PUnsubscribe {
+    // 和 UNSUBSCRIBE 相同，只作用于模式订阅
+    pub fn execute_with_client(
+        self,
+        backend: &Backend,
+        client: &mut ClientState,
+    ) -> Vec<RespFrame> {
+        let mut patterns = self.patterns;
+        if patterns.is_empty() {
+            patterns = client.patterns.iter().cloned().collect();
+            patterns.sort();
+            if patterns.is_empty() {
+                return vec![subscription_reply("punsubscribe", None, client)];
+            }
+        }
+        patterns
+            .into_iter()
+            .map(|pattern| {
+                backend.punsubscribe(client.id, &pattern);
+                client.patterns.remove(&pattern);
+                subscription_reply("punsubscribe", Some(pattern), client)
+            })
+            .collect()
+    }
+}
+
+// 订阅数是 channel 和模式订阅的总数，和 Redis 一致
+fn subscription_reply(kind: &str, channel: Option<String>, client: &ClientState) -> RespFrame {
+    let channel = match channel {
+        Some(channel) => BulkString::new(channel).into(),
+        None => RespFrame::Null(RespNull),
+    };
+    RespPush::new(vec![
+        BulkString::new(kind).into(),
+        channel,
+        RespFrame::Integer((client.subscriptions.len() + client.patterns.len()) as i64),
+    ])
+    .into()
+}
+
+// SUBSCRIBE channel [channel ...]
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["subscribe"], 1)?;
+        let channels = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(Subscribe { channels })
+    }
+}
+
+// UNSUBSCRIBE [channel ...]
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["unsubscribe"], 0)?;
+        let channels = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(Unsubscribe { channels })
+    }
+}
+
+// PSUBSCRIBE pattern [pattern ...]
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["psubscribe"], 1)?;
+        let patterns = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(PSubscribe { patterns })
+    }
+}
+
+// PUNSUBSCRIBE [pattern ...]
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["punsubscribe"], 0)?;
+        let patterns = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(PUnsubscribe { patterns })
+    }
+}
+
+// PUBSUB CHANNELS [pattern] | PUBSUB NUMSUB [channel ...] | PUBSUB NUMPAT
+impl TryFrom<RespArray> for PubSub {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["pubsub"], 1)?;
+        let mut args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        let sub = args.next().unwrap_or_default().to_ascii_lowercase();
+        let rest: Vec<String> = args.collect();
+        let subcommand = match (sub.as_str(), rest.len()) {
+            ("channels", 0) => PubSubSubcommand::Channels(None),
+            ("channels", 1) => PubSubSubcommand::Channels(rest.into_iter().next()),
+            ("numsub", _) => PubSubSubcommand::NumSub(rest),
+            ("numpat", 0) => PubSubSubcommand::NumPat,
+            ("help", 0) => PubSubSubcommand::Help,
+            (sub, _) => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand or wrong number of arguments for '{}'. Try PUBSUB HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(PubSub { subcommand })
+    }
+}
+
+// PUBLISH channel message
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["publish"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Publish {
+            channel: extract_string(args.next())?,
+            message: extract_string(args.next())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmd::{Command, Response};
+
+    use super::*;
+
+    async fn run(
+        backend: &Backend,
+        client: &mut ClientState,
+        args: &[&str],
+    ) -> anyhow::Result<Response> {
+        let frames: Vec<RespFrame> = args.iter().map(|a| BulkString::new(*a).into()).collect();
+        Ok(Command::try_from(RespArray::new(frames))?
+            .dispatch(backend, client)
+            .await)
+    }
+
+    fn reply(kind: &str, channel: Option<&str>, count: i64) -> RespFrame {
+        let channel = match channel {
+            Some(channel) => BulkString::new(channel).into(),
+            None => RespFrame::Null(RespNull),
+        };
+        RespPush::new(vec![
+            BulkString::new(kind).into(),
+            channel,
+            RespFrame::Integer(count),
+        ])
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_unsubscribe_replies() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let client = &mut ClientState::default();
+        assert_eq!(
+            run(&backend, client, &["subscribe", "a", "b"]).await?,
+            Response::Replies(vec![
+                reply("subscribe", Some("a"), 1),
+                reply("subscribe", Some("b"), 2),
+            ])
+        );
+        // 重复订阅不增加计数
+        assert_eq!(
+            run(&backend, client, &["subscribe", "a"]).await?,
+            Response::Replies(vec![reply("subscribe", Some("a"), 2)])
+        );
+        assert_eq!(
+            run(&backend, client, &["unsubscribe", "b"]).await?,
+            Response::Replies(vec![reply("unsubscribe", Some("b"), 1)])
+        );
+        assert_eq!(
+            run(&backend, client, &["unsubscribe"]).await?,
+            Response::Replies(vec![reply("unsubscribe", Some("a"), 0)])
+        );
+        assert_eq!(
+            run(&backend, client, &["unsubscribe"]).await?,
+            Response::Replies(vec![reply("unsubscribe", None, 0)])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribed_connection_rejects_other_commands() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let client = &mut ClientState::default();
+        run(&backend, client, &["subscribe", "a"]).await?;
+        assert_eq!(
+            run(&backend, client, &["get", "k"]).await?,
+            Response::Reply(RespFrame::error(
+                "ERR Can't execute 'get': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context"
+            ))
+        );
+        // RESP2 下订阅状态的 PING 回复 ["pong", message]
+        assert_eq!(
+            run(&backend, client, &["ping"]).await?,
+            Response::Reply(
+                RespArray::new(vec![
+                    BulkString::new("pong").into(),
+                    BulkString::new("").into(),
+                ])
+                .into()
+            )
+        );
+        run(&backend, client, &["unsubscribe"]).await?;
+        assert_eq!(
+            run(&backend, client, &["get", "k"]).await?,
+            Response::Reply(RespFrame::Null(RespNull))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pattern_and_channel_subscriptions() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let client = &mut ClientState::default();
+        let (_token, mut rx) = backend
+            .register_client(client.id, None)
+            .expect("client slot");
+        run(&backend, client, &["subscribe", "news.tech"]).await?;
+        // 模式和 channel 分别记录，订阅数是两者之和
+        assert_eq!(
+            run(&backend, client, &["psubscribe", "news.*"]).await?,
+            Response::Replies(vec![reply("psubscribe", Some("news.*"), 2)])
+        );
+
+        // 同时匹配 channel 和模式，一次发布收到两条消息
+        let publish = Publish {
+            channel: "news.tech".to_string(),
+            message: "hi".to_string(),
+        };
+        assert_eq!(publish.execute(&backend), RespFrame::Integer(2));
+        let messages: Vec<RespFrame> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                RespPush::new(vec![
+                    BulkString::new("message").into(),
+                    BulkString::new("news.tech").into(),
+                    BulkString::new("hi").into(),
+                ])
+                .into(),
+                RespPush::new(vec![
+                    BulkString::new("pmessage").into(),
+                    BulkString::new("news.*").into(),
+                    BulkString::new("news.tech").into(),
+                    BulkString::new("hi").into(),
+                ])
+                .into(),
+            ]
+        );
+
+        // UNSUBSCRIBE 不影响模式订阅，连接仍处于订阅状态
+        run(&backend, client, &["unsubscribe"]).await?;
+        assert!(client.is_subscribed());
+        assert_eq!(
+            run(&backend, client, &["punsubscribe"]).await?,
+            Response::Replies(vec![reply("punsubscribe", Some("news.*"), 0)])
+        );
+        assert_eq!(
+            run(&backend, client, &["punsubscribe"]).await?,
+            Response::Replies(vec![reply("punsubscribe", None, 0)])
+        );
+        assert!(!client.is_subscribed());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_introspection() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let alice = &mut ClientState::default();
+        let bob = &mut ClientState::default();
+        run(&backend, alice, &["subscribe", "news.tech", "weather"]).await?;
+        run(&backend, bob, &["subscribe", "news.tech"]).await?;
+        run(&backend, bob, &["psubscribe", "news.*", "sports.*"]).await?;
+        run(&backend, alice, &["psubscribe", "news.*"]).await?;
+
+        let bulks = |items: &[&str]| -> RespFrame {
+            RespArray::new(
+                items
+                    .iter()
+                    .map(|item| BulkString::new(*item).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+        let pubsub = |args: &[&str]| -> anyhow::Result<RespFrame> {
+            let frames: Vec<RespFrame> = ["pubsub"]
+                .iter()
+                .chain(args)
+                .map(|arg| BulkString::new(*arg).into())
+                .collect();
+            Ok(PubSub::try_from(RespArray::new(frames))?.execute(&backend))
+        };
+        assert_eq!(pubsub(&["channels"])?, bulks(&["news.tech", "weather"]));
+        assert_eq!(pubsub(&["channels", "w*"])?, bulks(&["weather"]));
+        assert_eq!(
+            pubsub(&["numsub", "news.tech", "nosuch"])?,
+            RespArray::new(vec![
+                BulkString::new("news.tech").into(),
+                RespFrame::Integer(2),
+                BulkString::new("nosuch").into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+        assert_eq!(pubsub(&["numpat"])?, RespFrame::Integer(2));
+        assert!(pubsub(&["numpat", "x"]).is_err());
+
+        run(&backend, alice, &["unsubscribe", "weather"]).await?;
+        run(&backend, bob, &["punsubscribe", "sports.*"]).await?;
+        assert_eq!(pubsub(&["channels"])?, bulks(&["news.tech"]));
+        assert_eq!(pubsub(&["numpat"])?, RespFrame::Integer(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_publish_counts_receivers() {
+        let backend = Backend::new();
+        let publish = |channel: &str| {
+            Publish {
+                channel: channel.to_string(),
+                message: "hi".to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(publish("news"), RespFrame::Integer(0));
+        // 订阅者必须是已注册的连接才能收到消息
+        let (_token, mut rx) = backend.register_client(1, None).expect("client slot");
+        backend.subscribe(1, "news");
+        assert_eq!(publish("news"), RespFrame::Integer(1));
+        assert_eq!(
+            rx.try_recv().ok(),
+            Some(
+                RespPush::new(vec![
+                    BulkString::new("message").into(),
+                    BulkString::new("news").into(),
+                    BulkString::new("hi").into(),
+                ])
+                .into()
+            )
+        );
+    }
+}
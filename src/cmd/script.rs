@@ -0,0 +1,277 @@
+use crate::{
+    cmd::{extract_args, extract_integer, extract_string, help_reply, validate_command_at_least},
+    script, Backend, BulkString, RespArray, RespFrame, ScriptKill, DEFAULT_USER,
+};
+
+use super::{
+    ClientState, CommandError, CommandExecutor, Eval, EvalSha, Script, ScriptSubcommand, BUSY_ERROR,
+};
+
+const SCRIPT_HELP: &[&str] = &[
+    "EXISTS <sha1> [<sha1> ...]",
+    "    Return information about the existence of the scripts in the script cache.",
+    "FLUSH [ASYNC|SYNC]",
+    "    Flush the Lua scripts cache.",
+    "KILL",
+    "    Kill the currently executing Lua script.",
+    "LOAD <script>",
+    "    Load a script into the scripts cache without executing it.",
+];
+
+// 不带连接状态执行时按 default 用户的权限执行
+impl CommandExecutor for Eval {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.eval_as(backend, DEFAULT_USER)
+    }
+}
+
+impl CommandExecutor for EvalSha {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.eval_as(backend, DEFAULT_USER)
+    }
+}
+
+impl Eval {
+    // 脚本中的 redis.call 按连接当前用户的权限检查
+    pub async fn execute_with_client(self, backend: &Backend, client: &ClientState) -> RespFrame {
+        let user = client.user.clone();
+        backend
+            .run_script(move |backend| self.eval_as(backend, &user))
+            .await
+            .unwrap_or_else(|| RespFrame::error(BUSY_ERROR))
+    }
+
+    fn eval_as(self, backend: &Backend, user: &str) -> RespFrame {
+        // 和 Redis 一样，执行过的脚本可以直接用 EVALSHA 调用
+        backend.script_load(&self.script);
+        script::eval(backend, user, &self.script, self.keys, self.args)
+    }
+}
+
+impl EvalSha {
+    pub async fn execute_with_client(self, backend: &Backend, client: &ClientState) -> RespFrame {
+        let user = client.user.clone();
+        backend
+            .run_script(move |backend| self.eval_as(backend, &user))
+            .await
+            .unwrap_or_else(|| RespFrame::error(BUSY_ERROR))
+    }
+
+    fn eval_as(self, backend: &Backend, user: &str) -> RespFrame {
+        match backend.script_get(&self.sha) {
+            Some(src) => script::eval(backend, user, &src, self.keys, self.args),
+            None => RespFrame::error("NOSCRIPT No matching script. Please use EVAL."),
+        }
+    }
+}
+
+impl CommandExecutor for Script {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            ScriptSubcommand::Load(src) => BulkString::new(backend.script_load(&src)).into(),
+            ScriptSubcommand::Exists(shas) => RespArray::new(
+                shas.iter()
+                    .map(|sha| RespFrame::Integer(backend.script_exists(sha) as i64))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            ScriptSubcommand::Flush => {
+                backend.script_flush();
+                RespFrame::ok()
+            }
+            ScriptSubcommand::Kill => match backend.script_kill() {
+                ScriptKill::Killed => RespFrame::ok(),
+                ScriptKill::NotBusy => RespFrame::error("NOTBUSY No scripts in execution right now."),
+                ScriptKill::Unkillable => RespFrame::error(
+                    "UNKILLABLE Sorry the script already executed write commands against the dataset. You can either wait the script termination or kill the server in a hard way using the SHUTDOWN NOSAVE command.",
+                ),
+            },
+            ScriptSubcommand::Help => help_reply("SCRIPT", SCRIPT_HELP),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Eval {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["eval"], 2)?;
+        let (script, keys, args) = parse_script_args(value)?;
+        Ok(Eval { script, keys, args })
+    }
+}
+
+impl TryFrom<RespArray> for EvalSha {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["evalsha"], 2)?;
+        let (sha, keys, args) = parse_script_args(value)?;
+        Ok(EvalSha { sha, keys, args })
+    }
+}
+
+// script numkeys key [key ...] arg [arg ...]
+fn parse_script_args(value: RespArray) -> Result<(String, Vec<String>, Vec<String>), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let script = extract_string(args.next())?;
+    let numkeys = extract_integer(args.next())?;
+    if numkeys < 0 {
+        return Err(CommandError::InvalidArgument(
+            "Number of keys can't be negative".to_string(),
+        ));
+    }
+    if numkeys as usize > args.len() {
+        return Err(CommandError::InvalidArgument(
+            "Number of keys can't be greater than number of args".to_string(),
+        ));
+    }
+    let keys = args
+        .by_ref()
+        .take(numkeys as usize)
+        .map(|frame| extract_string(Some(frame)))
+        .collect::<Result<_, _>>()?;
+    let args = args
+        .map(|frame| extract_string(Some(frame)))
+        .collect::<Result<_, _>>()?;
+    Ok((script, keys, args))
+}
+
+impl TryFrom<RespArray> for Script {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["script"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match extract_string(args.next())?.to_ascii_lowercase().as_str() {
+            "load" if args.len() == 1 => ScriptSubcommand::Load(extract_string(args.next())?),
+            "exists" if args.len() > 0 => ScriptSubcommand::Exists(
+                args.map(|frame| extract_string(Some(frame)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            // ASYNC 和 SYNC 效果相同
+            "flush" if args.len() <= 1 => {
+                if let Some(mode) = args.next() {
+                    let mode = extract_string(Some(mode))?;
+                    if !mode.eq_ignore_ascii_case("async") && !mode.eq_ignore_ascii_case("sync") {
+                        return Err(CommandError::InvalidArgument(format!(
+                            "SCRIPT FLUSH only support SYNC|ASYNC option, got {}",
+                            mode
+                        )));
+                    }
+                }
+                ScriptSubcommand::Flush
+            }
+            "kill" if args.len() == 0 => ScriptSubcommand::Kill,
+            "help" if args.len() == 0 => ScriptSubcommand::Help,
+            sub @ ("load" | "exists" | "flush" | "kill" | "help") => {
+                return Err(CommandError::WrongArity(format!("script|{}", sub)))
+            }
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try SCRIPT HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(Script { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cmd::{ClientState, Command, Response},
+        RespNull,
+    };
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CommandError> {
+        let frames: Vec<RespFrame> = args.iter().map(|a| BulkString::new(*a).into()).collect();
+        Command::try_from(RespArray::new(frames))
+    }
+
+    #[test]
+    fn test_eval_try_from_resp_array() -> anyhow::Result<()> {
+        let Command::Eval(eval) = parse(&["EVAL", "return 1", "2", "a", "b", "x"])? else {
+            panic!("expected EVAL");
+        };
+        assert_eq!(eval.keys, vec!["a", "b"]);
+        assert_eq!(eval.args, vec!["x"]);
+        assert!(parse(&["eval", "return 1", "3", "a"]).is_err());
+        assert!(parse(&["eval", "return 1", "-1"]).is_err());
+
+        let cmd = parse(&["eval", "return 1", "1", "k"])?;
+        assert_eq!(cmd.keys(), vec!["k"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_script_load_and_evalsha() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let sha = "e0e1f9fabfc9d4800c877a703b823ac0578ff8db";
+        let evalsha = |backend: &Backend| -> anyhow::Result<RespFrame> {
+            Ok(parse(&["evalsha", sha, "0"])?.execute(backend))
+        };
+        assert_eq!(
+            evalsha(&backend)?,
+            RespFrame::error("NOSCRIPT No matching script. Please use EVAL.")
+        );
+
+        let loaded = parse(&["script", "load", "return 1"])?.execute(&backend);
+        assert_eq!(loaded, BulkString::new(sha).into());
+        assert_eq!(evalsha(&backend)?, RespFrame::Integer(1));
+        // SHA1 不区分大小写
+        assert_eq!(
+            parse(&["script", "exists", &sha.to_uppercase(), "ffff"])?.execute(&backend),
+            RespArray::new(vec![RespFrame::Integer(1), RespFrame::Integer(0)]).into()
+        );
+
+        assert_eq!(
+            parse(&["script", "flush"])?.execute(&backend),
+            RespFrame::ok()
+        );
+        assert!(matches!(evalsha(&backend)?, RespFrame::Error(_)));
+        // EVAL 执行过的脚本也会被缓存
+        parse(&["eval", "return 1", "0"])?.execute(&backend);
+        assert_eq!(evalsha(&backend)?, RespFrame::Integer(1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_busy_script_and_script_kill() -> anyhow::Result<()> {
+        let backend = Backend::with_config(crate::ServerConfig {
+            busy_reply_threshold: 50,
+            ..Default::default()
+        });
+        let dispatch = |backend: &Backend, args: &[&str]| {
+            let (backend, cmd) = (backend.clone(), parse(args));
+            async move {
+                match cmd?.dispatch(&backend, &mut ClientState::default()).await {
+                    Response::Reply(frame) => Ok::<_, anyhow::Error>(frame),
+                    other => anyhow::bail!("unexpected response {:?}", other),
+                }
+            }
+        };
+        assert_eq!(
+            dispatch(&backend, &["script", "kill"]).await?,
+            RespFrame::error("NOTBUSY No scripts in execution right now.")
+        );
+
+        let script = tokio::spawn(dispatch(&backend, &["eval", "while true do end", "0"]));
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        // 脚本执行超过 busy-reply-threshold 之后，其它命令不再等待
+        assert_eq!(
+            dispatch(&backend, &["get", "k"]).await?,
+            RespFrame::error(BUSY_ERROR)
+        );
+        assert_eq!(
+            dispatch(&backend, &["script", "kill"]).await?,
+            RespFrame::ok()
+        );
+        assert_eq!(
+            script.await??,
+            RespFrame::error("ERR Script killed by user with SCRIPT KILL...")
+        );
+        assert_eq!(dispatch(&backend, &["get", "k"]).await?, RespNull.into());
+        Ok(())
+    }
+}
@@ -0,0 +1,1254 @@
+use std::time::Duration;
+
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use crate::{
+    cmd::{
+        extract_args, extract_integer, extract_string, help_reply, validate_command,
+        validate_command_at_least,
+    },
+    Backend, BulkString, RespArray, RespEncoder, RespFrame, RespMap, RespOrderedMap, SimpleString,
+    SlowLogEntry,
+};
+
+use super::{
+    lookup_command, Asking, BgSave, Cluster, ClusterSubcommand, Command, CommandError,
+    CommandExecutor, CommandMeta, CommandMetaSubcommand, CommandSpec, Debug, DebugSubcommand, Info,
+    LastSave, ReadOnly, ReadWrite, Save, Slowlog, SlowlogSubcommand, Sync, Wait, COMMAND_TABLE,
+};
+
+// INFO 不带参数（或 default / all / everything）时输出的 section
+const INFO_SECTIONS: &[&str] = &["server", "clients", "memory", "stats", "keyspace"];
+
+// SLOWLOG GET 默认返回的记录数
+const SLOWLOG_DEFAULT_GET_COUNT: usize = 128;
+
+// 单机模式下 CLUSTER INFO 的输出，字段和 Redis 7 一致
+const CLUSTER_INFO: &str = "cluster_enabled:0\r\n\
+cluster_state:ok\r\n\
+cluster_slots_assigned:0\r\n\
+cluster_slots_ok:0\r\n\
+cluster_slots_pfail:0\r\n\
+cluster_slots_fail:0\r\n\
+cluster_known_nodes:1\r\n\
+cluster_size:0\r\n\
+cluster_current_epoch:0\r\n\
+cluster_my_epoch:0\r\n\
+cluster_stats_messages_sent:0\r\n\
+cluster_stats_messages_received:0\r\n\
+total_cluster_links_buffer_limit_exceeded:0\r\n";
+
+// 固定的节点 id，40 个 0
+const CLUSTER_MYID: &str = "0000000000000000000000000000000000000000";
+
+const CLUSTER_HELP: &[&str] = &[
+    "INFO",
+    "    Return information about the cluster.",
+    "MYID",
+    "    Return the node id.",
+];
+
+const DEBUG_HELP: &[&str] = &[
+    "OBJECT <key>",
+    "    Show low level info about the <key> and associated value.",
+    "RELOAD",
+    "    Save the RDB on disk and reload it back to memory.",
+    "SET-ACTIVE-EXPIRE <0|1>",
+    "    Setting it to 0 disables expiring keys in background when they are not",
+    "    accessed (otherwise the Redis behavior). Setting it to 1 reenables back the",
+    "    default.",
+    "SLEEP <seconds>",
+    "    Stop the server for <seconds>. Decimals allowed.",
+];
+
+// 会阻塞服务器或改变服务器行为的 DEBUG 子命令，只在 debug 构建、测试或开启 debug-commands feature 时可用
+const RESTRICTED_DEBUG_SUBCOMMANDS: &[&str] = &["sleep", "reload", "set-active-expire"];
+const DEBUG_COMMANDS_ENABLED: bool = cfg!(any(debug_assertions, test, feature = "debug-commands"));
+
+const SLOWLOG_HELP: &[&str] = &[
+    "GET [<count>]",
+    "    Return top <count> entries from the slowlog (default: 128, -1 mean all).",
+    "    Entries are made of:",
+    "    id, timestamp, time in microseconds, arguments array, client IP and port,",
+    "    client name",
+    "LEN",
+    "    Return the length of the slowlog.",
+    "RESET",
+    "    Reset the slowlog.",
+];
+
+const COMMAND_HELP: &[&str] = &[
+    "DOCS [<command-name> ...]",
+    "    Return documentation details about multiple Redis commands.",
+    "    If no command names are given, documentation details for all",
+    "    commands are returned.",
+    "GETKEYS <full-command>",
+    "    Return the keys from a full Redis command.",
+];
+
+// WAIT 最多等待的时间
+const WAIT_MAX_TIMEOUT: Duration = Duration::from_secs(5);
+
+// DEBUG SLEEP 的秒数超出 Duration 的范围
+const SLEEP_OUT_OF_RANGE: &str = "ERR timeout is out of range";
+
+impl CommandExecutor for Debug {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            // 同步执行时会阻塞当前线程，连接上应通过 execute_async 执行；
+            // 在多线程 runtime 的 worker 上执行时用 block_in_place 把其它任务交给别的线程
+            DebugSubcommand::Sleep(secs) => {
+                let Ok(duration) = Duration::try_from_secs_f64(secs) else {
+                    return RespFrame::error(SLEEP_OUT_OF_RANGE);
+                };
+                let sleep = || std::thread::sleep(duration);
+                match Handle::try_current() {
+                    Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                        tokio::task::block_in_place(sleep)
+                    }
+                    _ => sleep(),
+                }
+                RespFrame::ok()
+            }
+            DebugSubcommand::SetActiveExpire(enabled) => {
+                backend.set_active_expire(enabled);
+                RespFrame::ok()
+            }
+            DebugSubcommand::Object(key) => debug_object(backend, &key),
+            DebugSubcommand::Reload => match backend.reload() {
+                Ok(()) => RespFrame::ok(),
+                Err(e) => RespFrame::error(format!("ERR {}", e)),
+            },
+            DebugSubcommand::Help => help_reply("DEBUG", DEBUG_HELP),
+        }
+    }
+}
+
+// serializedlength 是值在快照中按 RESP 编码后的字节数
+fn debug_object(backend: &Backend, key: &str) -> RespFrame {
+    let (Some((_, value)), Some(encoding)) =
+        (backend.snapshot_value(key), backend.object_encoding(key))
+    else {
+        return RespFrame::error("ERR no such key");
+    };
+    let idle = backend.idletime(key).unwrap_or_default();
+    SimpleString::new(format!(
+        "Value at:0x0 refcount:1 encoding:{} serializedlength:{} lru_seconds_idle:{}",
+        encoding,
+        value.encode().len(),
+        idle.as_secs()
+    ))
+    .into()
+}
+
+impl Debug {
+    // SLEEP 只挂起当前连接，不阻塞整个 runtime；RELOAD 等待其它命令结束后独占执行
+    pub async fn execute_async(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            DebugSubcommand::Sleep(secs) => {
+                let Ok(duration) = Duration::try_from_secs_f64(secs) else {
+                    return RespFrame::error(SLEEP_OUT_OF_RANGE);
+                };
+                tokio::time::sleep(duration).await;
+                RespFrame::ok()
+            }
+            // 独占执行，保存和加载之间其它命令不能修改数据；文件 I/O 在 blocking 线程上进行
+            DebugSubcommand::Reload => {
+                let _guard = backend.script_guard().await;
+                let backend = backend.clone();
+                tokio::task::spawn_blocking(move || self.execute(&backend))
+                    .await
+                    .unwrap_or_else(|e| RespFrame::error(format!("ERR {}", e)))
+            }
+            _ => self.execute(backend),
+        }
+    }
+}
+
+fn check_debug_subcommand(subcommand: &str, enabled: bool) -> Result<(), CommandError> {
+    if enabled || !RESTRICTED_DEBUG_SUBCOMMANDS.contains(&subcommand) {
+        return Ok(());
+    }
+    Err(CommandError::InvalidArgument(format!(
+        "DEBUG {} is disabled in this build. Rebuild with the debug-commands feature to enable it.",
+        subcommand.to_ascii_uppercase()
+    )))
+}
+
+impl TryFrom<RespArray> for Debug {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["debug"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = extract_string(args.next())?.to_ascii_lowercase();
+        check_debug_subcommand(&subcommand, DEBUG_COMMANDS_ENABLED)?;
+        match subcommand.as_str() {
+            "help" if args.len() == 0 => {
+                return Ok(Debug {
+                    subcommand: DebugSubcommand::Help,
+                })
+            }
+            "reload" if args.len() == 0 => {
+                return Ok(Debug {
+                    subcommand: DebugSubcommand::Reload,
+                })
+            }
+            _ => {}
+        }
+        let arg = match (args.next(), args.next()) {
+            (Some(arg), None) => extract_string(Some(arg))?,
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "Unknown subcommand or wrong number of arguments for '{}'. Try DEBUG HELP.",
+                    subcommand
+                )))
+            }
+        };
+        let subcommand = match subcommand.as_str() {
+            "sleep" => match arg.parse::<f64>() {
+                Ok(secs) if secs.is_finite() && secs >= 0.0 => DebugSubcommand::Sleep(secs),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "value is not a valid float".to_string(),
+                    ))
+                }
+            },
+            "object" => DebugSubcommand::Object(arg),
+            "set-active-expire" => match arg.as_str() {
+                "0" => DebugSubcommand::SetActiveExpire(false),
+                "1" => DebugSubcommand::SetActiveExpire(true),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "value is not an integer or out of range".to_string(),
+                    ))
+                }
+            },
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "Unknown subcommand or wrong number of arguments for '{}'. Try DEBUG HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(Debug { subcommand })
+    }
+}
+
+// 没有副本，确认的副本数总是 0
+impl CommandExecutor for Wait {
+    fn execute(self, _backend: &Backend) -> RespFrame {
+        RespFrame::Integer(0)
+    }
+}
+
+impl Wait {
+    // 要求有副本确认时等到超时再返回，避免客户端不停重试
+    pub async fn execute_async(self, backend: &Backend) -> RespFrame {
+        if self.numreplicas > 0 && self.timeout_ms > 0 {
+            let timeout = Duration::from_millis(self.timeout_ms).min(WAIT_MAX_TIMEOUT);
+            tokio::time::sleep(timeout).await;
+        }
+        self.execute(backend)
+    }
+}
+
+impl CommandExecutor for ReadOnly {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespFrame::ok()
+    }
+}
+
+impl CommandExecutor for ReadWrite {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespFrame::ok()
+    }
+}
+
+impl CommandExecutor for Asking {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespFrame::ok()
+    }
+}
+
+impl CommandExecutor for Save {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.save() {
+            Ok(()) => RespFrame::ok(),
+            Err(e) => RespFrame::error(format!("ERR {}", e)),
+        }
+    }
+}
+
+impl Save {
+    // 序列化和写文件都在 blocking 线程上进行，不占用 runtime 的 worker
+    pub async fn execute_async(self, backend: &Backend) -> RespFrame {
+        let backend = backend.clone();
+        tokio::task::spawn_blocking(move || self.execute(&backend))
+            .await
+            .unwrap_or_else(|e| RespFrame::error(format!("ERR {}", e)))
+    }
+}
+
+// 不等待后台线程结束，保存结果通过 LASTSAVE 查看
+impl CommandExecutor for BgSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.bgsave() {
+            Ok(_) => SimpleString::new("Background saving started").into(),
+            Err(e) => RespFrame::error(format!("ERR {}", e)),
+        }
+    }
+}
+
+// 副本用来拉取全量数据：回复快照文件的完整内容
+impl CommandExecutor for Sync {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        BulkString::new(backend.snapshot_bytes()).into()
+    }
+}
+
+impl CommandExecutor for LastSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::integer(backend.lastsave() as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Save {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["save"], 0)?;
+        Ok(Save)
+    }
+}
+
+impl TryFrom<RespArray> for BgSave {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["bgsave"], 0)?;
+        Ok(BgSave)
+    }
+}
+
+impl TryFrom<RespArray> for Sync {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["sync"], 0)?;
+        Ok(Sync)
+    }
+}
+
+impl TryFrom<RespArray> for LastSave {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lastsave"], 0)?;
+        Ok(LastSave)
+    }
+}
+
+impl TryFrom<RespArray> for ReadOnly {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["readonly"], 0)?;
+        Ok(ReadOnly)
+    }
+}
+
+impl TryFrom<RespArray> for ReadWrite {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["readwrite"], 0)?;
+        Ok(ReadWrite)
+    }
+}
+
+impl TryFrom<RespArray> for Asking {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["asking"], 0)?;
+        Ok(Asking)
+    }
+}
+
+// 不支持集群，只返回单机模式下的固定结果，让集群客户端连接时能正常识别拓扑
+impl CommandExecutor for Cluster {
+    fn execute(self, _: &Backend) -> RespFrame {
+        match self.subcommand {
+            ClusterSubcommand::Info => BulkString::new(CLUSTER_INFO).into(),
+            ClusterSubcommand::MyId => BulkString::new(CLUSTER_MYID).into(),
+            ClusterSubcommand::Help => help_reply("CLUSTER", CLUSTER_HELP),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Cluster {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["cluster"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match extract_string(args.next())?.to_ascii_lowercase().as_str() {
+            "info" => ClusterSubcommand::Info,
+            "myid" => ClusterSubcommand::MyId,
+            "help" => ClusterSubcommand::Help,
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand or wrong number of arguments for '{}'. Try CLUSTER HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(Cluster { subcommand })
+    }
+}
+
+// WAIT numreplicas timeout
+impl TryFrom<RespArray> for Wait {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["wait"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let numreplicas = extract_integer(args.next())?;
+        let timeout_ms = extract_integer(args.next())?;
+        if timeout_ms < 0 {
+            return Err(CommandError::InvalidArgument(
+                "timeout is negative".to_string(),
+            ));
+        }
+        Ok(Wait {
+            numreplicas,
+            timeout_ms: timeout_ms as u64,
+        })
+    }
+}
+
+impl CommandExecutor for Info {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let all = self.sections.is_empty()
+            || self
+                .sections
+                .iter()
+                .any(|s| matches!(s.as_str(), "default" | "all" | "everything"));
+        let sections: Vec<&str> = if all {
+            INFO_SECTIONS.to_vec()
+        } else {
+            INFO_SECTIONS
+                .iter()
+                .copied()
+                .filter(|name| self.sections.iter().any(|s| s == name))
+                .collect()
+        };
+        let text: Vec<String> = sections
+            .into_iter()
+            .map(|name| info_section(backend, name))
+            .collect();
+        BulkString::new(text.join("\r\n")).into()
+    }
+}
+
+// 一个 section：标题行加上若干 field:value 行
+fn info_section(backend: &Backend, name: &str) -> String {
+    let stats = backend.stats();
+    let (title, fields): (&str, Vec<(String, String)>) = match name {
+        "server" => (
+            "Server",
+            vec![
+                ("redis_version".into(), env!("CARGO_PKG_VERSION").into()),
+                ("redis_mode".into(), "standalone".into()),
+                ("process_id".into(), std::process::id().to_string()),
+                ("tcp_port".into(), backend.config().port.to_string()),
+                (
+                    "uptime_in_seconds".into(),
+                    stats.uptime().as_secs().to_string(),
+                ),
+                (
+                    "uptime_in_days".into(),
+                    (stats.uptime().as_secs() / 86400).to_string(),
+                ),
+            ],
+        ),
+        "clients" => (
+            "Clients",
+            vec![(
+                "connected_clients".into(),
+                backend.client_count().to_string(),
+            )],
+        ),
+        "memory" => (
+            "Memory",
+            vec![
+                ("used_memory".into(), backend.used_memory().to_string()),
+                ("maxmemory".into(), backend.config().maxmemory.to_string()),
+                (
+                    "maxmemory_policy".into(),
+                    backend.config().maxmemory_policy.to_string(),
+                ),
+            ],
+        ),
+        "stats" => (
+            "Stats",
+            vec![
+                (
+                    "total_connections_received".into(),
+                    stats.total_connections_received().to_string(),
+                ),
+                (
+                    "total_commands_processed".into(),
+                    stats.total_commands_processed().to_string(),
+                ),
+                ("keyspace_hits".into(), stats.keyspace_hits().to_string()),
+                (
+                    "keyspace_misses".into(),
+                    stats.keyspace_misses().to_string(),
+                ),
+                ("evicted_keys".into(), stats.evicted_keys().to_string()),
+            ],
+        ),
+        // 和 Redis 一样，没有 key 的数据库不输出
+        _ => {
+            let keys = backend.dbsize();
+            let fields = if keys > 0 {
+                let value = format!(
+                    "keys={},expires={},avg_ttl=0",
+                    keys,
+                    backend.expires_count()
+                );
+                vec![("db0".into(), value)]
+            } else {
+                vec![]
+            };
+            ("Keyspace", fields)
+        }
+    };
+    let mut ret = format!("# {}\r\n", title);
+    for (field, value) in fields {
+        ret.push_str(&format!("{}:{}\r\n", field, value));
+    }
+    ret
+}
+
+impl CommandExecutor for Slowlog {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            SlowlogSubcommand::Get(count) => {
+                let entries: Vec<RespFrame> = backend
+                    .slowlog_get(count)
+                    .into_iter()
+                    .map(slowlog_entry_to_frame)
+                    .collect();
+                RespArray::new(entries).into()
+            }
+            SlowlogSubcommand::Len => RespFrame::Integer(backend.slowlog_len() as i64),
+            SlowlogSubcommand::Reset => {
+                backend.slowlog_reset();
+                RespFrame::ok()
+            }
+            SlowlogSubcommand::Help => help_reply("SLOWLOG", SLOWLOG_HELP),
+        }
+    }
+}
+
+// [id, timestamp, duration, argv, client_addr, client_name]
+fn slowlog_entry_to_frame(entry: SlowLogEntry) -> RespFrame {
+    let argv: Vec<RespFrame> = entry
+        .argv
+        .into_iter()
+        .map(|arg| BulkString::new(arg).into())
+        .collect();
+    RespArray::new(vec![
+        RespFrame::Integer(entry.id as i64),
+        RespFrame::Integer(entry.timestamp as i64),
+        RespFrame::Integer(entry.duration_us as i64),
+        RespArray::new(argv).into(),
+        BulkString::new(entry.client_addr).into(),
+        BulkString::new(entry.client_name).into(),
+    ])
+    .into()
+}
+
+// INFO [section [section ...]]
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["info"], 0)?;
+        let sections = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)).map(|s| s.to_ascii_lowercase()))
+            .collect::<Result<_, _>>()?;
+        Ok(Info { sections })
+    }
+}
+
+impl TryFrom<RespArray> for Slowlog {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["slowlog"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = extract_string(args.next())?.to_ascii_lowercase();
+        let subcommand = match (subcommand.as_str(), args.next(), args.next()) {
+            ("get", None, None) => SlowlogSubcommand::Get(Some(SLOWLOG_DEFAULT_GET_COUNT)),
+            // -1 表示返回全部记录
+            ("get", Some(count), None) => match extract_integer(Some(count))? {
+                -1 => SlowlogSubcommand::Get(None),
+                count if count >= 0 => SlowlogSubcommand::Get(Some(count as usize)),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "count should be greater than or equal to -1".to_string(),
+                    ))
+                }
+            },
+            ("len", None, None) => SlowlogSubcommand::Len,
+            ("reset", None, None) => SlowlogSubcommand::Reset,
+            ("help", None, None) => SlowlogSubcommand::Help,
+            (sub, _, _) => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand or wrong number of arguments for '{}'. Try SLOWLOG HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(Slowlog { subcommand })
+    }
+}
+
+impl CommandExecutor for CommandMeta {
+    fn execute(self, _: &Backend) -> RespFrame {
+        match self.subcommand {
+            CommandMetaSubcommand::Docs(names) => {
+                let mut docs = RespMap::new();
+                if names.is_empty() {
+                    for spec in COMMAND_TABLE {
+                        docs.insert(spec.name.to_string(), command_docs(spec));
+                    }
+                }
+                // 未知命令返回空的文档
+                for name in names {
+                    let entry = match lookup_command(&name.to_ascii_lowercase()) {
+                        Some(spec) => command_docs(spec),
+                        None => RespMap::new().into(),
+                    };
+                    docs.insert(name, entry);
+                }
+                docs.into()
+            }
+            CommandMetaSubcommand::GetKeys(args) => command_getkeys(args),
+            CommandMetaSubcommand::Help => help_reply("COMMAND", COMMAND_HELP),
+        }
+    }
+}
+
+// 解析嵌套的命令并回复其中的 key，错误信息和 Redis 保持一致
+fn command_getkeys(args: RespArray) -> RespFrame {
+    let keys = match Command::try_from(args) {
+        Ok(Command::Unrecognized(_)) | Err(CommandError::InvalidCommand(_)) => {
+            return RespFrame::error("ERR Invalid command specified")
+        }
+        Err(CommandError::WrongArity(_)) => {
+            return RespFrame::error("ERR Invalid number of arguments specified for command")
+        }
+        Err(_) => return RespFrame::error("ERR Invalid arguments specified for command"),
+        Ok(cmd) => cmd
+            .keys()
+            .into_iter()
+            .map(|key| BulkString::new(key).into())
+            .collect::<Vec<RespFrame>>(),
+    };
+    if keys.is_empty() {
+        return RespFrame::error("ERR The command has no key arguments");
+    }
+    RespArray::new(keys).into()
+}
+
+fn command_docs(spec: &CommandSpec) -> RespFrame {
+    let arguments: Vec<RespFrame> = spec
+        .arguments
+        .iter()
+        .map(|(name, kind)| {
+            let mut arg = RespOrderedMap::new();
+            arg.insert("name".to_string(), BulkString::new(*name).into());
+            arg.insert("type".to_string(), BulkString::new(*kind).into());
+            arg.into()
+        })
+        .collect();
+    let flags: Vec<RespFrame> = spec
+        .flags()
+        .into_iter()
+        .map(|flag| SimpleString::new(flag).into())
+        .collect();
+    let mut doc = RespOrderedMap::new();
+    doc.insert("summary".to_string(), BulkString::new(spec.summary).into());
+    doc.insert("since".to_string(), BulkString::new(spec.since).into());
+    doc.insert("group".to_string(), BulkString::new(spec.group).into());
+    // 没有复杂度说明的命令不返回 complexity，和 Redis 一致
+    if !spec.complexity.is_empty() {
+        doc.insert(
+            "complexity".to_string(),
+            BulkString::new(spec.complexity).into(),
+        );
+    }
+    doc.insert("flags".to_string(), RespArray::new(flags).into());
+    doc.insert("arguments".to_string(), RespArray::new(arguments).into());
+    doc.into()
+}
+
+impl TryFrom<RespArray> for CommandMeta {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["command"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand = match extract_string(args.next())?.to_ascii_lowercase().as_str() {
+            "docs" => CommandMetaSubcommand::Docs(
+                args.map(|arg| extract_string(Some(arg)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "help" if args.len() == 0 => CommandMetaSubcommand::Help,
+            "getkeys" => {
+                let args: Vec<RespFrame> = args.collect();
+                if args.is_empty() {
+                    return Err(CommandError::WrongArity("command|getkeys".to_string()));
+                }
+                CommandMetaSubcommand::GetKeys(RespArray::new(args))
+            }
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try COMMAND HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(CommandMeta { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use bytes::BytesMut;
+
+    use crate::RespDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_debug_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$5\r\ndebug\r\n$5\r\nSLEEP\r\n$3\r\n0.5\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let debug: Debug = cmd.try_into()?;
+        assert_eq!(debug.subcommand, DebugSubcommand::Sleep(0.5));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$5\r\ndebug\r\n$17\r\nset-active-expire\r\n$1\r\n0\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let debug: Debug = cmd.try_into()?;
+        assert_eq!(debug.subcommand, DebugSubcommand::SetActiveExpire(false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_restricted_subcommands() {
+        assert!(check_debug_subcommand("sleep", true).is_ok());
+        assert!(check_debug_subcommand("object", false).is_ok());
+        assert!(check_debug_subcommand("help", false).is_ok());
+        for sub in ["sleep", "reload", "set-active-expire"] {
+            let Err(CommandError::InvalidArgument(msg)) = check_debug_subcommand(sub, false) else {
+                panic!("DEBUG {} should be rejected", sub);
+            };
+            assert!(msg.contains("debug-commands"), "{}", msg);
+        }
+    }
+
+    #[test]
+    fn test_debug_object_execute() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("hello").into());
+        let debug = |key: &str| {
+            Debug {
+                subcommand: DebugSubcommand::Object(key.to_string()),
+            }
+            .execute(&backend)
+        };
+        let RespFrame::SimpleString(reply) = debug("key") else {
+            panic!("DEBUG OBJECT should reply with a simple string");
+        };
+        let field = |name: &str| {
+            reply
+                .split(' ')
+                .find_map(|f| f.strip_prefix(name)?.strip_prefix(':'))
+                .map(str::to_string)
+        };
+        assert_eq!(field("encoding").as_deref(), Some("raw"));
+        // "$5\r\nhello\r\n"
+        assert_eq!(field("serializedlength").as_deref(), Some("11"));
+
+        backend.rpush("list".to_string(), vec![BulkString::new("a").into()]);
+        assert!(
+            matches!(debug("list"), RespFrame::SimpleString(s) if s.contains("encoding:listpack"))
+        );
+        assert_eq!(debug("missing"), RespFrame::error("ERR no such key"));
+    }
+
+    #[test]
+    fn test_debug_object_tracks_value_size() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let field = |key: &str, name: &str| -> Option<String> {
+            let RespFrame::SimpleString(reply) = (Debug {
+                subcommand: DebugSubcommand::Object(key.to_string()),
+            })
+            .execute(&backend) else {
+                return None;
+            };
+            reply
+                .split(' ')
+                .find_map(|f| f.strip_prefix(name)?.strip_prefix(':'))
+                .map(str::to_string)
+        };
+        let serialized =
+            |key: &str| -> usize { field(key, "serializedlength").unwrap().parse().unwrap() };
+
+        backend.set("small".to_string(), BulkString::new("x".repeat(10)).into());
+        backend.set(
+            "large".to_string(),
+            BulkString::new("x".repeat(10000)).into(),
+        );
+        assert!(serialized("small") < 20);
+        assert!((10000..10020).contains(&serialized("large")));
+
+        backend.set_config("list-max-listpack-size", "2")?;
+        backend.rpush("list".to_string(), vec![BulkString::new("a").into()]);
+        let before = serialized("list");
+        assert_eq!(field("list", "encoding").as_deref(), Some("listpack"));
+        backend.rpush(
+            "list".to_string(),
+            vec![BulkString::new("b").into(), BulkString::new("c").into()],
+        );
+        assert!(serialized("list") > before);
+        assert_eq!(field("list", "encoding").as_deref(), Some("quicklist"));
+        assert_eq!(field("list", "refcount").as_deref(), Some("1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_set_active_expire_execute() {
+        let backend = Backend::new();
+        assert!(backend.active_expire());
+        let debug = Debug {
+            subcommand: DebugSubcommand::SetActiveExpire(false),
+        };
+        assert_eq!(debug.execute(&backend), RespFrame::ok());
+        assert!(!backend.active_expire());
+    }
+
+    #[test]
+    fn test_cluster_noop_commands() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        for name in ["READONLY", "READWRITE", "ASKING"] {
+            let cmd = RespArray::new(vec![BulkString::new(name).into()]);
+            let cmd = Command::try_from(cmd)?;
+            assert_eq!(cmd.execute(&backend), RespFrame::ok(), "{}", name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cluster_execute() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let cluster = |sub: &str| -> anyhow::Result<RespFrame> {
+            let cmd = RespArray::new(vec![
+                BulkString::new("cluster").into(),
+                BulkString::new(sub).into(),
+            ]);
+            Ok(Cluster::try_from(cmd)?.execute(&backend))
+        };
+        let RespFrame::BulkString(info) = cluster("INFO")? else {
+            panic!("CLUSTER INFO should reply with a bulk string");
+        };
+        let info = String::from_utf8(info.0)?;
+        assert!(info.starts_with("cluster_enabled:0\r\ncluster_state:ok\r\n"));
+        assert!(info.contains("cluster_slots_assigned:0\r\n"));
+        assert_eq!(info.lines().count(), 13);
+
+        assert_eq!(cluster("myid")?, BulkString::new("0".repeat(40)).into());
+        assert!(cluster("nodes").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_execute_async() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nwait\r\n$1\r\n0\r\n$1\r\n0\r\n");
+        let wait: Wait = RespArray::decode(&mut buf)?.try_into()?;
+        let start = Instant::now();
+        assert_eq!(wait.execute_async(&backend).await, RespFrame::Integer(0));
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        let wait = Wait {
+            numreplicas: 1,
+            timeout_ms: 50,
+        };
+        let start = Instant::now();
+        assert_eq!(wait.execute_async(&backend).await, RespFrame::Integer(0));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_millis(200));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nwait\r\n$1\r\n1\r\n$2\r\n-1\r\n");
+        let ret: Result<Wait, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_debug_sleep_execute_async() {
+        let backend = Backend::new();
+        let debug = Debug {
+            subcommand: DebugSubcommand::Sleep(0.1),
+        };
+        let start = Instant::now();
+        assert_eq!(debug.execute_async(&backend).await, RespFrame::ok());
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_debug_sleep_out_of_range() {
+        let backend = Backend::new();
+        let debug = || Debug {
+            subcommand: DebugSubcommand::Sleep(1e300),
+        };
+        let err = RespFrame::error("ERR timeout is out of range");
+        assert_eq!(debug().execute_async(&backend).await, err);
+        assert_eq!(debug().execute(&backend), err);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_debug_sleep_execute_does_not_block_worker() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let sleeper = tokio::spawn(async move {
+            Debug {
+                subcommand: DebugSubcommand::Sleep(0.1),
+            }
+            .execute(&backend);
+            Instant::now()
+        });
+        let ticker = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Instant::now()
+        });
+        // 唯一的 worker 被 SLEEP 占用时，其它任务仍然能按时执行
+        assert!(ticker.await? < sleeper.await?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_slowlog_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$7\r\nslowlog\r\n$3\r\nGET\r\n$2\r\n-1\r\n");
+        let cmd: Slowlog = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.subcommand, SlowlogSubcommand::Get(None));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$7\r\nslowlog\r\n$3\r\nget\r\n");
+        let cmd: Slowlog = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.subcommand, SlowlogSubcommand::Get(Some(128)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_slowlog_execute() {
+        let backend = Backend::new();
+        backend.set_config("slowlog-log-slower-than", "0").unwrap();
+        backend.slowlog_record(
+            Duration::from_micros(5),
+            || vec!["get".to_string(), "key".to_string()],
+            "127.0.0.1:1234".to_string(),
+            "conn".to_string(),
+        );
+        let slowlog = |subcommand| Slowlog { subcommand }.execute(&backend);
+        assert_eq!(slowlog(SlowlogSubcommand::Len), RespFrame::Integer(1));
+
+        let RespFrame::Array(entries) = slowlog(SlowlogSubcommand::Get(Some(10))) else {
+            panic!("SLOWLOG GET should reply with an array");
+        };
+        let RespFrame::Array(entry) = &entries[0] else {
+            panic!("entry should be an array");
+        };
+        assert_eq!(entry[0], RespFrame::Integer(0));
+        assert_eq!(entry[2], RespFrame::Integer(5));
+        assert_eq!(
+            entry[3],
+            RespArray::new(vec![
+                BulkString::new("get").into(),
+                BulkString::new("key").into()
+            ])
+            .into()
+        );
+        assert_eq!(entry[4], BulkString::new("127.0.0.1:1234").into());
+        assert_eq!(entry[5], BulkString::new("conn").into());
+
+        assert_eq!(slowlog(SlowlogSubcommand::Reset), RespFrame::ok());
+        assert_eq!(slowlog(SlowlogSubcommand::Len), RespFrame::Integer(0));
+    }
+
+    #[test]
+    fn test_command_docs_execute() {
+        let backend = Backend::new();
+        let docs = |names: &[&str]| {
+            CommandMeta {
+                subcommand: CommandMetaSubcommand::Docs(
+                    names.iter().map(|name| name.to_string()).collect(),
+                ),
+            }
+            .execute(&backend)
+        };
+        let RespFrame::Map(map) = docs(&["GET", "nosuch"]) else {
+            panic!("COMMAND DOCS should reply with a map");
+        };
+        let Some(RespFrame::OrderedMap(get)) = map.get("GET") else {
+            panic!("missing docs for GET");
+        };
+        assert_eq!(get.get("group"), Some(&BulkString::new("string").into()));
+        assert_eq!(get.get("since"), Some(&BulkString::new("1.0.0").into()));
+        assert_eq!(get.get("complexity"), Some(&BulkString::new("O(1)").into()));
+        assert_eq!(
+            get.get("flags"),
+            Some(&RespArray::new(vec![SimpleString::new("readonly").into()]).into())
+        );
+        assert!(matches!(get.get("arguments"), Some(RespFrame::Array(args)) if args.len() == 1));
+        assert_eq!(map.get("nosuch"), Some(&RespMap::new().into()));
+
+        let RespFrame::Map(all) = docs(&[]) else {
+            panic!("COMMAND DOCS should reply with a map");
+        };
+        assert_eq!(all.len(), COMMAND_TABLE.len());
+    }
+
+    #[test]
+    fn test_command_getkeys_execute() {
+        let backend = Backend::new();
+        let getkeys = |args: &[&str]| {
+            let args: Vec<RespFrame> = args.iter().map(|a| BulkString::new(*a).into()).collect();
+            CommandMeta {
+                subcommand: CommandMetaSubcommand::GetKeys(RespArray::new(args)),
+            }
+            .execute(&backend)
+        };
+        let keys = |keys: &[&str]| -> RespFrame {
+            let keys: Vec<RespFrame> = keys.iter().map(|k| BulkString::new(*k).into()).collect();
+            RespArray::new(keys).into()
+        };
+        let error = |msg: &str| -> RespFrame { RespFrame::error(msg) };
+        assert_eq!(getkeys(&["GET", "k"]), keys(&["k"]));
+        assert_eq!(getkeys(&["set", "k", "v"]), keys(&["k"]));
+        // 可变数量的 key 都会被列出
+        assert_eq!(getkeys(&["del", "a", "b", "c"]), keys(&["a", "b", "c"]));
+        // MSET 的 key 从第 1 个参数开始，每隔一个参数一个
+        assert_eq!(
+            getkeys(&["mset", "k1", "v1", "k2", "v2"]),
+            keys(&["k1", "k2"])
+        );
+        assert_eq!(
+            getkeys(&["mset", "k1", "v1", "k2"]),
+            error("ERR Invalid number of arguments specified for command")
+        );
+        assert_eq!(
+            getkeys(&["zunionstore", "dst", "2", "a", "b", "weights", "1", "2"]),
+            keys(&["dst", "a", "b"])
+        );
+        assert_eq!(
+            getkeys(&["xread", "count", "1", "streams", "s1", "s2", "0", "0"]),
+            keys(&["s1", "s2"])
+        );
+        assert_eq!(
+            getkeys(&["sort", "list", "store", "dst"]),
+            keys(&["list", "dst"])
+        );
+
+        assert_eq!(
+            getkeys(&["nosuch", "k"]),
+            error("ERR Invalid command specified")
+        );
+        assert_eq!(
+            getkeys(&["get"]),
+            error("ERR Invalid number of arguments specified for command")
+        );
+        assert_eq!(
+            getkeys(&["info"]),
+            error("ERR The command has no key arguments")
+        );
+    }
+
+    fn info(backend: &Backend, sections: &[&str]) -> String {
+        let ret = Info {
+            sections: sections.iter().map(|s| s.to_string()).collect(),
+        }
+        .execute(backend);
+        let RespFrame::BulkString(text) = ret else {
+            panic!("INFO should reply with a bulk string");
+        };
+        String::from_utf8(text.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_info_sections() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        backend.get("key");
+        backend.get("missing");
+
+        let all = info(&backend, &[]);
+        for title in ["# Server", "# Clients", "# Memory", "# Stats", "# Keyspace"] {
+            assert!(all.contains(title), "{}", all);
+        }
+        assert!(all.contains("tcp_port:6379\r\n"));
+        assert!(all.contains("keyspace_hits:1\r\n"));
+        assert!(all.contains("keyspace_misses:1\r\n"));
+        assert!(all.contains("db0:keys=1,expires=0,avg_ttl=0\r\n"));
+
+        let memory = info(&backend, &["memory"]);
+        assert!(memory.starts_with("# Memory\r\nused_memory:"));
+        assert!(!memory.contains("# Server"));
+        assert_eq!(info(&backend, &["nosuchsection"]), "");
+    }
+
+    fn lastsave(backend: &Backend) -> i64 {
+        let RespFrame::Integer(ts) = LastSave.execute(backend) else {
+            panic!("LASTSAVE should reply with an integer");
+        };
+        ts
+    }
+
+    #[tokio::test]
+    async fn test_debug_reload_waits_for_running_commands() -> anyhow::Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-reload-async-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let config = crate::ServerConfig {
+            dir: dir.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let backend = Backend::with_config(config);
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let reload = || Debug {
+            subcommand: DebugSubcommand::Reload,
+        };
+
+        // 其它命令执行期间 RELOAD 一直等待
+        let guard = backend.command_guard().await;
+        let pending =
+            tokio::time::timeout(Duration::from_millis(50), reload().execute_async(&backend)).await;
+        assert!(pending.is_err());
+        drop(guard);
+        assert_eq!(reload().execute_async(&backend).await, RespFrame::ok());
+        assert_eq!(backend.get("key"), Some(BulkString::new("value").into()));
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_reload_round_trip() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("simple-redis-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let config = crate::ServerConfig {
+            dir: dir.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let backend = Backend::with_config(config);
+        backend.set("string".to_string(), BulkString::new("value").into());
+        backend.hset(
+            "hash".to_string(),
+            "field".to_string(),
+            BulkString::new("1").into(),
+        );
+        backend.rpush(
+            "list".to_string(),
+            vec![BulkString::new("a").into(), BulkString::new("b").into()],
+        );
+        backend.sadd("set".to_string(), vec!["x".to_string(), "y".to_string()]);
+        let cmd: Debug = RespArray::new(vec![
+            BulkString::new("debug").into(),
+            BulkString::new("reload").into(),
+        ])
+        .try_into()?;
+        assert_eq!(cmd.execute(&backend), RespFrame::ok());
+
+        assert_eq!(backend.dbsize(), 4);
+        assert_eq!(backend.get("string"), Some(BulkString::new("value").into()));
+        assert_eq!(
+            backend.hget("hash", "field"),
+            Some(BulkString::new("1").into())
+        );
+        assert_eq!(
+            backend.lrange("list", 0, -1),
+            vec![BulkString::new("a").into(), BulkString::new("b").into()]
+        );
+        let mut members: Vec<String> = backend
+            .smembers("set")
+            .map(|set| set.into_iter().collect())
+            .unwrap_or_default();
+        members.sort();
+        assert_eq!(members, ["x", "y"]);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_save_execute_async() -> anyhow::Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-save-async-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let config = crate::ServerConfig {
+            dir: dir.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let backend = Backend::with_config(config);
+        backend.set("key".to_string(), BulkString::new("value").into());
+        assert_eq!(Save.execute_async(&backend).await, RespFrame::ok());
+        assert!(dir.join("dump.rdb").exists());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_bgsave_lastsave() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("simple-redis-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let config = crate::ServerConfig {
+            dir: dir.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let backend = Backend::with_config(config);
+        backend.set("key".to_string(), BulkString::new("value").into());
+        let path = dir.join("dump.rdb");
+
+        // LASTSAVE 是秒级时间戳，等到下一秒再保存才能看到它变大
+        let started = lastsave(&backend);
+        while std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64
+            <= started
+        {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(Save.execute(&backend), RespFrame::ok());
+        assert!(path.exists());
+        let saved = lastsave(&backend);
+        assert!(saved > started);
+
+        // BGSAVE 立即返回，文件由后台线程写入
+        std::fs::remove_file(&path)?;
+        assert_eq!(
+            BgSave.execute(&backend),
+            SimpleString::new("Background saving started").into()
+        );
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !path.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(path.exists());
+        assert!(lastsave(&backend) >= saved);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{
+    cmd::{
+        extract_args, extract_string, parse_scan_args, validate_command, validate_command_at_least,
+    },
+    Backend, BulkString, RespArray, RespFrame, RespSet,
+};
+
+use super::{CommandError, CommandExecutor, SAdd, SInterCard, SMembers, SScan};
+
+impl CommandExecutor for SAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let added = backend.sadd(self.key, self.members);
+        RespFrame::Integer(added as i64)
+    }
+}
+
+impl CommandExecutor for SMembers {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let mut members: Vec<String> = backend
+            .smembers(&self.key)
+            .map(|set| set.into_iter().collect())
+            .unwrap_or_default();
+        members.sort();
+        let frames: Vec<RespFrame> = members
+            .into_iter()
+            .map(|member| BulkString::new(member).into())
+            .collect();
+        RespSet::new(frames).into()
+    }
+}
+
+impl CommandExecutor for SScan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, members) =
+            backend.sscan(&self.key, self.cursor, self.pattern.as_deref(), self.count);
+        let members: Vec<RespFrame> = members
+            .into_iter()
+            .map(|member| BulkString::new(member).into())
+            .collect();
+        RespArray::new(vec![
+            BulkString::new(cursor.to_string()).into(),
+            RespArray::new(members).into(),
+        ])
+        .into()
+    }
+}
+
+impl CommandExecutor for SInterCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let keys: Vec<&str> = self.keys.iter().map(String::as_str).collect();
+        RespFrame::Integer(backend.sintercard(&keys, self.limit) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for SAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["sadd"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let members = args
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SAdd { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SMembers {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["smembers"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SMembers {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SScan {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["sscan"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (cursor, pattern, count, _) = parse_scan_args(args, false)?;
+        Ok(SScan {
+            key,
+            cursor,
+            pattern,
+            count,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SInterCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["sintercard"], 2)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let numkeys = match args[0].parse::<i64>() {
+            Ok(n) if n > 0 => n as usize,
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "numkeys should be greater than 0".to_string(),
+                ))
+            }
+        };
+        if args.len() < numkeys + 1 {
+            return Err(CommandError::InvalidArgument(
+                "Number of keys can't be greater than number of args".to_string(),
+            ));
+        }
+        let limit = match &args[numkeys + 1..] {
+            [] => 0,
+            [opt, n] if opt.eq_ignore_ascii_case("limit") => match n.parse::<i64>() {
+                Ok(n) if n >= 0 => n as usize,
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "LIMIT can't be negative".to_string(),
+                    ))
+                }
+            },
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(SInterCard {
+            keys: args[1..=numkeys].to_vec(),
+            limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::RespDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_sadd_smembers_execute() {
+        let backend = Backend::new();
+        let sadd = SAdd {
+            key: "set".to_string(),
+            members: vec!["b".to_string(), "a".to_string(), "b".to_string()],
+        };
+        assert_eq!(sadd.execute(&backend), RespFrame::Integer(2));
+        let smembers = SMembers {
+            key: "set".to_string(),
+        };
+        let expected: RespFrame = RespSet::new(vec![
+            BulkString::new("a").into(),
+            BulkString::new("b").into(),
+        ])
+        .into();
+        assert_eq!(smembers.execute(&backend), expected);
+    }
+
+    #[test]
+    fn test_sscan_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nsscan\r\n$3\r\nset\r\n$1\r\n5\r\n$5\r\ncount\r\n$1\r\n0\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<SScan, _> = cmd.try_into();
+        assert!(ret.is_err());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$5\r\nsscan\r\n$3\r\nset\r\n$1\r\n5\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let sscan: SScan = cmd.try_into()?;
+        assert_eq!(sscan.cursor, 5);
+        assert_eq!(sscan.pattern, None);
+        assert_eq!(sscan.count, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sscan_execute() {
+        let backend = Backend::new();
+        let members = (0..30).map(|i| format!("m{:02}", i)).collect();
+        backend.sadd("set".to_string(), members);
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, members) = backend.sscan("set", cursor, Some("m1*"), 7);
+            seen.extend(members);
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+        seen.sort();
+        let expected: Vec<String> = (10..20).map(|i| format!("m{}", i)).collect();
+        assert_eq!(seen, expected);
+
+        let sscan = SScan {
+            key: "missing".to_string(),
+            cursor: 0,
+            pattern: None,
+            count: 10,
+        };
+        let expected: RespFrame = RespArray::new(vec![
+            BulkString::new("0").into(),
+            RespArray::new(vec![]).into(),
+        ])
+        .into();
+        assert_eq!(sscan.execute(&backend), expected);
+    }
+
+    #[test]
+    fn test_sintercard_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nLIMIT\r\n$1\r\n3\r\n",
+        );
+        let cmd: SInterCard = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(cmd.keys, vec!["a", "b"]);
+        assert_eq!(cmd.limit, 3);
+
+        // numkeys 大于实际的 key 数量
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n");
+        let ret: Result<SInterCard, _> = RespArray::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sintercard_execute() {
+        let backend = Backend::new();
+        let members = |range: std::ops::Range<u32>| range.map(|i| i.to_string()).collect();
+        backend.sadd("s1".to_string(), members(0..10));
+        backend.sadd("s2".to_string(), members(5..20));
+        backend.sadd("s3".to_string(), members(0..8));
+        let sintercard = |keys: &[&str], limit| {
+            SInterCard {
+                keys: keys.iter().map(|k| k.to_string()).collect(),
+                limit,
+            }
+            .execute(&backend)
+        };
+
+        // 和直接求交集的结果一致：{5, 6, 7}
+        let s1 = backend.smembers("s1").unwrap();
+        let expected = ["s2", "s3"].iter().fold(s1, |acc, key| {
+            let other = backend.smembers(key).unwrap();
+            acc.retain(|m| other.contains(m));
+            acc
+        });
+        assert_eq!(expected.len(), 3);
+        assert_eq!(
+            sintercard(&["s1", "s2", "s3"], 0),
+            RespFrame::Integer(expected.len() as i64)
+        );
+        // LIMIT 限制返回值
+        assert_eq!(sintercard(&["s1", "s2", "s3"], 2), RespFrame::Integer(2));
+        assert_eq!(sintercard(&["s1", "s2", "s3"], 10), RespFrame::Integer(3));
+        assert_eq!(sintercard(&["s1", "missing"], 0), RespFrame::Integer(0));
+        assert_eq!(sintercard(&["missing", "s1"], 0), RespFrame::Integer(0));
+    }
+
+    #[test]
+    fn test_sintercard_limit_stops_early() {
+        let backend = Backend::new();
+        let members: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        backend.sadd("big".to_string(), members.clone());
+        backend.sadd("other".to_string(), members);
+        backend.sadd("small".to_string(), vec!["1".to_string(), "x".to_string()]);
+        assert_eq!(backend.sintercard(&["big", "other"], 0), 1000);
+        assert_eq!(backend.sintercard(&["big", "other"], 7), 7);
+        // 从最小的集合开始检查，顺序不影响结果
+        assert_eq!(backend.sintercard(&["big", "small", "other"], 0), 1);
+        assert_eq!(backend.sintercard(&["small"], 1), 1);
+        assert_eq!(backend.sintercard(&[], 0), 0);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{
+    cmd::{extract_args, extract_string, validate_command_at_least},
+    Backend, RespArray, RespFrame, SortOptions,
+};
+
+use super::{CommandError, CommandExecutor, Sort};
+
+impl CommandExecutor for Sort {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sort(&self.key, &self.options) {
+            // STORE 时返回写入的元素数量
+            Ok(sorted) if self.options.store.is_some() => RespFrame::Integer(sorted.len() as i64),
+            Ok(sorted) => RespArray::new(sorted).into(),
+            Err(e) => RespFrame::error(e.to_string()),
+        }
+    }
+}
+
+// SORT key [LIMIT offset count] [ASC|DESC] [ALPHA] [STORE destination]
+impl TryFrom<RespArray> for Sort {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["sort"], 1)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+
+        let mut options = SortOptions::default();
+        let mut i = 1;
+        while i < args.len() {
+            match args[i].to_ascii_lowercase().as_str() {
+                "asc" => options.desc = false,
+                "desc" => options.desc = true,
+                "alpha" => options.alpha = true,
+                "limit" => {
+                    let (offset, count) = match (args.get(i + 1), args.get(i + 2)) {
+                        (Some(offset), Some(count)) => (offset, count),
+                        _ => return Err(syntax_error()),
+                    };
+                    let parse = |s: &String| {
+                        s.parse::<i64>().map_err(|_| {
+                            CommandError::InvalidArgument(
+                                "value is not an integer or out of range".to_string(),
+                            )
+                        })
+                    };
+                    // 负数 offset 按 0 处理，负数 count 表示取到结尾
+                    let offset = parse(offset)?.max(0) as usize;
+                    let count = usize::try_from(parse(count)?).unwrap_or(usize::MAX);
+                    options.limit = Some((offset, count));
+                    i += 2;
+                }
+                "store" => {
+                    options.store = Some(args.get(i + 1).ok_or_else(syntax_error)?.clone());
+                    i += 1;
+                }
+                _ => return Err(syntax_error()),
+            }
+            i += 1;
+        }
+        Ok(Sort {
+            key: args[0].clone(),
+            options,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::{BulkString, RespDecoder};
+
+    use super::*;
+
+    fn bulk(values: &[&str]) -> Vec<RespFrame> {
+        values.iter().map(|v| BulkString::new(*v).into()).collect()
+    }
+
+    fn sort(key: &str, options: SortOptions) -> Sort {
+        Sort {
+            key: key.to_string(),
+            options,
+        }
+    }
+
+    #[test]
+    fn test_sort_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*9\r\n$4\r\nSORT\r\n$4\r\nlist\r\n$5\r\nLIMIT\r\n$1\r\n1\r\n$1\r\n2\r\n$4\r\nDESC\r\n$5\r\nALPHA\r\n$5\r\nSTORE\r\n$3\r\ndst\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let sort: Sort = cmd.try_into()?;
+        assert_eq!(sort.key, "list");
+        assert_eq!(
+            sort.options,
+            SortOptions {
+                desc: true,
+                alpha: true,
+                limit: Some((1, 2)),
+                store: Some("dst".to_string()),
+            }
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nsort\r\n$4\r\nlist\r\n$5\r\nLIMIT\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<Sort, _> = cmd.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_list_numeric_and_alpha() {
+        let backend = Backend::new();
+        backend.rpush("list".to_string(), bulk(&["10", "2", "-1.5", "3"]));
+        let ret = sort("list", SortOptions::default()).execute(&backend);
+        assert_eq!(ret, RespArray::new(bulk(&["-1.5", "2", "3", "10"])).into());
+
+        let options = SortOptions {
+            alpha: true,
+            desc: true,
+            ..Default::default()
+        };
+        let ret = sort("list", options).execute(&backend);
+        assert_eq!(ret, RespArray::new(bulk(&["3", "2", "10", "-1.5"])).into());
+
+        let options = SortOptions {
+            limit: Some((1, 2)),
+            ..Default::default()
+        };
+        let ret = sort("list", options).execute(&backend);
+        assert_eq!(ret, RespArray::new(bulk(&["2", "3"])).into());
+
+        // LIMIT 在排序方向确定之后截取，超出范围时返回空
+        let options = SortOptions {
+            desc: true,
+            limit: Some((1, 2)),
+            ..Default::default()
+        };
+        let ret = sort("list", options).execute(&backend);
+        assert_eq!(ret, RespArray::new(bulk(&["3", "2"])).into());
+        let options = SortOptions {
+            limit: Some((10, 2)),
+            ..Default::default()
+        };
+        let ret = sort("list", options).execute(&backend);
+        assert_eq!(ret, RespArray::new(vec![]).into());
+    }
+
+    #[test]
+    fn test_sort_set_and_store() {
+        let backend = Backend::new();
+        backend.sadd(
+            "set".to_string(),
+            vec![
+                "banana".to_string(),
+                "apple".to_string(),
+                "cherry".to_string(),
+            ],
+        );
+        let ret = sort("set", SortOptions::default()).execute(&backend);
+        assert_eq!(
+            ret,
+            RespFrame::error("ERR One or more scores can't be converted into double")
+        );
+
+        let options = SortOptions {
+            alpha: true,
+            store: Some("dst".to_string()),
+            ..Default::default()
+        };
+        let ret = sort("set", options).execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(3));
+        assert_eq!(
+            backend.lrange("dst", 0, -1),
+            bulk(&["apple", "banana", "cherry"])
+        );
+    }
+
+    #[test]
+    fn test_sort_missing_and_wrong_type() {
+        let backend = Backend::new();
+        let ret = sort("missing", SortOptions::default()).execute(&backend);
+        assert_eq!(ret, RespArray::new(vec![]).into());
+
+        backend.set("string".to_string(), BulkString::new("v").into());
+        let ret = sort("string", SortOptions::default()).execute(&backend);
+        assert!(matches!(ret, RespFrame::Error(_)));
+    }
+}
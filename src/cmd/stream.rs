@@ -0,0 +1,1030 @@
+use std::time::Duration;
+
+use crate::{
+    cmd::{extract_args, extract_string, help_reply, validate_command, validate_command_at_least},
+    Backend, BulkString, RespArray, RespFrame, RespNullArray, StreamEntry, StreamGroupError,
+    StreamId, TrimStrategy,
+};
+
+use super::{
+    CommandError, CommandExecutor, XAck, XAdd, XDel, XGroup, XGroupSubcommand, XLen, XRange, XRead,
+    XReadGroup, XTrim,
+};
+
+const XGROUP_HELP: &[&str] = &[
+    "CREATE <key> <groupname> <id|$> [option]",
+    "    Create a new consumer group. Options are:",
+    "    * MKSTREAM",
+    "      Create the empty stream if it does not exist.",
+];
+
+impl CommandExecutor for XAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.xadd(self.key, self.id, self.fields) {
+            Ok(id) => BulkString::new(id.to_string()).into(),
+            Err(e) => RespFrame::error(e.to_string()),
+        }
+    }
+}
+
+impl CommandExecutor for XRead {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let streams = self.resolve_ids(backend);
+        read_streams(backend, &streams, self.count).unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+}
+
+impl XRead {
+    // BLOCK 模式：没有新记录时等待 XADD 唤醒，超时返回 null array
+    pub async fn execute_blocking(self, backend: &Backend) -> RespFrame {
+        let streams = self.resolve_ids(backend);
+        let keys: Vec<String> = streams.iter().map(|(key, _)| key.clone()).collect();
+        let timeout = match self.block {
+            Some(0) | None => None,
+            Some(ms) => Some(Duration::from_millis(ms)),
+        };
+        backend
+            .block_on_keys(&keys, timeout, |backend| {
+                read_streams(backend, &streams, self.count)
+            })
+            .await
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+
+    // 将 `$` 替换为 stream 当前最大的 ID
+    fn resolve_ids(&self, backend: &Backend) -> Vec<(String, StreamId)> {
+        self.streams
+            .iter()
+            .map(|(key, id)| (key.clone(), id.unwrap_or_else(|| backend.xlast_id(key))))
+            .collect()
+    }
+}
+
+impl CommandExecutor for XRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        entries_to_frame(backend.xrange(&self.key, self.start, self.end, self.count))
+    }
+}
+
+impl CommandExecutor for XLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.xlen(&self.key) as i64)
+    }
+}
+
+impl CommandExecutor for XDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.xdel(&self.key, &self.ids) as i64)
+    }
+}
+
+impl CommandExecutor for XTrim {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.xtrim(&self.key, self.strategy) as i64)
+    }
+}
+
+impl CommandExecutor for XGroup {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            XGroupSubcommand::Create {
+                key,
+                group,
+                id,
+                mkstream,
+            } => match backend.xgroup_create(&key, &group, id, mkstream) {
+                Ok(()) => RespFrame::ok(),
+                Err(e) => RespFrame::error(e.to_string()),
+            },
+            XGroupSubcommand::Help => help_reply("XGROUP", XGROUP_HELP),
+        }
+    }
+}
+
+impl CommandExecutor for XReadGroup {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.read(backend) {
+            Ok(Some(frame)) => frame,
+            Ok(None) => RespFrame::NullArray(RespNullArray),
+            Err(e) => RespFrame::error(e.to_string()),
+        }
+    }
+}
+
+impl XReadGroup {
+    // BLOCK 模式：只有读取 `>` 且没有新记录时才会阻塞，出错时立即返回
+    pub async fn execute_blocking(self, backend: &Backend) -> RespFrame {
+        let keys: Vec<String> = self.streams.iter().map(|(key, _)| key.clone()).collect();
+        let timeout = match self.block {
+            Some(0) | None => None,
+            Some(ms) => Some(Duration::from_millis(ms)),
+        };
+        backend
+            .block_on_keys(&keys, timeout, |backend| match self.read(backend) {
+                Ok(frame) => frame,
+                Err(e) => Some(RespFrame::error(e.to_string())),
+            })
+            .await
+            .unwrap_or(RespFrame::NullArray(RespNullArray))
+    }
+
+    // 与 XREAD 的返回格式相同；显式 ID 重放 PEL 时即使为空也返回该 stream
+    fn read(&self, backend: &Backend) -> Result<Option<RespFrame>, StreamGroupError> {
+        let mut ret = Vec::with_capacity(self.streams.len());
+        for (key, id) in &self.streams {
+            let entries = backend.xreadgroup(key, &self.group, &self.consumer, *id, self.count)?;
+            if entries.is_empty() && id.is_none() {
+                continue;
+            }
+            ret.push(
+                RespArray::new(vec![
+                    BulkString::new(key.as_str()).into(),
+                    entries_to_frame(entries),
+                ])
+                .into(),
+            );
+        }
+        Ok((!ret.is_empty()).then(|| RespArray::new(ret).into()))
+    }
+}
+
+impl CommandExecutor for XAck {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.xack(&self.key, &self.group, &self.ids) as i64)
+    }
+}
+
+// [[key, [[id, [field, value, ...]], ...]], ...]，所有 stream 都没有新记录时返回 None
+fn read_streams(
+    backend: &Backend,
+    streams: &[(String, StreamId)],
+    count: Option<usize>,
+) -> Option<RespFrame> {
+    let mut ret = Vec::with_capacity(streams.len());
+    for (key, id) in streams {
+        let entries = backend.xread(key, *id, count);
+        if entries.is_empty() {
+            continue;
+        }
+        ret.push(
+            RespArray::new(vec![
+                BulkString::new(key.as_str()).into(),
+                entries_to_frame(entries),
+            ])
+            .into(),
+        );
+    }
+    (!ret.is_empty()).then(|| RespArray::new(ret).into())
+}
+
+// [[id, [field, value, ...]], ...]
+pub(crate) fn entries_to_frame(entries: Vec<StreamEntry>) -> RespFrame {
+    let frames: Vec<RespFrame> = entries
+        .into_iter()
+        .map(|(id, fields)| {
+            let mut pairs = Vec::with_capacity(fields.len() * 2);
+            for (field, value) in fields {
+                pairs.push(BulkString::new(field).into());
+                pairs.push(value);
+            }
+            RespArray::new(vec![
+                BulkString::new(id.to_string()).into(),
+                RespArray::new(pairs).into(),
+            ])
+            .into()
+        })
+        .collect();
+    RespArray::new(frames).into()
+}
+
+pub(crate) fn parse_stream_id(s: &str) -> Result<StreamId, CommandError> {
+    s.parse().map_err(|_| {
+        CommandError::InvalidArgument(
+            "Invalid stream ID specified as stream command argument".to_string(),
+        )
+    })
+}
+
+// XRANGE 的区间边界：`-` / `+` 表示最小 / 最大 ID，省略 seq 时按边界补齐
+fn parse_range_id(s: &str, default_seq: u64) -> Result<StreamId, CommandError> {
+    match s {
+        "-" => Ok(StreamId::MIN),
+        "+" => Ok(StreamId::MAX),
+        s if !s.contains('-') => {
+            let ms = s.parse().map_err(|_| {
+                CommandError::InvalidArgument(
+                    "Invalid stream ID specified as stream command argument".to_string(),
+                )
+            })?;
+            Ok(StreamId(ms, default_seq))
+        }
+        s => parse_stream_id(s),
+    }
+}
+
+// 解析 XREAD / XREADGROUP 中 STREAMS 之前的 COUNT 和 BLOCK，返回 STREAMS 之后第一个参数的位置
+fn parse_read_options(
+    args: &[String],
+    start: usize,
+) -> Result<(Option<usize>, Option<u64>, usize), CommandError> {
+    let mut count = None;
+    let mut block = None;
+    let mut i = start;
+    while i < args.len() {
+        match args[i].to_ascii_lowercase().as_str() {
+            "count" => {
+                let n = args
+                    .get(i + 1)
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| {
+                        CommandError::InvalidArgument(
+                            "value is not an integer or out of range".to_string(),
+                        )
+                    })?;
+                count = Some(n);
+                i += 2;
+            }
+            "block" => {
+                let ms = args
+                    .get(i + 1)
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| {
+                        CommandError::InvalidArgument(
+                            "timeout is not an integer or out of range".to_string(),
+                        )
+                    })?;
+                block = Some(ms);
+                i += 2;
+            }
+            "streams" => return Ok((count, block, i + 1)),
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        }
+    }
+    Ok((count, block, i))
+}
+
+// 解析 STREAMS 之后的 key 列表和 ID 列表，special 对应的 ID 解析为 None
+fn parse_streams(
+    rest: &[String],
+    name: &str,
+    special: &str,
+) -> Result<Vec<(String, Option<StreamId>)>, CommandError> {
+    if rest.is_empty() || !rest.len().is_multiple_of(2) {
+        return Err(CommandError::InvalidArgument(format!(
+            "Unbalanced '{}' list of streams: for each stream key an ID must be specified",
+            name
+        )));
+    }
+    let (keys, ids) = rest.split_at(rest.len() / 2);
+    keys.iter()
+        .zip(ids)
+        .map(|(key, id)| match id.as_str() {
+            id if id == special => Ok((key.clone(), None)),
+            id => Ok((key.clone(), Some(parse_stream_id(id)?))),
+        })
+        .collect()
+}
+
+impl TryFrom<RespArray> for XAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xadd"], 4)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let id = match extract_string(args.next())?.as_str() {
+            "*" => None,
+            id => Some(parse_stream_id(id)?),
+        };
+        if args.len() % 2 != 0 {
+            return Err(CommandError::InvalidArgument(
+                "wrong number of arguments for 'xadd' command".to_string(),
+            ));
+        }
+        let mut fields = Vec::with_capacity(args.len() / 2);
+        while let Some(field) = args.next() {
+            let field = extract_string(Some(field))?;
+            let value = args.next().ok_or_else(|| {
+                CommandError::InvalidArgument("Invalid field or value".to_string())
+            })?;
+            fields.push((field, value));
+        }
+        Ok(XAdd { key, id, fields })
+    }
+}
+
+impl TryFrom<RespArray> for XRead {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xread"], 3)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (count, block, i) = parse_read_options(&args, 0)?;
+        let streams = parse_streams(&args[i..], "xread", "$")?;
+        Ok(XRead {
+            count,
+            block,
+            streams,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xrange"], 3)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let start = parse_range_id(&extract_string(args.next())?, 0)?;
+        let end = parse_range_id(&extract_string(args.next())?, u64::MAX)?;
+        let count = match (args.next(), args.next(), args.next()) {
+            (None, _, _) => None,
+            (Some(RespFrame::BulkString(kw)), Some(n), None)
+                if kw.eq_ignore_ascii_case(b"count") =>
+            {
+                let n = extract_string(Some(n))?;
+                Some(n.parse().map_err(|_| {
+                    CommandError::InvalidArgument(
+                        "value is not an integer or out of range".to_string(),
+                    )
+                })?)
+            }
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(XRange {
+            key,
+            start,
+            end,
+            count,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["xlen"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(XLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xdel"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let ids = args
+            .map(|id| parse_stream_id(&extract_string(Some(id))?))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(XDel { key, ids })
+    }
+}
+
+// XTRIM key MAXLEN|MINID [=|~] threshold
+impl TryFrom<RespArray> for XTrim {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xtrim"], 3)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let kind = extract_string(args.next())?.to_ascii_lowercase();
+        let mut threshold = extract_string(args.next())?;
+        let mut approx = false;
+        if threshold == "~" || threshold == "=" {
+            approx = threshold == "~";
+            threshold = extract_string(args.next())?;
+        }
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        let strategy = match kind.as_str() {
+            "maxlen" => {
+                let max_len = threshold.parse().map_err(|_| {
+                    CommandError::InvalidArgument(
+                        "value is not an integer or out of range".to_string(),
+                    )
+                })?;
+                TrimStrategy::MaxLen(max_len, approx)
+            }
+            "minid" => TrimStrategy::MinId(parse_stream_id(&threshold)?, approx),
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(XTrim { key, strategy })
+    }
+}
+
+// XGROUP CREATE key group id|$ [MKSTREAM]
+impl TryFrom<RespArray> for XGroup {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xgroup"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let sub = extract_string(args.next())?.to_ascii_lowercase();
+        let subcommand = match sub.as_str() {
+            "create" if (3..=4).contains(&args.len()) => {
+                let key = extract_string(args.next())?;
+                let group = extract_string(args.next())?;
+                let id = match extract_string(args.next())?.as_str() {
+                    "$" => None,
+                    id => Some(parse_stream_id(id)?),
+                };
+                let mkstream = match args.next() {
+                    None => false,
+                    Some(RespFrame::BulkString(opt)) if opt.eq_ignore_ascii_case(b"mkstream") => {
+                        true
+                    }
+                    _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+                };
+                XGroupSubcommand::Create {
+                    key,
+                    group,
+                    id,
+                    mkstream,
+                }
+            }
+            "help" if args.len() == 0 => XGroupSubcommand::Help,
+            sub => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand or wrong number of arguments for '{}'. Try XGROUP HELP.",
+                    sub
+                )))
+            }
+        };
+        Ok(XGroup { subcommand })
+    }
+}
+
+// XREADGROUP GROUP group consumer [COUNT n] [BLOCK ms] STREAMS key ... id|> ...
+impl TryFrom<RespArray> for XReadGroup {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xreadgroup"], 6)?;
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !args[0].eq_ignore_ascii_case("group") {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        let (count, block, i) = parse_read_options(&args, 3)?;
+        let streams = parse_streams(&args[i..], "xreadgroup", ">")?;
+        Ok(XReadGroup {
+            group: args[1].clone(),
+            consumer: args[2].clone(),
+            count,
+            block,
+            streams,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XAck {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["xack"], 3)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let group = extract_string(args.next())?;
+        let ids = args
+            .map(|id| parse_stream_id(&extract_string(Some(id))?))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(XAck { key, group, ids })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::RespDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_xadd_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nxadd\r\n$3\r\nkey\r\n$6\r\n1000-1\r\n$5\r\nfield\r\n$5\r\nvalue\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let xadd: XAdd = cmd.try_into()?;
+        assert_eq!(xadd.key, "key");
+        assert_eq!(xadd.id, Some(StreamId(1000, 1)));
+        assert_eq!(
+            xadd.fields,
+            vec![("field".to_string(), BulkString::new("value").into())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_xread_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*8\r\n$5\r\nxread\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n$7\r\nSTREAMS\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\n0\r\n$6\r\n1000-1\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let xread: XRead = cmd.try_into()?;
+        assert_eq!(xread.count, Some(2));
+        assert_eq!(xread.block, None);
+        assert_eq!(
+            xread.streams,
+            vec![
+                ("a".to_string(), Some(StreamId(0, 0))),
+                ("b".to_string(), Some(StreamId(1000, 1)))
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_xrange_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$6\r\nxrange\r\n$3\r\nkey\r\n$1\r\n-\r\n$1\r\n+\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let xrange: XRange = cmd.try_into()?;
+        assert_eq!(xrange.key, "key");
+        assert_eq!(xrange.start, StreamId(0, 0));
+        assert_eq!(xrange.end, StreamId(u64::MAX, u64::MAX));
+        assert_eq!(xrange.count, Some(2));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nxrange\r\n$3\r\nkey\r\n$3\r\n100\r\n$3\r\n200\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let xrange: XRange = cmd.try_into()?;
+        assert_eq!(xrange.start, StreamId(100, 0));
+        assert_eq!(xrange.end, StreamId(200, u64::MAX));
+        Ok(())
+    }
+
+    #[test]
+    fn test_xrange_xlen_xdel_execute() {
+        let backend = Backend::new();
+        for ms in 1..=4 {
+            backend
+                .xadd("s".to_string(), Some(StreamId(ms, 0)), vec![])
+                .unwrap();
+        }
+        let xrange = XRange {
+            key: "s".to_string(),
+            start: StreamId(2, 0),
+            end: StreamId::MAX,
+            count: Some(2),
+        };
+        let expected = entries_to_frame(vec![(StreamId(2, 0), vec![]), (StreamId(3, 0), vec![])]);
+        assert_eq!(xrange.execute(&backend), expected);
+
+        let xdel = XDel {
+            key: "s".to_string(),
+            ids: vec![StreamId(2, 0), StreamId(9, 0)],
+        };
+        assert_eq!(xdel.execute(&backend), RespFrame::Integer(1));
+
+        let xlen = XLen {
+            key: "s".to_string(),
+        };
+        assert_eq!(xlen.execute(&backend), RespFrame::Integer(3));
+    }
+
+    #[test]
+    fn test_stream_commands_on_missing_key() {
+        let backend = Backend::new();
+        let xrange = XRange {
+            key: "missing".to_string(),
+            start: StreamId::MIN,
+            end: StreamId::MAX,
+            count: None,
+        };
+        assert_eq!(xrange.execute(&backend), RespArray::new(vec![]).into());
+        let xlen = XLen {
+            key: "missing".to_string(),
+        };
+        assert_eq!(xlen.execute(&backend), RespFrame::Integer(0));
+        let xdel = XDel {
+            key: "missing".to_string(),
+            ids: vec![StreamId(1, 0)],
+        };
+        assert_eq!(xdel.execute(&backend), RespFrame::Integer(0));
+    }
+
+    #[test]
+    fn test_xtrim_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nxtrim\r\n$3\r\nkey\r\n$6\r\nMAXLEN\r\n$1\r\n~\r\n$2\r\n10\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let xtrim: XTrim = cmd.try_into()?;
+        assert_eq!(xtrim.key, "key");
+        assert_eq!(xtrim.strategy, TrimStrategy::MaxLen(10, true));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nxtrim\r\n$3\r\nkey\r\n$5\r\nminid\r\n$3\r\n5-1\r\n");
+        let cmd = RespArray::decode(&mut buf)?;
+        let xtrim: XTrim = cmd.try_into()?;
+        assert_eq!(xtrim.strategy, TrimStrategy::MinId(StreamId(5, 1), false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_xtrim_execute() {
+        let backend = Backend::new();
+        for ms in 1..=5 {
+            backend
+                .xadd("s".to_string(), Some(StreamId(ms, 0)), vec![])
+                .unwrap();
+        }
+        let xtrim = XTrim {
+            key: "s".to_string(),
+            strategy: TrimStrategy::MaxLen(3, false),
+        };
+        assert_eq!(xtrim.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(
+            backend.xrange("s", StreamId::MIN, StreamId::MAX, Some(1))[0].0,
+            StreamId(3, 0)
+        );
+
+        let xtrim = XTrim {
+            key: "s".to_string(),
+            strategy: TrimStrategy::MinId(StreamId(5, 0), false),
+        };
+        assert_eq!(xtrim.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.xlen("s"), 1);
+
+        let xtrim = XTrim {
+            key: "missing".to_string(),
+            strategy: TrimStrategy::MaxLen(0, true),
+        };
+        assert_eq!(xtrim.execute(&backend), RespFrame::Integer(0));
+    }
+
+    #[test]
+    fn test_xadd_auto_id_is_increasing() {
+        let backend = Backend::new();
+        let first = backend.xadd("s".to_string(), None, vec![]).unwrap();
+        let second = backend.xadd("s".to_string(), None, vec![]).unwrap();
+        assert!(second > first);
+    }
+
+    #[test]
+    fn test_xadd_rejects_smaller_id() {
+        let backend = Backend::new();
+        let xadd = XAdd {
+            key: "s".to_string(),
+            id: Some(StreamId(5, 0)),
+            fields: vec![],
+        };
+        assert_eq!(xadd.execute(&backend), BulkString::new("5-0").into());
+        let xadd = XAdd {
+            key: "s".to_string(),
+            id: Some(StreamId(4, 9)),
+            fields: vec![],
+        };
+        assert!(matches!(xadd.execute(&backend), RespFrame::Error(_)));
+    }
+
+    #[test]
+    fn test_xadd_auto_id_carries_and_exhausts() {
+        let backend = Backend::new();
+        // seq 用完时进位到下一毫秒
+        backend
+            .xadd(
+                "s".to_string(),
+                Some(StreamId(u64::MAX - 1, u64::MAX)),
+                vec![],
+            )
+            .unwrap();
+        assert_eq!(
+            backend.xadd("s".to_string(), None, vec![]),
+            Ok(StreamId(u64::MAX, 0))
+        );
+        backend
+            .xadd("s".to_string(), Some(StreamId::MAX), vec![])
+            .unwrap();
+        let xadd = XAdd {
+            key: "s".to_string(),
+            id: None,
+            fields: vec![],
+        };
+        assert_eq!(
+            xadd.execute(&backend),
+            RespFrame::error(
+                "ERR The stream has exhausted the last possible ID, unable to add more items"
+            )
+        );
+    }
+
+    #[test]
+    fn test_xread_execute() {
+        let backend = Backend::new();
+        for seq in 1..=3 {
+            backend
+                .xadd(
+                    "s".to_string(),
+                    Some(StreamId(1, seq)),
+                    vec![("f".to_string(), BulkString::new(seq.to_string()).into())],
+                )
+                .unwrap();
+        }
+        let xread = XRead {
+            count: None,
+            block: None,
+            streams: vec![("s".to_string(), Some(StreamId(1, 1)))],
+        };
+        let expected: RespFrame = RespArray::new(vec![RespArray::new(vec![
+            BulkString::new("s").into(),
+            entries_to_frame(vec![
+                (
+                    StreamId(1, 2),
+                    vec![("f".to_string(), BulkString::new("2").into())],
+                ),
+                (
+                    StreamId(1, 3),
+                    vec![("f".to_string(), BulkString::new("3").into())],
+                ),
+            ]),
+        ])
+        .into()])
+        .into();
+        assert_eq!(xread.execute(&backend), expected);
+
+        let xread = XRead {
+            count: None,
+            block: None,
+            streams: vec![("s".to_string(), Some(StreamId(1, 3)))],
+        };
+        assert_eq!(xread.execute(&backend), RespFrame::NullArray(RespNullArray));
+    }
+
+    #[test]
+    fn test_xread_block_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$5\r\nxread\r\n$5\r\nBLOCK\r\n$3\r\n100\r\n$7\r\nstreams\r\n$1\r\ns\r\n$1\r\n$\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let xread: XRead = cmd.try_into()?;
+        assert_eq!(xread.block, Some(100));
+        assert_eq!(xread.streams, vec![("s".to_string(), None)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_xread_block_dollar_woken_by_xadd() {
+        let backend = Backend::new();
+        backend
+            .xadd("s".to_string(), Some(StreamId(1, 1)), vec![])
+            .unwrap();
+        let xread = XRead {
+            count: None,
+            block: Some(1000),
+            streams: vec![("s".to_string(), None)],
+        };
+        let reader = {
+            let backend = backend.clone();
+            tokio::spawn(async move { xread.execute_blocking(&backend).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        backend
+            .xadd(
+                "s".to_string(),
+                Some(StreamId(2, 0)),
+                vec![("f".to_string(), BulkString::new("v").into())],
+            )
+            .unwrap();
+        let expected: RespFrame = RespArray::new(vec![RespArray::new(vec![
+            BulkString::new("s").into(),
+            entries_to_frame(vec![(
+                StreamId(2, 0),
+                vec![("f".to_string(), BulkString::new("v").into())],
+            )]),
+        ])
+        .into()])
+        .into();
+        assert_eq!(reader.await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_xread_block_times_out() {
+        let backend = Backend::new();
+        let xread = XRead {
+            count: None,
+            block: Some(10),
+            streams: vec![("s".to_string(), None)],
+        };
+        let ret = xread.execute_blocking(&backend).await;
+        assert_eq!(ret, RespFrame::NullArray(RespNullArray));
+    }
+
+    #[tokio::test]
+    async fn test_xread_block_zero_waits_until_woken() {
+        let backend = Backend::new();
+        let xread = XRead {
+            count: None,
+            block: Some(0),
+            streams: vec![("s".to_string(), Some(StreamId::MIN))],
+        };
+        let reader = {
+            let backend = backend.clone();
+            tokio::spawn(async move { xread.execute_blocking(&backend).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!reader.is_finished());
+        backend
+            .xadd("s".to_string(), Some(StreamId(1, 0)), vec![])
+            .unwrap();
+        let ret = tokio::time::timeout(Duration::from_secs(1), reader)
+            .await
+            .expect("XREAD BLOCK 0 should be woken by XADD")
+            .unwrap();
+        assert!(matches!(ret, RespFrame::Array(_)));
+    }
+
+    #[test]
+    fn test_xgroup_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$6\r\nXGROUP\r\n$6\r\nCREATE\r\n$1\r\ns\r\n$1\r\ng\r\n$1\r\n$\r\n$8\r\nMKSTREAM\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let xgroup: XGroup = cmd.try_into()?;
+        assert_eq!(
+            xgroup.subcommand,
+            XGroupSubcommand::Create {
+                key: "s".to_string(),
+                group: "g".to_string(),
+                id: None,
+                mkstream: true,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_xreadgroup_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*10\r\n$10\r\nxreadgroup\r\n$5\r\nGROUP\r\n$1\r\ng\r\n$1\r\nc\r\n$5\r\nCOUNT\r\n$1\r\n1\r\n$7\r\nSTREAMS\r\n$1\r\na\r\n$1\r\n>\r\n$1\r\n0\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf);
+        // key 和 ID 数量不匹配
+        let ret: Result<XReadGroup, _> = cmd?.try_into();
+        assert!(ret.is_err());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*11\r\n$10\r\nxreadgroup\r\n$5\r\nGROUP\r\n$1\r\ng\r\n$1\r\nc\r\n$5\r\nCOUNT\r\n$1\r\n1\r\n$7\r\nSTREAMS\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\n>\r\n$1\r\n0\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let xreadgroup: XReadGroup = cmd.try_into()?;
+        assert_eq!(xreadgroup.group, "g");
+        assert_eq!(xreadgroup.consumer, "c");
+        assert_eq!(xreadgroup.count, Some(1));
+        assert_eq!(
+            xreadgroup.streams,
+            vec![
+                ("a".to_string(), None),
+                ("b".to_string(), Some(StreamId(0, 0)))
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_xgroup_create_errors() {
+        let backend = Backend::new();
+        let create = |mkstream| XGroup {
+            subcommand: XGroupSubcommand::Create {
+                key: "s".to_string(),
+                group: "g".to_string(),
+                id: Some(StreamId::MIN),
+                mkstream,
+            },
+        };
+        assert!(matches!(
+            create(false).execute(&backend),
+            RespFrame::Error(_)
+        ));
+        assert_eq!(create(true).execute(&backend), RespFrame::ok());
+        assert_eq!(backend.xlen("s"), 0);
+        assert_eq!(
+            create(true).execute(&backend),
+            RespFrame::error("BUSYGROUP Consumer Group name already exists")
+        );
+    }
+
+    #[test]
+    fn test_xreadgroup_delivers_each_entry_once() {
+        let backend = Backend::new();
+        for ms in 1..=3 {
+            backend
+                .xadd("s".to_string(), Some(StreamId(ms, 0)), vec![])
+                .unwrap();
+        }
+        backend
+            .xgroup_create("s", "g", Some(StreamId::MIN), false)
+            .unwrap();
+        let read = |consumer: &str, id: Option<StreamId>, count| {
+            backend
+                .xreadgroup("s", "g", consumer, id, count)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            read("alice", None, Some(2)),
+            [StreamId(1, 0), StreamId(2, 0)]
+        );
+        assert_eq!(read("bob", None, None), [StreamId(3, 0)]);
+        assert!(read("bob", None, None).is_empty());
+
+        // 显式 ID 只重放该消费者自己的待确认记录
+        assert_eq!(
+            read("alice", Some(StreamId::MIN), None),
+            [StreamId(1, 0), StreamId(2, 0)]
+        );
+        assert_eq!(read("bob", Some(StreamId::MIN), None), [StreamId(3, 0)]);
+
+        let xack = XAck {
+            key: "s".to_string(),
+            group: "g".to_string(),
+            ids: vec![StreamId(1, 0), StreamId(3, 0), StreamId(9, 0)],
+        };
+        assert_eq!(xack.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(read("alice", Some(StreamId::MIN), None), [StreamId(2, 0)]);
+        assert!(read("bob", Some(StreamId::MIN), None).is_empty());
+
+        let group = backend.xgroup("s", "g").unwrap();
+        assert_eq!(group.last_delivered, StreamId(3, 0));
+        assert_eq!(group.pending.len(), 1);
+        assert_eq!(group.pending[&StreamId(2, 0)].consumer, "alice");
+    }
+
+    #[test]
+    fn test_xreadgroup_execute_replies() {
+        let backend = Backend::new();
+        let xreadgroup = |id| XReadGroup {
+            group: "g".to_string(),
+            consumer: "c".to_string(),
+            count: None,
+            block: None,
+            streams: vec![("s".to_string(), id)],
+        };
+        assert!(matches!(
+            xreadgroup(None).execute(&backend),
+            RespFrame::Error(_)
+        ));
+
+        backend.xgroup_create("s", "g", None, true).unwrap();
+        assert_eq!(
+            xreadgroup(None).execute(&backend),
+            RespFrame::NullArray(RespNullArray)
+        );
+        let expected: RespFrame = RespArray::new(vec![RespArray::new(vec![
+            BulkString::new("s").into(),
+            RespArray::new(vec![]).into(),
+        ])
+        .into()])
+        .into();
+        assert_eq!(xreadgroup(Some(StreamId::MIN)).execute(&backend), expected);
+    }
+
+    #[tokio::test]
+    async fn test_xreadgroup_block_woken_by_xadd() {
+        let backend = Backend::new();
+        backend.xgroup_create("s", "g", None, true).unwrap();
+        let xreadgroup = XReadGroup {
+            group: "g".to_string(),
+            consumer: "c".to_string(),
+            count: None,
+            block: Some(1000),
+            streams: vec![("s".to_string(), None)],
+        };
+        let reader = {
+            let backend = backend.clone();
+            tokio::spawn(async move { xreadgroup.execute_blocking(&backend).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        backend
+            .xadd("s".to_string(), Some(StreamId(1, 0)), vec![])
+            .unwrap();
+        let expected: RespFrame = RespArray::new(vec![RespArray::new(vec![
+            BulkString::new("s").into(),
+            entries_to_frame(vec![(StreamId(1, 0), vec![])]),
+        ])
+        .into()])
+        .into();
+        assert_eq!(reader.await.unwrap(), expected);
+        assert_eq!(backend.xgroup("s", "g").unwrap().pending.len(), 1);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    cmd::{
+        extract_args, extract_integer, extract_string, validate_command, validate_command_at_least,
+    },
+    Backend, RespArray, RespFrame, MAX_BIT_OFFSET,
+};
+
+use super::{BitCount, CommandError, CommandExecutor, GetBit, SetBit};
+
+impl CommandExecutor for SetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.setbit(self.key, self.offset, self.value) {
+            Ok(old) => RespFrame::Integer(old as i64),
+            Err(e) => RespFrame::error(e.to_string()),
+        }
+    }
+}
+
+impl CommandExecutor for GetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getbit(&self.key, self.offset) {
+            Ok(bit) => RespFrame::Integer(bit as i64),
+            Err(e) => RespFrame::error(e.to_string()),
+        }
+    }
+}
+
+impl CommandExecutor for BitCount {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.bitcount(&self.key, self.range) {
+            Ok(count) => RespFrame::Integer(count as i64),
+            Err(e) => RespFrame::error(e.to_string()),
+        }
+    }
+}
+
+// SETBIT key offset 0|1
+impl TryFrom<RespArray> for SetBit {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setbit"], 3)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let offset = parse_bit_offset(args.next())?;
+        let value = match extract_string(args.next())?.as_str() {
+            "0" => false,
+            "1" => true,
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "bit is not an integer or out of range".to_string(),
+                ))
+            }
+        };
+        Ok(SetBit { key, offset, value })
+    }
+}
+
+// GETBIT key offset
+impl TryFrom<RespArray> for GetBit {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getbit"], 2)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetBit {
+            key: extract_string(args.next())?,
+            offset: parse_bit_offset(args.next())?,
+        })
+    }
+}
+
+// BITCOUNT key [start end]
+impl TryFrom<RespArray> for BitCount {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let range = match value.len() {
+            2 => false,
+            4 => true,
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        validate_command_at_least(&value, &["bitcount"], 1)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let range = if range {
+            Some((extract_integer(args.next())?, extract_integer(args.next())?))
+        } else {
+            None
+        };
+        Ok(BitCount { key, range })
+    }
+}
+
+// 位偏移量必须是非负整数，且不超过 MAX_BIT_OFFSET
+fn parse_bit_offset(frame: Option<RespFrame>) -> Result<u64, CommandError> {
+    extract_string(frame)?
+        .parse()
+        .ok()
+        .filter(|offset| *offset <= MAX_BIT_OFFSET)
+        .ok_or_else(|| {
+            CommandError::InvalidArgument(
+                "bit offset is not an integer or out of range".to_string(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cmd::Command, BulkString, RespDecoder};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use super::*;
+
+    fn parse(input: &[u8]) -> Result<Command> {
+        let mut buf = BytesMut::from(input);
+        Ok(RespArray::decode(&mut buf)?.try_into()?)
+    }
+
+    #[test]
+    fn test_setbit_from_resp_array() -> Result<()> {
+        let cmd = parse(b"*4\r\n$6\r\nsetbit\r\n$3\r\nkey\r\n$2\r\n10\r\n$1\r\n1\r\n")?;
+        let Command::SetBit(cmd) = cmd else {
+            panic!("expected SETBIT");
+        };
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.offset, 10);
+        assert!(cmd.value);
+
+        assert!(parse(b"*4\r\n$6\r\nsetbit\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n").is_err());
+        assert!(parse(b"*4\r\n$6\r\nsetbit\r\n$3\r\nkey\r\n$1\r\n0\r\n$1\r\n2\r\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitcount_from_resp_array() -> Result<()> {
+        let cmd = parse(b"*4\r\n$8\r\nbitcount\r\n$3\r\nkey\r\n$1\r\n1\r\n$2\r\n-1\r\n")?;
+        let Command::BitCount(cmd) = cmd else {
+            panic!("expected BITCOUNT");
+        };
+        assert_eq!(cmd.range, Some((1, -1)));
+
+        assert!(parse(b"*3\r\n$8\r\nbitcount\r\n$3\r\nkey\r\n$1\r\n1\r\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_setbit_past_end_and_bitcount() {
+        let backend = Backend::new();
+        let ret = SetBit {
+            key: "key".to_string(),
+            offset: 100,
+            value: true,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+        let Some(RespFrame::BulkString(value)) = backend.get("key") else {
+            panic!("SETBIT should store a bulk string");
+        };
+        assert_eq!(value.len(), 13);
+
+        let getbit = |offset| {
+            GetBit {
+                key: "key".to_string(),
+                offset,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(getbit(100), RespFrame::Integer(1));
+        assert_eq!(getbit(99), RespFrame::Integer(0));
+        assert_eq!(getbit(10_000), RespFrame::Integer(0));
+
+        backend.set("key".to_string(), BulkString::new("foobar").into());
+        let ret = BitCount {
+            key: "key".to_string(),
+            range: None,
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(26));
+        let ret = BitCount {
+            key: "key".to_string(),
+            range: Some((1, 1)),
+        }
+        .execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(6));
+    }
+}
@@ -0,0 +1,1205 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::RespArray;
+
+use super::{Command, CommandError};
+
+// 命令表中的一项：命令名、文档信息和解析函数。新增命令时只需要在 COMMAND_TABLE 中注册
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    // 参数个数（包含命令名本身），负数 -N 表示至少 N 个，和 Redis 的 arity 含义相同
+    pub arity: i64,
+    // 是否会修改数据，CLIENT PAUSE WRITE 只暂缓这些命令
+    pub write: bool,
+    pub group: &'static str,
+    pub since: &'static str,
+    pub summary: &'static str,
+    // 时间复杂度说明，和 Redis 文档一致，空字符串表示没有
+    pub complexity: &'static str,
+    // (参数名, 参数类型)
+    pub arguments: &'static [(&'static str, &'static str)],
+    pub(crate) parse: fn(RespArray) -> Result<Command, CommandError>,
+}
+
+pub static COMMAND_TABLE: &[CommandSpec] = &[
+    CommandSpec {
+        name: "get",
+        arity: 2,
+        write: false,
+        group: "string",
+        since: "1.0.0",
+        summary: "Returns the string value of a key.",
+        complexity: "O(1)",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::Get),
+    },
+    CommandSpec {
+        name: "set",
+        arity: -3,
+        write: true,
+        group: "string",
+        since: "1.0.0",
+        summary: "Sets the string value of a key, ignoring its type.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("value", "string")],
+        parse: |v| v.try_into().map(Command::Set),
+    },
+    CommandSpec {
+        name: "mset",
+        arity: -3,
+        write: true,
+        group: "string",
+        since: "1.0.1",
+        summary: "Atomically creates or modifies the string values of one or more keys.",
+        complexity: "O(N) where N is the number of keys to set.",
+        arguments: &[("key", "key"), ("value", "string")],
+        parse: |v| v.try_into().map(Command::MSet),
+    },
+    CommandSpec {
+        name: "setbit",
+        arity: 4,
+        write: true,
+        group: "bitmap",
+        since: "2.2.0",
+        summary: "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("offset", "integer"), ("value", "integer")],
+        parse: |v| v.try_into().map(Command::SetBit),
+    },
+    CommandSpec {
+        name: "getbit",
+        arity: 3,
+        write: false,
+        group: "bitmap",
+        since: "2.2.0",
+        summary: "Returns a bit value by offset.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("offset", "integer")],
+        parse: |v| v.try_into().map(Command::GetBit),
+    },
+    CommandSpec {
+        name: "bitcount",
+        arity: -2,
+        write: false,
+        group: "bitmap",
+        since: "2.6.0",
+        summary: "Counts the number of set bits (population counting) in a string.",
+        complexity: "O(N)",
+        arguments: &[("key", "key"), ("start", "integer"), ("end", "integer")],
+        parse: |v| v.try_into().map(Command::BitCount),
+    },
+    CommandSpec {
+        name: "hget",
+        arity: 3,
+        write: false,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns the value of a field in a hash.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("field", "string")],
+        parse: |v| v.try_into().map(Command::HGet),
+    },
+    CommandSpec {
+        name: "hset",
+        arity: -4,
+        write: true,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Creates or modifies the value of a field in a hash.",
+        complexity: "O(1) for each field/value pair added, so O(N) to add N field/value pairs when the command is called with multiple field/value pairs.",
+        arguments: &[("key", "key"), ("field", "string"), ("value", "string")],
+        parse: |v| v.try_into().map(Command::HSet),
+    },
+    CommandSpec {
+        name: "hgetall",
+        arity: 2,
+        write: false,
+        group: "hash",
+        since: "2.0.0",
+        summary: "Returns all fields and values in a hash.",
+        complexity: "O(N) where N is the size of the hash.",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::HGetAll),
+    },
+    CommandSpec {
+        name: "hscan",
+        arity: -3,
+        write: false,
+        group: "hash",
+        since: "2.8.0",
+        summary: "Iterates over fields and values of a hash.",
+        complexity: "O(N) for every call, where N is the number of elements inside the collection: each call hashes them to find the slots after the cursor.",
+        arguments: &[("key", "key"), ("cursor", "integer"), ("pattern", "pattern"), ("count", "integer")],
+        parse: |v| v.try_into().map(Command::HScan),
+    },
+    CommandSpec {
+        name: "hrandfield",
+        arity: -2,
+        write: false,
+        group: "hash",
+        since: "6.2.0",
+        summary: "Returns one or more random fields from a hash.",
+        complexity: "O(N) where N is the number of fields returned",
+        arguments: &[("key", "key"), ("count", "integer"), ("withvalues", "pure-token")],
+        parse: |v| v.try_into().map(Command::HRandField),
+    },
+    CommandSpec {
+        name: "lpush",
+        arity: -3,
+        write: true,
+        group: "list",
+        since: "1.0.0",
+        summary: "Prepends one or more elements to a list. Creates the key if it doesn't exist.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        arguments: &[("key", "key"), ("element", "string")],
+        parse: |v| v.try_into().map(Command::LPush),
+    },
+    CommandSpec {
+        name: "rpush",
+        arity: -3,
+        write: true,
+        group: "list",
+        since: "1.0.0",
+        summary: "Appends one or more elements to a list. Creates the key if it doesn't exist.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        arguments: &[("key", "key"), ("element", "string")],
+        parse: |v| v.try_into().map(Command::RPush),
+    },
+    CommandSpec {
+        name: "lrange",
+        arity: 4,
+        write: false,
+        group: "list",
+        since: "1.0.0",
+        summary: "Returns a range of elements from a list.",
+        complexity: "O(S+N) where S is the distance of start offset from HEAD for small lists, from nearest end (HEAD or TAIL) for large lists; and N is the number of elements in the specified range.",
+        arguments: &[("key", "key"), ("start", "integer"), ("stop", "integer")],
+        parse: |v| v.try_into().map(Command::LRange),
+    },
+    CommandSpec {
+        name: "lpos",
+        arity: -3,
+        write: false,
+        group: "list",
+        since: "6.0.6",
+        summary: "Returns the index of matching elements in a list.",
+        complexity: "O(N) where N is the number of elements in the list, for the average case. When searching for elements near the head or the tail of the list, or when the MAXLEN option is provided, the command may run in constant time.",
+        arguments: &[("key", "key"), ("element", "string"), ("rank", "integer"), ("num-matches", "integer"), ("len", "integer")],
+        parse: |v| v.try_into().map(Command::LPos),
+    },
+    CommandSpec {
+        name: "lmpop",
+        arity: -4,
+        write: true,
+        group: "list",
+        since: "7.0.0",
+        summary: "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped.",
+        complexity: "O(N+M) where N is the number of provided keys and M is the number of elements returned.",
+        arguments: &[("numkeys", "integer"), ("key", "key"), ("where", "oneof"), ("count", "integer")],
+        parse: |v| v.try_into().map(Command::LMPop),
+    },
+    CommandSpec {
+        name: "blpop",
+        arity: -3,
+        write: true,
+        group: "list",
+        since: "2.0.0",
+        summary: "Removes and returns the first element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        complexity: "O(N) where N is the number of provided keys.",
+        arguments: &[("key", "key"), ("timeout", "double")],
+        parse: |v| v.try_into().map(Command::BLPop),
+    },
+    CommandSpec {
+        name: "brpop",
+        arity: -3,
+        write: true,
+        group: "list",
+        since: "2.0.0",
+        summary: "Removes and returns the last element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        complexity: "O(N) where N is the number of provided keys.",
+        arguments: &[("key", "key"), ("timeout", "double")],
+        parse: |v| v.try_into().map(Command::BRPop),
+    },
+    CommandSpec {
+        name: "sadd",
+        arity: -3,
+        write: true,
+        group: "set",
+        since: "1.0.0",
+        summary: "Adds one or more members to a set. Creates the key if it doesn't exist.",
+        complexity: "O(1) for each element added, so O(N) to add N elements when the command is called with multiple arguments.",
+        arguments: &[("key", "key"), ("member", "string")],
+        parse: |v| v.try_into().map(Command::SAdd),
+    },
+    CommandSpec {
+        name: "smembers",
+        arity: 2,
+        write: false,
+        group: "set",
+        since: "1.0.0",
+        summary: "Returns all members of a set.",
+        complexity: "O(N) where N is the set cardinality.",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::SMembers),
+    },
+    CommandSpec {
+        name: "sscan",
+        arity: -3,
+        write: false,
+        group: "set",
+        since: "2.8.0",
+        summary: "Iterates over members of a set.",
+        complexity: "O(N) for every call, where N is the number of elements inside the collection: each call hashes them to find the slots after the cursor.",
+        arguments: &[("key", "key"), ("cursor", "integer"), ("pattern", "pattern"), ("count", "integer")],
+        parse: |v| v.try_into().map(Command::SScan),
+    },
+    CommandSpec {
+        name: "sintercard",
+        arity: -3,
+        write: false,
+        group: "set",
+        since: "7.0.0",
+        summary: "Returns the number of members of the intersect of multiple sets.",
+        complexity: "O(N*M) worst case where N is the cardinality of the smallest set and M is the number of sets.",
+        arguments: &[("numkeys", "integer"), ("key", "key"), ("limit", "integer")],
+        parse: |v| v.try_into().map(Command::SInterCard),
+    },
+    CommandSpec {
+        name: "del",
+        arity: -2,
+        write: true,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Deletes one or more keys.",
+        complexity: "O(N) where N is the number of keys that will be removed. When a key to remove holds a value other than a string, the individual complexity for this key is O(M) where M is the number of elements in the list, set, sorted set or hash. Removing a single key that holds a string value is O(1).",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::Del),
+    },
+    CommandSpec {
+        name: "copy",
+        arity: -3,
+        write: true,
+        group: "generic",
+        since: "6.2.0",
+        summary: "Copies the value of a key to a new key.",
+        complexity: "O(N) worst case for collections, where N is the number of nested items. O(1) for string values.",
+        arguments: &[("source", "key"), ("destination", "key"), ("destination-db", "integer"), ("replace", "pure-token")],
+        parse: |v| v.try_into().map(Command::Copy),
+    },
+    CommandSpec {
+        name: "scan",
+        arity: -2,
+        write: false,
+        group: "generic",
+        since: "2.8.0",
+        summary: "Iterates over the key names in the database.",
+        complexity: "O(N) for every call, where N is the number of elements inside the collection: each call hashes them to find the slots after the cursor.",
+        arguments: &[("cursor", "integer"), ("pattern", "pattern"), ("count", "integer"), ("type", "string")],
+        parse: |v| v.try_into().map(Command::Scan),
+    },
+    CommandSpec {
+        name: "expire",
+        arity: -3,
+        write: true,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Sets the expiration time of a key in seconds.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("seconds", "integer"), ("condition", "oneof")],
+        parse: |v| v.try_into().map(Command::Expire),
+    },
+    CommandSpec {
+        name: "pexpire",
+        arity: -3,
+        write: true,
+        group: "generic",
+        since: "2.6.0",
+        summary: "Sets the expiration time of a key in milliseconds.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("milliseconds", "integer"), ("condition", "oneof")],
+        parse: |v| v.try_into().map(Command::PExpire),
+    },
+    CommandSpec {
+        name: "expireat",
+        arity: -3,
+        write: true,
+        group: "generic",
+        since: "1.2.0",
+        summary: "Sets the expiration time of a key to a Unix timestamp.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("unix-time-seconds", "unix-time")],
+        parse: |v| v.try_into().map(Command::ExpireAt),
+    },
+    CommandSpec {
+        name: "pexpireat",
+        arity: -3,
+        write: true,
+        group: "generic",
+        since: "2.6.0",
+        summary: "Sets the expiration time of a key to a Unix milliseconds timestamp.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("unix-time-milliseconds", "unix-time")],
+        parse: |v| v.try_into().map(Command::PExpireAt),
+    },
+    CommandSpec {
+        name: "ttl",
+        arity: 2,
+        write: false,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Returns the expiration time in seconds of a key.",
+        complexity: "O(1)",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::Ttl),
+    },
+    CommandSpec {
+        name: "pttl",
+        arity: 2,
+        write: false,
+        group: "generic",
+        since: "2.6.0",
+        summary: "Returns the expiration time in milliseconds of a key.",
+        complexity: "O(1)",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::PTtl),
+    },
+    CommandSpec {
+        name: "expiretime",
+        arity: 2,
+        write: false,
+        group: "generic",
+        since: "7.0.0",
+        summary: "Returns the expiration time of a key as a Unix timestamp.",
+        complexity: "O(1)",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::ExpireTime),
+    },
+    CommandSpec {
+        name: "pexpiretime",
+        arity: 2,
+        write: false,
+        group: "generic",
+        since: "7.0.0",
+        summary: "Returns the expiration time of a key as a Unix milliseconds timestamp.",
+        complexity: "O(1)",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::PExpireTime),
+    },
+    CommandSpec {
+        name: "sort",
+        arity: -2,
+        write: true,
+        group: "generic",
+        since: "1.0.0",
+        summary: "Sorts the elements in a list, a set, or a sorted set, optionally storing the result.",
+        complexity: "O(N+M*log(M)) where N is the number of elements in the list or set to sort, and M the number of returned elements. When the elements are not sorted, complexity is O(N).",
+        arguments: &[("key", "key"), ("offset", "integer"), ("count", "integer"), ("order", "oneof"), ("sorting", "pure-token"), ("destination", "key")],
+        parse: |v| v.try_into().map(Command::Sort),
+    },
+    CommandSpec {
+        name: "xadd",
+        arity: -5,
+        write: true,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Appends a new message to a stream. Creates the key if it doesn't exist.",
+        complexity: "O(1) when adding a new entry, O(N) when trimming where N being the number of entries evicted.",
+        arguments: &[("key", "key"), ("id", "string"), ("field", "string"), ("value", "string")],
+        parse: |v| v.try_into().map(Command::XAdd),
+    },
+    CommandSpec {
+        name: "xread",
+        arity: -4,
+        write: false,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns messages from multiple streams with IDs greater than the ones requested. Blocks until a message is available otherwise.",
+        complexity: "For each stream mentioned: O(N) with N being the number of elements being returned, it means that XREAD-ing with a fixed COUNT is O(1). Note that when the BLOCK option is used, XADD will pay O(M) time in order to serve the M clients blocked on the stream getting new data.",
+        arguments: &[("count", "integer"), ("milliseconds", "integer"), ("key", "key"), ("id", "string")],
+        parse: |v| v.try_into().map(Command::XRead),
+    },
+    CommandSpec {
+        name: "xrange",
+        arity: -4,
+        write: false,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the messages from a stream within a range of IDs.",
+        complexity: "O(N) with N being the number of elements being returned. If N is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1).",
+        arguments: &[("key", "key"), ("start", "string"), ("end", "string"), ("count", "integer")],
+        parse: |v| v.try_into().map(Command::XRange),
+    },
+    CommandSpec {
+        name: "xlen",
+        arity: 2,
+        write: false,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Return the number of messages in a stream.",
+        complexity: "O(1)",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::XLen),
+    },
+    CommandSpec {
+        name: "xdel",
+        arity: -3,
+        write: true,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the number of messages after removing them from a stream.",
+        complexity: "O(1) for each single item to delete in the stream, regardless of the stream size.",
+        arguments: &[("key", "key"), ("id", "string")],
+        parse: |v| v.try_into().map(Command::XDel),
+    },
+    CommandSpec {
+        name: "xtrim",
+        arity: -4,
+        write: true,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Deletes messages from the beginning of a stream.",
+        complexity: "O(N), with N being the number of evicted entries. Constant times are very small however, since entries are organized in macro nodes containing multiple entries that can be released with a single deallocation.",
+        arguments: &[("key", "key"), ("strategy", "oneof"), ("operator", "oneof"), ("threshold", "string")],
+        parse: |v| v.try_into().map(Command::XTrim),
+    },
+    CommandSpec {
+        name: "xgroup",
+        arity: -2,
+        write: true,
+        group: "stream",
+        since: "5.0.0",
+        summary: "A container for consumer groups commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("key", "key"), ("group", "string"), ("id", "string"), ("mkstream", "pure-token")],
+        parse: |v| v.try_into().map(Command::XGroup),
+    },
+    CommandSpec {
+        name: "xreadgroup",
+        arity: -7,
+        write: true,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns new or historical messages from a stream for a consumer in a group. Blocks until a message is available otherwise.",
+        complexity: "For each stream mentioned: O(M) with M being the number of elements returned. If M is constant (e.g. always asking for the first 10 elements with COUNT), you can consider it O(1). On the other side when XREADGROUP blocks, XADD will pay the O(N) time in order to serve the N clients blocked on the stream getting new data.",
+        arguments: &[("group", "string"), ("consumer", "string"), ("count", "integer"), ("milliseconds", "integer"), ("key", "key"), ("id", "string")],
+        parse: |v| v.try_into().map(Command::XReadGroup),
+    },
+    CommandSpec {
+        name: "xack",
+        arity: -4,
+        write: true,
+        group: "stream",
+        since: "5.0.0",
+        summary: "Returns the number of messages that were successfully acknowledged by the consumer group member of a stream.",
+        complexity: "O(1) for each message ID processed.",
+        arguments: &[("key", "key"), ("group", "string"), ("id", "string")],
+        parse: |v| v.try_into().map(Command::XAck),
+    },
+    CommandSpec {
+        name: "zadd",
+        arity: -4,
+        write: true,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Adds one or more members to a sorted set, or updates their scores. Creates the key if it doesn't exist.",
+        complexity: "O(log(N)) for each item added, where N is the number of elements in the sorted set.",
+        arguments: &[("key", "key"), ("condition", "oneof"), ("change", "pure-token"), ("score", "double"), ("member", "string")],
+        parse: |v| v.try_into().map(Command::ZAdd),
+    },
+    CommandSpec {
+        name: "zincrby",
+        arity: 4,
+        write: true,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Increments the score of a member in a sorted set.",
+        complexity: "O(log(N)) where N is the number of elements in the sorted set.",
+        arguments: &[("key", "key"), ("increment", "integer"), ("member", "string")],
+        parse: |v| v.try_into().map(Command::ZIncrBy),
+    },
+    CommandSpec {
+        name: "zrank",
+        arity: 3,
+        write: false,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Returns the index of a member in a sorted set ordered by ascending scores.",
+        complexity: "O(N) where N is the number of members ranked lower than the given member.",
+        arguments: &[("key", "key"), ("member", "string")],
+        parse: |v| v.try_into().map(Command::ZRank),
+    },
+    CommandSpec {
+        name: "zcard",
+        arity: 2,
+        write: false,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Returns the number of members in a sorted set.",
+        complexity: "O(1)",
+        arguments: &[("key", "key")],
+        parse: |v| v.try_into().map(Command::ZCard),
+    },
+    CommandSpec {
+        name: "zrem",
+        arity: -3,
+        write: true,
+        group: "sorted-set",
+        since: "1.2.0",
+        summary: "Removes one or more members from a sorted set. Deletes the sorted set if all members were removed.",
+        complexity: "O(M*log(N)) with N being the number of elements in the sorted set and M the number of elements to be removed.",
+        arguments: &[("key", "key"), ("member", "string")],
+        parse: |v| v.try_into().map(Command::ZRem),
+    },
+    CommandSpec {
+        name: "zrangebyscore",
+        arity: -4,
+        write: false,
+        group: "sorted-set",
+        since: "1.0.5",
+        summary: "Returns members in a sorted set within a range of scores.",
+        complexity: "O(log(N)+M) with N being the number of elements in the sorted set and M the number of elements being returned. If M is constant (e.g. always asking for the first 10 elements with LIMIT), you can consider it O(log(N)).",
+        arguments: &[("key", "key"), ("min", "double"), ("max", "double"), ("withscores", "pure-token"), ("offset", "integer"), ("count", "integer")],
+        parse: |v| v.try_into().map(Command::ZRangeByScore),
+    },
+    CommandSpec {
+        name: "zunionstore",
+        arity: -4,
+        write: true,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Stores the union of multiple sorted sets in a key.",
+        complexity: "O(N)+O(M log(M)) with N being the sum of the sizes of the input sorted sets, and M being the number of elements in the resulting sorted set.",
+        arguments: &[("destination", "key"), ("numkeys", "integer"), ("key", "key"), ("weight", "integer"), ("aggregate", "oneof")],
+        parse: |v| v.try_into().map(Command::ZUnionStore),
+    },
+    CommandSpec {
+        name: "zinterstore",
+        arity: -4,
+        write: true,
+        group: "sorted-set",
+        since: "2.0.0",
+        summary: "Stores the intersect of multiple sorted sets in a key.",
+        complexity: "O(N*K)+O(M*log(M)) worst case with N being the smallest input sorted set, K being the number of input sorted sets and M being the number of elements in the resulting sorted set.",
+        arguments: &[("destination", "key"), ("numkeys", "integer"), ("key", "key"), ("weight", "integer"), ("aggregate", "oneof")],
+        parse: |v| v.try_into().map(Command::ZInterStore),
+    },
+    CommandSpec {
+        name: "zdiffstore",
+        arity: -4,
+        write: true,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Stores the difference of multiple sorted sets in a key.",
+        complexity: "O(L + (N-K)log(N)) worst case where L is the total number of elements in all the sets, N is the size of the first set, and K is the size of the result set.",
+        arguments: &[("destination", "key"), ("numkeys", "integer"), ("key", "key")],
+        parse: |v| v.try_into().map(Command::ZDiffStore),
+    },
+    CommandSpec {
+        name: "zunion",
+        arity: -3,
+        write: false,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns the union of multiple sorted sets.",
+        complexity: "O(N)+O(M log(M)) with N being the sum of the sizes of the input sorted sets, and M being the number of elements in the resulting sorted set.",
+        arguments: &[("numkeys", "integer"), ("key", "key"), ("weight", "integer"), ("aggregate", "oneof"), ("withscores", "pure-token")],
+        parse: |v| v.try_into().map(Command::ZUnion),
+    },
+    CommandSpec {
+        name: "zinter",
+        arity: -3,
+        write: false,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns the intersect of multiple sorted sets.",
+        complexity: "O(N*K)+O(M*log(M)) worst case with N being the smallest input sorted set, K being the number of input sorted sets and M being the number of elements in the resulting sorted set.",
+        arguments: &[("numkeys", "integer"), ("key", "key"), ("weight", "integer"), ("aggregate", "oneof"), ("withscores", "pure-token")],
+        parse: |v| v.try_into().map(Command::ZInter),
+    },
+    CommandSpec {
+        name: "zdiff",
+        arity: -3,
+        write: false,
+        group: "sorted-set",
+        since: "6.2.0",
+        summary: "Returns the difference between multiple sorted sets.",
+        complexity: "O(L + (N-K)log(N)) worst case where L is the total number of elements in all the sets, N is the size of the first set, and K is the size of the result set.",
+        arguments: &[("numkeys", "integer"), ("key", "key"), ("withscores", "pure-token")],
+        parse: |v| v.try_into().map(Command::ZDiff),
+    },
+    CommandSpec {
+        name: "zmpop",
+        arity: -4,
+        write: true,
+        group: "sorted-set",
+        since: "7.0.0",
+        summary: "Returns the highest- or lowest-scoring members from one or more sorted sets after removing them. Deletes the sorted set if the last member was popped.",
+        complexity: "O(K) + O(M*log(N)) where K is the number of provided keys, N being the number of elements in the sorted set, and M being the number of elements popped.",
+        arguments: &[("numkeys", "integer"), ("key", "key"), ("where", "oneof"), ("count", "integer")],
+        parse: |v| v.try_into().map(Command::ZMPop),
+    },
+    CommandSpec {
+        name: "geoadd",
+        arity: -5,
+        write: true,
+        group: "geo",
+        since: "3.2.0",
+        summary: "Adds one or more members to a geospatial index. The key is created if it doesn't exist.",
+        complexity: "O(log(N)) for each item added, where N is the number of elements in the sorted set.",
+        arguments: &[("key", "key"), ("condition", "oneof"), ("change", "pure-token"), ("longitude", "double"), ("latitude", "double"), ("member", "string")],
+        parse: |v| v.try_into().map(Command::GeoAdd),
+    },
+    CommandSpec {
+        name: "geopos",
+        arity: -2,
+        write: false,
+        group: "geo",
+        since: "3.2.0",
+        summary: "Returns the longitude and latitude of members from a geospatial index.",
+        complexity: "O(1) for each member requested.",
+        arguments: &[("key", "key"), ("member", "string")],
+        parse: |v| v.try_into().map(Command::GeoPos),
+    },
+    CommandSpec {
+        name: "geodist",
+        arity: -4,
+        write: false,
+        group: "geo",
+        since: "3.2.0",
+        summary: "Returns the distance between two members of a geospatial index.",
+        complexity: "O(1)",
+        arguments: &[("key", "key"), ("member1", "string"), ("member2", "string"), ("unit", "oneof")],
+        parse: |v| v.try_into().map(Command::GeoDist),
+    },
+    CommandSpec {
+        name: "geosearch",
+        arity: -7,
+        write: false,
+        group: "geo",
+        since: "6.2.0",
+        summary: "Queries a geospatial index for members inside an area of a box or a circle.",
+        complexity: "O(N+log(M)) where N is the number of elements in the grid-aligned bounding box area around the shape provided as the filter and M is the number of items inside the shape",
+        arguments: &[("key", "key"), ("from", "oneof"), ("by", "oneof"), ("order", "oneof"), ("count", "integer"), ("withcoord", "pure-token"), ("withdist", "pure-token")],
+        parse: |v| v.try_into().map(Command::GeoSearch),
+    },
+    CommandSpec {
+        name: "georadius",
+        arity: -6,
+        write: true,
+        group: "geo",
+        since: "3.2.0",
+        summary: "Queries a geospatial index for members within a distance from a coordinate, optionally stores the result.",
+        complexity: "O(N+log(M)) where N is the number of elements inside the bounding box of the circular area delimited by center and radius and M is the number of items inside the index.",
+        arguments: &[("key", "key"), ("longitude", "double"), ("latitude", "double"), ("radius", "double"), ("unit", "oneof"), ("withcoord", "pure-token"), ("withdist", "pure-token"), ("count", "integer"), ("order", "oneof"), ("store", "key")],
+        parse: |v| v.try_into().map(Command::GeoRadius),
+    },
+    CommandSpec {
+        name: "georadiusbymember",
+        arity: -5,
+        write: true,
+        group: "geo",
+        since: "3.2.0",
+        summary: "Queries a geospatial index for members within a distance from a member, optionally stores the result.",
+        complexity: "O(N+log(M)) where N is the number of elements inside the bounding box of the circular area delimited by center and radius and M is the number of items inside the index.",
+        arguments: &[("key", "key"), ("member", "string"), ("radius", "double"), ("unit", "oneof"), ("withcoord", "pure-token"), ("withdist", "pure-token"), ("count", "integer"), ("order", "oneof"), ("store", "key")],
+        parse: |v| v.try_into().map(Command::GeoRadiusByMember),
+    },
+    CommandSpec {
+        name: "object",
+        arity: -2,
+        write: false,
+        group: "generic",
+        since: "2.2.3",
+        summary: "A container for object introspection commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("key", "key")],
+        parse: |v| v.try_into().map(Command::Object),
+    },
+    CommandSpec {
+        name: "debug",
+        arity: -2,
+        write: false,
+        group: "server",
+        since: "1.0.0",
+        summary: "A container for debugging commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("argument", "string")],
+        parse: |v| v.try_into().map(Command::Debug),
+    },
+    CommandSpec {
+        name: "wait",
+        arity: 3,
+        write: false,
+        group: "generic",
+        since: "3.0.0",
+        summary: "Blocks until the asynchronous replication of all preceding write commands sent by the connection is completed.",
+        complexity: "O(1)",
+        arguments: &[("numreplicas", "integer"), ("timeout", "integer")],
+        parse: |v| v.try_into().map(Command::Wait),
+    },
+    CommandSpec {
+        name: "readonly",
+        arity: 1,
+        write: false,
+        group: "cluster",
+        since: "3.0.0",
+        summary: "Enables read-only queries for a connection to a Redis Cluster replica node.",
+        complexity: "O(1)",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::ReadOnly),
+    },
+    CommandSpec {
+        name: "readwrite",
+        arity: 1,
+        write: false,
+        group: "cluster",
+        since: "3.0.0",
+        summary: "Enables read-write queries for a connection to a Redis Cluster replica node.",
+        complexity: "O(1)",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::ReadWrite),
+    },
+    CommandSpec {
+        name: "asking",
+        arity: 1,
+        write: false,
+        group: "cluster",
+        since: "3.0.0",
+        summary: "Signals that a cluster client is following an -ASK redirect.",
+        complexity: "O(1)",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::Asking),
+    },
+    CommandSpec {
+        name: "cluster",
+        arity: -2,
+        write: false,
+        group: "cluster",
+        since: "3.0.0",
+        summary: "A container for Redis Cluster commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string")],
+        parse: |v| v.try_into().map(Command::Cluster),
+    },
+    CommandSpec {
+        name: "save",
+        arity: 1,
+        write: false,
+        group: "server",
+        since: "1.0.0",
+        summary: "Synchronously saves the database(s) to disk.",
+        complexity: "O(N) where N is the total number of keys in all databases",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::Save),
+    },
+    CommandSpec {
+        name: "sync",
+        arity: 1,
+        write: false,
+        group: "server",
+        since: "1.0.0",
+        summary: "An internal command used in replication.",
+        complexity: "",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::Sync),
+    },
+    CommandSpec {
+        name: "bgsave",
+        arity: 1,
+        write: false,
+        group: "server",
+        since: "1.0.0",
+        summary: "Asynchronously saves the database(s) to disk.",
+        complexity: "O(1)",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::BgSave),
+    },
+    CommandSpec {
+        name: "lastsave",
+        arity: 1,
+        write: false,
+        group: "server",
+        since: "1.0.0",
+        summary: "Returns the Unix timestamp of the last successful save to disk.",
+        complexity: "O(1)",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::LastSave),
+    },
+    CommandSpec {
+        name: "subscribe",
+        arity: -2,
+        write: false,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Listens for messages published to channels.",
+        complexity: "O(N) where N is the number of channels to subscribe to.",
+        arguments: &[("channel", "string")],
+        parse: |v| v.try_into().map(Command::Subscribe),
+    },
+    CommandSpec {
+        name: "unsubscribe",
+        arity: -1,
+        write: false,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Stops listening to messages posted to channels.",
+        complexity: "O(N) where N is the number of channels to unsubscribe.",
+        arguments: &[("channel", "string")],
+        parse: |v| v.try_into().map(Command::Unsubscribe),
+    },
+    CommandSpec {
+        name: "publish",
+        arity: 3,
+        write: false,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Posts a message to a channel.",
+        complexity: "O(N+M) where N is the number of clients subscribed to the receiving channel and M is the total number of subscribed patterns (by any client).",
+        arguments: &[("channel", "string"), ("message", "string")],
+        parse: |v| v.try_into().map(Command::Publish),
+    },
+    CommandSpec {
+        name: "psubscribe",
+        arity: -2,
+        write: false,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Listens for messages published to channels that match one or more patterns.",
+        complexity: "O(N) where N is the number of patterns to subscribe to.",
+        arguments: &[("pattern", "pattern")],
+        parse: |v| v.try_into().map(Command::PSubscribe),
+    },
+    CommandSpec {
+        name: "punsubscribe",
+        arity: -1,
+        write: false,
+        group: "pubsub",
+        since: "2.0.0",
+        summary: "Stops listening to messages published to channels that match one or more patterns.",
+        complexity: "O(N) where N is the number of patterns to unsubscribe.",
+        arguments: &[("pattern", "pattern")],
+        parse: |v| v.try_into().map(Command::PUnsubscribe),
+    },
+    CommandSpec {
+        name: "pubsub",
+        arity: -2,
+        write: false,
+        group: "pubsub",
+        since: "2.8.0",
+        summary: "A container for Pub/Sub commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("argument", "string")],
+        parse: |v| v.try_into().map(Command::PubSub),
+    },
+    CommandSpec {
+        name: "monitor",
+        arity: 1,
+        write: false,
+        group: "server",
+        since: "1.0.0",
+        summary: "Listens for all requests received by the server in real-time.",
+        complexity: "",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::Monitor),
+    },
+    #[cfg(feature = "scripting")]
+    CommandSpec {
+        name: "eval",
+        arity: -3,
+        write: true,
+        group: "scripting",
+        since: "2.6.0",
+        summary: "Executes a server-side Lua script.",
+        complexity: "Depends on the script that is executed.",
+        arguments: &[("script", "string"), ("numkeys", "integer"), ("key", "key"), ("arg", "string")],
+        parse: |v| v.try_into().map(Command::Eval),
+    },
+    #[cfg(feature = "scripting")]
+    CommandSpec {
+        name: "evalsha",
+        arity: -3,
+        write: true,
+        group: "scripting",
+        since: "2.6.0",
+        summary: "Executes a server-side Lua script by SHA1 digest.",
+        complexity: "Depends on the script that is executed.",
+        arguments: &[("sha1", "string"), ("numkeys", "integer"), ("key", "key"), ("arg", "string")],
+        parse: |v| v.try_into().map(Command::EvalSha),
+    },
+    #[cfg(feature = "scripting")]
+    CommandSpec {
+        name: "script",
+        arity: -2,
+        write: false,
+        group: "scripting",
+        since: "2.6.0",
+        summary: "A container for Lua scripts management commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("arg", "string")],
+        parse: |v| v.try_into().map(Command::Script),
+    },
+    CommandSpec {
+        name: "info",
+        arity: -1,
+        write: false,
+        group: "server",
+        since: "1.0.0",
+        summary: "Returns information and statistics about the server.",
+        complexity: "O(1)",
+        arguments: &[("section", "string")],
+        parse: |v| v.try_into().map(Command::Info),
+    },
+    CommandSpec {
+        name: "slowlog",
+        arity: -2,
+        write: false,
+        group: "server",
+        since: "2.2.12",
+        summary: "A container for slow log commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("count", "integer")],
+        parse: |v| v.try_into().map(Command::Slowlog),
+    },
+    CommandSpec {
+        name: "config",
+        arity: -2,
+        write: false,
+        group: "server",
+        since: "2.0.0",
+        summary: "A container for server configuration commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("parameter", "string"), ("value", "string")],
+        parse: |v| v.try_into().map(Command::Config),
+    },
+    CommandSpec {
+        name: "command",
+        arity: -1,
+        write: false,
+        group: "server",
+        since: "2.8.13",
+        summary: "A container for command introspection commands.",
+        complexity: "O(N) where N is the total number of Redis commands",
+        arguments: &[("subcommand", "string"), ("command-name", "string"), ("arg", "string")],
+        parse: |v| v.try_into().map(Command::CommandMeta),
+    },
+    CommandSpec {
+        name: "quit",
+        arity: -1,
+        write: false,
+        group: "connection",
+        since: "1.0.0",
+        summary: "Closes the connection.",
+        complexity: "O(1)",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::Quit),
+    },
+    CommandSpec {
+        name: "reset",
+        arity: 1,
+        write: false,
+        group: "connection",
+        since: "6.2.0",
+        summary: "Resets the connection.",
+        complexity: "O(1)",
+        arguments: &[],
+        parse: |v| v.try_into().map(Command::Reset),
+    },
+    CommandSpec {
+        name: "ping",
+        arity: -1,
+        write: false,
+        group: "connection",
+        since: "1.0.0",
+        summary: "Returns the server's liveliness response.",
+        complexity: "O(1)",
+        arguments: &[("message", "string")],
+        parse: |v| v.try_into().map(Command::Ping),
+    },
+    CommandSpec {
+        name: "auth",
+        arity: -2,
+        write: false,
+        group: "connection",
+        since: "1.0.0",
+        summary: "Authenticates the connection.",
+        complexity: "O(N) where N is the number of passwords defined for the user",
+        arguments: &[("username", "string"), ("password", "string")],
+        parse: |v| v.try_into().map(Command::Auth),
+    },
+    CommandSpec {
+        name: "hello",
+        arity: -1,
+        write: false,
+        group: "connection",
+        since: "6.0.0",
+        summary: "Handshakes with the Redis server.",
+        complexity: "O(1)",
+        arguments: &[("protover", "integer"), ("username", "string"), ("password", "string"), ("clientname", "string")],
+        parse: |v| v.try_into().map(Command::Hello),
+    },
+    CommandSpec {
+        name: "client",
+        arity: -2,
+        write: false,
+        group: "connection",
+        since: "2.4.0",
+        summary: "A container for client connection commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("filter", "oneof"), ("connection-name", "string")],
+        parse: |v| v.try_into().map(Command::Client),
+    },
+    CommandSpec {
+        name: "acl",
+        arity: -2,
+        write: false,
+        group: "server",
+        since: "6.0.0",
+        summary: "A container for Access List Control commands.",
+        complexity: "Depends on subcommand.",
+        arguments: &[("subcommand", "string"), ("username", "string"), ("rule", "string")],
+        parse: |v| v.try_into().map(Command::Acl),
+    },
+];
+
+lazy_static! {
+    static ref COMMAND_INDEX: HashMap<&'static str, &'static CommandSpec> =
+        COMMAND_TABLE.iter().map(|spec| (spec.name, spec)).collect();
+}
+
+impl CommandSpec {
+    // argc 为包含命令名在内的参数个数
+    pub fn accepts(&self, argc: usize) -> bool {
+        match self.arity {
+            arity if arity >= 0 => argc as i64 == arity,
+            arity => argc as i64 >= -arity,
+        }
+    }
+
+    // ACL 分类：@all、group 对应的分类，数据命令再按是否修改数据分为 @read 和 @write。
+    // 管理命令只属于 @admin 和 @dangerous，+@server 不会授予它们
+    pub fn acl_categories(&self) -> Vec<&'static str> {
+        if self.is_admin() {
+            return vec!["all", "admin", "dangerous"];
+        }
+        let group = match self.group {
+            "generic" => "keyspace",
+            "sorted-set" => "sortedset",
+            group => group,
+        };
+        let mut categories = vec!["all", group];
+        if self.write {
+            categories.push("write");
+        } else if !matches!(
+            group,
+            "connection" | "server" | "cluster" | "scripting" | "pubsub"
+        ) {
+            categories.push("read");
+        }
+        categories
+    }
+
+    // 对应 Redis 的 admin 标记：修改用户和配置、读取命令流、复制和调试服务器的命令
+    fn is_admin(&self) -> bool {
+        matches!(
+            self.name,
+            "acl"
+                | "config"
+                | "monitor"
+                | "sync"
+                | "debug"
+                | "save"
+                | "bgsave"
+                | "lastsave"
+                | "slowlog"
+        )
+    }
+
+    // COMMAND DOCS 中的 flags：沿用 ACL 分类里的读写划分，订阅相关命令另外标记 pubsub，管理命令标记 admin
+    pub fn flags(&self) -> Vec<&'static str> {
+        let categories = self.acl_categories();
+        let mut flags = Vec::new();
+        if self.is_admin() {
+            flags.push("admin");
+        }
+        if categories.contains(&"write") {
+            flags.push("write");
+        } else if categories.contains(&"read") {
+            flags.push("readonly");
+        }
+        if self.group == "pubsub" {
+            flags.push("pubsub");
+        }
+        flags
+    }
+}
+
+// 按小写的命令名查找
+pub fn lookup_command(name: &str) -> Option<&'static CommandSpec> {
+    COMMAND_INDEX.get(name).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_command_names_are_unique_and_lowercase() {
+        let mut names = HashSet::new();
+        for spec in COMMAND_TABLE {
+            assert_eq!(spec.name, spec.name.to_ascii_lowercase());
+            assert!(names.insert(spec.name), "duplicate command {}", spec.name);
+        }
+        assert!(lookup_command("get").is_some());
+        assert!(lookup_command("nosuchcommand").is_none());
+    }
+
+    #[test]
+    fn test_arity() {
+        let get = lookup_command("get").unwrap();
+        assert!(!get.accepts(1));
+        assert!(get.accepts(2));
+        assert!(!get.accepts(3));
+        let del = lookup_command("del").unwrap();
+        assert!(!del.accepts(1));
+        assert!(del.accepts(2));
+        assert!(del.accepts(10));
+    }
+
+    #[test]
+    fn test_acl_categories() {
+        for spec in COMMAND_TABLE {
+            for category in spec.acl_categories() {
+                assert!(
+                    crate::ACL_CATEGORIES.contains(&category),
+                    "{} has unknown category {}",
+                    spec.name,
+                    category
+                );
+            }
+        }
+        let get = lookup_command("get").unwrap().acl_categories();
+        assert_eq!(get, ["all", "string", "read"]);
+        let zadd = lookup_command("zadd").unwrap().acl_categories();
+        assert_eq!(zadd, ["all", "sortedset", "write"]);
+        let info = lookup_command("info").unwrap().acl_categories();
+        assert_eq!(info, ["all", "server"]);
+        for name in ["acl", "config", "monitor", "sync", "debug"] {
+            let categories = lookup_command(name).unwrap().acl_categories();
+            assert_eq!(categories, ["all", "admin", "dangerous"], "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(lookup_command("get").unwrap().flags(), ["readonly"]);
+        assert_eq!(lookup_command("zadd").unwrap().flags(), ["write"]);
+        assert_eq!(lookup_command("publish").unwrap().flags(), ["pubsub"]);
+        assert!(lookup_command("ping").unwrap().flags().is_empty());
+        assert_eq!(lookup_command("config").unwrap().flags(), ["admin"]);
+    }
+}
@@ -0,0 +1,968 @@
+use crate::{
+    cmd::{
+        extract_args, extract_string, geo::parse_float, parse_mpop, validate_command,
+        validate_command_at_least,
+    },
+    Aggregate, Backend, BulkString, RespArray, RespFrame, RespNull, RespNullArray, ScoreBound,
+    ZAddOptions, ZSet,
+};
+
+use super::{
+    CommandError, CommandExecutor, ZAdd, ZCard, ZDiff, ZDiffStore, ZIncrBy, ZInter, ZInterStore,
+    ZMPop, ZRangeByScore, ZRank, ZRem, ZUnion, ZUnionStore,
+};
+
+impl CommandExecutor for ZAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.incr {
+            // 解析时已保证 INCR 只有一对 increment member
+            let Some((increment, member)) = self.members.into_iter().next() else {
+                return RespFrame::Null(RespNull);
+            };
+            return match backend.zadd_incr(self.key, member, increment, self.options) {
+                Some(score) if score.is_nan() => {
+                    RespFrame::error("ERR resulting score is not a number (NaN)")
+                }
+                Some(score) => score.into(),
+                None => RespFrame::Null(RespNull),
+            };
+        }
+        let members = self
+            .members
+            .into_iter()
+            .map(|(score, member)| (member, score))
+            .collect();
+        let changed = backend.zadd(self.key, members, self.options);
+        RespFrame::Integer(changed as i64)
+    }
+}
+
+impl CommandExecutor for ZIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zincrby(self.key, self.member, self.increment) {
+            Some(score) => score.into(),
+            None => RespFrame::error("ERR resulting score is not a number (NaN)"),
+        }
+    }
+}
+
+impl CommandExecutor for ZRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zrank(&self.key, &self.member) {
+            Some(rank) => RespFrame::Integer(rank as i64),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl CommandExecutor for ZCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.zcard(&self.key) as i64)
+    }
+}
+
+impl CommandExecutor for ZRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let removed = backend.zrem(&self.key, &self.members);
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl CommandExecutor for ZRangeByScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (offset, count) = self.limit.unwrap_or((0, None));
+        let members = backend.zrange_by_score(&self.key, self.min, self.max, offset, count);
+        members_to_frame(members, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZUnionStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let zset = backend.zunion(&self.keys, &self.weights, self.aggregate);
+        RespFrame::Integer(backend.zstore(self.destination, zset) as i64)
+    }
+}
+
+impl CommandExecutor for ZInterStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let zset = backend.zinter(&self.keys, &self.weights, self.aggregate);
+        RespFrame::Integer(backend.zstore(self.destination, zset) as i64)
+    }
+}
+
+impl CommandExecutor for ZDiffStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let zset = backend.zdiff(&self.keys);
+        RespFrame::Integer(backend.zstore(self.destination, zset) as i64)
+    }
+}
+
+impl CommandExecutor for ZUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let zset = backend.zunion(&self.keys, &self.weights, self.aggregate);
+        zset_to_frame(&zset, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let zset = backend.zinter(&self.keys, &self.weights, self.aggregate);
+        zset_to_frame(&zset, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let zset = backend.zdiff(&self.keys);
+        zset_to_frame(&zset, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zmpop(&self.keys, self.min, self.count) {
+            // [key, [[member, score], ...]]
+            Some((key, members)) => {
+                let members = members
+                    .into_iter()
+                    .map(|(member, score)| {
+                        RespArray::new(vec![BulkString::new(member).into(), score.into()]).into()
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(vec![
+                    BulkString::new(key).into(),
+                    RespArray::new(members).into(),
+                ])
+                .into()
+            }
+            None => RespFrame::NullArray(RespNullArray),
+        }
+    }
+}
+
+// 按 score 升序输出临时计算出的有序集合
+fn zset_to_frame(zset: &ZSet, with_scores: bool) -> RespFrame {
+    members_to_frame(
+        zset.iter()
+            .map(|(member, score)| (member.to_string(), score)),
+        with_scores,
+    )
+}
+
+// [member, ...]，with_scores 为 true 时为 [member, score, ...]
+pub(crate) fn members_to_frame(
+    members: impl IntoIterator<Item = (String, f64)>,
+    with_scores: bool,
+) -> RespFrame {
+    let members = members.into_iter();
+    let per_member = if with_scores { 2 } else { 1 };
+    let mut frames = RespArray::with_capacity(members.size_hint().0 * per_member);
+    for (member, score) in members {
+        frames.push(BulkString::new(member).into());
+        if with_scores {
+            frames.push(score.into());
+        }
+    }
+    frames.into()
+}
+
+// 集合运算命令共用的参数：numkeys key [key ...] [WEIGHTS w ...] [AGGREGATE SUM|MIN|MAX] [WITHSCORES]
+#[derive(Debug, PartialEq)]
+pub(crate) struct SetOpArgs {
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+    pub with_scores: bool,
+}
+
+// allow_weights 控制是否接受 WEIGHTS / AGGREGATE，allow_with_scores 控制是否接受 WITHSCORES
+pub(crate) fn parse_set_op_args(
+    args: &[String],
+    name: &str,
+    allow_weights: bool,
+    allow_with_scores: bool,
+) -> Result<SetOpArgs, CommandError> {
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let numkeys: usize = args.first().and_then(|n| n.parse().ok()).ok_or_else(|| {
+        CommandError::InvalidArgument("value is not an integer or out of range".to_string())
+    })?;
+    if numkeys == 0 {
+        return Err(CommandError::InvalidArgument(format!(
+            "at least 1 input key is needed for '{}' command",
+            name
+        )));
+    }
+    // numkeys 由客户端给出，可能是 usize::MAX，不能直接加 1
+    if args.len() <= numkeys {
+        return Err(syntax_error());
+    }
+    let keys = args[1..=numkeys].to_vec();
+
+    let mut weights = vec![1.0; numkeys];
+    let mut aggregate = Aggregate::default();
+    let mut with_scores = false;
+    let mut i = numkeys + 1;
+    while i < args.len() {
+        match args[i].to_ascii_lowercase().as_str() {
+            "weights" if allow_weights => {
+                if args.len() < i + 1 + numkeys {
+                    return Err(syntax_error());
+                }
+                for (j, weight) in weights.iter_mut().enumerate() {
+                    *weight = parse_float(&args[i + 1 + j]).map_err(|_| {
+                        CommandError::InvalidArgument("weight value is not a float".to_string())
+                    })?;
+                }
+                i += 1 + numkeys;
+            }
+            "aggregate" if allow_weights => {
+                aggregate = match args.get(i + 1).map(|a| a.to_ascii_lowercase()).as_deref() {
+                    Some("sum") => Aggregate::Sum,
+                    Some("min") => Aggregate::Min,
+                    Some("max") => Aggregate::Max,
+                    _ => return Err(syntax_error()),
+                };
+                i += 2;
+            }
+            "withscores" if allow_with_scores => {
+                with_scores = true;
+                i += 1;
+            }
+            _ => return Err(syntax_error()),
+        }
+    }
+    Ok(SetOpArgs {
+        keys,
+        weights,
+        aggregate,
+        with_scores,
+    })
+}
+
+// 解析 score 区间边界：`(` 前缀表示开区间，支持 -inf / +inf
+fn parse_score_bound(s: &str) -> Result<ScoreBound, CommandError> {
+    let (exclusive, value) = match s.strip_prefix('(') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let value = parse_float(value)
+        .map_err(|_| CommandError::InvalidArgument("min or max is not a float".to_string()))?;
+    Ok(ScoreBound { value, exclusive })
+}
+
+fn string_args(value: RespArray) -> Result<Vec<String>, CommandError> {
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect()
+}
+
+// ZADD key [NX|XX] [CH] score member [score member ...]
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zadd"], 3)?;
+        let args = string_args(value)?;
+        let key = args[0].clone();
+
+        let mut options = ZAddOptions::default();
+        let mut incr = false;
+        let mut i = 1;
+        while let Some(arg) = args.get(i) {
+            match arg.to_ascii_lowercase().as_str() {
+                "nx" => options.nx = true,
+                "xx" => options.xx = true,
+                "gt" => options.gt = true,
+                "lt" => options.lt = true,
+                "ch" => options.ch = true,
+                "incr" => incr = true,
+                _ => break,
+            }
+            i += 1;
+        }
+        if options.nx && options.xx {
+            return Err(CommandError::InvalidArgument(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if (options.gt && options.lt) || (options.nx && (options.gt || options.lt)) {
+            return Err(CommandError::InvalidArgument(
+                "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+            ));
+        }
+
+        let rest = &args[i..];
+        if rest.is_empty() || !rest.len().is_multiple_of(2) {
+            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+        }
+        if incr && rest.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "INCR option supports a single increment-element pair".to_string(),
+            ));
+        }
+        let members = rest
+            .chunks(2)
+            .map(|pair| Ok((parse_float(&pair[0])?, pair[1].clone())))
+            .collect::<Result<Vec<_>, CommandError>>()?;
+        Ok(ZAdd {
+            key,
+            options,
+            incr,
+            members,
+        })
+    }
+}
+
+// ZINCRBY key increment member
+impl TryFrom<RespArray> for ZIncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zincrby"], 3)?;
+        let args = string_args(value)?;
+        Ok(ZIncrBy {
+            key: args[0].clone(),
+            increment: parse_float(&args[1])?,
+            member: args[2].clone(),
+        })
+    }
+}
+
+// ZRANK key member
+impl TryFrom<RespArray> for ZRank {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zrank"], 2)?;
+        let mut args = string_args(value)?;
+        let member = args.pop().unwrap_or_default();
+        let key = args.pop().unwrap_or_default();
+        Ok(ZRank { key, member })
+    }
+}
+
+// ZCARD key
+impl TryFrom<RespArray> for ZCard {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zcard"], 1)?;
+        let key = string_args(value)?.swap_remove(0);
+        Ok(ZCard { key })
+    }
+}
+
+// ZREM key member [member ...]
+impl TryFrom<RespArray> for ZRem {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zrem"], 2)?;
+        let mut args = string_args(value)?;
+        let members = args.split_off(1);
+        Ok(ZRem {
+            key: args.swap_remove(0),
+            members,
+        })
+    }
+}
+
+// ZRANGEBYSCORE key min max [WITHSCORES] [LIMIT offset count]
+impl TryFrom<RespArray> for ZRangeByScore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zrangebyscore"], 3)?;
+        let args = string_args(value)?;
+        let min = parse_score_bound(&args[1])?;
+        let max = parse_score_bound(&args[2])?;
+
+        let mut with_scores = false;
+        let mut limit = None;
+        let mut i = 3;
+        while i < args.len() {
+            match args[i].to_ascii_lowercase().as_str() {
+                "withscores" => {
+                    with_scores = true;
+                    i += 1;
+                }
+                "limit" if i + 2 < args.len() => {
+                    let parse = |s: &String| {
+                        s.parse::<i64>().map_err(|_| {
+                            CommandError::InvalidArgument(
+                                "value is not an integer or out of range".to_string(),
+                            )
+                        })
+                    };
+                    let (offset, count) = (parse(&args[i + 1])?, parse(&args[i + 2])?);
+                    // 负数 offset 返回空结果，负数 count 表示不限制数量
+                    let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+                    let count = usize::try_from(count).ok();
+                    limit = Some((offset, count));
+                    i += 3;
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(ZRangeByScore {
+            key: args[0].clone(),
+            min,
+            max,
+            with_scores,
+            limit,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnionStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zunionstore"], 3)?;
+        let args = string_args(value)?;
+        let op = parse_set_op_args(&args[1..], "zunionstore", true, false)?;
+        Ok(ZUnionStore {
+            destination: args[0].clone(),
+            keys: op.keys,
+            weights: op.weights,
+            aggregate: op.aggregate,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInterStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zinterstore"], 3)?;
+        let args = string_args(value)?;
+        let op = parse_set_op_args(&args[1..], "zinterstore", true, false)?;
+        Ok(ZInterStore {
+            destination: args[0].clone(),
+            keys: op.keys,
+            weights: op.weights,
+            aggregate: op.aggregate,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiffStore {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zdiffstore"], 3)?;
+        let args = string_args(value)?;
+        let op = parse_set_op_args(&args[1..], "zdiffstore", false, false)?;
+        Ok(ZDiffStore {
+            destination: args[0].clone(),
+            keys: op.keys,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnion {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zunion"], 2)?;
+        let op = parse_set_op_args(&string_args(value)?, "zunion", true, true)?;
+        Ok(ZUnion {
+            keys: op.keys,
+            weights: op.weights,
+            aggregate: op.aggregate,
+            with_scores: op.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInter {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zinter"], 2)?;
+        let op = parse_set_op_args(&string_args(value)?, "zinter", true, true)?;
+        Ok(ZInter {
+            keys: op.keys,
+            weights: op.weights,
+            aggregate: op.aggregate,
+            with_scores: op.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiff {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_at_least(&value, &["zdiff"], 2)?;
+        let op = parse_set_op_args(&string_args(value)?, "zdiff", false, true)?;
+        Ok(ZDiff {
+            keys: op.keys,
+            with_scores: op.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZMPop {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, min, count) = parse_mpop(value, "zmpop", ["min", "max"])?;
+        Ok(ZMPop { keys, min, count })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::RespDecoder;
+
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn zadd(backend: &Backend, key: &str, members: &[(f64, &str)]) {
+        let members = members
+            .iter()
+            .map(|(score, member)| (member.to_string(), *score))
+            .collect();
+        backend.zadd(key.to_string(), members, ZAddOptions::default());
+    }
+
+    #[test]
+    fn test_zadd_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$4\r\nzadd\r\n$1\r\nz\r\n$2\r\nNX\r\n$1\r\n1\r\n$1\r\na\r\n$4\r\n-inf\r\n$1\r\nb\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let zadd: ZAdd = cmd.try_into()?;
+        assert_eq!(zadd.key, "z");
+        assert!(zadd.options.nx);
+        assert_eq!(
+            zadd.members,
+            vec![(1.0, "a".to_string()), (f64::NEG_INFINITY, "b".to_string())]
+        );
+        Ok(())
+    }
+
+    fn zadd_cmd(args: &[&str]) -> Result<ZAdd, CommandError> {
+        let frames: Vec<RespFrame> = ["zadd"]
+            .iter()
+            .chain(args)
+            .map(|arg| BulkString::new(*arg).into())
+            .collect();
+        RespArray::new(frames).try_into()
+    }
+
+    #[test]
+    fn test_zadd_flag_conflicts() {
+        let cmd = zadd_cmd(&["z", "GT", "CH", "INCR", "1", "a"]).unwrap();
+        assert!(cmd.options.gt && cmd.options.ch && cmd.incr);
+        for args in [
+            &["z", "NX", "XX", "1", "a"][..],
+            &["z", "NX", "GT", "1", "a"],
+            &["z", "GT", "LT", "1", "a"],
+            &["z", "INCR", "1", "a", "2", "b"],
+        ] {
+            assert!(zadd_cmd(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_zadd_gt_lt_ch_execute() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        zadd(&backend, "z", &[(5.0, "a")]);
+
+        // GT 不会把 score 调低，但不影响新成员的添加
+        let ret = zadd_cmd(&["z", "GT", "3", "a", "1", "b"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(backend.zscore("z", "a"), Some(5.0));
+        assert_eq!(backend.zscore("z", "b"), Some(1.0));
+
+        // CH 把更新也计入回复
+        let ret = zadd_cmd(&["z", "GT", "CH", "7", "a", "0", "b", "2", "c"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        assert_eq!(backend.zscore("z", "a"), Some(7.0));
+        assert_eq!(backend.zscore("z", "b"), Some(1.0));
+
+        let ret = zadd_cmd(&["z", "LT", "CH", "6", "a", "9", "c"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(backend.zscore("z", "a"), Some(6.0));
+        assert_eq!(backend.zscore("z", "c"), Some(2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_incr_execute() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let ret = zadd_cmd(&["z", "INCR", "2", "a"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Double(2.0));
+        let ret = zadd_cmd(&["z", "XX", "INCR", "1.5", "a"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Double(3.5));
+
+        // 条件不满足时回复 nil，不创建成员也不修改 score
+        let ret = zadd_cmd(&["z", "NX", "INCR", "1", "a"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+        let ret = zadd_cmd(&["z", "GT", "INCR", "-1", "a"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+        let ret = zadd_cmd(&["other", "XX", "INCR", "1", "a"])?.execute(&backend);
+        assert_eq!(ret, RespFrame::Null(RespNull));
+        assert_eq!(backend.zscore("z", "a"), Some(3.5));
+        assert!(!backend.exists("other"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zincrby_zrank_zcard_zrem_try_from_resp_array() -> anyhow::Result<()> {
+        let parse = |req: &[u8]| RespArray::decode(&mut bytes::BytesMut::from(req));
+        let cmd: ZIncrBy =
+            parse(b"*4\r\n$7\r\nzincrby\r\n$1\r\nz\r\n$3\r\n1.5\r\n$1\r\na\r\n")?.try_into()?;
+        assert_eq!(
+            (cmd.key.as_str(), cmd.increment, cmd.member.as_str()),
+            ("z", 1.5, "a")
+        );
+        let ret: Result<ZIncrBy, _> =
+            parse(b"*4\r\n$7\r\nzincrby\r\n$1\r\nz\r\n$3\r\nabc\r\n$1\r\na\r\n")?.try_into();
+        assert!(ret.is_err());
+
+        let cmd: ZRank = parse(b"*3\r\n$5\r\nzrank\r\n$1\r\nz\r\n$1\r\na\r\n")?.try_into()?;
+        assert_eq!((cmd.key.as_str(), cmd.member.as_str()), ("z", "a"));
+        let cmd: ZCard = parse(b"*2\r\n$5\r\nzcard\r\n$1\r\nz\r\n")?.try_into()?;
+        assert_eq!(cmd.key, "z");
+        let cmd: ZRem =
+            parse(b"*4\r\n$4\r\nzrem\r\n$1\r\nz\r\n$1\r\na\r\n$1\r\nb\r\n")?.try_into()?;
+        assert_eq!(cmd.key, "z");
+        assert_eq!(cmd.members, ["a", "b"]);
+        let ret: Result<ZRem, _> = parse(b"*2\r\n$4\r\nzrem\r\n$1\r\nz\r\n")?.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zincrby_reorders_ranks() {
+        let backend = Backend::new();
+        zadd(&backend, "z", &[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+        let rank = |member: &str| {
+            ZRank {
+                key: "z".to_string(),
+                member: member.to_string(),
+            }
+            .execute(&backend)
+        };
+        let incr = |member: &str, increment| {
+            ZIncrBy {
+                key: "z".to_string(),
+                increment,
+                member: member.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(rank("a"), RespFrame::Integer(0));
+        assert_eq!(rank("c"), RespFrame::Integer(2));
+
+        // a 从最小变成最大，其余成员依次前移
+        assert_eq!(incr("a", 5.0), RespFrame::Double(6.0));
+        assert_eq!(rank("a"), RespFrame::Integer(2));
+        assert_eq!(rank("b"), RespFrame::Integer(0));
+        assert_eq!(rank("c"), RespFrame::Integer(1));
+        assert_eq!(rank("nosuch"), RespFrame::Null(RespNull));
+
+        // 不存在的成员从 0 开始累加
+        assert_eq!(incr("d", -1.0), RespFrame::Double(-1.0));
+        assert_eq!(rank("d"), RespFrame::Integer(0));
+        assert_eq!(incr("e", f64::INFINITY), RespFrame::Double(f64::INFINITY));
+        assert!(matches!(incr("e", f64::NEG_INFINITY), RespFrame::Error(_)));
+        assert_eq!(backend.zscore("z", "e"), Some(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_zcard_zrem_execute() {
+        let backend = Backend::new();
+        zadd(&backend, "z", &[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+        let card = || {
+            ZCard {
+                key: "z".to_string(),
+            }
+            .execute(&backend)
+        };
+        let rem = |members: &[&str]| {
+            ZRem {
+                key: "z".to_string(),
+                members: strings(members),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(card(), RespFrame::Integer(3));
+        assert_eq!(rem(&["a", "nosuch", "a"]), RespFrame::Integer(1));
+        assert_eq!(card(), RespFrame::Integer(2));
+        assert_eq!(backend.zrank("z", "b"), Some(0));
+        // 删空后删除 key
+        assert_eq!(rem(&["b", "c"]), RespFrame::Integer(2));
+        assert!(!backend.exists("z"));
+        assert_eq!(card(), RespFrame::Integer(0));
+        assert_eq!(rem(&["a"]), RespFrame::Integer(0));
+    }
+
+    #[test]
+    fn test_zrangebyscore_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*8\r\n$13\r\nzrangebyscore\r\n$1\r\nz\r\n$2\r\n(1\r\n$4\r\n+inf\r\n$10\r\nWITHSCORES\r\n$5\r\nLIMIT\r\n$1\r\n1\r\n$2\r\n-1\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let range: ZRangeByScore = cmd.try_into()?;
+        assert_eq!(
+            range.min,
+            ScoreBound {
+                value: 1.0,
+                exclusive: true
+            }
+        );
+        assert_eq!(range.max, ScoreBound::inclusive(f64::INFINITY));
+        assert!(range.with_scores);
+        assert_eq!(range.limit, Some((1, None)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebyscore_execute() {
+        let backend = Backend::new();
+        zadd(
+            &backend,
+            "z",
+            &[(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d")],
+        );
+        let range = ZRangeByScore {
+            key: "z".to_string(),
+            min: ScoreBound {
+                value: 1.0,
+                exclusive: true,
+            },
+            max: ScoreBound::inclusive(4.0),
+            with_scores: true,
+            limit: Some((1, Some(1))),
+        };
+        let expected: RespFrame =
+            RespArray::new(vec![BulkString::new("c").into(), 3.0.into()]).into();
+        assert_eq!(range.execute(&backend), expected);
+    }
+
+    #[test]
+    fn test_parse_set_op_args() {
+        let op = parse_set_op_args(
+            &strings(&["2", "a", "b", "WEIGHTS", "2", "3", "AGGREGATE", "max"]),
+            "zunionstore",
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            op,
+            SetOpArgs {
+                keys: strings(&["a", "b"]),
+                weights: vec![2.0, 3.0],
+                aggregate: Aggregate::Max,
+                with_scores: false,
+            }
+        );
+        // numkeys 与 key 的数量不一致
+        assert!(parse_set_op_args(&strings(&["3", "a", "b"]), "zunionstore", true, false).is_err());
+        assert!(parse_set_op_args(&strings(&["0"]), "zunionstore", true, false).is_err());
+        let max = usize::MAX.to_string();
+        assert!(matches!(
+            parse_set_op_args(&strings(&[&max, "a"]), "zunionstore", true, false),
+            Err(CommandError::InvalidArgument(msg)) if msg == "syntax error"
+        ));
+        assert!(parse_set_op_args(
+            &strings(&["1", "a", "WEIGHTS", "2"]),
+            "zdiffstore",
+            false,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_zunionstore_zinterstore_execute() {
+        let backend = Backend::new();
+        zadd(&backend, "z1", &[(1.0, "a"), (2.0, "b")]);
+        zadd(&backend, "z2", &[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+
+        let union = ZUnionStore {
+            destination: "out".to_string(),
+            keys: strings(&["z1", "z2"]),
+            weights: vec![2.0, 3.0],
+            aggregate: Aggregate::Sum,
+        };
+        assert_eq!(union.execute(&backend), RespFrame::Integer(3));
+        assert_eq!(
+            backend.zmembers("out"),
+            vec![
+                ("a".to_string(), 5.0),
+                ("c".to_string(), 9.0),
+                ("b".to_string(), 10.0)
+            ]
+        );
+
+        let inter = ZInterStore {
+            destination: "out".to_string(),
+            keys: strings(&["z1", "z2"]),
+            weights: vec![1.0, 1.0],
+            aggregate: Aggregate::Max,
+        };
+        assert_eq!(inter.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(
+            backend.zmembers("out"),
+            vec![("a".to_string(), 1.0), ("b".to_string(), 2.0)]
+        );
+
+        // 与不存在的 key 求交集会删除目标 key
+        let inter = ZInterStore {
+            destination: "out".to_string(),
+            keys: strings(&["z1", "missing"]),
+            weights: vec![1.0, 1.0],
+            aggregate: Aggregate::Sum,
+        };
+        assert_eq!(inter.execute(&backend), RespFrame::Integer(0));
+        assert!(!backend.exists("out"));
+    }
+
+    #[test]
+    fn test_zdiffstore_execute() {
+        let backend = Backend::new();
+        zadd(&backend, "z1", &[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+        zadd(&backend, "z2", &[(1.0, "a")]);
+        backend.set("out".to_string(), BulkString::new("string").into());
+        let diff = ZDiffStore {
+            destination: "out".to_string(),
+            keys: strings(&["z1", "z2"]),
+        };
+        assert_eq!(diff.execute(&backend), RespFrame::Integer(2));
+        assert!(backend.get("out").is_none());
+        assert_eq!(
+            backend.zmembers("out"),
+            vec![("b".to_string(), 2.0), ("c".to_string(), 3.0)]
+        );
+    }
+
+    #[test]
+    fn test_zunion_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$6\r\nZUNION\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$9\r\nAGGREGATE\r\n$3\r\nMIN\r\n$10\r\nWITHSCORES\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let zunion: ZUnion = cmd.try_into()?;
+        assert_eq!(zunion.keys, strings(&["a", "b"]));
+        assert_eq!(zunion.weights, vec![1.0, 1.0]);
+        assert_eq!(zunion.aggregate, Aggregate::Min);
+        assert!(zunion.with_scores);
+
+        // ZDIFF 不支持 AGGREGATE
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nzdiff\r\n$1\r\n1\r\n$1\r\na\r\n$9\r\nAGGREGATE\r\n$3\r\nMIN\r\n",
+        );
+        let cmd = RespArray::decode(&mut buf)?;
+        let ret: Result<ZDiff, _> = cmd.try_into();
+        assert!(ret.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zunion_zinter_zdiff_execute() {
+        let backend = Backend::new();
+        zadd(&backend, "z1", &[(1.0, "a"), (2.0, "b")]);
+        zadd(&backend, "z2", &[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+
+        let zunion = ZUnion {
+            keys: strings(&["z1", "z2"]),
+            weights: vec![1.0, 1.0],
+            aggregate: Aggregate::Sum,
+            with_scores: true,
+        };
+        let expected: RespFrame = RespArray::new(vec![
+            BulkString::new("a").into(),
+            2.0.into(),
+            BulkString::new("c").into(),
+            3.0.into(),
+            BulkString::new("b").into(),
+            4.0.into(),
+        ])
+        .into();
+        assert_eq!(zunion.execute(&backend), expected);
+
+        let zinter = ZInter {
+            keys: strings(&["z1", "z2"]),
+            weights: vec![1.0, 10.0],
+            aggregate: Aggregate::Min,
+            with_scores: false,
+        };
+        let expected: RespFrame = RespArray::new(vec![
+            BulkString::new("a").into(),
+            BulkString::new("b").into(),
+        ])
+        .into();
+        assert_eq!(zinter.execute(&backend), expected);
+
+        let zdiff = ZDiff {
+            keys: strings(&["z2", "z1"]),
+            with_scores: true,
+        };
+        let expected: RespFrame =
+            RespArray::new(vec![BulkString::new("c").into(), 3.0.into()]).into();
+        assert_eq!(zdiff.execute(&backend), expected);
+
+        // 不写入任何 key
+        assert!(!backend.exists("out"));
+        assert_eq!(backend.zmembers("z1").len(), 2);
+    }
+
+    #[test]
+    fn test_zmpop_try_from_resp_array() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$5\r\nZMPOP\r\n$1\r\n1\r\n$1\r\nz\r\n$3\r\nMAX\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n",
+        );
+        let zmpop: ZMPop = RespArray::decode(&mut buf)?.try_into()?;
+        assert_eq!(zmpop.keys, strings(&["z"]));
+        assert!(!zmpop.min);
+        assert_eq!(zmpop.count, 2);
+
+        // numkeys 与 key 的数量不一致，LEFT 不是 ZMPOP 的方向
+        for args in [&["2", "z", "min"][..], &["1", "z", "left"]] {
+            let mut frames = vec![BulkString::new("zmpop").into()];
+            frames.extend(args.iter().map(|a| BulkString::new(*a).into()));
+            let ret: Result<ZMPop, _> = RespArray::new(frames).try_into();
+            assert_eq!(
+                ret.unwrap_err().to_string(),
+                "Invalid argument: syntax error"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_zmpop_execute() {
+        let backend = Backend::new();
+        zadd(&backend, "z2", &[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+        let zmpop = |min, count| {
+            ZMPop {
+                keys: strings(&["z1", "z2"]),
+                min,
+                count,
+            }
+            .execute(&backend)
+        };
+        let reply = |members: &[(&str, f64)]| -> RespFrame {
+            let members = members
+                .iter()
+                .map(|(member, score)| {
+                    RespArray::new(vec![BulkString::new(*member).into(), (*score).into()]).into()
+                })
+                .collect::<Vec<RespFrame>>();
+            RespArray::new(vec![
+                BulkString::new("z2").into(),
+                RespArray::new(members).into(),
+            ])
+            .into()
+        };
+        assert_eq!(zmpop(false, 1), reply(&[("c", 3.0)]));
+        assert_eq!(zmpop(true, 5), reply(&[("a", 1.0), ("b", 2.0)]));
+        // 弹空后删除 key
+        assert!(!backend.exists("z2"));
+        assert_eq!(zmpop(true, 1), RespFrame::NullArray(RespNullArray));
+    }
+}
@@ -0,0 +1,525 @@
+use std::{fmt, ops::BitOr, path::PathBuf, time::Duration};
+
+use thiserror::Error;
+
+// 服务端配置，可以通过命令行参数 `--name value` 设置
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub bind: String,
+    pub port: u16,
+    // 设置后客户端必须先通过 AUTH 认证
+    pub requirepass: Option<String>,
+    // 执行时间超过这个微秒数的命令记入慢日志，负数表示关闭慢日志
+    pub slowlog_log_slower_than: i64,
+    // 慢日志最多保留的条数
+    pub slowlog_max_len: usize,
+    // 同时连接的客户端数量上限，超过时新连接收到错误后被关闭
+    pub maxclients: usize,
+    // 需要发布的 keyspace 通知，默认全部关闭
+    pub notify_keyspace_events: KeyspaceFlags,
+    // 列表按 listpack 编码报告的上限：正数为元素个数，-1 到 -5 为 4KB 到 64KB 的字节数
+    pub list_max_listpack_size: i64,
+    // SAVE / BGSAVE 写入的目录和文件名
+    pub dir: String,
+    pub dbfilename: String,
+    // 数据占用内存的上限（字节），0 表示不限制
+    pub maxmemory: usize,
+    // 超过 maxmemory 时的淘汰策略
+    pub maxmemory_policy: EvictionPolicy,
+    // allkeys-lru 每淘汰一个 key 时随机采样的 key 数量，从中淘汰最久没有访问的
+    pub maxmemory_samples: usize,
+    // 作为只读副本时主节点的地址（host:port），定期从主节点拉取全量数据
+    pub replica_of: Option<String>,
+    // 主节点开启认证时，副本同步前用这个用户名和密码执行 AUTH；没有用户名时使用 default 用户
+    pub masteruser: Option<String>,
+    pub masterauth: Option<String>,
+    // 后台任务（主动过期）每秒执行的次数，启动时生效
+    pub hz: u32,
+    // 新连接开启 TCP keepalive 的空闲秒数，0 表示不开启
+    pub tcp_keepalive: u64,
+    // 新连接是否设置 TCP_NODELAY，关闭 Nagle 算法以降低小回复的延迟
+    pub tcp_nodelay: bool,
+    // 脚本执行超过这个毫秒数之后，其它命令不再等待，直接回复 BUSY
+    pub busy_reply_threshold: u64,
+}
+
+// maxmemory-policy 的取值
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    // 不淘汰，写命令返回 OOM 错误
+    #[default]
+    NoEviction,
+    // 淘汰最久没有访问的 key
+    AllKeysLru,
+    // 随机淘汰 key
+    AllKeysRandom,
+}
+
+// notify-keyspace-events 的取值，每个字符对应一位，和 Redis 一致
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyspaceFlags(u32);
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConfigError {
+    #[error("Unknown option or number of arguments for CONFIG SET - '{0}'")]
+    UnknownOption(String),
+    #[error("Invalid argument '{1}' for CONFIG SET '{0}'")]
+    InvalidValue(String, String),
+    #[error("Missing value for option '{0}'")]
+    MissingValue(String),
+    #[error("CONFIG SET failed (possibly related to argument '{0}') - can't set immutable config")]
+    Immutable(String),
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "0.0.0.0".to_string(),
+            port: 6379,
+            requirepass: None,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            maxclients: 10000,
+            notify_keyspace_events: KeyspaceFlags::default(),
+            list_max_listpack_size: -2,
+            dir: ".".to_string(),
+            dbfilename: "dump.rdb".to_string(),
+            maxmemory: 0,
+            maxmemory_policy: EvictionPolicy::default(),
+            maxmemory_samples: 5,
+            replica_of: None,
+            masteruser: None,
+            masterauth: None,
+            hz: 10,
+            tcp_keepalive: 300,
+            tcp_nodelay: true,
+            busy_reply_threshold: 5000,
+        }
+    }
+}
+
+impl ServerConfig {
+    // 从命令行参数解析配置，例如 `--port 6380 --requirepass secret`
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let name = arg.trim_start_matches("--");
+            let value = args
+                .next()
+                .ok_or_else(|| ConfigError::MissingValue(name.to_string()))?;
+            config.set(name, &value)?;
+        }
+        Ok(config)
+    }
+
+    // 按名字设置单个配置项
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidValue(name.to_string(), value.to_string());
+        match name.to_ascii_lowercase().as_str() {
+            "bind" => self.bind = value.to_string(),
+            "port" => self.port = value.parse().map_err(|_| invalid())?,
+            // 空字符串表示关闭密码认证，和 Redis 一致
+            "requirepass" => {
+                self.requirepass = (!value.is_empty()).then(|| value.to_string());
+            }
+            "slowlog-log-slower-than" => {
+                self.slowlog_log_slower_than = value.parse().map_err(|_| invalid())?
+            }
+            "slowlog-max-len" => self.slowlog_max_len = value.parse().map_err(|_| invalid())?,
+            "maxclients" => match value.parse() {
+                Ok(n) if n > 0 => self.maxclients = n,
+                _ => return Err(invalid()),
+            },
+            "notify-keyspace-events" => {
+                self.notify_keyspace_events = KeyspaceFlags::parse(value).ok_or_else(invalid)?
+            }
+            "list-max-listpack-size" => {
+                self.list_max_listpack_size = value.parse().map_err(|_| invalid())?
+            }
+            "dir" => self.dir = value.to_string(),
+            // 文件名不能包含路径，和 Redis 一致
+            "dbfilename" if value.is_empty() || value.contains('/') => return Err(invalid()),
+            "dbfilename" => self.dbfilename = value.to_string(),
+            "maxmemory" => self.maxmemory = parse_memory(value).ok_or_else(invalid)?,
+            "maxmemory-policy" => {
+                self.maxmemory_policy = EvictionPolicy::parse(value).ok_or_else(invalid)?
+            }
+            // 和 Redis 一样限制在 1 到 64 之间
+            "maxmemory-samples" => match value.parse() {
+                Ok(n @ 1..=64) => self.maxmemory_samples = n,
+                _ => return Err(invalid()),
+            },
+            // 和 Redis 一样，超出 1 到 500 的值取最近的边界
+            "hz" => {
+                let hz: i64 = value.parse().map_err(|_| invalid())?;
+                self.hz = hz.clamp(1, 500) as u32;
+            }
+            "tcp-keepalive" => self.tcp_keepalive = value.parse().map_err(|_| invalid())?,
+            "tcp-nodelay" => {
+                self.tcp_nodelay = match value.to_ascii_lowercase().as_str() {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return Err(invalid()),
+                }
+            }
+            // lua-time-limit 是 Redis 7.0 之前的名字
+            "busy-reply-threshold" | "lua-time-limit" => {
+                self.busy_reply_threshold = value.parse().map_err(|_| invalid())?
+            }
+            "masteruser" => self.masteruser = (!value.is_empty()).then(|| value.to_string()),
+            "masterauth" => self.masterauth = (!value.is_empty()).then(|| value.to_string()),
+            // 空字符串或 "no one" 表示不作为副本
+            "replica-of" | "replicaof" => {
+                self.replica_of = match value.to_ascii_lowercase().as_str() {
+                    "" | "no one" => None,
+                    _ if value
+                        .rsplit_once(':')
+                        .is_some_and(|(_, port)| port.parse::<u16>().is_ok()) =>
+                    {
+                        Some(value.to_string())
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(ConfigError::UnknownOption(name.to_string())),
+        }
+        Ok(())
+    }
+
+    // CONFIG GET 可以读取的配置项和当前值，顺序固定
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("bind", self.bind.clone()),
+            ("port", self.port.to_string()),
+            ("requirepass", self.requirepass.clone().unwrap_or_default()),
+            (
+                "slowlog-log-slower-than",
+                self.slowlog_log_slower_than.to_string(),
+            ),
+            ("slowlog-max-len", self.slowlog_max_len.to_string()),
+            ("maxclients", self.maxclients.to_string()),
+            (
+                "notify-keyspace-events",
+                self.notify_keyspace_events.to_string(),
+            ),
+            (
+                "list-max-listpack-size",
+                self.list_max_listpack_size.to_string(),
+            ),
+            ("dir", self.dir.clone()),
+            ("dbfilename", self.dbfilename.clone()),
+            ("maxmemory", self.maxmemory.to_string()),
+            ("maxmemory-policy", self.maxmemory_policy.to_string()),
+            ("maxmemory-samples", self.maxmemory_samples.to_string()),
+            ("hz", self.hz.to_string()),
+            ("tcp-keepalive", self.tcp_keepalive.to_string()),
+            (
+                "tcp-nodelay",
+                if self.tcp_nodelay { "yes" } else { "no" }.to_string(),
+            ),
+            ("replica-of", self.replica_of.clone().unwrap_or_default()),
+            ("masteruser", self.masteruser.clone().unwrap_or_default()),
+            ("masterauth", self.masterauth.clone().unwrap_or_default()),
+            (
+                "busy-reply-threshold",
+                self.busy_reply_threshold.to_string(),
+            ),
+        ]
+    }
+
+    // 只能在启动时设置的配置项：运行时修改不会生效（hz 只在启动主动过期任务时读取），
+    // 或者允许客户端修改会带来风险（dir 决定快照写到哪个目录）
+    pub fn is_immutable(name: &str) -> bool {
+        matches!(
+            name.to_ascii_lowercase().as_str(),
+            "bind" | "port" | "replica-of" | "replicaof" | "hz" | "dir"
+        )
+    }
+
+    // TCP keepalive 的空闲时间，没有开启时为 None
+    pub fn tcp_keepalive_idle(&self) -> Option<Duration> {
+        (self.tcp_keepalive > 0).then(|| Duration::from_secs(self.tcp_keepalive))
+    }
+
+    // 主动过期任务的执行间隔
+    pub fn hz_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.hz.max(1)
+    }
+
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.bind, self.port)
+    }
+
+    // 快照文件的完整路径
+    pub fn snapshot_path(&self) -> PathBuf {
+        PathBuf::from(&self.dir).join(&self.dbfilename)
+    }
+}
+
+// 内存大小，可以带 kb / mb / gb 等单位，例如 "100mb"，单位不区分大小写
+fn parse_memory(value: &str) -> Option<usize> {
+    let value = value.to_ascii_lowercase();
+    let units = [
+        ("gb", 1 << 30),
+        ("mb", 1 << 20),
+        ("kb", 1 << 10),
+        ("g", 1_000_000_000),
+        ("m", 1_000_000),
+        ("k", 1_000),
+        ("b", 1),
+    ];
+    let (digits, unit) = units
+        .iter()
+        .find_map(|(suffix, unit)| Some((value.strip_suffix(suffix)?, *unit)))
+        .unwrap_or((value.as_str(), 1));
+    digits.parse::<usize>().ok()?.checked_mul(unit)
+}
+
+impl EvictionPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "noeviction" => Some(Self::NoEviction),
+            "allkeys-lru" => Some(Self::AllKeysLru),
+            "allkeys-random" => Some(Self::AllKeysRandom),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EvictionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::NoEviction => "noeviction",
+            Self::AllKeysLru => "allkeys-lru",
+            Self::AllKeysRandom => "allkeys-random",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl KeyspaceFlags {
+    // K：发布到 __keyspace@<db>__:<key>
+    pub const KEYSPACE: Self = Self(1 << 0);
+    // E：发布到 __keyevent@<db>__:<event>
+    pub const KEYEVENT: Self = Self(1 << 1);
+    pub const GENERIC: Self = Self(1 << 2);
+    pub const STRING: Self = Self(1 << 3);
+    pub const LIST: Self = Self(1 << 4);
+    pub const SET: Self = Self(1 << 5);
+    pub const HASH: Self = Self(1 << 6);
+    pub const ZSET: Self = Self(1 << 7);
+    pub const EXPIRED: Self = Self(1 << 8);
+    pub const EVICTED: Self = Self(1 << 9);
+    pub const STREAM: Self = Self(1 << 10);
+    pub const KEY_MISS: Self = Self(1 << 11);
+    pub const NEW: Self = Self(1 << 12);
+    // A：g$lshzxet 的别名，不包括 m 和 n
+    pub const ALL: Self = Self(0b111_1111_1100);
+
+    const CHARS: [(char, Self); 13] = [
+        ('K', Self::KEYSPACE),
+        ('E', Self::KEYEVENT),
+        ('g', Self::GENERIC),
+        ('$', Self::STRING),
+        ('l', Self::LIST),
+        ('s', Self::SET),
+        ('h', Self::HASH),
+        ('z', Self::ZSET),
+        ('x', Self::EXPIRED),
+        ('e', Self::EVICTED),
+        ('t', Self::STREAM),
+        ('m', Self::KEY_MISS),
+        ('n', Self::NEW),
+    ];
+
+    // 解析 "KEA" 这样的字符串，包含未知字符时返回 None
+    pub fn parse(s: &str) -> Option<Self> {
+        s.chars().try_fold(Self::default(), |flags, c| {
+            let flag = match c {
+                'A' => Self::ALL,
+                c => Self::CHARS.iter().find(|(ch, _)| *ch == c)?.1,
+            };
+            Some(flags | flag)
+        })
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for KeyspaceFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+// 输出时把 g$lshzxet 合并为 A，例如 "AKE"
+impl fmt::Display for KeyspaceFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut flags = *self;
+        if flags.contains(Self::ALL) {
+            write!(f, "A")?;
+            flags = Self(flags.0 & !Self::ALL.0);
+        }
+        for (c, flag) in Self::CHARS {
+            if flags.contains(flag) {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_config_from_args() {
+        let config =
+            ServerConfig::from_args(args(&["--port", "6380", "--requirepass", "secret"])).unwrap();
+        assert_eq!(config.addr(), "0.0.0.0:6380");
+        assert_eq!(config.requirepass.as_deref(), Some("secret"));
+
+        assert_eq!(
+            ServerConfig::from_args(args(&["--port", "abc"])),
+            Err(ConfigError::InvalidValue(
+                "port".to_string(),
+                "abc".to_string()
+            ))
+        );
+        assert_eq!(
+            ServerConfig::from_args(args(&["--unknown", "1"])),
+            Err(ConfigError::UnknownOption("unknown".to_string()))
+        );
+        assert_eq!(
+            ServerConfig::from_args(args(&["--port"])),
+            Err(ConfigError::MissingValue("port".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_keyspace_flags() {
+        let flags = KeyspaceFlags::parse("KEA").unwrap();
+        assert!(flags.contains(KeyspaceFlags::KEYSPACE | KeyspaceFlags::KEYEVENT));
+        assert!(flags.contains(KeyspaceFlags::STRING | KeyspaceFlags::EXPIRED));
+        assert!(!flags.contains(KeyspaceFlags::KEY_MISS));
+        assert_eq!(flags.to_string(), "AKE");
+
+        let flags = KeyspaceFlags::parse("El").unwrap();
+        assert_eq!(flags, KeyspaceFlags::KEYEVENT | KeyspaceFlags::LIST);
+        assert_eq!(flags.to_string(), "El");
+        assert!(KeyspaceFlags::parse("").unwrap().is_empty());
+        assert_eq!(KeyspaceFlags::parse("Kq"), None);
+
+        let mut config = ServerConfig::default();
+        assert!(config.set("notify-keyspace-events", "Kx").is_ok());
+        assert_eq!(
+            config.notify_keyspace_events,
+            KeyspaceFlags::KEYSPACE | KeyspaceFlags::EXPIRED
+        );
+        assert!(config.set("notify-keyspace-events", "?").is_err());
+    }
+
+    #[test]
+    fn test_maxmemory_options() {
+        let mut config = ServerConfig::default();
+        assert_eq!(config.maxmemory, 0);
+        assert_eq!(config.maxmemory_policy, EvictionPolicy::NoEviction);
+
+        config.set("maxmemory", "1024").unwrap();
+        assert_eq!(config.maxmemory, 1024);
+        config.set("maxmemory", "100MB").unwrap();
+        assert_eq!(config.maxmemory, 100 << 20);
+        config.set("maxmemory", "2k").unwrap();
+        assert_eq!(config.maxmemory, 2000);
+        assert!(config.set("maxmemory", "-1").is_err());
+        assert!(config.set("maxmemory", "mb").is_err());
+
+        config.set("maxmemory-policy", "ALLKEYS-LRU").unwrap();
+        assert_eq!(config.maxmemory_policy, EvictionPolicy::AllKeysLru);
+        assert_eq!(config.maxmemory_policy.to_string(), "allkeys-lru");
+        assert!(config.set("maxmemory-policy", "volatile-ttl").is_err());
+    }
+
+    #[test]
+    fn test_tcp_options() {
+        let mut config = ServerConfig::default();
+        assert!(config.tcp_nodelay);
+        assert_eq!(config.tcp_keepalive_idle(), Some(Duration::from_secs(300)));
+
+        config.set("tcp-nodelay", "NO").unwrap();
+        assert!(!config.tcp_nodelay);
+        config.set("tcp-keepalive", "0").unwrap();
+        assert_eq!(config.tcp_keepalive_idle(), None);
+        assert!(config.set("tcp-nodelay", "maybe").is_err());
+        assert!(config.set("tcp-keepalive", "-1").is_err());
+        let params = config.params();
+        assert!(params.contains(&("tcp-nodelay", "no".to_string())));
+        assert!(params.contains(&("tcp-keepalive", "0".to_string())));
+    }
+
+    #[test]
+    fn test_replica_of_option() {
+        let config =
+            ServerConfig::from_args(["--replica-of".to_string(), "127.0.0.1:6379".to_string()])
+                .unwrap();
+        assert_eq!(config.replica_of.as_deref(), Some("127.0.0.1:6379"));
+
+        let mut config = config;
+        assert!(config.set("replica-of", "127.0.0.1").is_err());
+        config.set("replicaof", "NO ONE").unwrap();
+        assert_eq!(config.replica_of, None);
+    }
+
+    #[test]
+    fn test_hz_and_params() {
+        let mut config = ServerConfig::default();
+        assert_eq!(config.hz_interval(), Duration::from_millis(100));
+        config.set("hz", "1000").unwrap();
+        assert_eq!(config.hz, 500);
+        config.set("hz", "0").unwrap();
+        assert_eq!(config.hz, 1);
+        assert!(config.set("hz", "fast").is_err());
+
+        config.set("notify-keyspace-events", "KEA").unwrap();
+        let params = config.params();
+        let get = |name: &str| {
+            params
+                .iter()
+                .find(|(param, _)| *param == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("hz"), Some("1"));
+        assert_eq!(get("requirepass"), Some(""));
+        assert_eq!(get("maxmemory-policy"), Some("noeviction"));
+        // params 中的每一项都可以原样设置回去
+        for (name, value) in config.params() {
+            config.set(name, &value).unwrap();
+        }
+        assert!(ServerConfig::is_immutable("PORT"));
+        assert!(ServerConfig::is_immutable("hz"));
+        assert!(ServerConfig::is_immutable("Dir"));
+        assert!(!ServerConfig::is_immutable("maxmemory"));
+    }
+
+    #[test]
+    fn test_empty_requirepass_disables_auth() {
+        let mut config = ServerConfig::default();
+        config.set("requirepass", "secret").unwrap();
+        config.set("requirepass", "").unwrap();
+        assert_eq!(config.requirepass, None);
+    }
+}
@@ -1,7 +1,27 @@
 mod backend;
+mod client;
 pub mod cmd;
+mod config;
+mod glob;
 pub mod network;
+pub mod replica;
 mod resp;
+mod rng;
+#[cfg(feature = "scripting")]
+mod script;
+#[cfg(feature = "scripting")]
+mod sha1;
+mod sha256;
 
-pub use backend::Backend;
+pub use backend::{
+    AclDenied, AclError, AclUser, Aggregate, Backend, ClientHandle, ClientPause, ConsumerGroup,
+    ExpireFlag, KeyEvent, KeyspaceListener, PauseMode, PendingEntry, ScoreBound, ScriptKill,
+    ServerStats, SlowLog, SlowLogEntry, SnapshotError, SortError, SortOptions, StreamEntry,
+    StreamGroupError, StreamId, StringError, TrimStrategy, XAddError, ZAddOptions, ZSet,
+    ACL_CATEGORIES, ACTIVE_EXPIRE_INTERVAL, ACTIVE_EXPIRE_SAMPLE_SIZE, DEFAULT_SCAN_COUNT,
+    DEFAULT_USER, LFU_INIT_VAL, MAX_BIT_OFFSET, SNAPSHOT_MAGIC,
+};
+pub use client::Client;
+pub use config::{ConfigError, EvictionPolicy, KeyspaceFlags, ServerConfig};
 pub use resp::*;
+pub use rng::Rng;
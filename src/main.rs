@@ -1,26 +1,29 @@
 use anyhow::Result;
-use simple_redis::{network, Backend};
+use simple_redis::{network, replica, Backend, ServerConfig, ACTIVE_EXPIRE_SAMPLE_SIZE};
 use tokio::net::TcpListener;
-use tracing::{info, warn};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
-    let addr = "0.0.0.0:6379";
+    let config = ServerConfig::from_args(std::env::args().skip(1))?;
+    let addr = config.addr();
     info!("Simple Redis Server listening on {}", addr);
-    let listener = TcpListener::bind(addr).await?;
+    let listener = TcpListener::bind(&addr).await?;
 
-    let backend = Backend::new();
-    loop {
-        let (socket, raddr) = listener.accept().await?;
-        info!("Accepted connection from: {}", raddr);
-        let backend_clone = backend.clone();
-        tokio::spawn(async move {
-            match network::stream_handler(socket, backend_clone).await {
-                Ok(_) => info!("Connection closed from: {}", raddr),
-                Err(e) => warn!("Connection error from {}: {}", raddr, e),
-            }
-        });
+    let upstream = config.replica_of.clone();
+    let expire_interval = config.hz_interval();
+    let backend = Backend::with_config(config);
+    backend.spawn_active_expire(expire_interval, ACTIVE_EXPIRE_SAMPLE_SIZE);
+    if let Some(upstream) = upstream {
+        info!("Replicating from {}", upstream);
+        tokio::spawn(replica::run(
+            backend.clone(),
+            upstream,
+            replica::RESYNC_INTERVAL,
+        ));
     }
+    network::serve(listener, backend, CancellationToken::new()).await
 }
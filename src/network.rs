@@ -1,13 +1,25 @@
 use crate::{
-    cmd::{Command, CommandExecutor},
-    Backend, RespDecoder, RespEncoder, RespError, RespFrame,
+    cmd::{ClientState, Command, Response},
+    Backend, RespDecoder, RespEncoder, RespError, RespFrame, ServerConfig, SimpleError,
+    SimpleString,
 };
 use anyhow::Result;
-use futures::SinkExt;
-use tokio::net::TcpStream;
+use socket2::{SockRef, TcpKeepalive};
+use std::{io, time::Instant};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::Receiver,
+    },
+};
 use tokio_stream::StreamExt;
-use tokio_util::codec::{Decoder, Encoder, Framed};
-use tracing::info;
+use tokio_util::{
+    codec::{Decoder, Encoder, Framed},
+    sync::CancellationToken,
+};
+use tracing::{debug, debug_span, info, warn, Instrument};
 
 #[derive(Debug)]
 pub struct RespFrameCodec;
@@ -20,24 +32,116 @@ struct RedisRequest {
 
 #[derive(Debug)]
 struct RedisResponse {
-    frame: RespFrame,
+    frames: Vec<RespFrame>,
+    // 发送回复之后是否关闭连接
+    close: bool,
+}
+
+// 服务端的 accept 循环，每个连接一个任务；shutdown 被取消后不再接受新连接
+pub async fn serve(
+    listener: TcpListener,
+    backend: Backend,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    loop {
+        let (socket, raddr) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = shutdown.cancelled() => return Ok(()),
+        };
+        info!("Accepted connection from: {}", raddr);
+        if let Err(e) = configure_socket(&socket, &backend.config()) {
+            warn!("Failed to set socket options for {}: {}", raddr, e);
+        }
+        let backend = backend.clone();
+        tokio::spawn(async move {
+            match stream_handler(socket, backend).await {
+                Ok(_) => info!("Connection closed from: {}", raddr),
+                Err(e) => warn!("Connection error from {}: {}", raddr, e),
+            }
+        });
+    }
+}
+
+// 按 tcp-nodelay 和 tcp-keepalive 设置新连接，运行时修改只影响之后的连接
+fn configure_socket(socket: &TcpStream, config: &ServerConfig) -> io::Result<()> {
+    socket.set_nodelay(config.tcp_nodelay)?;
+    let socket = SockRef::from(socket);
+    match config.tcp_keepalive_idle() {
+        Some(idle) => socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(idle)),
+        None => socket.set_keepalive(false),
+    }
 }
 
 pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
+    let mut client = ClientState {
+        addr: stream.peer_addr().ok(),
+        ..Default::default()
+    };
+    let Some((token, push)) = backend.register_client(client.id, client.addr) else {
+        info!("Rejecting connection: max number of clients reached");
+        let mut stream = stream;
+        let err = SimpleError::new("ERR max number of clients reached");
+        write_frame(&mut stream, err.into()).await?;
+        return Ok(());
+    };
+    backend.stats().record_connection();
+    let id = client.id;
+    // CLIENT KILL 取消 token 时立即关闭连接，包括阻塞在 BLPOP、XREAD BLOCK 或者写不出回复的时候
+    let ret = tokio::select! {
+        ret = handle_connection(stream, &backend, &mut client, push) => ret,
+        _ = token.cancelled() => {
+            info!("Connection {} killed", id);
+            Ok(())
+        }
+    };
+    backend.unregister_client(id);
+    ret
+}
+
+// 等待命令的同时把其它连接推送过来的帧发送给客户端，进入 MONITOR 模式后还转发命令流
+async fn handle_connection<S>(
+    stream: S,
+    backend: &Backend,
+    client: &mut ClientState,
+    mut push: Receiver<RespFrame>,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
     // how to get a frame from the stream?
     let mut framed = Framed::new(stream, RespFrameCodec);
+    let mut monitor: Option<broadcast::Receiver<String>> = None;
     loop {
-        match framed.next().await {
+        let next = tokio::select! {
+            next = framed.next() => next,
+            Some(frame) = push.recv() => {
+                write_frame(framed.get_mut(), encode_for(client, frame)).await?;
+                continue;
+            }
+            Some(line) = next_monitor_line(&mut monitor) => {
+                write_frame(framed.get_mut(), SimpleString::new(line).into()).await?;
+                continue;
+            }
+        };
+        match next {
             Some(Ok(frame)) => {
-                info!("Received frame: {:?}", frame);
+                debug!("Received frame: {:?}", frame);
                 let request = RedisRequest {
                     frame,
                     backend: backend.clone(),
                 };
-                let response = request_handler(request).await?;
-                info!("Sending frame: {:?}", response.frame);
-                framed.send(response.frame).await?;
-                // how to send a frame to the stream?
+                let response = request_handler(request, client).await?;
+                for frame in response.frames {
+                    debug!("Sending frame: {:?}", frame);
+                    write_frame(framed.get_mut(), frame).await?;
+                }
+                if response.close {
+                    return Ok(());
+                }
+                // 在 MONITOR 的回复之后订阅，不会收到 MONITOR 命令本身；RESET 之后停止接收
+                if client.in_monitor_mode != monitor.is_some() {
+                    monitor = client.in_monitor_mode.then(|| backend.monitor_subscribe());
+                }
             }
             Some(Err(e)) => {
                 info!("Error receiving frame: {:?}", e);
@@ -48,19 +152,155 @@ pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
     }
 }
 
-async fn request_handler(request: RedisRequest) -> Result<RedisResponse> {
+// 没有进入 MONITOR 模式时一直挂起；跟不上命令流时跳过被丢弃的部分
+async fn next_monitor_line(monitor: &mut Option<broadcast::Receiver<String>>) -> Option<String> {
+    let Some(rx) = monitor else {
+        return std::future::pending().await;
+    };
+    loop {
+        match rx.recv().await {
+            Ok(line) => return Some(line),
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+async fn request_handler(request: RedisRequest, client: &mut ClientState) -> Result<RedisResponse> {
     let (frame, backend) = (request.frame, request.backend);
-    let cmd = Command::try_from(frame)?;
-    info!("Executing command: {:?}", cmd);
-    let ret = cmd.execute(&backend);
-    Ok(RedisResponse { frame: ret })
+    // 慢日志开启时保留一份原始请求，用于记录参数；和 MONITOR 一样隐藏密码，只记录执行过的命令
+    let slowlog_frame = (backend.config().slowlog_log_slower_than >= 0).then(|| frame.clone());
+    // 有 MONITOR 连接时同样保留一份。MONITOR 连接自己发送的命令只会被拒绝，不转发
+    let monitor_frame = (backend.has_monitors() && !client.in_monitor_mode).then(|| frame.clone());
+    // 参数不合法只回复错误，连接保持打开；只有读写连接出错时才返回 Err
+    let cmd = match Command::try_from(frame) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            return Ok(RedisResponse {
+                frames: vec![encode_for(client, e.into())],
+                close: false,
+            })
+        }
+    };
+    backend.stats().record_command();
+    // 每条命令一个 span，记录命令名和 key，执行完成后输出耗时
+    let span = debug_span!("command", name = cmd.name(), key = cmd.key());
+    let start = Instant::now();
+    // 没有通过检查的命令（NOAUTH、NOPERM 等）没有执行，和 Redis 一样不转发给 MONITOR，也不记入慢日志
+    let (response, executed) = async {
+        match cmd.check(&backend, client).await {
+            Ok(()) => (cmd.run(&backend, client).await, true),
+            Err(e) => (Response::Reply(e), false),
+        }
+    }
+    .instrument(span.clone())
+    .await;
+    let elapsed = start.elapsed();
+    span.in_scope(|| debug!(latency_us = elapsed.as_micros() as u64, "command executed"));
+    if let Some(frame) = monitor_frame.filter(|_| executed) {
+        backend.feed_monitors(client.addr, &redacted_argv(frame));
+    }
+    if let Some(frame) = slowlog_frame.filter(|_| executed) {
+        backend.slowlog_record(
+            elapsed,
+            || redacted_argv(frame),
+            client.addr.map(|addr| addr.to_string()).unwrap_or_default(),
+            client.name.clone().unwrap_or_default(),
+        );
+    }
+    let (frames, close) = match response {
+        Response::Reply(frame) => (vec![frame], false),
+        Response::ReplyAndClose(frame) => (vec![frame], true),
+        Response::Replies(frames) => (frames, false),
+    };
+    Ok(RedisResponse {
+        frames: frames
+            .into_iter()
+            .map(|frame| encode_for(client, frame))
+            .collect(),
+        close,
+    })
+}
+
+// 写出完整的回复并 flush。write_all 在慢客户端每次只接收一部分时写完剩余的字节，
+// 对端关闭导致写不进去时返回 WriteZero 错误，不会丢弃数据。
+// 回复直接写到底层连接，Framed 只用来解码请求，它的写缓冲区始终为空
+async fn write_frame<W>(writer: &mut W, frame: RespFrame) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer.write_all(&frame.encode()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+// 命令统一返回 RESP3 类型，RESP2 连接在这里转换
+fn encode_for(client: &ClientState, frame: RespFrame) -> RespFrame {
+    match client.protocol {
+        3 => frame,
+        _ => frame.into_resp2(),
+    }
+}
+
+// 和 Redis 一样隐藏参数中的密码：AUTH 的全部参数，HELLO AUTH 的用户名和密码，ACL SETUSER 的规则，
+// CONFIG SET requirepass / masterauth 的值，MIGRATE AUTH 的密码和 AUTH2 的用户名、密码
+fn redacted_argv(frame: RespFrame) -> Vec<String> {
+    let mut argv = frame_argv(frame);
+    let is = |arg: Option<&String>, name: &str| arg.is_some_and(|a| a.eq_ignore_ascii_case(name));
+    let mut redacted = vec![false; argv.len()];
+    let mut redact = |from: usize, len: usize| {
+        for flag in redacted.iter_mut().skip(from).take(len) {
+            *flag = true;
+        }
+    };
+    if is(argv.first(), "auth") {
+        redact(1, argv.len());
+    } else if is(argv.first(), "acl") && is(argv.get(1), "setuser") {
+        redact(3, argv.len());
+    } else if is(argv.first(), "config") && is(argv.get(1), "set") {
+        for i in (2..argv.len()).step_by(2) {
+            if is(argv.get(i), "requirepass") || is(argv.get(i), "masterauth") {
+                redact(i + 1, 1);
+            }
+        }
+    } else if is(argv.first(), "hello") || is(argv.first(), "migrate") {
+        for i in 1..argv.len() {
+            if is(argv.get(i), "auth") {
+                // HELLO AUTH 带用户名和密码，MIGRATE AUTH 只带密码
+                redact(i + 1, if is(argv.first(), "hello") { 2 } else { 1 });
+            } else if is(argv.get(i), "auth2") {
+                redact(i + 1, 2);
+            }
+        }
+    }
+    for (arg, redacted) in argv.iter_mut().zip(redacted) {
+        if redacted {
+            *arg = "(redacted)".to_string();
+        }
+    }
+    argv
+}
+
+// 把请求数组转换成字符串参数列表
+fn frame_argv(frame: RespFrame) -> Vec<String> {
+    match frame {
+        RespFrame::Array(array) => array
+            .0
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(s) => String::from_utf8_lossy(&s).into_owned(),
+                other => format!("{:?}", other),
+            })
+            .collect(),
+        other => vec![format!("{:?}", other)],
+    }
 }
 
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn encode(&mut self, item: RespFrame, dst: &mut bytes::BytesMut) -> Result<()> {
-        info!("Encoding frame: {:?}", item);
+        debug!("Encoding frame: {:?}", item);
         let encoded = item.encode();
         dst.extend_from_slice(&encoded);
         Ok(())
@@ -72,7 +312,6 @@ impl Decoder for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Self::Item>> {
-        info!("Decoding frame");
         match RespFrame::decode(src) {
             Ok(frame) => Ok(Some(frame)),
             Err(RespError::NotComplete) => Ok(None),
@@ -80,3 +319,780 @@ impl Decoder for RespFrameCodec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    use tokio::{
+        io::{AsyncReadExt, ReadBuf},
+        net::TcpListener,
+    };
+    use tracing::Level;
+
+    use crate::{BulkString, RespArray, RespPush, SimpleString};
+
+    use super::*;
+
+    // 发送一条请求，返回一次读到的回复
+    async fn roundtrip(stream: &mut TcpStream, req: &[u8]) -> Result<Vec<u8>> {
+        stream.write_all(req).await?;
+        let mut buf = vec![0; 4096];
+        let n = stream.read(&mut buf).await?;
+        buf.truncate(n);
+        Ok(buf)
+    }
+
+    #[tokio::test]
+    async fn test_quit_closes_connection() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await?;
+            stream_handler(socket, Backend::new()).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        client.write_all(b"*1\r\n$4\r\nQUIT\r\n").await?;
+        // 服务端回复 +OK 之后主动关闭，read_to_end 会读到 EOF
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"+OK\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quit_drops_pipelined_commands_and_subscriptions() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        let server = backend.clone();
+        let handler = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await?;
+            stream_handler(socket, server).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        client
+            .write_all(b"*2\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n*1\r\n$4\r\nquit\r\n*3\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n")
+            .await?;
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await?;
+        assert_eq!(
+            buf,
+            b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:+1\r\n+OK\r\n"
+        );
+        handler.await??;
+        // QUIT 之后的命令不再执行，连接的订阅随连接一起释放
+        assert!(!backend.exists("k"));
+        assert_eq!(backend.pubsub_numsub("news"), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requirepass_over_connection() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::with_config(crate::ServerConfig {
+            requirepass: Some("secret".to_string()),
+            ..Default::default()
+        });
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await?;
+            stream_handler(socket, backend).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        let set = b"*3\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n";
+        assert_eq!(
+            roundtrip(&mut client, set).await?,
+            b"-NOAUTH Authentication required.\r\n"
+        );
+        let ret = roundtrip(&mut client, b"*2\r\n$4\r\nauth\r\n$5\r\nwrong\r\n").await?;
+        assert!(ret.starts_with(b"-WRONGPASS"));
+        let ret = roundtrip(&mut client, b"*2\r\n$4\r\nauth\r\n$6\r\nsecret\r\n").await?;
+        assert_eq!(ret, b"+OK\r\n");
+        assert_eq!(roundtrip(&mut client, set).await?, b"+OK\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_names_are_per_connection() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(stream_handler(socket, backend.clone()));
+            }
+        });
+
+        let mut a = TcpStream::connect(addr).await?;
+        let mut b = TcpStream::connect(addr).await?;
+        let id = b"*2\r\n$6\r\nclient\r\n$2\r\nid\r\n";
+        let (id_a, id_b) = (roundtrip(&mut a, id).await?, roundtrip(&mut b, id).await?);
+        assert!(id_a.starts_with(b":"));
+        assert_ne!(id_a, id_b);
+
+        let setname = b"*3\r\n$6\r\nclient\r\n$7\r\nsetname\r\n$1\r\na\r\n";
+        assert_eq!(roundtrip(&mut a, setname).await?, b"+OK\r\n");
+        let getname = b"*2\r\n$6\r\nclient\r\n$7\r\ngetname\r\n";
+        assert_eq!(roundtrip(&mut a, getname).await?, b"$1\r\na\r\n");
+        assert_eq!(roundtrip(&mut b, getname).await?, b"$0\r\n\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_pause_write_delays_only_writes() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::new(), CancellationToken::new()));
+
+        let mut admin = TcpStream::connect(addr).await?;
+        let mut other = TcpStream::connect(addr).await?;
+        let pause = b"*4\r\n$6\r\nclient\r\n$5\r\npause\r\n$3\r\n500\r\n$5\r\nWRITE\r\n";
+        assert_eq!(roundtrip(&mut admin, pause).await?, b"+OK\r\n");
+
+        let start = Instant::now();
+        let get = b"*2\r\n$3\r\nget\r\n$1\r\nk\r\n";
+        assert_eq!(roundtrip(&mut other, get).await?, b"$-1\r\n");
+        assert!(start.elapsed() < std::time::Duration::from_millis(300));
+
+        let set = b"*3\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n";
+        assert_eq!(roundtrip(&mut other, set).await?, b"+OK\r\n");
+        assert!(start.elapsed() >= std::time::Duration::from_millis(450));
+        Ok(())
+    }
+
+    // 把日志写到共享的缓冲区里，方便检查输出
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_command_log_fields() -> Result<()> {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let frame = RespArray::new(vec![
+            BulkString::new("set").into(),
+            BulkString::new("mykey").into(),
+            BulkString::new("value").into(),
+        ])
+        .into();
+        let request = RedisRequest {
+            frame,
+            backend: Backend::new(),
+        };
+        request_handler(request, &mut ClientState::default()).await?;
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone())?;
+        let line = logs
+            .lines()
+            .find(|line| line.contains("command executed"))
+            .expect("command log line");
+        assert!(line.contains("name=\"set\""), "{}", line);
+        assert!(line.contains("key=\"mykey\""), "{}", line);
+        assert!(line.contains("latency_us="), "{}", line);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hello_negotiates_reply_encoding() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new("v").into(),
+        );
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(stream_handler(socket, backend.clone()));
+            }
+        });
+
+        let hgetall = b"*2\r\n$7\r\nhgetall\r\n$1\r\nh\r\n";
+
+        let mut resp2 = TcpStream::connect(addr).await?;
+        assert_eq!(
+            roundtrip(&mut resp2, hgetall).await?,
+            b"*2\r\n$1\r\nf\r\n$1\r\nv\r\n"
+        );
+
+        let mut resp3 = TcpStream::connect(addr).await?;
+        let ret = roundtrip(&mut resp3, b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n").await?;
+        assert!(ret.starts_with(b"%7\r\n"));
+        assert_eq!(
+            roundtrip(&mut resp3, hgetall).await?,
+            b"%1\r\n+f\r\n$1\r\nv\r\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_kill_closes_connection() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(stream_handler(socket, backend.clone()));
+            }
+        });
+
+        let mut victim = TcpStream::connect(addr).await?;
+        let mut killer = TcpStream::connect(addr).await?;
+        // 先让 victim 执行一条命令，保证它已经注册
+        victim.write_all(b"*1\r\n$5\r\nhello\r\n").await?;
+        let mut buf = vec![0; 1024];
+        let n = victim.read(&mut buf).await?;
+        let info = String::from_utf8_lossy(&buf[..n]).into_owned();
+        // RESP2 下 HELLO 回复是扁平数组，id 紧跟在 "id" 之后
+        let id = info
+            .split("\r\n")
+            .skip_while(|line| *line != "id")
+            .nth(1)
+            .and_then(|line| line.strip_prefix(":+"))
+            .expect("client id");
+        // 阻塞在 BLPOP 中的连接同样会被关闭
+        victim
+            .write_all(b"*3\r\n$5\r\nblpop\r\n$4\r\nlist\r\n$1\r\n0\r\n")
+            .await?;
+
+        let req = format!(
+            "*4\r\n$6\r\nclient\r\n$4\r\nkill\r\n$2\r\nID\r\n${}\r\n{}\r\n",
+            id.len(),
+            id
+        );
+        killer.write_all(req.as_bytes()).await?;
+        let n = killer.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b":+1\r\n");
+
+        let mut rest = Vec::new();
+        victim.read_to_end(&mut rest).await?;
+        assert!(rest.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slow_command_is_logged() -> Result<()> {
+        let backend = Backend::new();
+        backend.set_config("slowlog-log-slower-than", "50000")?;
+        let mut client = ClientState::default();
+        let request = |args: &[&str]| RedisRequest {
+            frame: RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::new(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            backend: backend.clone(),
+        };
+        request_handler(request(&["get", "key"]), &mut client).await?;
+        request_handler(request(&["debug", "sleep", "0.1"]), &mut client).await?;
+
+        let entries = backend.slowlog_get(None);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].argv, ["debug", "sleep", "0.1"]);
+        assert!(entries[0].duration_us >= 100_000);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_frames_are_delivered_between_commands() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        let server = backend.clone();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await?;
+            stream_handler(socket, server).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        let mut buf = vec![0; 1024];
+        client
+            .write_all(b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        let info = String::from_utf8_lossy(&buf[..n]).into_owned();
+        let id: u64 = info
+            .split("\r\n")
+            .skip_while(|line| *line != "+id")
+            .nth(1)
+            .and_then(|line| line.strip_prefix(":+"))
+            .and_then(|id| id.parse().ok())
+            .expect("client id");
+
+        let frame = RespPush::new(vec![
+            BulkString::new("message").into(),
+            BulkString::new("hi").into(),
+        ]);
+        assert!(backend.push_to_client(id, frame.into()));
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b">2\r\n$7\r\nmessage\r\n$2\r\nhi\r\n");
+
+        // 推送之后连接照常处理命令
+        client.write_all(b"*2\r\n$3\r\nget\r\n$1\r\nk\r\n").await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"_\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_publish_reaches_subscriber() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::new(), CancellationToken::new()));
+
+        let mut subscriber = TcpStream::connect(addr).await?;
+        let mut publisher = TcpStream::connect(addr).await?;
+        let ret = roundtrip(&mut subscriber, b"*2\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n").await?;
+        assert_eq!(ret, b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:+1\r\n");
+
+        let publish = b"*3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$5\r\nhello\r\n";
+        assert_eq!(roundtrip(&mut publisher, publish).await?, b":+1\r\n");
+        let mut buf = vec![0; 1024];
+        let n = subscriber.read(&mut buf).await?;
+        assert_eq!(
+            &buf[..n],
+            b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+        );
+
+        let ret = roundtrip(&mut subscriber, b"*1\r\n$11\r\nunsubscribe\r\n").await?;
+        assert_eq!(ret, b"*3\r\n$11\r\nunsubscribe\r\n$4\r\nnews\r\n:+0\r\n");
+        assert_eq!(roundtrip(&mut publisher, publish).await?, b":+0\r\n");
+        // 取消订阅之后恢复普通命令，下一次读取到的是 GET 的回复而不是消息
+        let ret = roundtrip(&mut subscriber, b"*2\r\n$3\r\nget\r\n$1\r\nk\r\n").await?;
+        assert_eq!(ret, b"$-1\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_wire_format_follows_protocol() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::new(), CancellationToken::new()));
+
+        let mut resp2 = TcpStream::connect(addr).await?;
+        let mut resp3 = TcpStream::connect(addr).await?;
+        let mut publisher = TcpStream::connect(addr).await?;
+        roundtrip(&mut resp3, b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n").await?;
+
+        // 订阅确认：RESP2 是数组，RESP3 是 push
+        let subscribe = b"*2\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n";
+        assert_eq!(
+            roundtrip(&mut resp2, subscribe).await?,
+            b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:+1\r\n"
+        );
+        assert_eq!(
+            roundtrip(&mut resp3, subscribe).await?,
+            b">3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:+1\r\n"
+        );
+
+        let publish = b"*3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$2\r\nhi\r\n";
+        assert_eq!(roundtrip(&mut publisher, publish).await?, b":+2\r\n");
+        let mut buf = vec![0; 1024];
+        let n = resp2.read(&mut buf).await?;
+        assert_eq!(
+            &buf[..n],
+            b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n"
+        );
+        let n = resp3.read(&mut buf).await?;
+        assert_eq!(
+            &buf[..n],
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n"
+        );
+
+        // 订阅状态下 RESP3 连接可以继续执行普通命令，RESP2 连接不行
+        let get = b"*2\r\n$3\r\nget\r\n$1\r\nk\r\n";
+        assert_eq!(roundtrip(&mut resp3, get).await?, b"_\r\n");
+        assert!(roundtrip(&mut resp2, get)
+            .await?
+            .starts_with(b"-ERR Can't execute 'get'"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_configure_socket_applies_tcp_options() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let _client = TcpStream::connect(listener.local_addr()?).await?;
+        let (socket, _) = listener.accept().await?;
+
+        configure_socket(&socket, &ServerConfig::default())?;
+        assert!(socket.nodelay()?);
+        assert!(SockRef::from(&socket).keepalive()?);
+
+        let config = ServerConfig {
+            tcp_nodelay: false,
+            tcp_keepalive: 0,
+            ..Default::default()
+        };
+        configure_socket(&socket, &config)?;
+        assert!(!socket.nodelay()?);
+        assert!(!SockRef::from(&socket).keepalive()?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_monitor_streams_commands() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::new(), CancellationToken::new()));
+
+        let mut monitor = TcpStream::connect(addr).await?;
+        let mut client = TcpStream::connect(addr).await?;
+        let mut buf = vec![0; 1024];
+        monitor.write_all(b"*1\r\n$7\r\nmonitor\r\n").await?;
+        let n = monitor.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+
+        client
+            .write_all(b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+        let n = monitor.read(&mut buf).await?;
+        let line = String::from_utf8_lossy(&buf[..n]).into_owned();
+        let expected = format!(
+            " [0 {}] \"SET\" \"key\" \"value\"\r\n",
+            client.local_addr()?
+        );
+        assert!(
+            line.starts_with('+') && line.ends_with(&expected),
+            "{}",
+            line
+        );
+
+        // MONITOR 模式下只能执行 QUIT
+        monitor
+            .write_all(b"*2\r\n$3\r\nget\r\n$3\r\nkey\r\n")
+            .await?;
+        let n = monitor.read(&mut buf).await?;
+        assert!(buf[..n].starts_with(b"-ERR Can't execute 'get'"));
+        monitor.write_all(b"*1\r\n$4\r\nquit\r\n").await?;
+        let n = monitor.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+        assert_eq!(monitor.read(&mut buf).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_monitor_redacts_secrets_and_skips_rejected() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::with_config(crate::ServerConfig {
+            requirepass: Some("secret".to_string()),
+            ..Default::default()
+        });
+        tokio::spawn(serve(listener, backend, CancellationToken::new()));
+
+        let mut monitor = TcpStream::connect(addr).await?;
+        let mut client = TcpStream::connect(addr).await?;
+        let mut buf = vec![0; 1024];
+        monitor
+            .write_all(b"*2\r\n$4\r\nauth\r\n$6\r\nsecret\r\n*1\r\n$7\r\nmonitor\r\n")
+            .await?;
+        let mut received = Vec::new();
+        while received.len() < 10 {
+            let n = monitor.read(&mut buf).await?;
+            received.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(received, b"+OK\r\n+OK\r\n");
+
+        // 认证之前被拒绝的命令没有执行，不转发
+        client
+            .write_all(b"*2\r\n$3\r\nget\r\n$3\r\nkey\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert!(buf[..n].starts_with(b"-NOAUTH"));
+        client
+            .write_all(b"*2\r\n$4\r\nauth\r\n$6\r\nsecret\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+        let n = monitor.read(&mut buf).await?;
+        let line = String::from_utf8_lossy(&buf[..n]).into_owned();
+        assert!(line.ends_with("\"auth\" \"(redacted)\"\r\n"), "{}", line);
+
+        client
+            .write_all(b"*4\r\n$6\r\nconfig\r\n$3\r\nset\r\n$11\r\nrequirepass\r\n$6\r\nsecret\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+        let n = monitor.read(&mut buf).await?;
+        let line = String::from_utf8_lossy(&buf[..n]).into_owned();
+        assert!(
+            line.ends_with("\"config\" \"set\" \"requirepass\" \"(redacted)\"\r\n"),
+            "{}",
+            line
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slowlog_redacts_secrets_and_skips_rejected() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::with_config(crate::ServerConfig {
+            requirepass: Some("secret".to_string()),
+            slowlog_log_slower_than: 0,
+            ..Default::default()
+        });
+        tokio::spawn(serve(listener, backend.clone(), CancellationToken::new()));
+
+        let mut client = TcpStream::connect(addr).await?;
+        let mut buf = vec![0; 1024];
+        client
+            .write_all(b"*2\r\n$3\r\nget\r\n$3\r\nkey\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert!(buf[..n].starts_with(b"-NOAUTH"));
+        client
+            .write_all(b"*2\r\n$4\r\nauth\r\n$6\r\nsecret\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+        client
+            .write_all(
+                b"*5\r\n$3\r\nacl\r\n$7\r\nsetuser\r\n$5\r\nalice\r\n$2\r\non\r\n$5\r\n>pass\r\n",
+            )
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+
+        let argv: Vec<_> = backend
+            .slowlog_get(None)
+            .into_iter()
+            .map(|entry| entry.argv)
+            .collect();
+        assert_eq!(
+            argv,
+            [
+                vec!["acl", "setuser", "alice", "(redacted)", "(redacted)"],
+                vec!["auth", "(redacted)"],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_redacted_argv() {
+        let argv = |args: &[&str]| {
+            redacted_argv(RespFrame::Array(RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::new(arg.as_bytes().to_vec()).into())
+                    .collect::<Vec<RespFrame>>(),
+            )))
+        };
+        assert_eq!(
+            argv(&["ACL", "SETUSER", "alice", "on", ">pass", "+@all"]),
+            [
+                "ACL",
+                "SETUSER",
+                "alice",
+                "(redacted)",
+                "(redacted)",
+                "(redacted)"
+            ]
+        );
+        assert_eq!(
+            argv(&["HELLO", "3", "AUTH", "alice", "pass", "SETNAME", "c"]),
+            [
+                "HELLO",
+                "3",
+                "AUTH",
+                "(redacted)",
+                "(redacted)",
+                "SETNAME",
+                "c"
+            ]
+        );
+        assert_eq!(
+            argv(&["CONFIG", "SET", "maxmemory", "1mb", "masterauth", "pass"]),
+            [
+                "CONFIG",
+                "SET",
+                "maxmemory",
+                "1mb",
+                "masterauth",
+                "(redacted)"
+            ]
+        );
+        assert_eq!(
+            argv(&["MIGRATE", "h", "6379", "k", "0", "1000", "AUTH2", "u", "p", "KEYS"]),
+            [
+                "MIGRATE",
+                "h",
+                "6379",
+                "k",
+                "0",
+                "1000",
+                "AUTH2",
+                "(redacted)",
+                "(redacted)",
+                "KEYS"
+            ]
+        );
+        assert_eq!(argv(&["SET", "auth", "v"]), ["SET", "auth", "v"]);
+    }
+
+    #[tokio::test]
+    async fn test_reset_leaves_monitor_mode() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::new(), CancellationToken::new()));
+
+        let mut monitor = TcpStream::connect(addr).await?;
+        let mut client = TcpStream::connect(addr).await?;
+        let mut buf = vec![0; 1024];
+        monitor.write_all(b"*1\r\n$7\r\nmonitor\r\n").await?;
+        let n = monitor.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+        monitor.write_all(b"*1\r\n$5\r\nreset\r\n").await?;
+        let n = monitor.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+RESET\r\n");
+
+        // RESET 之后不再收到其他连接的命令，自己也可以正常执行命令
+        client.write_all(b"*1\r\n$4\r\nping\r\n").await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+PONG\r\n");
+        monitor
+            .write_all(b"*2\r\n$3\r\nget\r\n$3\r\nkey\r\n")
+            .await?;
+        let n = monitor.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"$-1\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_maxclients_rejects_extra_connection() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        backend.set_config("maxclients", "2")?;
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(stream_handler(socket, backend.clone()));
+            }
+        });
+
+        let get = b"*2\r\n$3\r\nget\r\n$1\r\nk\r\n";
+        let mut buf = vec![0; 1024];
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            // 执行一条命令，确保连接已经注册
+            let mut client = TcpStream::connect(addr).await?;
+            client.write_all(get).await?;
+            let n = client.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b"$-1\r\n");
+            clients.push(client);
+        }
+
+        let mut rejected = TcpStream::connect(addr).await?;
+        let mut reply = Vec::new();
+        rejected.read_to_end(&mut reply).await?;
+        assert_eq!(reply, b"-ERR max number of clients reached\r\n");
+        Ok(())
+    }
+
+    // 每次 write 最多接收 max 个字节，模拟很慢的客户端
+    struct Throttled<S> {
+        inner: S,
+        max: usize,
+    }
+
+    impl<S: AsyncRead + Unpin> AsyncRead for Throttled<S> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+        }
+    }
+
+    impl<S: AsyncWrite + Unpin> AsyncWrite for Throttled<S> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let n = buf.len().min(this.max);
+            Pin::new(&mut this.inner).poll_write(cx, &buf[..n])
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_writes_deliver_full_response() -> Result<()> {
+        let backend = Backend::new();
+        let value = "x".repeat(10_000);
+        backend.set("big".to_string(), BulkString::new(value.clone()).into());
+        // duplex 的缓冲区比回复小得多，服务端要等客户端读走数据才能继续写
+        let (server, mut client) = tokio::io::duplex(64);
+        let mut state = ClientState::default();
+        let (_token, push) = backend
+            .register_client(state.id, None)
+            .expect("client slot");
+        let handle = tokio::spawn(async move {
+            let stream = Throttled {
+                inner: server,
+                max: 7,
+            };
+            handle_connection(stream, &backend, &mut state, push).await
+        });
+
+        client
+            .write_all(b"*2\r\n$3\r\nget\r\n$3\r\nbig\r\n")
+            .await?;
+        let expected = format!("$10000\r\n{}\r\n", value);
+        let mut buf = vec![0; expected.len()];
+        tokio::time::timeout(Duration::from_secs(5), client.read_exact(&mut buf)).await??;
+        assert_eq!(buf, expected.as_bytes());
+
+        drop(client);
+        handle.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_frame_reports_short_write() {
+        // 只能写入 4 个字节，"+OK\r\n" 写不完时返回错误而不是静默丢弃
+        let mut buf = [0; 4];
+        let mut writer = io::Cursor::new(&mut buf[..]);
+        let ret = write_frame(&mut writer, SimpleString::new("OK").into()).await;
+        assert!(ret.is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::net::ToSocketAddrs;
+use tracing::{debug, warn};
+
+use crate::{Backend, Client, RespFrame};
+
+// 副本重新拉取全量数据的间隔
+pub const RESYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+// 用 SYNC 从主节点拉取一次全量数据，替换本地所有的 key。
+// 不是完整的复制协议：两次同步之间主节点上的修改要等到下一次同步才能看到。
+// 设置了 masterauth 时先认证，每次同步都读取最新的配置
+pub async fn sync_from(backend: &Backend, upstream: impl ToSocketAddrs) -> Result<()> {
+    let mut client = Client::connect(upstream).await?;
+    let (user, password) = {
+        let config = backend.config();
+        (config.masteruser.clone(), config.masterauth.clone())
+    };
+    if let Some(password) = password {
+        let auth = match user {
+            Some(user) => client.command(["AUTH".to_string(), user, password]).await?,
+            None => client.command(["AUTH".to_string(), password]).await?,
+        };
+        if let RespFrame::Error(e) = auth {
+            return Err(anyhow!("{}", e.as_str()));
+        }
+    }
+    match client.command(["SYNC"]).await? {
+        RespFrame::BulkString(data) => Ok(backend.load_bytes(&data)?),
+        RespFrame::Error(e) => Err(anyhow!("{}", e.as_str())),
+        other => Err(anyhow!("unexpected reply to SYNC: {:?}", other)),
+    }
+}
+
+// 副本的同步循环：启动时立即同步一次，之后每隔 interval 重新同步，失败时等下一轮重试
+pub async fn run(backend: Backend, upstream: String, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match sync_from(&backend, upstream.as_str()).await {
+            Ok(()) => debug!("Synced {} keys from {}", backend.dbsize(), upstream),
+            Err(e) => warn!("Failed to sync from {}: {}", upstream, e),
+        }
+    }
+}
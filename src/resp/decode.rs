@@ -3,12 +3,26 @@ use bytes::{Buf, BytesMut};
 
 const CRLF: &[u8] = b"\r\n";
 const CRLF_LEN: usize = CRLF.len();
+// 聚合类型的最大嵌套层数，解码是递归的，不限制的话深层嵌套的帧会把栈撑爆
+const MAX_NESTING_DEPTH: usize = 128;
 
 // 定义 RESP 解码器
 // 从 RESP 协议中解析帧，帧数据格式是 Bytes 格式，每次解析一个帧，返回一个 RespFrame，然后 指针移动到下一个帧的位置
 impl RespDecoder for RespFrame {
     const PREFIX: &'static str = "";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_at(buf, 0)
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_at(buf, 0)
+    }
+}
+
+impl RespFrame {
+    // depth 是当前帧所在的嵌套层数，顶层帧为 0
+    fn decode_at(buf: &mut BytesMut, depth: usize) -> Result<Self, RespError> {
+        check_depth(depth)?;
         let mut iter = buf.iter().peekable();
         match iter.peek() {
             Some(b'+') => {
@@ -23,34 +37,30 @@ impl RespDecoder for RespFrame {
                 let s: Self = i64::decode(buf)?.into();
                 Ok(s)
             }
-            Some(b'$') => match RespNullBulkString::decode(buf) {
-                Ok(s) => Ok(s.into()),
-                Err(RespError::NotComplete) => {
-                    let s: Self = BulkString::decode(buf)?.into();
-                    Ok(s)
-                }
-                Err(_) => {
-                    let s: Self = BulkString::decode(buf)?.into();
-                    Ok(s)
-                }
-            },
+            // 只有完整的 `$-1\r\n` 是 null bulk string，`$0\r\n\r\n` 是长度为 0 的普通 bulk string
+            Some(b'$') if buf.starts_with(b"$-1\r\n") => {
+                let s: Self = RespNullBulkString::decode(buf)?.into();
+                Ok(s)
+            }
+            Some(b'$') => {
+                let s: Self = BulkString::decode(buf)?.into();
+                Ok(s)
+            }
+            // 和 bulk string 一样按完整的 `*-1\r\n` 判断 null array，`*0\r\n` 比它短但已经是完整的空数组
+            Some(b'*') if buf.starts_with(b"*-1\r\n") => {
+                let s: Self = RespNullArray::decode(buf)?.into();
+                Ok(s)
+            }
             Some(b'*') => {
-                // try null array first
-                match RespNullArray::decode(buf) {
-                    Ok(s) => Ok(s.into()),
-                    Err(RespError::NotComplete) => Err(RespError::NotComplete),
-                    Err(_) => {
-                        let s: Self = RespArray::decode(buf)?.into();
-                        Ok(s)
-                    }
-                }
+                let s: Self = RespArray::decode_at(buf, depth)?.into();
+                Ok(s)
             }
             Some(b'%') => {
-                let s: Self = RespMap::decode(buf)?.into();
+                let s: Self = RespMap::decode_at(buf, depth)?.into();
                 Ok(s)
             }
             Some(b'~') => {
-                let s: Self = RespSet::decode(buf)?.into();
+                let s: Self = RespSet::decode_at(buf, depth)?.into();
                 Ok(s)
             }
             Some(b'_') => {
@@ -65,6 +75,10 @@ impl RespDecoder for RespFrame {
                 let s: Self = f64::decode(buf)?.into();
                 Ok(s)
             }
+            Some(b'>') => {
+                let s: Self = RespPush::decode_at(buf, depth)?.into();
+                Ok(s)
+            }
             None => Err(RespError::NotComplete),
             _ => Err(RespError::InvalidFrameType(format!(
                 "expect_length: unknown frame type: {:?}",
@@ -73,22 +87,40 @@ impl RespDecoder for RespFrame {
         }
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length_at(buf: &[u8], depth: usize) -> Result<usize, RespError> {
+        check_depth(depth)?;
         let mut iter = buf.iter().peekable();
         match iter.peek() {
             Some(b'+') => SimpleString::expect_length(buf),
             Some(b'-') => SimpleError::expect_length(buf),
             Some(b':') => i64::expect_length(buf),
+            // 嵌套在数组中的 null 值没有数据部分
+            Some(b'$') if buf.starts_with(b"$-1\r\n") => Ok(5),
             Some(b'$') => BulkString::expect_length(buf),
-            Some(b'*') => RespArray::expect_length(buf),
-            Some(b'%') => RespMap::expect_length(buf),
-            Some(b'~') => RespSet::expect_length(buf),
+            Some(b'*') if buf.starts_with(b"*-1\r\n") => Ok(5),
+            Some(b'*') => RespArray::expect_length_at(buf, depth),
+            Some(b'%') => RespMap::expect_length_at(buf, depth),
+            Some(b'~') => RespSet::expect_length_at(buf, depth),
             Some(b'_') => RespNull::expect_length(buf),
             Some(b'#') => bool::expect_length(buf),
             Some(b',') => f64::expect_length(buf),
+            Some(b'>') => RespPush::expect_length_at(buf, depth),
             _ => Err(RespError::NotComplete),
         }
     }
+
+    // 从 buf 中依次解码所有完整的帧，遇到第一个不完整（或无法解析）的帧时停止，
+    // 返回已经解码的帧，剩余的数据留在 buf 中等待后续数据或由调用方用 decode 报告错误
+    pub fn decode_all(buf: &mut BytesMut) -> Vec<RespFrame> {
+        let mut frames = Vec::new();
+        while !buf.is_empty() {
+            match RespFrame::decode(buf) {
+                Ok(frame) => frames.push(frame),
+                Err(_) => break,
+            }
+        }
+        frames
+    }
 }
 
 impl RespDecoder for SimpleString {
@@ -205,7 +237,7 @@ impl RespDecoder for BulkString {
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
         let remained = &buf[end + CRLF_LEN..];
-        if remained.len() < len + CRLF_LEN {
+        if remained.len() < check_bulk_length(len)? + CRLF_LEN {
             return Err(RespError::NotComplete);
         }
 
@@ -216,7 +248,7 @@ impl RespDecoder for BulkString {
 
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        Ok(end + CRLF_LEN + len + CRLF_LEN)
+        Ok(end + CRLF_LEN + check_bulk_length(len)? + CRLF_LEN)
     }
 }
 
@@ -231,24 +263,67 @@ impl RespDecoder for RespArray {
 
     // 解析 RESP 数组
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_at(buf, 0)
+    }
+
+    // 期望的长度
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_at(buf, 0)
+    }
+}
+
+impl RespArray {
+    fn decode_at(buf: &mut BytesMut, depth: usize) -> Result<Self, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total = calc_total_length(buf, end, len, Self::PREFIX)?;
+        let total = calc_total_length(buf, end, len, Self::PREFIX, depth)?;
         if buf.len() < total {
             return Err(RespError::NotComplete);
         }
         buf.advance(end + CRLF_LEN);
         let mut array = Vec::with_capacity(len);
         for _ in 0..len {
-            let frame = RespFrame::decode(buf)?;
+            let frame = RespFrame::decode_at(buf, depth + 1)?;
             array.push(frame);
         }
         Ok(RespArray::new(array))
     }
 
-    // 期望的长度
+    fn expect_length_at(buf: &[u8], depth: usize) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calc_total_length(buf, end, len, Self::PREFIX, depth)
+    }
+}
+
+// - push: "><length-for-elements>\r\n<element-1>..<element-n>"
+impl RespDecoder for RespPush {
+    const PREFIX: &'static str = ">";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_at(buf, 0)
+    }
+
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_at(buf, 0)
+    }
+}
+
+impl RespPush {
+    fn decode_at(buf: &mut BytesMut, depth: usize) -> Result<Self, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        calc_total_length(buf, end, len, Self::PREFIX)
+        let total = calc_total_length(buf, end, len, Self::PREFIX, depth)?;
+        if buf.len() < total {
+            return Err(RespError::NotComplete);
+        }
+        buf.advance(end + CRLF_LEN);
+        let mut push = Vec::with_capacity(len);
+        for _ in 0..len {
+            push.push(RespFrame::decode_at(buf, depth + 1)?);
+        }
+        Ok(RespPush::new(push))
+    }
+
+    fn expect_length_at(buf: &[u8], depth: usize) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calc_total_length(buf, end, len, Self::PREFIX, depth)
     }
 }
 
@@ -256,8 +331,18 @@ impl RespDecoder for RespArray {
 impl RespDecoder for RespMap {
     const PREFIX: &'static str = "%";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_at(buf, 0)
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_at(buf, 0)
+    }
+}
+
+impl RespMap {
+    fn decode_at(buf: &mut BytesMut, depth: usize) -> Result<Self, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total = calc_total_length(buf, end, len, Self::PREFIX)?;
+        let total = calc_total_length(buf, end, len, Self::PREFIX, depth)?;
         if buf.len() < total {
             return Err(RespError::NotComplete);
         }
@@ -265,15 +350,15 @@ impl RespDecoder for RespMap {
         let mut map = RespMap::new();
         for _ in 0..len {
             let key = SimpleString::decode(buf)?;
-            let value = RespFrame::decode(buf)?;
+            let value = RespFrame::decode_at(buf, depth + 1)?;
             map.insert(key.0, value);
         }
         Ok(map)
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length_at(buf: &[u8], depth: usize) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        calc_total_length(buf, end, len, Self::PREFIX)
+        calc_total_length(buf, end, len, Self::PREFIX, depth)
     }
 }
 
@@ -281,24 +366,34 @@ impl RespDecoder for RespMap {
 impl RespDecoder for RespSet {
     const PREFIX: &'static str = "~";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_at(buf, 0)
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_at(buf, 0)
+    }
+}
+
+impl RespSet {
+    fn decode_at(buf: &mut BytesMut, depth: usize) -> Result<Self, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total = calc_total_length(buf, end, len, Self::PREFIX)?;
+        let total = calc_total_length(buf, end, len, Self::PREFIX, depth)?;
         if buf.len() < total {
             return Err(RespError::NotComplete);
         }
         buf.advance(end + CRLF_LEN); // skip the prefix
         let mut set = Vec::with_capacity(len);
         for _ in 0..len {
-            let frame = RespFrame::decode(buf)?;
+            let frame = RespFrame::decode_at(buf, depth + 1)?;
             set.push(frame);
         }
 
         Ok(RespSet::new(set))
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length_at(buf: &[u8], depth: usize) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        calc_total_length(buf, end, len, Self::PREFIX)
+        calc_total_length(buf, end, len, Self::PREFIX, depth)
     }
 }
 
@@ -357,14 +452,44 @@ fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), RespError> {
     Ok((end, s.parse()?))
 }
 
-// 获得去掉前缀后的长度，然后根据长度计算包括CRLF的总长度, 用于判断是否完整, 以及截取数据
-fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result<usize, RespError> {
+// bulk string 的长度由客户端给出，超过上限直接报错，避免计算总长度时溢出或一直等待不可能到达的数据
+fn check_bulk_length(len: usize) -> Result<usize, RespError> {
+    if len > MAX_BULK_LENGTH {
+        return Err(RespError::InvalidFrameLength(len as isize));
+    }
+    Ok(len)
+}
+
+// 嵌套层数超过上限时报错，不再继续递归
+fn check_depth(depth: usize) -> Result<(), RespError> {
+    if depth > MAX_NESTING_DEPTH {
+        return Err(RespError::InvalidFrame(format!(
+            "nesting depth exceeds {}",
+            MAX_NESTING_DEPTH
+        )));
+    }
+    Ok(())
+}
+
+// 获得去掉前缀后的长度，然后根据长度计算包括CRLF的总长度, 用于判断是否完整, 以及截取数据。
+// depth 是聚合类型自身的嵌套层数，元素在 depth + 1 层
+fn calc_total_length(
+    buf: &[u8],
+    end: usize,
+    len: usize,
+    prefix: &str,
+    depth: usize,
+) -> Result<usize, RespError> {
     let mut total = end + CRLF_LEN;
     let mut data = &buf[total..];
     match prefix {
-        "*" | "~" => {
+        "*" | "~" | ">" => {
             for _ in 0..len {
-                let len = RespFrame::expect_length(data)?;
+                let len = RespFrame::expect_length_at(data, depth + 1)?;
+                // 元素只收到了一部分
+                if data.len() < len {
+                    return Err(RespError::NotComplete);
+                }
                 data = &data[len..];
                 total += len;
             }
@@ -376,7 +501,10 @@ fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result
                 data = &data[len..];
                 total += len;
 
-                let len = RespFrame::expect_length(data)?;
+                let len = RespFrame::expect_length_at(data, depth + 1)?;
+                if data.len() < len {
+                    return Err(RespError::NotComplete);
+                }
                 data = &data[len..];
                 total += len;
             }
@@ -448,6 +576,67 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_empty_bulk_string_decode() -> Result<()> {
+        let mut buf = BytesMut::from("$0\r\n\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, BulkString::new(Vec::new()).into());
+        assert!(buf.is_empty());
+
+        // 空 bulk string 还没有收到结尾的 CRLF
+        let mut buf = BytesMut::from("$0\r\n");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_length_decode() -> Result<()> {
+        // 长度接近 usize::MAX 时计算总长度不能溢出
+        let mut buf = BytesMut::from("$18446744073709551615\r\n");
+        assert!(matches!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::InvalidFrameLength(_))
+        ));
+        let mut buf = BytesMut::from("*1\r\n$18446744073709551614\r\n");
+        assert!(matches!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::InvalidFrameLength(_))
+        ));
+
+        // 数组声明了大量元素但数据不足时只返回 NotComplete，不会预先分配
+        let mut buf = BytesMut::from("*1000000\r\n:1\r\n");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_bulk_string_frame_decode() -> Result<()> {
+        let mut buf = BytesMut::from("$-1\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespNullBulkString.into());
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("$-1\r");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_with_empty_and_null_elements_decode() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$0\r\n\r\n$-1\r\n*-1\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![
+                BulkString::new(Vec::new()).into(),
+                RespNullBulkString.into(),
+                RespNullArray.into(),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_null_bulk_string_decode() -> Result<()> {
         let mut buf = BytesMut::from("$-1\r\n");
@@ -474,6 +663,20 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_empty_array_frame_decode() -> Result<()> {
+        let mut buf = BytesMut::from("*0\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespArray::new(vec![]).into());
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("*-1\r");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        buf.extend_from_slice(b"\n");
+        assert_eq!(RespFrame::decode(&mut buf)?, RespNullArray.into());
+        Ok(())
+    }
+
     #[test]
     fn test_array_decode() -> Result<()> {
         let mut buf = BytesMut::from("*2\r\n+hello\r\n-error\r\n");
@@ -506,6 +709,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_push_decode() -> Result<()> {
+        let mut buf = BytesMut::from(">2\r\n$7\r\nmessage\r\n:1\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        let expected: RespFrame =
+            RespPush::new(vec![BulkString::new("message").into(), 1.into()]).into();
+        assert_eq!(frame, expected);
+
+        let mut buf = BytesMut::from(">2\r\n$7\r\nmessage\r\n");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+
     #[test]
     fn test_f64_decode() -> Result<()> {
         let mut buf = BytesMut::from(",1000.0\r\n");
@@ -524,4 +740,65 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_decode_all_stops_at_partial_frame() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*1\r\n$4\r\nping\r\n+OK\r\n*2\r\n$3\r\nget\r\n$3\r\nke".as_bytes());
+        let frames = RespFrame::decode_all(&mut buf);
+        assert_eq!(
+            frames,
+            vec![
+                RespArray::new(vec![BulkString::new("ping").into()]).into(),
+                SimpleString::new("OK").into(),
+            ]
+        );
+        assert_eq!(
+            buf,
+            BytesMut::from("*2\r\n$3\r\nget\r\n$3\r\nke".as_bytes())
+        );
+
+        buf.extend_from_slice(b"y\r\n");
+        let frames = RespFrame::decode_all(&mut buf);
+        assert_eq!(
+            frames,
+            vec![RespArray::new(vec![
+                BulkString::new("get").into(),
+                BulkString::new("key").into()
+            ])
+            .into()]
+        );
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nesting_depth_limit() -> Result<()> {
+        let nested = |depth: usize| {
+            let mut data = "*1\r\n".repeat(depth);
+            data.push_str(":1\r\n");
+            BytesMut::from(data.as_bytes())
+        };
+        let mut buf = nested(MAX_NESTING_DEPTH);
+        assert!(RespFrame::decode(&mut buf).is_ok());
+        assert!(buf.is_empty());
+
+        let mut buf = nested(MAX_NESTING_DEPTH + 1);
+        assert!(matches!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::InvalidFrame(_))
+        ));
+        // 还没收完的深层嵌套帧同样直接报错，不会递归到底
+        let mut buf = BytesMut::from("*1\r\n".repeat(500_000).as_bytes());
+        assert!(matches!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::InvalidFrame(_))
+        ));
+        let mut buf = BytesMut::from(">1\r\n~1\r\n".repeat(100).as_bytes());
+        assert!(matches!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::InvalidFrame(_))
+        ));
+        Ok(())
+    }
 }
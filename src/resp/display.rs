@@ -0,0 +1,138 @@
+use std::fmt;
+
+use crate::resp::*;
+
+// 按 redis-cli 的格式输出帧，方便在日志中查看：
+// bulk string 带引号，数组逐行编号，null 输出为 (nil)，整数输出为 (integer) N
+impl fmt::Display for RespFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", render(self).join("\n"))
+    }
+}
+
+fn render(frame: &RespFrame) -> Vec<String> {
+    match frame {
+        RespFrame::SimpleString(s) => vec![s.to_string()],
+        RespFrame::Error(e) => vec![format!("(error) {}", e.as_str())],
+        RespFrame::Integer(n) => vec![format!("(integer) {}", n)],
+        RespFrame::BulkString(s) => vec![quote(s)],
+        RespFrame::Null(_) | RespFrame::NullArray(_) | RespFrame::NullBulkString(_) => {
+            vec!["(nil)".to_string()]
+        }
+        RespFrame::Boolean(b) => vec![format!("({})", b)],
+        RespFrame::Double(d) => vec![format!("(double) {}", d)],
+        RespFrame::Array(array) if array.is_empty() => vec!["(empty array)".to_string()],
+        RespFrame::Array(array) => render_items(array.iter().map(|item| (None, item)), ')'),
+        RespFrame::Set(set) if set.is_empty() => vec!["(empty set)".to_string()],
+        RespFrame::Set(set) => render_items(set.iter().map(|item| (None, item)), '~'),
+        RespFrame::Push(push) if push.is_empty() => vec!["(empty push)".to_string()],
+        RespFrame::Push(push) => render_items(push.iter().map(|item| (None, item)), '>'),
+        RespFrame::Map(map) if map.is_empty() => vec!["(empty hash)".to_string()],
+        RespFrame::Map(map) => render_items(
+            map.iter()
+                .map(|(key, value)| (Some(quote(key.as_bytes())), value)),
+            '#',
+        ),
+        RespFrame::OrderedMap(map) if map.is_empty() => vec!["(empty hash)".to_string()],
+        RespFrame::OrderedMap(map) => render_items(
+            map.iter()
+                .map(|(key, value)| (Some(quote(key.as_bytes())), value)),
+            '#',
+        ),
+    }
+}
+
+// 聚合类型：每个元素一行，序号右对齐，嵌套元素的续行与元素内容对齐
+fn render_items<'a>(
+    items: impl ExactSizeIterator<Item = (Option<String>, &'a RespFrame)>,
+    marker: char,
+) -> Vec<String> {
+    let width = items.len().to_string().len();
+    let mut lines = Vec::new();
+    for (i, (key, item)) in items.enumerate() {
+        let mut prefix = format!("{:>width$}{} ", i + 1, marker, width = width);
+        if let Some(key) = key {
+            prefix.push_str(&key);
+            prefix.push_str(" => ");
+        }
+        let pad = " ".repeat(prefix.chars().count());
+        for (j, line) in render(item).into_iter().enumerate() {
+            let lead = if j == 0 { &prefix } else { &pad };
+            lines.push(format!("{}{}", lead, line));
+        }
+    }
+    lines
+}
+
+// 与 redis-cli 相同的转义规则：不可打印字符输出为 \xHH
+pub(crate) fn quote(s: &[u8]) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    for &c in s {
+        match c {
+            b'\\' => ret.push_str("\\\\"),
+            b'"' => ret.push_str("\\\""),
+            b'\n' => ret.push_str("\\n"),
+            b'\r' => ret.push_str("\\r"),
+            b'\t' => ret.push_str("\\t"),
+            0x07 => ret.push_str("\\a"),
+            0x08 => ret.push_str("\\b"),
+            c if c.is_ascii_graphic() || c == b' ' => ret.push(c as char),
+            c => ret.push_str(&format!("\\x{:02x}", c)),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_scalars() {
+        let frame: RespFrame = SimpleString::new("OK").into();
+        assert_eq!(frame.to_string(), "OK");
+        let frame: RespFrame = SimpleError::new("ERR boom").into();
+        assert_eq!(frame.to_string(), "(error) ERR boom");
+        assert_eq!(RespFrame::Integer(42).to_string(), "(integer) 42");
+        let frame: RespFrame = BulkString::new(b"a \"b\"\n\xff".to_vec()).into();
+        assert_eq!(frame.to_string(), r#""a \"b\"\n\xff""#);
+        assert_eq!(RespFrame::Null(RespNull).to_string(), "(nil)");
+        assert_eq!(
+            RespFrame::NullBulkString(RespNullBulkString).to_string(),
+            "(nil)"
+        );
+        let frame: RespFrame = RespArray::new(vec![]).into();
+        assert_eq!(frame.to_string(), "(empty array)");
+    }
+
+    #[test]
+    fn test_display_nested_array() {
+        let mut items: Vec<RespFrame> = vec![
+            BulkString::new("key").into(),
+            RespArray::new(vec![
+                RespFrame::Integer(1),
+                RespArray::new(vec![BulkString::new("deep").into(), RespNull.into()]).into(),
+            ])
+            .into(),
+        ];
+        let frame: RespFrame = RespArray::new(items.clone()).into();
+        let expected = [
+            r#"1) "key""#,
+            "2) 1) (integer) 1",
+            r#"   2) 1) "deep""#,
+            "      2) (nil)",
+        ]
+        .join("\n");
+        assert_eq!(frame.to_string(), expected);
+
+        // 超过 9 个元素时序号右对齐
+        items.extend((0..8).map(RespFrame::Integer));
+        let frame: RespFrame = RespArray::new(items).into();
+        let lines: Vec<String> = frame.to_string().lines().map(String::from).collect();
+        assert_eq!(lines[0], r#" 1) "key""#);
+        assert_eq!(lines[2], r#"    2) 1) "deep""#);
+        assert_eq!(lines[11], "10) (integer) 7");
+    }
+}
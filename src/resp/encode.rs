@@ -100,6 +100,19 @@ impl RespEncoder for RespMap {
     }
 }
 
+// 和 RespMap 的格式相同，按插入顺序输出
+impl RespEncoder for RespOrderedMap {
+    fn encode(self) -> Vec<u8> {
+        let mut buf: Vec<_> = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
+        for (key, value) in self.0 {
+            buf.extend_from_slice(&SimpleString::new(key).encode());
+            buf.extend_from_slice(&value.encode());
+        }
+        buf
+    }
+}
+
 // -set: ~<number-of-elements>\r\n<element-1>...<element-n>
 impl RespEncoder for RespSet {
     fn encode(self) -> Vec<u8> {
@@ -112,6 +125,18 @@ impl RespEncoder for RespSet {
     }
 }
 
+// - push: ><number-of-elements>\r\n<element-1>...<element-n>
+impl RespEncoder for RespPush {
+    fn encode(self) -> Vec<u8> {
+        let mut buf: Vec<_> = Vec::with_capacity(BUF_CAP);
+        buf.extend_from_slice(&format!(">{}\r\n", self.len()).into_bytes());
+        for frame in self.0 {
+            buf.extend_from_slice(&frame.encode());
+        }
+        buf
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -178,16 +203,30 @@ mod tests {
         assert_eq!(s.encode(), b"*3\r\n:+1\r\n:+2\r\n:+3\r\n");
     }
 
+    #[test]
+    fn test_ordered_map_encode() {
+        let mut s = RespOrderedMap::new();
+        s.insert("hello".into(), BulkString::new(b"world".to_vec()).into());
+        s.insert("foo".into(), RespFrame::Integer(1));
+        // 替换已有的 key 时保持原来的位置
+        s.insert("hello".into(), BulkString::new(b"again".to_vec()).into());
+        let s: RespFrame = s.into();
+        assert_eq!(
+            s.encode(),
+            b"%2\r\n+hello\r\n$5\r\nagain\r\n+foo\r\n:+1\r\n"
+        );
+    }
+
     #[test]
     fn test_map_encode() {
         let mut s: RespMap = RespMap::new();
         s.insert("hello".into(), BulkString::new(b"world".to_vec()).into());
         s.insert("foo".into(), (-123456.789).into());
         let s: RespFrame = s.into();
-        // println!("{}",String::from_utf8_lossy(&s.encode()));
+        // 按插入顺序而不是 key 的字典序输出
         assert_eq!(
             s.encode(),
-            b"%2\r\n+foo\r\n,-123456.789\r\n+hello\r\n$5\r\nworld\r\n"
+            b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n,-123456.789\r\n"
         );
     }
 
@@ -204,6 +243,26 @@ mod tests {
         assert_eq!(s.encode(), b"~4\r\n:+1\r\n:+2\r\n:+3\r\n+hello\r\n");
     }
 
+    #[test]
+    fn test_mutate_through_deref_mut() {
+        let mut array = RespArray::new(vec![RespFrame::Integer(3), RespFrame::Integer(1)]);
+        array.push(RespFrame::Integer(2));
+        array.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        array.retain(|f| *f != RespFrame::Integer(3));
+        assert_eq!(array.encode(), b"*2\r\n:+1\r\n:+2\r\n");
+
+        let array = RespArray::with_capacity(8);
+        assert!(array.is_empty() && array.capacity() >= 8);
+        assert_eq!(array.encode(), b"*0\r\n");
+
+        let mut set = RespSet::with_capacity(2);
+        assert!(set.capacity() >= 2);
+        set.push(RespFrame::Integer(1));
+        set.push(RespFrame::Integer(1));
+        set.dedup();
+        assert_eq!(set.encode(), b"~1\r\n:+1\r\n");
+    }
+
     #[test]
     fn test_f64_encode() {
         let s: RespFrame = 123.456.into();
@@ -213,4 +272,14 @@ mod tests {
         let s: RespFrame = (-1.23456e-9).into();
         assert_eq!(s.encode(), b",-1.23456e-9\r\n");
     }
+
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame = RespPush::new(vec![
+            BulkString::new("message").into(),
+            BulkString::new("news").into(),
+        ])
+        .into();
+        assert_eq!(frame.encode(), b">2\r\n$7\r\nmessage\r\n$4\r\nnews\r\n");
+    }
 }
@@ -0,0 +1,156 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+// 按插入顺序遍历的 map，接口参照 indexmap crate；构建环境中没有 indexmap，只实现 RespMap 用到的部分。
+// entries 保存键值对，index 记录每个 key 在 entries 中的位置
+#[derive(Debug, Clone)]
+pub struct IndexMap<K, V> {
+    entries: Vec<(K, V)>,
+    index: HashMap<K, usize>,
+}
+
+impl<K, V> Default for IndexMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> IndexMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 替换已有的 key 时保持原来的位置，返回旧值
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key).map(|&i| &mut self.entries[i].1)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    // 删除后其余 key 保持原来的相对顺序，相当于 indexmap 的 shift_remove
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for pos in self.index.values_mut() {
+            if *pos > i {
+                *pos -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_, v)| v)
+    }
+}
+
+// 顺序也参与比较，两个 map 相等当且仅当编码结果相同
+impl<K: PartialEq, V: PartialEq> PartialEq for IndexMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<K: PartialOrd, V: PartialOrd> PartialOrd for IndexMap<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.entries.partial_cmp(&other.entries)
+    }
+}
+
+impl<K, V> IntoIterator for IndexMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for IndexMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_map_keeps_insertion_order() {
+        let mut map: IndexMap<String, i64> = IndexMap::new();
+        assert_eq!(map.insert("b".to_string(), 1), None);
+        map.insert("a".to_string(), 2);
+        map.insert("c".to_string(), 3);
+        assert_eq!(map.insert("b".to_string(), 4), Some(1));
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["b", "a", "c"]);
+        assert_eq!(map.get("b"), Some(&4));
+
+        assert_eq!(map.shift_remove("a"), Some(2));
+        assert_eq!(map.shift_remove("a"), None);
+        assert_eq!(map.get("c"), Some(&3));
+        assert_eq!(
+            map.into_iter().collect::<Vec<_>>(),
+            [("b".to_string(), 4), ("c".to_string(), 3)]
+        );
+
+        let x: IndexMap<&str, i64> = [("x", 1), ("y", 2)].into_iter().collect();
+        let y: IndexMap<&str, i64> = [("y", 2), ("x", 1)].into_iter().collect();
+        assert_ne!(x, y);
+    }
+}
@@ -1,15 +1,26 @@
 mod decode;
+mod display;
 mod encode;
+mod index_map;
+mod resp2;
 
-use std::{
-    collections::BTreeMap,
-    ops::{Deref, DerefMut},
-};
+use std::ops::{Deref, DerefMut};
 
 use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
+use lazy_static::lazy_static;
 use thiserror::Error;
 
+pub(crate) use display::quote;
+pub use index_map::IndexMap;
+
+// bulk string 的最大长度，和 Redis 的 proto-max-bulk-len 默认值一致
+pub(crate) const MAX_BULK_LENGTH: usize = 512 * 1024 * 1024;
+
+lazy_static! {
+    pub(crate) static ref RESP_OK: RespFrame = SimpleString::new("OK").into();
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum RespError {
     #[error("Invalid frame: {0}")]
@@ -60,7 +71,9 @@ pub enum RespFrame {
     Boolean(bool),
     Double(f64),
     Map(RespMap),
+    OrderedMap(RespOrderedMap),
     Set(RespSet),
+    Push(RespPush),
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
@@ -77,10 +90,17 @@ pub struct RespNull;
 pub struct RespNullArray;
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Clone)]
 pub struct RespNullBulkString;
+// 按插入顺序编码
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub struct RespMap(BTreeMap<String, RespFrame>);
+pub struct RespMap(IndexMap<String, RespFrame>);
+// 按插入顺序编码的 map，用于 HELLO 等字段顺序固定的回复；解码时仍然得到 RespMap
+#[derive(Debug, PartialEq, PartialOrd, Clone, Default)]
+pub struct RespOrderedMap(Vec<(String, RespFrame)>);
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub struct RespSet(Vec<RespFrame>);
+// RESP3 的 push 类型，用于服务端主动推送的消息
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
 
 impl Deref for SimpleString {
     type Target = String;
@@ -106,15 +126,9 @@ impl Deref for BulkString {
     }
 }
 
-// impl AsRef<[u8]> for BulkString {
-//     fn as_ref(&self) -> &[u8] {
-//         &self.0
-//     }
-// }
-
-impl AsRef<str> for BulkString {
-    fn as_ref(&self) -> &str {
-        std::str::from_utf8(&self.0).unwrap()
+impl AsRef<[u8]> for BulkString {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
     }
 }
 
@@ -132,8 +146,14 @@ impl Deref for RespArray {
     }
 }
 
+impl DerefMut for RespArray {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 impl Deref for RespMap {
-    type Target = BTreeMap<String, RespFrame>;
+    type Target = IndexMap<String, RespFrame>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -146,6 +166,14 @@ impl DerefMut for RespMap {
     }
 }
 
+impl Deref for RespOrderedMap {
+    type Target = Vec<(String, RespFrame)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl Deref for RespSet {
     type Target = Vec<RespFrame>;
 
@@ -154,6 +182,20 @@ impl Deref for RespSet {
     }
 }
 
+impl DerefMut for RespSet {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl SimpleString {
     pub fn new(s: impl Into<String>) -> Self {
         SimpleString(s.into())
@@ -172,15 +214,36 @@ impl BulkString {
     }
 }
 
+// 常用回复的简写
+impl RespFrame {
+    pub fn error(msg: impl Into<String>) -> Self {
+        RespFrame::Error(SimpleError::new(msg))
+    }
+
+    // 复制缓存的 +OK，不需要重新构造
+    pub fn ok() -> Self {
+        RESP_OK.clone()
+    }
+
+    pub fn integer(n: i64) -> Self {
+        RespFrame::Integer(n)
+    }
+}
+
 impl RespArray {
     pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
         RespArray(s.into())
     }
+
+    // 预先分配 n 个元素的空间，适合逐个 push 构造回复
+    pub fn with_capacity(n: usize) -> Self {
+        RespArray(Vec::with_capacity(n))
+    }
 }
 
 impl RespMap {
     pub fn new() -> Self {
-        RespMap(BTreeMap::new())
+        RespMap(IndexMap::new())
     }
 }
 
@@ -190,11 +253,42 @@ impl Default for RespMap {
     }
 }
 
+impl RespOrderedMap {
+    pub fn new() -> Self {
+        RespOrderedMap(Vec::new())
+    }
+
+    // 已有的 key 原地替换值，保持它原来的位置
+    pub fn insert(&mut self, key: String, value: RespFrame) -> Option<RespFrame> {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&RespFrame> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+}
+
 impl RespSet {
     pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
         // let set = BTreeSet::from_iter(s.into().into_iter());
         RespSet(s.into())
     }
+
+    pub fn with_capacity(n: usize) -> Self {
+        RespSet(Vec::with_capacity(n))
+    }
+}
+
+impl RespPush {
+    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(s.into())
+    }
 }
 
 impl From<&str> for SimpleString {
@@ -0,0 +1,73 @@
+use super::{BulkString, RespArray, RespFrame, RespNullBulkString};
+
+impl RespFrame {
+    // 把 RESP3 才有的类型转换成 RESP2 客户端能理解的形式：
+    // null 变成 null bulk string，map 展开成 key/value 交替的数组，set 和 push 变成数组，
+    // double 变成 bulk string，boolean 变成 0/1
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Null(_) => RespNullBulkString.into(),
+            RespFrame::Boolean(b) => RespFrame::Integer(b as i64),
+            RespFrame::Double(d) => BulkString::new(d.to_string()).into(),
+            RespFrame::Map(map) => {
+                let frames: Vec<RespFrame> = map
+                    .0
+                    .into_iter()
+                    .flat_map(|(k, v)| [BulkString::new(k).into(), v.into_resp2()])
+                    .collect();
+                RespArray::new(frames).into()
+            }
+            RespFrame::OrderedMap(map) => {
+                let frames: Vec<RespFrame> = map
+                    .0
+                    .into_iter()
+                    .flat_map(|(k, v)| [BulkString::new(k).into(), v.into_resp2()])
+                    .collect();
+                RespArray::new(frames).into()
+            }
+            RespFrame::Set(set) => into_resp2_array(set.0),
+            RespFrame::Push(push) => into_resp2_array(push.0),
+            RespFrame::Array(array) => into_resp2_array(array.0),
+            frame => frame,
+        }
+    }
+}
+
+fn into_resp2_array(frames: Vec<RespFrame>) -> RespFrame {
+    RespArray::new(
+        frames
+            .into_iter()
+            .map(RespFrame::into_resp2)
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespEncoder, RespMap, RespNull, RespSet};
+
+    use super::*;
+
+    #[test]
+    fn test_map_into_resp2() {
+        let mut map = RespMap::new();
+        map.insert("a".to_string(), RespFrame::Double(1.5));
+        map.insert("b".to_string(), RespFrame::Null(RespNull));
+        let frame: RespFrame = map.into();
+        assert_eq!(
+            frame.into_resp2().encode(),
+            b"*4\r\n$1\r\na\r\n$3\r\n1.5\r\n$1\r\nb\r\n$-1\r\n"
+        );
+    }
+
+    #[test]
+    fn test_nested_into_resp2() {
+        let frame: RespFrame = RespArray::new(vec![
+            RespSet::new(vec![RespFrame::Boolean(true)]).into(),
+            RespFrame::Integer(3),
+        ])
+        .into();
+        assert_eq!(frame.into_resp2().encode(), b"*2\r\n*1\r\n:+1\r\n:+3\r\n");
+    }
+}
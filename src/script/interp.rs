@@ -0,0 +1,679 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use crate::Backend;
+
+use super::{
+    parser::{BinOp, Block, Expr, FuncBody, Stat, TableField, UnOp},
+    stdlib,
+    value::{Function, Table, TableRef, Value},
+};
+
+// 函数调用的最大嵌套层数，和 Lua 的 LUAI_MAXCCALLS 相同
+const MAX_CALL_DEPTH: usize = 200;
+
+// 解释器递归占用的栈空间上限。tokio 线程的栈默认只有 2MB，debug 构建下每层调用的栈帧很大，
+// 只限制层数仍可能栈溢出
+const MAX_STACK_BYTES: usize = 512 * 1024;
+
+// 脚本最多执行的步数（语句和循环次数），超过之后终止脚本，避免死循环一直占用服务
+const MAX_STEPS: u64 = 100_000_000;
+
+// 每执行这么多步检查一次 SCRIPT KILL
+const KILL_CHECK_INTERVAL: u64 = 1024;
+
+// 脚本抛出的错误，值可以是任意 Lua 值：error("msg") 为字符串，redis.call 失败时为 {err = "..."}
+#[derive(Debug, Clone)]
+pub struct LuaError(pub Value);
+
+// 语句执行后的控制流
+enum Flow {
+    Normal,
+    Break,
+    Return(Vec<Value>),
+}
+
+fn stack_addr() -> usize {
+    let marker = 0u8;
+    std::ptr::addr_of!(marker) as usize
+}
+
+// 词法作用域，每个块一层；函数的 varargs 保存在函数体的最外层
+pub struct Scope {
+    vars: RefCell<Vec<(String, Value)>>,
+    parent: Option<Rc<Scope>>,
+    varargs: Option<Rc<Vec<Value>>>,
+}
+
+impl Scope {
+    fn child(parent: &Rc<Scope>, varargs: Option<Rc<Vec<Value>>>) -> Rc<Scope> {
+        Rc::new(Scope {
+            vars: RefCell::new(Vec::new()),
+            parent: Some(parent.clone()),
+            varargs,
+        })
+    }
+
+    fn declare(&self, name: &str, value: Value) {
+        self.vars.borrow_mut().push((name.to_string(), value));
+    }
+
+    fn lookup(&self, name: &str) -> Option<Value> {
+        let found = self
+            .vars
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone());
+        found.or_else(|| self.parent.as_ref()?.lookup(name))
+    }
+
+    // 给已经声明的局部变量赋值，变量不存在时返回 false
+    fn assign(&self, name: &str, value: Value) -> bool {
+        if let Some(slot) = self
+            .vars
+            .borrow_mut()
+            .iter_mut()
+            .rev()
+            .find(|(n, _)| n == name)
+        {
+            slot.1 = value;
+            return true;
+        }
+        match &self.parent {
+            Some(parent) => parent.assign(name, value),
+            None => false,
+        }
+    }
+
+    fn varargs(&self) -> Rc<Vec<Value>> {
+        match (&self.varargs, &self.parent) {
+            (Some(varargs), _) => varargs.clone(),
+            (None, Some(parent)) => parent.varargs(),
+            (None, None) => Rc::new(Vec::new()),
+        }
+    }
+}
+
+// 解释执行语法树。全局变量只读，脚本只能声明局部变量，和 Redis 一致
+pub struct Interp<'a> {
+    pub backend: &'a Backend,
+    // 执行脚本的用户，redis.call 按它的权限检查
+    pub user: &'a str,
+    globals: HashMap<String, Value>,
+    line: usize,
+    depth: usize,
+    // run 开始时的栈地址，用来估算已经使用的栈空间
+    stack_base: usize,
+    // 已经执行的步数和上限
+    steps: u64,
+    budget: u64,
+    // 脚本被终止的原因，设置之后每一步都重新抛出，pcall 不能拦截
+    aborted: Option<&'static str>,
+    // 闭包引用自己所在的作用域、表引用自己时会形成 Rc 环，脚本结束时清空它们来释放内存
+    envs: Vec<Weak<Scope>>,
+    tables: Vec<Weak<RefCell<Table>>>,
+}
+
+impl Drop for Interp<'_> {
+    fn drop(&mut self) {
+        for env in self.envs.drain(..) {
+            let mut scope = env.upgrade();
+            while let Some(s) = scope {
+                s.vars.borrow_mut().clear();
+                scope = s.parent.clone();
+            }
+        }
+        for table in self.tables.drain(..) {
+            if let Some(table) = table.upgrade() {
+                *table.borrow_mut() = Table::default();
+            }
+        }
+    }
+}
+
+impl<'a> Interp<'a> {
+    pub fn new(backend: &'a Backend, user: &'a str, globals: HashMap<String, Value>) -> Self {
+        Self {
+            backend,
+            user,
+            globals,
+            line: 0,
+            depth: 0,
+            stack_base: 0,
+            steps: 0,
+            budget: MAX_STEPS,
+            aborted: None,
+            envs: Vec::new(),
+            tables: Vec::new(),
+        }
+    }
+
+    // 表中存入了表或函数，可能形成环
+    pub fn track_table(&mut self, table: &TableRef) {
+        self.tables.push(Rc::downgrade(table));
+    }
+
+    fn closure(&mut self, body: &Rc<FuncBody>, env: &Rc<Scope>) -> Value {
+        self.envs.push(Rc::downgrade(env));
+        Value::Function(Function::Closure(body.clone(), env.clone()))
+    }
+
+    pub fn run(&mut self, chunk: &Block) -> Result<Vec<Value>, LuaError> {
+        self.stack_base = stack_addr();
+        let root = Rc::new(Scope {
+            vars: RefCell::new(Vec::new()),
+            parent: None,
+            varargs: Some(Rc::new(Vec::new())),
+        });
+        match self.exec_stats(chunk, &root)? {
+            Flow::Return(values) => Ok(values),
+            Flow::Normal | Flow::Break => Ok(Vec::new()),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_budget(&mut self, budget: u64) {
+        self.budget = budget;
+    }
+
+    // 脚本被终止时的错误回复
+    pub fn aborted(&self) -> Option<&'static str> {
+        self.aborted
+    }
+
+    // 每执行一条语句或一次循环计一步，超过步数上限或者收到 SCRIPT KILL 时终止脚本
+    fn step(&mut self) -> Result<(), LuaError> {
+        if self.aborted.is_none() {
+            self.steps += 1;
+            if self.steps > self.budget {
+                self.aborted = Some("ERR Script exceeded the maximum number of instructions");
+            } else if self.steps.is_multiple_of(KILL_CHECK_INTERVAL)
+                && self.backend.script_kill_requested()
+            {
+                self.aborted = Some("ERR Script killed by user with SCRIPT KILL...");
+            }
+        }
+        match self.aborted {
+            Some(msg) => Err(LuaError(Value::str(msg))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn global(&self, name: &str) -> Value {
+        self.globals.get(name).cloned().unwrap_or(Value::Nil)
+    }
+
+    // 带上当前行号的运行时错误
+    pub fn error(&self, msg: impl AsRef<str>) -> LuaError {
+        LuaError(Value::str(format!(
+            "user_script:{}: {}",
+            self.line,
+            msg.as_ref()
+        )))
+    }
+
+    pub fn call(&mut self, func: &Value, args: Vec<Value>) -> Result<Vec<Value>, LuaError> {
+        let Value::Function(func) = func else {
+            return Err(self.error(format!("attempt to call a {} value", func.type_name())));
+        };
+        if self.depth >= MAX_CALL_DEPTH || stack_addr().abs_diff(self.stack_base) > MAX_STACK_BYTES
+        {
+            return Err(self.error("stack overflow"));
+        }
+        self.depth += 1;
+        let line = self.line;
+        let ret = match func {
+            Function::Native(f) => f(self, args),
+            Function::Closure(body, env) => self.call_closure(body, env, args),
+        };
+        self.depth -= 1;
+        self.line = line;
+        ret
+    }
+
+    fn call_closure(
+        &mut self,
+        body: &FuncBody,
+        env: &Rc<Scope>,
+        args: Vec<Value>,
+    ) -> Result<Vec<Value>, LuaError> {
+        let mut args = args.into_iter();
+        let params: Vec<Value> = body
+            .params
+            .iter()
+            .map(|_| args.next().unwrap_or(Value::Nil))
+            .collect();
+        let varargs = if body.varargs {
+            args.collect()
+        } else {
+            Vec::new()
+        };
+        let scope = Scope::child(env, Some(Rc::new(varargs)));
+        for (name, value) in body.params.iter().zip(params) {
+            scope.declare(name, value);
+        }
+        match self.exec_stats(&body.body, &scope)? {
+            Flow::Return(values) => Ok(values),
+            Flow::Normal | Flow::Break => Ok(Vec::new()),
+        }
+    }
+
+    fn exec_block(&mut self, block: &Block, parent: &Rc<Scope>) -> Result<Flow, LuaError> {
+        let scope = Scope::child(parent, None);
+        self.exec_stats(block, &scope)
+    }
+
+    fn exec_stats(&mut self, block: &Block, scope: &Rc<Scope>) -> Result<Flow, LuaError> {
+        for (stat, line) in &block.0 {
+            self.line = *line;
+            self.step()?;
+            match self.exec(stat, scope)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn exec(&mut self, stat: &Stat, scope: &Rc<Scope>) -> Result<Flow, LuaError> {
+        match stat {
+            Stat::Local(names, exprs) => {
+                let mut values = self.eval_multi(exprs, scope)?.into_iter();
+                for name in names {
+                    scope.declare(name, values.next().unwrap_or(Value::Nil));
+                }
+            }
+            Stat::LocalFunction(name, body) => {
+                // 先声明再赋值，函数体内可以递归调用自己
+                scope.declare(name, Value::Nil);
+                let func = self.closure(body, scope);
+                scope.assign(name, func);
+            }
+            Stat::Assign(targets, exprs) => {
+                let mut values = self.eval_multi(exprs, scope)?.into_iter();
+                for target in targets {
+                    let value = values.next().unwrap_or(Value::Nil);
+                    self.assign(target, value, scope)?;
+                }
+            }
+            Stat::Call(expr) => {
+                self.eval_call(expr, scope)?;
+            }
+            Stat::If(branches, otherwise) => {
+                for (cond, block) in branches {
+                    if self.eval(cond, scope)?.is_truthy() {
+                        return self.exec_block(block, scope);
+                    }
+                }
+                if let Some(block) = otherwise {
+                    return self.exec_block(block, scope);
+                }
+            }
+            Stat::While(cond, body) => {
+                while self.eval(cond, scope)?.is_truthy() {
+                    self.step()?;
+                    match self.exec_block(body, scope)? {
+                        Flow::Break => break,
+                        Flow::Return(values) => return Ok(Flow::Return(values)),
+                        Flow::Normal => {}
+                    }
+                }
+            }
+            Stat::Repeat(body, cond) => loop {
+                self.step()?;
+                // until 的条件可以访问循环体中声明的局部变量
+                let inner = Scope::child(scope, None);
+                match self.exec_stats(body, &inner)? {
+                    Flow::Break => break,
+                    Flow::Return(values) => return Ok(Flow::Return(values)),
+                    Flow::Normal => {}
+                }
+                if self.eval(cond, &inner)?.is_truthy() {
+                    break;
+                }
+            },
+            Stat::NumericFor(name, start, limit, step, body) => {
+                let number = |interp: &mut Self, expr: &Expr, what: &str| {
+                    interp
+                        .eval(expr, scope)?
+                        .to_number()
+                        .ok_or_else(|| interp.error(format!("'for' {} must be a number", what)))
+                };
+                let mut i = number(self, start, "initial value")?;
+                let limit = number(self, limit, "limit")?;
+                let step = match step {
+                    Some(step) => number(self, step, "step")?,
+                    None => 1.0,
+                };
+                while (step > 0.0 && i <= limit) || (step <= 0.0 && i >= limit) {
+                    self.step()?;
+                    let inner = Scope::child(scope, None);
+                    inner.declare(name, Value::Number(i));
+                    match self.exec_stats(body, &inner)? {
+                        Flow::Break => break,
+                        Flow::Return(values) => return Ok(Flow::Return(values)),
+                        Flow::Normal => {}
+                    }
+                    i += step;
+                }
+            }
+            Stat::GenericFor(names, exprs, body) => {
+                let mut init = self.eval_multi(exprs, scope)?.into_iter();
+                let func = init.next().unwrap_or(Value::Nil);
+                let state = init.next().unwrap_or(Value::Nil);
+                let mut control = init.next().unwrap_or(Value::Nil);
+                loop {
+                    self.step()?;
+                    let mut values = self.call(&func, vec![state.clone(), control])?.into_iter();
+                    let first = values.next().unwrap_or(Value::Nil);
+                    if matches!(first, Value::Nil) {
+                        break;
+                    }
+                    control = first.clone();
+                    let inner = Scope::child(scope, None);
+                    inner.declare(&names[0], first);
+                    for name in &names[1..] {
+                        inner.declare(name, values.next().unwrap_or(Value::Nil));
+                    }
+                    match self.exec_stats(body, &inner)? {
+                        Flow::Break => break,
+                        Flow::Return(values) => return Ok(Flow::Return(values)),
+                        Flow::Normal => {}
+                    }
+                }
+            }
+            Stat::Do(body) => return self.exec_block(body, scope),
+            Stat::Break => return Ok(Flow::Break),
+            Stat::Return(exprs) => return Ok(Flow::Return(self.eval_multi(exprs, scope)?)),
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn assign(&mut self, target: &Expr, value: Value, scope: &Rc<Scope>) -> Result<(), LuaError> {
+        match target {
+            Expr::Name(name) => {
+                if scope.assign(name, value) {
+                    Ok(())
+                } else {
+                    Err(self.error(format!(
+                        "Script attempted to create global variable '{}'",
+                        name
+                    )))
+                }
+            }
+            Expr::Index(object, key) => {
+                let object = self.eval(object, scope)?;
+                let key = self.eval(key, scope)?;
+                let Value::Table(table) = object else {
+                    return Err(
+                        self.error(format!("attempt to index a {} value", object.type_name()))
+                    );
+                };
+                match key {
+                    Value::Nil => Err(self.error("table index is nil")),
+                    Value::Number(n) if n.is_nan() => Err(self.error("table index is NaN")),
+                    key => {
+                        if matches!(value, Value::Table(_) | Value::Function(_)) {
+                            self.track_table(&table);
+                        }
+                        table.borrow_mut().set(key, value);
+                        Ok(())
+                    }
+                }
+            }
+            _ => Err(self.error("cannot assign to this expression")),
+        }
+    }
+
+    // 求值表达式列表：最后一个表达式是函数调用或 ... 时展开所有返回值，其余的只取第一个值
+    pub fn eval_multi(
+        &mut self,
+        exprs: &[Expr],
+        scope: &Rc<Scope>,
+    ) -> Result<Vec<Value>, LuaError> {
+        let mut values = Vec::with_capacity(exprs.len());
+        for (i, expr) in exprs.iter().enumerate() {
+            if i + 1 == exprs.len() && expr.is_multi() {
+                values.extend(self.eval_call(expr, scope)?);
+            } else {
+                values.push(self.eval(expr, scope)?);
+            }
+        }
+        Ok(values)
+    }
+
+    // 求值可能返回多个值的表达式
+    fn eval_call(&mut self, expr: &Expr, scope: &Rc<Scope>) -> Result<Vec<Value>, LuaError> {
+        match expr {
+            Expr::Call(func, args, line) => {
+                let func = self.eval(func, scope)?;
+                let args = self.eval_multi(args, scope)?;
+                self.line = *line;
+                self.call(&func, args)
+            }
+            Expr::Method(object, name, args, line) => {
+                let object = self.eval(object, scope)?;
+                let func = match &object {
+                    Value::String(_) => match self.global("string") {
+                        Value::Table(string) => string.borrow().get_str(name),
+                        _ => Value::Nil,
+                    },
+                    Value::Table(table) => table.borrow().get_str(name),
+                    other => {
+                        return Err(
+                            self.error(format!("attempt to index a {} value", other.type_name()))
+                        )
+                    }
+                };
+                let mut call_args = vec![object];
+                call_args.extend(self.eval_multi(args, scope)?);
+                self.line = *line;
+                self.call(&func, call_args)
+            }
+            Expr::VarArgs => Ok(scope.varargs().to_vec()),
+            expr => Ok(vec![self.eval(expr, scope)?]),
+        }
+    }
+
+    pub fn eval(&mut self, expr: &Expr, scope: &Rc<Scope>) -> Result<Value, LuaError> {
+        let value = match expr {
+            Expr::Nil => Value::Nil,
+            Expr::True => Value::Boolean(true),
+            Expr::False => Value::Boolean(false),
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Str(s) => Value::str(s),
+            Expr::VarArgs => scope.varargs().first().cloned().unwrap_or(Value::Nil),
+            Expr::Function(body) => self.closure(body, scope),
+            Expr::Table(fields) => self.eval_table(fields, scope)?,
+            Expr::Binary(BinOp::And, left, right) => {
+                let left = self.eval(left, scope)?;
+                if left.is_truthy() {
+                    self.eval(right, scope)?
+                } else {
+                    left
+                }
+            }
+            Expr::Binary(BinOp::Or, left, right) => {
+                let left = self.eval(left, scope)?;
+                if left.is_truthy() {
+                    left
+                } else {
+                    self.eval(right, scope)?
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, scope)?;
+                let right = self.eval(right, scope)?;
+                self.binary(*op, left, right)?
+            }
+            Expr::Unary(op, operand) => {
+                let operand = self.eval(operand, scope)?;
+                self.unary(*op, operand)?
+            }
+            Expr::Name(name) => match scope.lookup(name) {
+                Some(value) => value,
+                None => match self.globals.get(name) {
+                    Some(value) => value.clone(),
+                    None => {
+                        return Err(self.error(format!(
+                            "Script attempted to access nonexistent global variable '{}'",
+                            name
+                        )))
+                    }
+                },
+            },
+            Expr::Index(object, key) => {
+                let object = self.eval(object, scope)?;
+                let key = self.eval(key, scope)?;
+                self.index(&object, &key)?
+            }
+            Expr::Paren(expr) => self.eval(expr, scope)?,
+            Expr::Call(..) | Expr::Method(..) => self
+                .eval_call(expr, scope)?
+                .into_iter()
+                .next()
+                .unwrap_or(Value::Nil),
+        };
+        Ok(value)
+    }
+
+    fn eval_table(&mut self, fields: &[TableField], scope: &Rc<Scope>) -> Result<Value, LuaError> {
+        let mut table = Table::default();
+        // 位置字段按出现顺序从 1 编号，nil 也占一个位置
+        let mut n = 0.0;
+        for (i, field) in fields.iter().enumerate() {
+            match field {
+                TableField::Positional(expr) if i + 1 == fields.len() && expr.is_multi() => {
+                    for value in self.eval_call(expr, scope)? {
+                        n += 1.0;
+                        table.set(Value::Number(n), value);
+                    }
+                }
+                TableField::Positional(expr) => {
+                    let value = self.eval(expr, scope)?;
+                    n += 1.0;
+                    table.set(Value::Number(n), value);
+                }
+                TableField::Named(key, value) => {
+                    let key = self.eval(key, scope)?;
+                    let value = self.eval(value, scope)?;
+                    if matches!(key, Value::Nil) {
+                        return Err(self.error("table index is nil"));
+                    }
+                    table.set(key, value);
+                }
+            }
+        }
+        Ok(Value::table(table))
+    }
+
+    fn index(&self, object: &Value, key: &Value) -> Result<Value, LuaError> {
+        match object {
+            Value::Table(table) => Ok(table.borrow().get(key)),
+            // 字符串的方法来自 string 库，例如 ("abc").len
+            Value::String(_) => match self.global("string") {
+                Value::Table(string) => Ok(string.borrow().get(key)),
+                _ => Ok(Value::Nil),
+            },
+            other => Err(self.error(format!("attempt to index a {} value", other.type_name()))),
+        }
+    }
+
+    fn binary(&self, op: BinOp, left: Value, right: Value) -> Result<Value, LuaError> {
+        use BinOp::*;
+        match op {
+            Add | Sub | Mul | Div | Mod | Pow => {
+                let (Some(a), Some(b)) = (left.to_number(), right.to_number()) else {
+                    let bad = if left.to_number().is_none() {
+                        &left
+                    } else {
+                        &right
+                    };
+                    return Err(self.error(format!(
+                        "attempt to perform arithmetic on a {} value",
+                        bad.type_name()
+                    )));
+                };
+                let n = match op {
+                    Add => a + b,
+                    Sub => a - b,
+                    Mul => a * b,
+                    Div => a / b,
+                    Mod => a - (a / b).floor() * b,
+                    _ => a.powf(b),
+                };
+                Ok(Value::Number(n))
+            }
+            Concat => {
+                let (Some(a), Some(b)) = (left.to_bytes(), right.to_bytes()) else {
+                    let bad = if left.to_bytes().is_none() {
+                        &left
+                    } else {
+                        &right
+                    };
+                    return Err(self.error(format!(
+                        "attempt to concatenate a {} value",
+                        bad.type_name()
+                    )));
+                };
+                stdlib::check_length(self, a.len() + b.len())?;
+                Ok(Value::str([&a[..], &b[..]].concat()))
+            }
+            Eq => Ok(Value::Boolean(left.raw_equals(&right))),
+            Ne => Ok(Value::Boolean(!left.raw_equals(&right))),
+            Lt | Le | Gt | Ge => {
+                let ordering = match (&left, &right) {
+                    (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+                    (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                    (a, b) if a.type_name() == b.type_name() => {
+                        return Err(
+                            self.error(format!("attempt to compare two {} values", a.type_name()))
+                        )
+                    }
+                    (a, b) => {
+                        return Err(self.error(format!(
+                            "attempt to compare {} with {}",
+                            a.type_name(),
+                            b.type_name()
+                        )))
+                    }
+                };
+                // 和 NaN 比较总是 false
+                let result = ordering.is_some_and(|ordering| match op {
+                    Lt => ordering.is_lt(),
+                    Le => ordering.is_le(),
+                    Gt => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                });
+                Ok(Value::Boolean(result))
+            }
+            And | Or => unreachable!("short-circuit operators are evaluated in eval"),
+        }
+    }
+
+    fn unary(&self, op: UnOp, operand: Value) -> Result<Value, LuaError> {
+        match op {
+            UnOp::Not => Ok(Value::Boolean(!operand.is_truthy())),
+            UnOp::Neg => match operand.to_number() {
+                Some(n) => Ok(Value::Number(-n)),
+                None => Err(self.error(format!(
+                    "attempt to perform arithmetic on a {} value",
+                    operand.type_name()
+                ))),
+            },
+            UnOp::Len => match &operand {
+                Value::String(s) => Ok(Value::Number(s.len() as f64)),
+                Value::Table(t) => Ok(Value::Number(t.borrow().len() as f64)),
+                other => Err(self.error(format!(
+                    "attempt to get length of a {} value",
+                    other.type_name()
+                ))),
+            },
+        }
+    }
+}
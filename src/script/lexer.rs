@@ -0,0 +1,280 @@
+use super::ScriptError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Name(String),
+    Number(f64),
+    Str(Vec<u8>),
+    // 关键字和符号都按原文保存，例如 "local"、"=="、"..."
+    Keyword(&'static str),
+    Symbol(&'static str),
+    Eof,
+}
+
+const KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in", "local",
+    "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+// 按长度从长到短匹配，保证 "..." 不会被拆成 ".." 和 "."
+const SYMBOLS: &[&str] = &[
+    "...", "==", "~=", "<=", ">=", "..", "+", "-", "*", "/", "%", "^", "#", "<", ">", "=", "(",
+    ")", "{", "}", "[", "]", ";", ":", ",", ".",
+];
+
+// 把脚本切分成 (token, 所在行号)，最后一个 token 为 Eof
+pub fn tokenize(src: &[u8]) -> Result<Vec<(Token, usize)>, ScriptError> {
+    let mut lexer = Lexer {
+        src,
+        pos: 0,
+        line: 1,
+    };
+    let mut tokens = Vec::new();
+    loop {
+        lexer.skip_whitespace_and_comments()?;
+        let line = lexer.line;
+        let token = lexer.next_token()?;
+        let eof = token == Token::Eof;
+        tokens.push((token, line));
+        if eof {
+            return Ok(tokens);
+        }
+    }
+}
+
+struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl Lexer<'_> {
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.src.get(self.pos + offset).copied()
+    }
+
+    fn error(&self, msg: impl Into<String>) -> ScriptError {
+        ScriptError::Compile(format!("user_script:{}: {}", self.line, msg.into()))
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), ScriptError> {
+        while let Some(c) = self.peek(0) {
+            match c {
+                b'\n' => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                c if c.is_ascii_whitespace() => self.pos += 1,
+                b'-' if self.peek(1) == Some(b'-') => {
+                    self.pos += 2;
+                    if let Some(level) = self.long_bracket_level() {
+                        self.long_string(level)?;
+                    } else {
+                        while self.peek(0).is_some_and(|c| c != b'\n') {
+                            self.pos += 1;
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn next_token(&mut self) -> Result<Token, ScriptError> {
+        let Some(c) = self.peek(0) else {
+            return Ok(Token::Eof);
+        };
+        if c.is_ascii_alphabetic() || c == b'_' {
+            let start = self.pos;
+            while self
+                .peek(0)
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_')
+            {
+                self.pos += 1;
+            }
+            let word = String::from_utf8_lossy(&self.src[start..self.pos]).into_owned();
+            return Ok(match KEYWORDS.iter().find(|k| **k == word) {
+                Some(keyword) => Token::Keyword(keyword),
+                None => Token::Name(word),
+            });
+        }
+        if c.is_ascii_digit() || (c == b'.' && self.peek(1).is_some_and(|c| c.is_ascii_digit())) {
+            return self.number();
+        }
+        if c == b'"' || c == b'\'' {
+            return self.quoted_string(c);
+        }
+        if c == b'[' {
+            if let Some(level) = self.long_bracket_level() {
+                return Ok(Token::Str(self.long_string(level)?));
+            }
+        }
+        for symbol in SYMBOLS {
+            if self.src[self.pos..].starts_with(symbol.as_bytes()) {
+                self.pos += symbol.len();
+                return Ok(Token::Symbol(symbol));
+            }
+        }
+        Err(self.error(format!("unexpected symbol near '{}'", c as char)))
+    }
+
+    fn number(&mut self) -> Result<Token, ScriptError> {
+        let start = self.pos;
+        if self.peek(0) == Some(b'0') && matches!(self.peek(1), Some(b'x' | b'X')) {
+            self.pos += 2;
+            while self.peek(0).is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.pos += 1;
+            }
+            let digits = std::str::from_utf8(&self.src[start + 2..self.pos]).unwrap_or_default();
+            return u64::from_str_radix(digits, 16)
+                .map(|n| Token::Number(n as f64))
+                .map_err(|_| self.error("malformed number"));
+        }
+        while let Some(c) = self.peek(0) {
+            let exponent_sign =
+                matches!(c, b'+' | b'-') && matches!(self.src.get(self.pos - 1), Some(b'e' | b'E'));
+            if c.is_ascii_alphanumeric() || c == b'.' || exponent_sign {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+        text.parse()
+            .map(Token::Number)
+            .map_err(|_| self.error(format!("malformed number near '{}'", text)))
+    }
+
+    fn quoted_string(&mut self, quote: u8) -> Result<Token, ScriptError> {
+        self.pos += 1;
+        let mut s = Vec::new();
+        loop {
+            let Some(c) = self.peek(0) else {
+                return Err(self.error("unfinished string"));
+            };
+            self.pos += 1;
+            match c {
+                c if c == quote => return Ok(Token::Str(s)),
+                b'\n' => return Err(self.error("unfinished string")),
+                b'\\' => {
+                    let Some(e) = self.peek(0) else {
+                        return Err(self.error("unfinished string"));
+                    };
+                    self.pos += 1;
+                    match e {
+                        b'n' => s.push(b'\n'),
+                        b't' => s.push(b'\t'),
+                        b'r' => s.push(b'\r'),
+                        b'a' => s.push(0x07),
+                        b'b' => s.push(0x08),
+                        b'f' => s.push(0x0c),
+                        b'v' => s.push(0x0b),
+                        b'\n' => {
+                            self.line += 1;
+                            s.push(b'\n');
+                        }
+                        // \ddd：最多三位十进制数
+                        e if e.is_ascii_digit() => {
+                            let mut n = (e - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek(0) {
+                                    Some(d) if d.is_ascii_digit() => {
+                                        n = n * 10 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            let byte =
+                                u8::try_from(n).map_err(|_| self.error("escape too large"))?;
+                            s.push(byte);
+                        }
+                        e => s.push(e),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    // 当前位置是 [[ 或 [==[ 时返回等号的个数
+    fn long_bracket_level(&self) -> Option<usize> {
+        if self.peek(0) != Some(b'[') {
+            return None;
+        }
+        let level = self.src[self.pos + 1..]
+            .iter()
+            .take_while(|c| **c == b'=')
+            .count();
+        (self.peek(level + 1) == Some(b'[')).then_some(level)
+    }
+
+    fn long_string(&mut self, level: usize) -> Result<Vec<u8>, ScriptError> {
+        self.pos += level + 2;
+        // 紧跟在开头的换行不属于字符串
+        if self.peek(0) == Some(b'\n') {
+            self.line += 1;
+            self.pos += 1;
+        }
+        let close = format!("]{}]", "=".repeat(level));
+        let start = self.pos;
+        while !self.src[self.pos..].starts_with(close.as_bytes()) {
+            match self.peek(0) {
+                Some(b'\n') => self.line += 1,
+                Some(_) => {}
+                None => return Err(self.error("unfinished long string")),
+            }
+            self.pos += 1;
+        }
+        let s = self.src[start..self.pos].to_vec();
+        self.pos += close.len();
+        Ok(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(src: &str) -> Vec<Token> {
+        tokenize(src.as_bytes())
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokens("local x = KEYS[1] .. 'a\\n' -- comment\nreturn #x ~= 0x10"),
+            vec![
+                Token::Keyword("local"),
+                Token::Name("x".to_string()),
+                Token::Symbol("="),
+                Token::Name("KEYS".to_string()),
+                Token::Symbol("["),
+                Token::Number(1.0),
+                Token::Symbol("]"),
+                Token::Symbol(".."),
+                Token::Str(b"a\n".to_vec()),
+                Token::Keyword("return"),
+                Token::Symbol("#"),
+                Token::Name("x".to_string()),
+                Token::Symbol("~="),
+                Token::Number(16.0),
+                Token::Eof,
+            ]
+        );
+        assert_eq!(
+            tokens("--[[ long\ncomment ]] [==[a]]b]==] 1.5e+2"),
+            vec![
+                Token::Str(b"a]]b".to_vec()),
+                Token::Number(150.0),
+                Token::Eof
+            ]
+        );
+        assert!(tokenize(b"'unfinished").is_err());
+    }
+}
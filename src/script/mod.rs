@@ -0,0 +1,532 @@
+// EVAL / EVALSHA 使用的 Lua 5.1 子集解释器，只在开启 scripting feature 时编译。
+// 支持：local / 全局变量、if / while / repeat / 数值和泛型 for、函数和闭包、可变参数、table，
+// 以及 base、table、string、math 库中常用的函数和 redis.call / pcall / error_reply / status_reply / sha1hex / log。
+// 不支持：metatable（setmetatable / getmetatable）、字符串模式匹配（string.find / match / gmatch / gsub）、
+// goto、coroutine、cjson / cmsgpack / bit 等扩展库。调用不支持的函数时脚本报错，不会静默得到错误的结果。
+// 数字和 Redis 使用的 Lua 5.1 一样只有双精度浮点数，没有整数和浮点数的区分，转换成回复时截断为整数
+mod interp;
+mod lexer;
+mod parser;
+mod stdlib;
+mod value;
+
+use thiserror::Error;
+
+use crate::{
+    cmd::{Command, CommandError, CommandExecutor},
+    sha1::sha1_hex,
+    Backend, BulkString, RespArray, RespFrame, RespNullBulkString, SimpleString,
+};
+
+use interp::{Interp, LuaError};
+use stdlib::library;
+use value::{format_number, Table, Value};
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("{0}")]
+    Compile(String),
+}
+
+// 脚本中不能执行的命令：嵌套执行脚本、改变连接状态或需要等待的命令
+const FORBIDDEN_COMMANDS: &[&str] = &[
+    "eval",
+    "evalsha",
+    "script",
+    "quit",
+    "reset",
+    "auth",
+    "hello",
+    "client",
+    "wait",
+    "blpop",
+    "brpop",
+    "subscribe",
+    "unsubscribe",
+    "psubscribe",
+    "punsubscribe",
+    "monitor",
+    "sync",
+    "config",
+    "acl",
+];
+
+// 以 user 的权限执行脚本并把返回值转换成回复。
+// 调用方需要持有 Backend::script_guard，保证脚本执行期间没有其它命令穿插
+pub fn eval(
+    backend: &Backend,
+    user: &str,
+    script: &str,
+    keys: Vec<String>,
+    args: Vec<String>,
+) -> RespFrame {
+    let chunk = match parser::parse(script.as_bytes()) {
+        Ok(chunk) => chunk,
+        Err(e) => {
+            return RespFrame::error(format!("ERR Error compiling script (new function): {}", e))
+        }
+    };
+    let mut globals = stdlib::globals();
+    globals.insert("KEYS".to_string(), string_array(keys));
+    globals.insert("ARGV".to_string(), string_array(args));
+    globals.insert("redis".to_string(), redis_lib());
+    let mut interp = Interp::new(backend, user, globals);
+    run_chunk(&mut interp, &chunk, script)
+}
+
+fn run_chunk(interp: &mut Interp, chunk: &parser::Block, script: &str) -> RespFrame {
+    let ret = interp.run(chunk);
+    if let Some(msg) = interp.aborted() {
+        return RespFrame::error(msg);
+    }
+    match ret {
+        Ok(values) => lua_to_resp(values.into_iter().next().unwrap_or(Value::Nil)),
+        Err(LuaError(Value::Table(t))) if t.borrow().get_str("err").is_truthy() => {
+            lua_to_resp(Value::Table(t))
+        }
+        Err(LuaError(e)) => {
+            RespFrame::error(format!("ERR {} script: {}", e, sha1_hex(script.as_bytes())))
+        }
+    }
+}
+
+fn string_array(items: Vec<String>) -> Value {
+    Value::table(Table::from_array(
+        items.into_iter().map(Value::str).collect(),
+    ))
+}
+
+fn redis_lib() -> Value {
+    let lib = library(&[
+        ("call", |interp, args| redis_call(interp, args, true)),
+        ("pcall", |interp, args| redis_call(interp, args, false)),
+        ("error_reply", |interp, args| {
+            let msg = stdlib::check_bytes(interp, &args, 0, "error_reply")?;
+            Ok(vec![status_table("err", Value::String(msg))])
+        }),
+        ("status_reply", |interp, args| {
+            let msg = stdlib::check_bytes(interp, &args, 0, "status_reply")?;
+            Ok(vec![status_table("ok", Value::String(msg))])
+        }),
+        ("sha1hex", |interp, args| {
+            let data = stdlib::check_bytes(interp, &args, 0, "sha1hex")?;
+            Ok(vec![Value::str(sha1_hex(&data))])
+        }),
+        // 没有接入日志系统，调用不会报错
+        ("log", |_, _| Ok(Vec::new())),
+    ]);
+    if let Value::Table(t) = &lib {
+        let mut t = t.borrow_mut();
+        for (i, level) in ["LOG_DEBUG", "LOG_VERBOSE", "LOG_NOTICE", "LOG_WARNING"]
+            .iter()
+            .enumerate()
+        {
+            t.set_str(level, Value::Number(i as f64));
+        }
+    }
+    lib
+}
+
+fn status_table(field: &str, msg: Value) -> Value {
+    let mut table = Table::default();
+    table.set_str(field, msg);
+    Value::table(table)
+}
+
+// redis.call 遇到错误时抛出 {err = "..."}，redis.pcall 把它作为返回值。
+// 和直接执行命令一样检查 ACL、只读副本和 maxmemory
+fn redis_call(interp: &mut Interp, args: Vec<Value>, raise: bool) -> Result<Vec<Value>, LuaError> {
+    let backend = interp.backend;
+    let reply = match build_command(args) {
+        Ok(cmd) => match cmd
+            .acl_check(backend, interp.user)
+            .and_then(|_| cmd.write_check(backend))
+        {
+            Ok(()) => {
+                if cmd.is_write() {
+                    backend.script_mark_wrote();
+                }
+                cmd.execute(backend).into_resp2()
+            }
+            Err(e) => e,
+        },
+        Err(msg) => RespFrame::error(msg),
+    };
+    let value = resp_to_lua(reply);
+    if raise {
+        if let Value::Table(t) = &value {
+            if t.borrow().get_str("err").is_truthy() {
+                return Err(LuaError(value));
+            }
+        }
+    }
+    Ok(vec![value])
+}
+
+fn build_command(args: Vec<Value>) -> Result<Command, String> {
+    if args.is_empty() {
+        return Err("Please specify at least one argument for this redis lib call".to_string());
+    }
+    let mut frames = Vec::with_capacity(args.len());
+    for arg in args {
+        let bytes = match arg {
+            Value::String(s) => s.to_vec(),
+            Value::Number(n) => format_number(n).into_bytes(),
+            _ => {
+                return Err(
+                    "Lua redis lib command arguments must be strings or integers".to_string(),
+                )
+            }
+        };
+        frames.push(BulkString::new(bytes).into());
+    }
+    let cmd = match Command::try_from(RespArray::new(frames)) {
+        Ok(Command::Unrecognized(_)) => {
+            return Err("ERR Unknown Redis command called from script".to_string())
+        }
+        Ok(cmd) => cmd,
+        Err(e @ CommandError::WrongArity(_)) => return Err(e.to_string()),
+        Err(e) => return Err(format!("ERR {}", e)),
+    };
+    if FORBIDDEN_COMMANDS.contains(&cmd.name()) {
+        return Err("ERR This Redis command is not allowed from script".to_string());
+    }
+    Ok(cmd)
+}
+
+// Redis 回复转换成 Lua 值：整数为 number，bulk string 为 string，nil 为 false，
+// 状态回复为 {ok = ...}，错误回复为 {err = ...}，数组为 table
+fn resp_to_lua(frame: RespFrame) -> Value {
+    match frame {
+        RespFrame::Integer(n) => Value::Number(n as f64),
+        RespFrame::BulkString(s) => Value::str(&*s),
+        RespFrame::SimpleString(s) => status_table("ok", Value::str(&*s)),
+        RespFrame::Error(e) => status_table("err", Value::str(&*e)),
+        RespFrame::Array(array) => Value::table(Table::from_array(
+            array.0.into_iter().map(resp_to_lua).collect(),
+        )),
+        RespFrame::Null(_) | RespFrame::NullArray(_) | RespFrame::NullBulkString(_) => {
+            Value::Boolean(false)
+        }
+        // into_resp2 之后不会再有其它 RESP3 类型
+        frame => resp_to_lua(frame.into_resp2()),
+    }
+}
+
+// Lua 值转换成 Redis 回复：number 截断为整数，true 为 1，nil 和 false 为 nil，
+// 带 err 或 ok 字段的 table 为错误或状态回复，其它 table 取数组部分
+fn lua_to_resp(value: Value) -> RespFrame {
+    match value {
+        Value::Nil | Value::Boolean(false) => RespNullBulkString.into(),
+        Value::Boolean(true) => RespFrame::Integer(1),
+        Value::Number(n) => RespFrame::Integer(n as i64),
+        Value::String(s) => BulkString::new(s.to_vec()).into(),
+        Value::Table(t) => {
+            let t = t.borrow();
+            if let Some(err) = t.get_str("err").to_bytes() {
+                return RespFrame::error(String::from_utf8_lossy(&err));
+            }
+            if let Some(ok) = t.get_str("ok").to_bytes() {
+                return SimpleString::new(String::from_utf8_lossy(&ok)).into();
+            }
+            RespArray::new(
+                t.array()
+                    .iter()
+                    .cloned()
+                    .map(lua_to_resp)
+                    .collect::<Vec<_>>(),
+            )
+            .into()
+        }
+        Value::Function(_) => RespNullBulkString.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_USER;
+
+    fn run(backend: &Backend, script: &str, keys: &[&str], args: &[&str]) -> RespFrame {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        eval(backend, DEFAULT_USER, script, strings(keys), strings(args))
+    }
+
+    fn bulk(s: &str) -> RespFrame {
+        BulkString::new(s).into()
+    }
+
+    #[test]
+    fn test_unsupported_features_raise_errors() {
+        let backend = Backend::new();
+        for script in [
+            "return setmetatable({}, {})",
+            "return string.find('hello', 'l+')",
+            "return string.gsub('hello', 'l', 'L')",
+            "return cjson.encode({1})",
+        ] {
+            assert!(
+                matches!(run(&backend, script, &[], &[]), RespFrame::Error(ref e) if e.starts_with("ERR ")),
+                "{}",
+                script
+            );
+        }
+        // 和 Lua 5.1 一样，数字转换成回复时截断为整数
+        assert_eq!(
+            run(&backend, "return 3.99", &[], &[]),
+            RespFrame::Integer(3)
+        );
+    }
+
+    #[test]
+    fn test_redis_call_checks_permissions() {
+        let backend = Backend::new();
+        backend
+            .acl_setuser("scripter", &["on", "nopass", "~pub:*", "+eval", "+get"])
+            .unwrap();
+        backend.set("secret".to_string(), bulk("v"));
+        let call = |script: &str, keys: &[&str]| {
+            let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+            eval(&backend, "scripter", script, strings(keys), Vec::new())
+        };
+        assert_eq!(
+            call("return redis.call('get', KEYS[1])", &["pub:1"]),
+            RespNullBulkString.into()
+        );
+        let denied =
+            |reply: RespFrame| matches!(reply, RespFrame::Error(e) if e.starts_with("NOPERM"));
+        assert!(denied(call(
+            "return redis.call('get', KEYS[1])",
+            &["secret"]
+        )));
+        assert!(denied(call(
+            "return redis.call('set', KEYS[1], 'x')",
+            &["pub:1"]
+        )));
+        // ACL 命令不能在脚本中执行，避免通过脚本给自己加权限
+        assert_eq!(
+            call(
+                "return redis.call('acl', 'setuser', 'scripter', '+@all')",
+                &[]
+            ),
+            RespFrame::error("ERR This Redis command is not allowed from script")
+        );
+        assert!(!backend
+            .acl_user("scripter")
+            .unwrap()
+            .can_run("set", &["write"]));
+
+        let replica = Backend::with_config(crate::ServerConfig {
+            replica_of: Some("127.0.0.1:6379".to_string()),
+            maxmemory: 1,
+            ..Default::default()
+        });
+        let reply = run(&replica, "return redis.call('set', 'k', 'v')", &[], &[]);
+        assert!(matches!(reply, RespFrame::Error(e) if e.starts_with("READONLY")));
+        replica.set_config("replica-of", "").unwrap();
+        replica.set("big".to_string(), bulk("value"));
+        let reply = run(&replica, "return redis.call('set', 'k', 'v')", &[], &[]);
+        assert!(matches!(reply, RespFrame::Error(e) if e.starts_with("OOM")));
+    }
+
+    #[test]
+    fn test_instruction_budget() {
+        let backend = Backend::new();
+        for script in [
+            "while true do end",
+            "repeat until false",
+            // 终止之后 pcall 不能拦截
+            "while true do pcall(function() while true do end end) end",
+        ] {
+            let chunk = parser::parse(script.as_bytes()).unwrap();
+            let mut interp = Interp::new(&backend, DEFAULT_USER, stdlib::globals());
+            interp.set_budget(10_000);
+            assert_eq!(
+                run_chunk(&mut interp, &chunk, script),
+                RespFrame::error("ERR Script exceeded the maximum number of instructions"),
+                "{}",
+                script
+            );
+        }
+    }
+
+    #[test]
+    fn test_string_length_limit() {
+        let backend = Backend::new();
+        for script in [
+            "return string.rep('x', 1e15)",
+            "return string.format('%0999999999d', 1)",
+        ] {
+            let RespFrame::Error(e) = run(&backend, script, &[], &[]) else {
+                panic!("expected error: {}", script);
+            };
+            assert!(e.starts_with("ERR user_script:1: "), "{:?}", e);
+        }
+        assert_eq!(
+            run(&backend, "return #string.rep('ab', 1000)", &[], &[]),
+            RespFrame::Integer(2000)
+        );
+    }
+
+    #[test]
+    fn test_compare_and_delete() {
+        let backend = Backend::new();
+        backend.set("lock".to_string(), bulk("token"));
+        let script = "if redis.call('get', KEYS[1]) == ARGV[1] then \
+                      return redis.call('del', KEYS[1]) else return 0 end";
+
+        assert_eq!(
+            run(&backend, script, &["lock"], &["other"]),
+            RespFrame::Integer(0)
+        );
+        assert!(backend.get("lock").is_some());
+        assert_eq!(
+            run(&backend, script, &["lock"], &["token"]),
+            RespFrame::Integer(1)
+        );
+        assert!(backend.get("lock").is_none());
+    }
+
+    #[test]
+    fn test_error_propagation() {
+        let backend = Backend::new();
+        assert_eq!(
+            run(&backend, "return redis.error_reply('MY custom')", &[], &[]),
+            RespFrame::error("MY custom")
+        );
+        // redis.call 的错误中断脚本，redis.pcall 的错误可以被脚本处理
+        backend.lpush("l".to_string(), vec![bulk("a")]);
+        assert_eq!(
+            run(&backend, "redis.call('getbit', 'l', 0) return 1", &[], &[]),
+            RespFrame::error("WRONGTYPE Operation against a key holding the wrong kind of value")
+        );
+        assert_eq!(
+            run(
+                &backend,
+                "local r = redis.pcall('getbit', 'l', 0) return r.err ~= nil",
+                &[],
+                &[]
+            ),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run(
+                &backend,
+                "return redis.call('eval', 'return 1', 0)",
+                &[],
+                &[]
+            ),
+            RespFrame::error("ERR This Redis command is not allowed from script")
+        );
+
+        let RespFrame::Error(e) = run(&backend, "error('boom')", &[], &[]) else {
+            panic!("expected error");
+        };
+        assert!(e.starts_with("ERR user_script:1: boom script: "));
+        let RespFrame::Error(e) = run(&backend, "return nosuch", &[], &[]) else {
+            panic!("expected error");
+        };
+        assert!(e.contains("Script attempted to access nonexistent global variable 'nosuch'"));
+        let RespFrame::Error(e) = run(&backend, "return (", &[], &[]) else {
+            panic!("expected error");
+        };
+        assert!(e.starts_with("ERR Error compiling script (new function): user_script:1:"));
+    }
+
+    #[test]
+    fn test_conversion_table() {
+        let backend = Backend::new();
+        let cases = [
+            ("return 3.99", RespFrame::Integer(3)),
+            ("return 'x'", bulk("x")),
+            ("return true", RespFrame::Integer(1)),
+            ("return false", RespNullBulkString.into()),
+            ("return nil", RespNullBulkString.into()),
+            ("return {ok = 'FINE'}", SimpleString::new("FINE").into()),
+            (
+                "return {1, 'a', {2}, nil, 3}",
+                RespArray::new(vec![
+                    RespFrame::Integer(1),
+                    bulk("a"),
+                    RespArray::new(vec![RespFrame::Integer(2)]).into(),
+                ])
+                .into(),
+            ),
+        ];
+        for (script, expected) in cases {
+            assert_eq!(run(&backend, script, &[], &[]), expected, "{}", script);
+        }
+
+        // Redis 回复到 Lua 值
+        let script = "local t = {}
+            t[1] = type(redis.call('get', 'missing'))
+            t[2] = redis.call('set', 'k', 'v').ok
+            t[3] = type(redis.call('rpush', 'l', 'a', 'b'))
+            t[4] = type(redis.call('get', 'k'))
+            t[5] = #redis.call('lrange', 'l', 0, -1)
+            t[6] = redis.pcall('getbit', 'l', 0).err
+            return t";
+        let frame = run(&backend, script, &[], &[]);
+        assert_eq!(
+            frame,
+            RespArray::new(vec![
+                bulk("boolean"),
+                bulk("OK"),
+                bulk("number"),
+                bulk("string"),
+                RespFrame::Integer(2),
+                bulk("WRONGTYPE Operation against a key holding the wrong kind of value"),
+            ])
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_stdlib() {
+        let backend = Backend::new();
+        let script = "local parts = {}
+            for i, v in ipairs(ARGV) do table.insert(parts, string.upper(v)) end
+            local n = 0
+            for k, v in pairs({a = 1, b = 2}) do n = n + v end
+            local function fact(x) if x <= 1 then return 1 end return x * fact(x - 1) end
+            return {table.concat(parts, ','), n, fact(5), string.format('%05.1f|%-3s|%x', 3.14159, 'a', 255),
+                    ('hello'):sub(-3), tostring(tonumber('0x10')), select('#', 1, 2, 3),
+                    redis.sha1hex('return 1')}";
+        assert_eq!(
+            run(&backend, script, &[], &["a", "b"]),
+            RespArray::new(vec![
+                bulk("A,B"),
+                RespFrame::Integer(3),
+                RespFrame::Integer(120),
+                bulk("003.1|a  |ff"),
+                bulk("llo"),
+                bulk("16"),
+                RespFrame::Integer(3),
+                bulk("e0e1f9fabfc9d4800c877a703b823ac0578ff8db"),
+            ])
+            .into()
+        );
+        let RespFrame::Error(e) = run(&backend, "x = 1", &[], &[]) else {
+            panic!("expected error");
+        };
+        assert!(e.contains("Script attempted to create global variable 'x'"));
+        let RespFrame::Error(e) = run(
+            &backend,
+            "local function f() return f() + 1 end return f()",
+            &[],
+            &[],
+        ) else {
+            panic!("expected error");
+        };
+        assert!(e.contains("stack overflow"));
+        assert_eq!(
+            run(
+                &backend,
+                "local function f(n) if n == 0 then return 0 end return f(n - 1) + 1 end return f(30)",
+                &[],
+                &[]
+            ),
+            RespFrame::Integer(30)
+        );
+    }
+}
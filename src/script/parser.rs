@@ -0,0 +1,648 @@
+use std::rc::Rc;
+
+use super::{
+    lexer::{tokenize, Token},
+    ScriptError,
+};
+
+// 一串语句，每条语句带上所在的行号，用于错误信息
+#[derive(Debug, Default)]
+pub struct Block(pub Vec<(Stat, usize)>);
+
+#[derive(Debug)]
+pub struct FuncBody {
+    pub params: Vec<String>,
+    pub varargs: bool,
+    pub body: Block,
+}
+
+#[derive(Debug)]
+pub enum Stat {
+    Local(Vec<String>, Vec<Expr>),
+    LocalFunction(String, Rc<FuncBody>),
+    // 赋值目标只能是 Name 或 Index
+    Assign(Vec<Expr>, Vec<Expr>),
+    Call(Expr),
+    If(Vec<(Expr, Block)>, Option<Block>),
+    While(Expr, Block),
+    Repeat(Block, Expr),
+    NumericFor(String, Expr, Expr, Option<Expr>, Block),
+    GenericFor(Vec<String>, Vec<Expr>, Block),
+    Do(Block),
+    Break,
+    Return(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnOp {
+    Neg,
+    Not,
+    Len,
+}
+
+#[derive(Debug)]
+pub enum TableField {
+    Positional(Expr),
+    Named(Expr, Expr),
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Nil,
+    True,
+    False,
+    Number(f64),
+    Str(Vec<u8>),
+    VarArgs,
+    Function(Rc<FuncBody>),
+    Table(Vec<TableField>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Unary(UnOp, Box<Expr>),
+    Name(String),
+    Index(Box<Expr>, Box<Expr>),
+    // 函数、参数、调用所在的行号
+    Call(Box<Expr>, Vec<Expr>, usize),
+    // obj:name(args)
+    Method(Box<Expr>, String, Vec<Expr>, usize),
+    // 括号把多个返回值截断为一个
+    Paren(Box<Expr>),
+}
+
+impl Expr {
+    // 位于表达式列表末尾时会展开为多个值
+    pub fn is_multi(&self) -> bool {
+        matches!(self, Expr::Call(..) | Expr::Method(..) | Expr::VarArgs)
+    }
+}
+
+pub fn parse(src: &[u8]) -> Result<Block, ScriptError> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        depth: 0,
+    };
+    let block = parser.block()?;
+    if parser.peek() != &Token::Eof {
+        return Err(parser.unexpected());
+    }
+    Ok(block)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    // 语法树的嵌套层数，限制它使解析和执行时的递归深度有上限
+    depth: usize,
+}
+
+// 和 Lua 5.1 的 LUAI_MAXCCALLS 相同
+const MAX_SYNTAX_LEVELS: usize = 200;
+
+// 二元运算符的左右优先级，和 Lua 5.1 一致：右结合的运算符右侧优先级更低
+fn binary_op(token: &Token) -> Option<(BinOp, u8, u8)> {
+    let op = match token {
+        Token::Keyword("or") => (BinOp::Or, 1, 1),
+        Token::Keyword("and") => (BinOp::And, 2, 2),
+        Token::Symbol("<") => (BinOp::Lt, 3, 3),
+        Token::Symbol(">") => (BinOp::Gt, 3, 3),
+        Token::Symbol("<=") => (BinOp::Le, 3, 3),
+        Token::Symbol(">=") => (BinOp::Ge, 3, 3),
+        Token::Symbol("~=") => (BinOp::Ne, 3, 3),
+        Token::Symbol("==") => (BinOp::Eq, 3, 3),
+        Token::Symbol("..") => (BinOp::Concat, 5, 4),
+        Token::Symbol("+") => (BinOp::Add, 6, 6),
+        Token::Symbol("-") => (BinOp::Sub, 6, 6),
+        Token::Symbol("*") => (BinOp::Mul, 7, 7),
+        Token::Symbol("/") => (BinOp::Div, 7, 7),
+        Token::Symbol("%") => (BinOp::Mod, 7, 7),
+        Token::Symbol("^") => (BinOp::Pow, 10, 9),
+        _ => return None,
+    };
+    Some(op)
+}
+
+// 一元运算符的优先级，高于乘除、低于乘方
+const UNARY_PRIORITY: u8 = 8;
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn check(&self, token: &Token) -> bool {
+        self.peek() == token
+    }
+
+    fn accept(&mut self, token: &Token) -> bool {
+        let matched = self.check(token);
+        if matched {
+            self.advance();
+        }
+        matched
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), ScriptError> {
+        if self.accept(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "'{}' expected near {}",
+                token_text(token),
+                self.near()
+            )))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, ScriptError> {
+        match self.peek().clone() {
+            Token::Name(name) => {
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.error(format!("<name> expected near {}", self.near()))),
+        }
+    }
+
+    fn near(&self) -> String {
+        match self.peek() {
+            Token::Eof => "<eof>".to_string(),
+            token => format!("'{}'", token_text(token)),
+        }
+    }
+
+    fn error(&self, msg: String) -> ScriptError {
+        ScriptError::Compile(format!("user_script:{}: {}", self.line(), msg))
+    }
+
+    fn unexpected(&self) -> ScriptError {
+        self.error(format!("unexpected symbol near {}", self.near()))
+    }
+
+    fn block_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            Token::Eof
+                | Token::Keyword("end")
+                | Token::Keyword("else")
+                | Token::Keyword("elseif")
+                | Token::Keyword("until")
+        )
+    }
+
+    // 进入一层嵌套，调用方负责恢复 depth
+    fn enter(&mut self) -> Result<(), ScriptError> {
+        self.depth += 1;
+        if self.depth > MAX_SYNTAX_LEVELS {
+            return Err(self.error("chunk has too many syntax levels".to_string()));
+        }
+        Ok(())
+    }
+
+    fn block(&mut self) -> Result<Block, ScriptError> {
+        let depth = self.depth;
+        self.enter()?;
+        let mut stats = Vec::new();
+        while !self.block_end() {
+            let line = self.line();
+            if self.accept(&Token::Keyword("return")) {
+                let exprs = if self.block_end() || self.check(&Token::Symbol(";")) {
+                    Vec::new()
+                } else {
+                    self.expr_list()?
+                };
+                self.accept(&Token::Symbol(";"));
+                stats.push((Stat::Return(exprs), line));
+                // return 必须是块中的最后一条语句
+                if !self.block_end() {
+                    return Err(self.error(format!("'end' expected near {}", self.near())));
+                }
+                break;
+            }
+            let stat = self.statement()?;
+            self.accept(&Token::Symbol(";"));
+            let is_break = matches!(stat, Stat::Break);
+            stats.push((stat, line));
+            if is_break {
+                break;
+            }
+        }
+        self.depth = depth;
+        Ok(Block(stats))
+    }
+
+    fn statement(&mut self) -> Result<Stat, ScriptError> {
+        match self.peek().clone() {
+            Token::Keyword("local") => {
+                self.advance();
+                if self.accept(&Token::Keyword("function")) {
+                    let name = self.name()?;
+                    return Ok(Stat::LocalFunction(name, self.func_body()?));
+                }
+                let mut names = vec![self.name()?];
+                while self.accept(&Token::Symbol(",")) {
+                    names.push(self.name()?);
+                }
+                let exprs = if self.accept(&Token::Symbol("=")) {
+                    self.expr_list()?
+                } else {
+                    Vec::new()
+                };
+                Ok(Stat::Local(names, exprs))
+            }
+            Token::Keyword("function") => {
+                self.advance();
+                let mut target = Expr::Name(self.name()?);
+                while self.accept(&Token::Symbol(".")) {
+                    let key = Expr::Str(self.name()?.into_bytes());
+                    target = Expr::Index(Box::new(target), Box::new(key));
+                }
+                let body = self.func_body()?;
+                Ok(Stat::Assign(vec![target], vec![Expr::Function(body)]))
+            }
+            Token::Keyword("if") => {
+                self.advance();
+                let mut branches = Vec::new();
+                let cond = self.expr()?;
+                self.expect(&Token::Keyword("then"))?;
+                branches.push((cond, self.block()?));
+                let mut otherwise = None;
+                loop {
+                    if self.accept(&Token::Keyword("elseif")) {
+                        let cond = self.expr()?;
+                        self.expect(&Token::Keyword("then"))?;
+                        branches.push((cond, self.block()?));
+                    } else if self.accept(&Token::Keyword("else")) {
+                        otherwise = Some(self.block()?);
+                        self.expect(&Token::Keyword("end"))?;
+                        break;
+                    } else {
+                        self.expect(&Token::Keyword("end"))?;
+                        break;
+                    }
+                }
+                Ok(Stat::If(branches, otherwise))
+            }
+            Token::Keyword("while") => {
+                self.advance();
+                let cond = self.expr()?;
+                self.expect(&Token::Keyword("do"))?;
+                let body = self.block()?;
+                self.expect(&Token::Keyword("end"))?;
+                Ok(Stat::While(cond, body))
+            }
+            Token::Keyword("repeat") => {
+                self.advance();
+                let body = self.block()?;
+                self.expect(&Token::Keyword("until"))?;
+                Ok(Stat::Repeat(body, self.expr()?))
+            }
+            Token::Keyword("for") => {
+                self.advance();
+                let first = self.name()?;
+                if self.accept(&Token::Symbol("=")) {
+                    let start = self.expr()?;
+                    self.expect(&Token::Symbol(","))?;
+                    let limit = self.expr()?;
+                    let step = if self.accept(&Token::Symbol(",")) {
+                        Some(self.expr()?)
+                    } else {
+                        None
+                    };
+                    self.expect(&Token::Keyword("do"))?;
+                    let body = self.block()?;
+                    self.expect(&Token::Keyword("end"))?;
+                    return Ok(Stat::NumericFor(first, start, limit, step, body));
+                }
+                let mut names = vec![first];
+                while self.accept(&Token::Symbol(",")) {
+                    names.push(self.name()?);
+                }
+                self.expect(&Token::Keyword("in"))?;
+                let exprs = self.expr_list()?;
+                self.expect(&Token::Keyword("do"))?;
+                let body = self.block()?;
+                self.expect(&Token::Keyword("end"))?;
+                Ok(Stat::GenericFor(names, exprs, body))
+            }
+            Token::Keyword("do") => {
+                self.advance();
+                let body = self.block()?;
+                self.expect(&Token::Keyword("end"))?;
+                Ok(Stat::Do(body))
+            }
+            Token::Keyword("break") => {
+                self.advance();
+                Ok(Stat::Break)
+            }
+            _ => {
+                let expr = self.suffixed_expr()?;
+                if self.check(&Token::Symbol("=")) || self.check(&Token::Symbol(",")) {
+                    let mut targets = vec![expr];
+                    while self.accept(&Token::Symbol(",")) {
+                        targets.push(self.suffixed_expr()?);
+                    }
+                    if targets
+                        .iter()
+                        .any(|t| !matches!(t, Expr::Name(_) | Expr::Index(..)))
+                    {
+                        return Err(self.error("syntax error near '='".to_string()));
+                    }
+                    self.expect(&Token::Symbol("="))?;
+                    Ok(Stat::Assign(targets, self.expr_list()?))
+                } else if matches!(expr, Expr::Call(..) | Expr::Method(..)) {
+                    Ok(Stat::Call(expr))
+                } else {
+                    Err(self.unexpected())
+                }
+            }
+        }
+    }
+
+    fn func_body(&mut self) -> Result<Rc<FuncBody>, ScriptError> {
+        self.expect(&Token::Symbol("("))?;
+        let mut params = Vec::new();
+        let mut varargs = false;
+        if !self.check(&Token::Symbol(")")) {
+            loop {
+                if self.accept(&Token::Symbol("...")) {
+                    varargs = true;
+                    break;
+                }
+                params.push(self.name()?);
+                if !self.accept(&Token::Symbol(",")) {
+                    break;
+                }
+            }
+        }
+        self.expect(&Token::Symbol(")"))?;
+        let body = self.block()?;
+        self.expect(&Token::Keyword("end"))?;
+        Ok(Rc::new(FuncBody {
+            params,
+            varargs,
+            body,
+        }))
+    }
+
+    fn expr_list(&mut self) -> Result<Vec<Expr>, ScriptError> {
+        let mut exprs = vec![self.expr()?];
+        while self.accept(&Token::Symbol(",")) {
+            exprs.push(self.expr()?);
+        }
+        Ok(exprs)
+    }
+
+    fn expr(&mut self) -> Result<Expr, ScriptError> {
+        self.sub_expr(0)
+    }
+
+    // 按优先级爬升解析，只合并左优先级高于 limit 的运算符
+    fn sub_expr(&mut self, limit: u8) -> Result<Expr, ScriptError> {
+        let depth = self.depth;
+        self.enter()?;
+        let unary = match self.peek() {
+            Token::Keyword("not") => Some(UnOp::Not),
+            Token::Symbol("-") => Some(UnOp::Neg),
+            Token::Symbol("#") => Some(UnOp::Len),
+            _ => None,
+        };
+        let mut left = match unary {
+            Some(op) => {
+                self.advance();
+                Expr::Unary(op, Box::new(self.sub_expr(UNARY_PRIORITY)?))
+            }
+            None => self.simple_expr()?,
+        };
+        while let Some((op, left_priority, right_priority)) = binary_op(self.peek()) {
+            if left_priority <= limit {
+                break;
+            }
+            self.advance();
+            // 左结合的运算链每多一个运算符，语法树就深一层
+            self.enter()?;
+            let right = self.sub_expr(right_priority)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn simple_expr(&mut self) -> Result<Expr, ScriptError> {
+        let expr = match self.peek().clone() {
+            Token::Number(n) => Expr::Number(n),
+            Token::Str(s) => Expr::Str(s),
+            Token::Keyword("nil") => Expr::Nil,
+            Token::Keyword("true") => Expr::True,
+            Token::Keyword("false") => Expr::False,
+            Token::Symbol("...") => Expr::VarArgs,
+            Token::Symbol("{") => return self.table(),
+            Token::Keyword("function") => {
+                self.advance();
+                return Ok(Expr::Function(self.func_body()?));
+            }
+            _ => return self.suffixed_expr(),
+        };
+        self.advance();
+        Ok(expr)
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr, ScriptError> {
+        match self.peek().clone() {
+            Token::Name(name) => {
+                self.advance();
+                Ok(Expr::Name(name))
+            }
+            Token::Symbol("(") => {
+                self.advance();
+                let expr = self.expr()?;
+                self.expect(&Token::Symbol(")"))?;
+                Ok(Expr::Paren(Box::new(expr)))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // primary { '.' Name | '[' exp ']' | ':' Name args | args }
+    fn suffixed_expr(&mut self) -> Result<Expr, ScriptError> {
+        let depth = self.depth;
+        let mut expr = self.primary_expr()?;
+        loop {
+            let line = self.line();
+            if self.is_suffix() {
+                self.enter()?;
+            }
+            match self.peek() {
+                Token::Symbol(".") => {
+                    self.advance();
+                    let key = Expr::Str(self.name()?.into_bytes());
+                    expr = Expr::Index(Box::new(expr), Box::new(key));
+                }
+                Token::Symbol("[") => {
+                    self.advance();
+                    let key = self.expr()?;
+                    self.expect(&Token::Symbol("]"))?;
+                    expr = Expr::Index(Box::new(expr), Box::new(key));
+                }
+                Token::Symbol(":") => {
+                    self.advance();
+                    let name = self.name()?;
+                    let args = self.call_args()?;
+                    expr = Expr::Method(Box::new(expr), name, args, line);
+                }
+                Token::Symbol("(") | Token::Symbol("{") | Token::Str(_) => {
+                    let args = self.call_args()?;
+                    expr = Expr::Call(Box::new(expr), args, line);
+                }
+                _ => {
+                    self.depth = depth;
+                    return Ok(expr);
+                }
+            }
+        }
+    }
+
+    fn is_suffix(&self) -> bool {
+        matches!(
+            self.peek(),
+            Token::Symbol(".")
+                | Token::Symbol("[")
+                | Token::Symbol(":")
+                | Token::Symbol("(")
+                | Token::Symbol("{")
+                | Token::Str(_)
+        )
+    }
+
+    // f(args) | f{table} | f"string"
+    fn call_args(&mut self) -> Result<Vec<Expr>, ScriptError> {
+        match self.peek().clone() {
+            Token::Str(s) => {
+                self.advance();
+                Ok(vec![Expr::Str(s)])
+            }
+            Token::Symbol("{") => Ok(vec![self.table()?]),
+            Token::Symbol("(") => {
+                self.advance();
+                if self.accept(&Token::Symbol(")")) {
+                    return Ok(Vec::new());
+                }
+                let args = self.expr_list()?;
+                self.expect(&Token::Symbol(")"))?;
+                Ok(args)
+            }
+            _ => Err(self.error(format!("function arguments expected near {}", self.near()))),
+        }
+    }
+
+    fn table(&mut self) -> Result<Expr, ScriptError> {
+        self.expect(&Token::Symbol("{"))?;
+        let mut fields = Vec::new();
+        while !self.check(&Token::Symbol("}")) {
+            if self.accept(&Token::Symbol("[")) {
+                let key = self.expr()?;
+                self.expect(&Token::Symbol("]"))?;
+                self.expect(&Token::Symbol("="))?;
+                fields.push(TableField::Named(key, self.expr()?));
+            } else if matches!(self.peek(), Token::Name(_))
+                && self.tokens.get(self.pos + 1).map(|t| &t.0) == Some(&Token::Symbol("="))
+            {
+                let key = Expr::Str(self.name()?.into_bytes());
+                self.advance();
+                fields.push(TableField::Named(key, self.expr()?));
+            } else {
+                fields.push(TableField::Positional(self.expr()?));
+            }
+            if !self.accept(&Token::Symbol(",")) && !self.accept(&Token::Symbol(";")) {
+                break;
+            }
+        }
+        self.expect(&Token::Symbol("}"))?;
+        Ok(Expr::Table(fields))
+    }
+}
+
+fn token_text(token: &Token) -> String {
+    match token {
+        Token::Name(name) => name.clone(),
+        Token::Number(n) => n.to_string(),
+        Token::Str(s) => String::from_utf8_lossy(s).into_owned(),
+        Token::Keyword(k) | Token::Symbol(k) => k.to_string(),
+        Token::Eof => "<eof>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operator_precedence() -> anyhow::Result<()> {
+        let Block(stats) = parse(b"return 1 + 2 * 3 .. 'x' == 'y' or not a")?;
+        let Stat::Return(exprs) = &stats[0].0 else {
+            panic!("expected a return statement");
+        };
+        // ((1 + (2 * 3)) .. 'x') == 'y') or (not a)
+        let Expr::Binary(BinOp::Or, left, right) = &exprs[0] else {
+            panic!("or should bind loosest: {:?}", exprs[0]);
+        };
+        assert!(matches!(**right, Expr::Unary(UnOp::Not, _)));
+        let Expr::Binary(BinOp::Eq, concat, _) = &**left else {
+            panic!("expected ==: {:?}", left);
+        };
+        let Expr::Binary(BinOp::Concat, sum, _) = &**concat else {
+            panic!("expected ..: {:?}", concat);
+        };
+        assert!(
+            matches!(&**sum, Expr::Binary(BinOp::Add, _, product) if matches!(**product, Expr::Binary(BinOp::Mul, ..)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse(b"local x = \nif x then").unwrap_err();
+        assert!(err.to_string().contains("user_script:2:"), "{}", err);
+        assert!(parse(b"return 1 return 2").is_err());
+        assert!(parse(b"x + 1").is_err());
+        assert!(parse(b"for i = 1, 10 do end while true do break end").is_ok());
+
+        // 嵌套过深的脚本在解析时报错，而不是在解析或执行时栈溢出
+        let nested = format!("return {}1{}", "(".repeat(1000), ")".repeat(1000));
+        let err = parse(nested.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("chunk has too many syntax levels"));
+        let chain = format!("return {}1", "1 + ".repeat(1000));
+        assert!(parse(chain.as_bytes()).is_err());
+        assert!(parse(format!("return {}1", "1 + ".repeat(100)).as_bytes()).is_ok());
+    }
+}
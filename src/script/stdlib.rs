@@ -0,0 +1,541 @@
+use std::{cell::Cell, collections::HashMap, rc::Rc};
+
+use crate::resp::MAX_BULK_LENGTH;
+
+use super::{
+    interp::{Interp, LuaError},
+    value::{format_number, Table, Value},
+};
+
+type NativeResult = Result<Vec<Value>, LuaError>;
+
+// Redis 脚本中可以使用的基础函数和 table、string、math 库的常用部分
+pub fn globals() -> HashMap<String, Value> {
+    let mut globals = HashMap::new();
+    let mut set = |name: &str, f: fn(&mut Interp<'_>, Vec<Value>) -> NativeResult| {
+        globals.insert(name.to_string(), Value::native(f));
+    };
+    set("type", lua_type);
+    set("tostring", tostring);
+    set("tonumber", tonumber);
+    set("ipairs", ipairs);
+    set("pairs", pairs);
+    set("next", next);
+    set("unpack", unpack);
+    set("select", select);
+    set("error", error);
+    set("assert", assert);
+    set("pcall", pcall);
+    globals.insert("table".to_string(), library(TABLE_LIB));
+    globals.insert("string".to_string(), library(STRING_LIB));
+    let math = library(MATH_LIB);
+    if let Value::Table(t) = &math {
+        t.borrow_mut().set_str("huge", Value::Number(f64::INFINITY));
+        t.borrow_mut()
+            .set_str("pi", Value::Number(std::f64::consts::PI));
+    }
+    globals.insert("math".to_string(), math);
+    globals
+}
+
+type Library = &'static [(
+    &'static str,
+    fn(&mut Interp<'_>, Vec<Value>) -> NativeResult,
+)];
+
+const TABLE_LIB: Library = &[
+    ("insert", table_insert),
+    ("remove", table_remove),
+    ("concat", table_concat),
+    ("getn", table_getn),
+];
+
+const STRING_LIB: Library = &[
+    ("len", string_len),
+    ("sub", string_sub),
+    ("upper", string_upper),
+    ("lower", string_lower),
+    ("rep", string_rep),
+    ("byte", string_byte),
+    ("char", string_char),
+    ("format", string_format),
+];
+
+const MATH_LIB: Library = &[
+    ("floor", |i, a| math1(i, a, "floor", f64::floor)),
+    ("ceil", |i, a| math1(i, a, "ceil", f64::ceil)),
+    ("abs", |i, a| math1(i, a, "abs", f64::abs)),
+    ("sqrt", |i, a| math1(i, a, "sqrt", f64::sqrt)),
+    ("max", math_max),
+    ("min", math_min),
+    ("fmod", math_fmod),
+];
+
+pub fn library(functions: Library) -> Value {
+    let mut table = Table::default();
+    for (name, f) in functions {
+        table.set_str(name, Value::native(*f));
+    }
+    Value::table(table)
+}
+
+fn arg(args: &[Value], n: usize) -> Value {
+    args.get(n).cloned().unwrap_or(Value::Nil)
+}
+
+fn bad_argument(interp: &Interp, n: usize, name: &str, expected: &str, got: &Value) -> LuaError {
+    interp.error(format!(
+        "bad argument #{} to '{}' ({} expected, got {})",
+        n + 1,
+        name,
+        expected,
+        match got {
+            Value::Nil => "no value",
+            v => v.type_name(),
+        }
+    ))
+}
+
+pub fn check_number(
+    interp: &Interp,
+    args: &[Value],
+    n: usize,
+    name: &str,
+) -> Result<f64, LuaError> {
+    let v = arg(args, n);
+    v.to_number()
+        .ok_or_else(|| bad_argument(interp, n, name, "number", &v))
+}
+
+// 脚本生成的字符串不能超过 proto-max-bulk-len，避免一次分配耗尽内存
+pub fn check_length(interp: &Interp, len: usize) -> Result<(), LuaError> {
+    if len > MAX_BULK_LENGTH {
+        return Err(interp.error("resulting string too large"));
+    }
+    Ok(())
+}
+
+fn opt_number(
+    interp: &Interp,
+    args: &[Value],
+    n: usize,
+    name: &str,
+    default: f64,
+) -> Result<f64, LuaError> {
+    match arg(args, n) {
+        Value::Nil => Ok(default),
+        _ => check_number(interp, args, n, name),
+    }
+}
+
+pub fn check_bytes(
+    interp: &Interp,
+    args: &[Value],
+    n: usize,
+    name: &str,
+) -> Result<Rc<[u8]>, LuaError> {
+    let v = arg(args, n);
+    v.to_bytes()
+        .ok_or_else(|| bad_argument(interp, n, name, "string", &v))
+}
+
+fn check_table(
+    interp: &Interp,
+    args: &[Value],
+    n: usize,
+    name: &str,
+) -> Result<super::value::TableRef, LuaError> {
+    match arg(args, n) {
+        Value::Table(t) => Ok(t),
+        v => Err(bad_argument(interp, n, name, "table", &v)),
+    }
+}
+
+fn lua_type(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    if args.is_empty() {
+        return Err(bad_argument(interp, 0, "type", "value", &Value::Nil));
+    }
+    Ok(vec![Value::str(args[0].type_name())])
+}
+
+fn tostring(_: &mut Interp, args: Vec<Value>) -> NativeResult {
+    Ok(vec![match arg(&args, 0) {
+        Value::String(s) => Value::String(s),
+        v => Value::str(v.to_string()),
+    }])
+}
+
+fn tonumber(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let value = arg(&args, 0);
+    let n = match arg(&args, 1) {
+        Value::Nil => value.to_number(),
+        _ => {
+            let base = check_number(interp, &args, 1, "tonumber")? as u32;
+            if !(2..=36).contains(&base) {
+                return Err(interp.error("bad argument #2 to 'tonumber' (base out of range)"));
+            }
+            let s = check_bytes(interp, &args, 0, "tonumber")?;
+            std::str::from_utf8(&s)
+                .ok()
+                .and_then(|s| i64::from_str_radix(s.trim(), base).ok())
+                .map(|n| n as f64)
+        }
+    };
+    Ok(vec![n.map_or(Value::Nil, Value::Number)])
+}
+
+fn ipairs(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "ipairs")?;
+    let iter = Value::native(|_, args| {
+        let Value::Table(table) = arg(&args, 0) else {
+            return Ok(vec![Value::Nil]);
+        };
+        let i = arg(&args, 1).to_number().unwrap_or(0.0) + 1.0;
+        let value = table.borrow().get(&Value::Number(i));
+        match value {
+            Value::Nil => Ok(vec![Value::Nil]),
+            v => Ok(vec![Value::Number(i), v]),
+        }
+    });
+    Ok(vec![iter, Value::Table(table), Value::Number(0.0)])
+}
+
+// 遍历开始时复制一份键值对，遍历中修改表不会影响本次遍历
+fn pairs(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "pairs")?;
+    let entries = table.borrow().pairs();
+    let pos = Cell::new(0);
+    let iter = Value::native(move |_, _| {
+        let i = pos.get();
+        pos.set(i + 1);
+        match entries.get(i) {
+            Some((k, v)) => Ok(vec![k.clone(), v.clone()]),
+            None => Ok(vec![Value::Nil]),
+        }
+    });
+    Ok(vec![iter, Value::Table(table), Value::Nil])
+}
+
+fn next(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "next")?;
+    let entries = table.borrow().pairs();
+    let key = arg(&args, 1);
+    let pos = match key {
+        Value::Nil => 0,
+        key => match entries.iter().position(|(k, _)| k.raw_equals(&key)) {
+            Some(i) => i + 1,
+            None => return Err(interp.error("invalid key to 'next'")),
+        },
+    };
+    Ok(match entries.into_iter().nth(pos) {
+        Some((k, v)) => vec![k, v],
+        None => vec![Value::Nil],
+    })
+}
+
+fn unpack(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "unpack")?;
+    let table = table.borrow();
+    let start = opt_number(interp, &args, 1, "unpack", 1.0)? as i64;
+    let end = opt_number(interp, &args, 2, "unpack", table.len() as f64)? as i64;
+    Ok((start..=end)
+        .map(|i| table.get(&Value::Number(i as f64)))
+        .collect())
+}
+
+fn select(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let rest = args.len().saturating_sub(1);
+    if let Value::String(s) = arg(&args, 0) {
+        if &*s == b"#" {
+            return Ok(vec![Value::Number(rest as f64)]);
+        }
+    }
+    let n = check_number(interp, &args, 0, "select")? as i64;
+    let start = match n {
+        n if n < 0 && n.unsigned_abs() as usize <= rest => rest - n.unsigned_abs() as usize,
+        n if n > 0 => (n as usize - 1).min(rest),
+        _ => return Err(interp.error("bad argument #1 to 'select' (index out of range)")),
+    };
+    Ok(args.into_iter().skip(1 + start).collect())
+}
+
+// 字符串错误加上行号，其它类型的值原样抛出
+fn error(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    match arg(&args, 0) {
+        Value::String(s) => Err(interp.error(String::from_utf8_lossy(&s))),
+        Value::Number(n) => Err(interp.error(format_number(n))),
+        v => Err(LuaError(v)),
+    }
+}
+
+fn assert(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    if arg(&args, 0).is_truthy() {
+        return Ok(args);
+    }
+    match arg(&args, 1) {
+        Value::Nil => Err(interp.error("assertion failed!")),
+        msg => Err(LuaError(msg)),
+    }
+}
+
+fn pcall(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let mut args = args.into_iter();
+    let func = args.next().unwrap_or(Value::Nil);
+    match interp.call(&func, args.collect()) {
+        Ok(values) => {
+            let mut ret = vec![Value::Boolean(true)];
+            ret.extend(values);
+            Ok(ret)
+        }
+        Err(LuaError(e)) => Ok(vec![Value::Boolean(false), e]),
+    }
+}
+
+fn table_insert(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "insert")?;
+    let value = match args.len() {
+        2 => {
+            let value = arg(&args, 1);
+            table.borrow_mut().push(value.clone());
+            value
+        }
+        3 => {
+            let pos = check_number(interp, &args, 1, "insert")? as usize;
+            let value = arg(&args, 2);
+            table.borrow_mut().insert_at(pos, value.clone());
+            value
+        }
+        _ => return Err(interp.error("wrong number of arguments to 'insert'")),
+    };
+    if matches!(value, Value::Table(_) | Value::Function(_)) {
+        interp.track_table(&table);
+    }
+    Ok(Vec::new())
+}
+
+fn table_remove(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "remove")?;
+    let len = table.borrow().len();
+    let pos = opt_number(interp, &args, 1, "remove", len as f64)? as usize;
+    let removed = table.borrow_mut().remove_at(pos);
+    Ok(vec![removed])
+}
+
+fn table_concat(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "concat")?;
+    let sep = match arg(&args, 1) {
+        Value::Nil => Rc::from(&b""[..]),
+        _ => check_bytes(interp, &args, 1, "concat")?,
+    };
+    let table = table.borrow();
+    let start = opt_number(interp, &args, 2, "concat", 1.0)? as usize;
+    let end = opt_number(interp, &args, 3, "concat", table.len() as f64)? as usize;
+    let mut out = Vec::new();
+    for i in start..=end {
+        let value = table.get(&Value::Number(i as f64));
+        let Some(bytes) = value.to_bytes() else {
+            return Err(interp.error(format!(
+                "invalid value (at index {}) in table for 'concat'",
+                i
+            )));
+        };
+        check_length(interp, out.len() + sep.len() + bytes.len())?;
+        if i > start {
+            out.extend_from_slice(&sep);
+        }
+        out.extend_from_slice(&bytes);
+    }
+    Ok(vec![Value::str(out)])
+}
+
+fn table_getn(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let table = check_table(interp, &args, 0, "getn")?;
+    let len = table.borrow().len();
+    Ok(vec![Value::Number(len as f64)])
+}
+
+fn string_len(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let s = check_bytes(interp, &args, 0, "len")?;
+    Ok(vec![Value::Number(s.len() as f64)])
+}
+
+// 下标从 1 开始，负数表示从末尾倒数
+fn string_range(len: usize, start: i64, end: i64) -> std::ops::Range<usize> {
+    let pos = |i: i64| -> i64 {
+        if i < 0 {
+            (len as i64 + i + 1).max(0)
+        } else {
+            i
+        }
+    };
+    let start = pos(start).max(1) as usize;
+    let end = (pos(end).max(0) as usize).min(len);
+    if start > end {
+        0..0
+    } else {
+        start - 1..end
+    }
+}
+
+fn string_sub(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let s = check_bytes(interp, &args, 0, "sub")?;
+    let start = opt_number(interp, &args, 1, "sub", 1.0)? as i64;
+    let end = opt_number(interp, &args, 2, "sub", -1.0)? as i64;
+    Ok(vec![Value::str(&s[string_range(s.len(), start, end)])])
+}
+
+fn string_upper(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let s = check_bytes(interp, &args, 0, "upper")?;
+    Ok(vec![Value::str(s.to_ascii_uppercase())])
+}
+
+fn string_lower(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let s = check_bytes(interp, &args, 0, "lower")?;
+    Ok(vec![Value::str(s.to_ascii_lowercase())])
+}
+
+fn string_rep(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let s = check_bytes(interp, &args, 0, "rep")?;
+    let n = check_number(interp, &args, 1, "rep")?.max(0.0) as usize;
+    check_length(interp, s.len().saturating_mul(n))?;
+    Ok(vec![Value::str(s.repeat(n))])
+}
+
+fn string_byte(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let s = check_bytes(interp, &args, 0, "byte")?;
+    let start = opt_number(interp, &args, 1, "byte", 1.0)? as i64;
+    let end = opt_number(interp, &args, 2, "byte", start as f64)? as i64;
+    Ok(s[string_range(s.len(), start, end)]
+        .iter()
+        .map(|b| Value::Number(*b as f64))
+        .collect())
+}
+
+fn string_char(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let mut out = Vec::with_capacity(args.len());
+    for i in 0..args.len() {
+        let n = check_number(interp, &args, i, "char")?;
+        match u8::try_from(n as i64) {
+            Ok(b) => out.push(b),
+            Err(_) => {
+                return Err(
+                    interp.error(format!("bad argument #{} to 'char' (invalid value)", i + 1))
+                )
+            }
+        }
+    }
+    Ok(vec![Value::str(out)])
+}
+
+// 支持 %s %d %i %f %g %x %q 和 %%，可以带宽度和精度，例如 "%5.2f"
+fn string_format(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let fmt = check_bytes(interp, &args, 0, "format")?;
+    let mut out = Vec::new();
+    let mut n = 1;
+    let mut i = 0;
+    while i < fmt.len() {
+        if fmt[i] != b'%' {
+            out.push(fmt[i]);
+            i += 1;
+            continue;
+        }
+        i += 1;
+        let spec_start = i;
+        while i < fmt.len() && matches!(fmt[i], b'-' | b'0'..=b'9' | b'.') {
+            i += 1;
+        }
+        let spec = String::from_utf8_lossy(&fmt[spec_start..i]).into_owned();
+        let Some(&conv) = fmt.get(i) else {
+            return Err(interp.error("invalid option '%' to 'format'"));
+        };
+        i += 1;
+        let left = spec.starts_with('-');
+        let spec = spec.trim_start_matches('-');
+        // 和 Lua 一样宽度和精度最多两位数，避免生成超长的字符串
+        if spec
+            .split('.')
+            .any(|part| part.trim_start_matches('0').len() > 2)
+            || spec.matches('.').count() > 1
+        {
+            return Err(interp.error("invalid format (width or precision too long)"));
+        }
+        let (width, precision): (usize, Option<usize>) = match spec.split_once('.') {
+            Some((w, p)) => (w.parse().unwrap_or(0), Some(p.parse().unwrap_or(0))),
+            None => (spec.parse().unwrap_or(0), None),
+        };
+        let zero_pad = spec.starts_with('0') && !left;
+        let text = match conv {
+            b'%' => {
+                out.push(b'%');
+                continue;
+            }
+            b'd' | b'i' => (check_number(interp, &args, n, "format")? as i64).to_string(),
+            b'x' => format!("{:x}", check_number(interp, &args, n, "format")? as i64),
+            b'X' => format!("{:X}", check_number(interp, &args, n, "format")? as i64),
+            b'f' => format!(
+                "{:.*}",
+                precision.unwrap_or(6),
+                check_number(interp, &args, n, "format")?
+            ),
+            b'g' => format_number(check_number(interp, &args, n, "format")?),
+            b's' => {
+                let s = match arg(&args, n) {
+                    Value::String(s) => String::from_utf8_lossy(&s).into_owned(),
+                    v => v.to_string(),
+                };
+                match precision {
+                    Some(p) => s.chars().take(p).collect(),
+                    None => s,
+                }
+            }
+            b'q' => format!(
+                "{:?}",
+                String::from_utf8_lossy(&check_bytes(interp, &args, n, "format")?)
+            ),
+            c => return Err(interp.error(format!("invalid option '%{}' to 'format'", c as char))),
+        };
+        n += 1;
+        let pad = width.saturating_sub(text.chars().count());
+        let fill = if zero_pad { "0" } else { " " }.repeat(pad);
+        if left {
+            out.extend_from_slice(text.as_bytes());
+            out.extend_from_slice(fill.as_bytes());
+        } else if zero_pad && text.starts_with('-') {
+            out.push(b'-');
+            out.extend_from_slice(fill.as_bytes());
+            out.extend_from_slice(&text.as_bytes()[1..]);
+        } else {
+            out.extend_from_slice(fill.as_bytes());
+            out.extend_from_slice(text.as_bytes());
+        }
+    }
+    Ok(vec![Value::str(out)])
+}
+
+fn math1(interp: &mut Interp, args: Vec<Value>, name: &str, f: fn(f64) -> f64) -> NativeResult {
+    Ok(vec![Value::Number(f(check_number(
+        interp, &args, 0, name,
+    )?))])
+}
+
+fn math_max(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let mut max = check_number(interp, &args, 0, "max")?;
+    for i in 1..args.len() {
+        max = max.max(check_number(interp, &args, i, "max")?);
+    }
+    Ok(vec![Value::Number(max)])
+}
+
+fn math_min(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let mut min = check_number(interp, &args, 0, "min")?;
+    for i in 1..args.len() {
+        min = min.min(check_number(interp, &args, i, "min")?);
+    }
+    Ok(vec![Value::Number(min)])
+}
+
+fn math_fmod(interp: &mut Interp, args: Vec<Value>) -> NativeResult {
+    let a = check_number(interp, &args, 0, "fmod")?;
+    let b = check_number(interp, &args, 1, "fmod")?;
+    Ok(vec![Value::Number(a % b)])
+}
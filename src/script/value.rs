@@ -0,0 +1,360 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use super::{
+    interp::{Interp, LuaError, Scope},
+    parser::FuncBody,
+};
+
+pub type NativeFn = dyn Fn(&mut Interp<'_>, Vec<Value>) -> Result<Vec<Value>, LuaError>;
+
+#[derive(Clone)]
+pub enum Function {
+    Native(Rc<NativeFn>),
+    // 用户定义的函数，保存定义时所在的作用域
+    Closure(Rc<FuncBody>, Rc<Scope>),
+}
+
+pub type TableRef = Rc<RefCell<Table>>;
+
+// Lua 5.1 的值，数字只有双精度浮点一种
+#[derive(Clone)]
+pub enum Value {
+    Nil,
+    Boolean(bool),
+    Number(f64),
+    String(Rc<[u8]>),
+    Table(TableRef),
+    Function(Function),
+}
+
+// 表的 key，数字按位保存，表和函数按地址比较
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum TableKey {
+    Boolean(bool),
+    Number(u64),
+    String(Rc<[u8]>),
+    Table(usize),
+    Function(usize),
+}
+
+// 1..=array.len() 的整数 key 放在数组部分，其余放在哈希部分；保留 key 作为原始值用于 pairs
+#[derive(Default)]
+pub struct Table {
+    array: Vec<Value>,
+    hash: HashMap<TableKey, (Value, Value)>,
+}
+
+impl Value {
+    pub fn str(s: impl AsRef<[u8]>) -> Self {
+        Value::String(Rc::from(s.as_ref()))
+    }
+
+    pub fn table(table: Table) -> Self {
+        Value::Table(Rc::new(RefCell::new(table)))
+    }
+
+    pub fn native(
+        f: impl Fn(&mut Interp<'_>, Vec<Value>) -> Result<Vec<Value>, LuaError> + 'static,
+    ) -> Self {
+        Value::Function(Function::Native(Rc::new(f)))
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Boolean(false))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Boolean(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Table(_) => "table",
+            Value::Function(_) => "function",
+        }
+    }
+
+    // 数字和可以解析为数字的字符串都能参与算术运算
+    pub fn to_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::String(s) => parse_number(s),
+            _ => None,
+        }
+    }
+
+    // 字符串和数字可以拼接
+    pub fn to_bytes(&self) -> Option<Rc<[u8]>> {
+        match self {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(Rc::from(format_number(*n).as_bytes())),
+            _ => None,
+        }
+    }
+
+    pub fn raw_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Table(a), Value::Table(b)) => Rc::ptr_eq(a, b),
+            (Value::Function(a), Value::Function(b)) => a.addr() == b.addr(),
+            _ => false,
+        }
+    }
+}
+
+impl Function {
+    fn addr(&self) -> usize {
+        match self {
+            Function::Native(f) => Rc::as_ptr(f) as *const () as usize,
+            Function::Closure(body, _) => Rc::as_ptr(body) as usize,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", format_number(*n)),
+            Value::String(s) => write!(f, "{}", String::from_utf8_lossy(s)),
+            Value::Table(t) => write!(f, "table: {:p}", Rc::as_ptr(t)),
+            Value::Function(func) => write!(f, "function: 0x{:x}", func.addr()),
+        }
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", String::from_utf8_lossy(s)),
+            v => write!(f, "{}", v),
+        }
+    }
+}
+
+impl Table {
+    pub fn from_array(values: Vec<Value>) -> Self {
+        let mut table = Table::default();
+        for v in values {
+            table.push(v);
+        }
+        table
+    }
+
+    // 数组部分的长度，即 # 运算符的结果
+    pub fn len(&self) -> usize {
+        self.array.len()
+    }
+
+    pub fn push(&mut self, value: Value) {
+        let key = Value::Number((self.array.len() + 1) as f64);
+        self.set(key, value);
+    }
+
+    pub fn get(&self, key: &Value) -> Value {
+        if let Some(i) = array_index(key) {
+            if let Some(v) = self.array.get(i) {
+                return v.clone();
+            }
+        }
+        match table_key(key) {
+            Some(key) => self
+                .hash
+                .get(&key)
+                .map(|(_, v)| v.clone())
+                .unwrap_or(Value::Nil),
+            None => Value::Nil,
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Value {
+        self.get(&Value::str(key))
+    }
+
+    // key 为 nil 或 NaN 时报错，值为 nil 时删除 key
+    pub fn set(&mut self, key: Value, value: Value) {
+        if let Some(i) = array_index(&key) {
+            if i < self.array.len() {
+                self.array[i] = value;
+                if i + 1 == self.array.len() {
+                    while matches!(self.array.last(), Some(Value::Nil)) {
+                        self.array.pop();
+                    }
+                }
+                return;
+            }
+            if i == self.array.len() && !matches!(value, Value::Nil) {
+                self.array.push(value);
+                self.hash
+                    .remove(&TableKey::Number(((i + 1) as f64).to_bits()));
+                // 把哈希部分中紧接着的整数 key 移到数组部分
+                loop {
+                    let next = TableKey::Number(((self.array.len() + 1) as f64).to_bits());
+                    match self.hash.remove(&next) {
+                        Some((_, v)) => self.array.push(v),
+                        None => break,
+                    }
+                }
+                return;
+            }
+        }
+        let Some(hash_key) = table_key(&key) else {
+            return;
+        };
+        if matches!(value, Value::Nil) {
+            self.hash.remove(&hash_key);
+        } else {
+            self.hash.insert(hash_key, (key, value));
+        }
+    }
+
+    pub fn set_str(&mut self, key: &str, value: Value) {
+        self.set(Value::str(key), value);
+    }
+
+    // 所有非 nil 的键值对：先按顺序遍历数组部分，再遍历哈希部分
+    pub fn pairs(&self) -> Vec<(Value, Value)> {
+        let array = self
+            .array
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !matches!(v, Value::Nil))
+            .map(|(i, v)| (Value::Number((i + 1) as f64), v.clone()));
+        array.chain(self.hash.values().cloned()).collect()
+    }
+
+    pub fn array(&self) -> &[Value] {
+        &self.array
+    }
+
+    pub fn remove_at(&mut self, pos: usize) -> Value {
+        if pos == 0 || pos > self.array.len() {
+            return Value::Nil;
+        }
+        self.array.remove(pos - 1)
+    }
+
+    pub fn insert_at(&mut self, pos: usize, value: Value) {
+        let pos = pos.clamp(1, self.array.len() + 1);
+        self.array.insert(pos - 1, value);
+    }
+}
+
+fn array_index(key: &Value) -> Option<usize> {
+    match key {
+        Value::Number(n) if n.fract() == 0.0 && *n >= 1.0 && *n <= usize::MAX as f64 => {
+            Some(*n as usize - 1)
+        }
+        _ => None,
+    }
+}
+
+fn table_key(key: &Value) -> Option<TableKey> {
+    match key {
+        Value::Nil => None,
+        Value::Number(n) if n.is_nan() => None,
+        // -0.0 和 0.0 是同一个 key
+        Value::Number(n) => Some(TableKey::Number((n + 0.0).to_bits())),
+        Value::Boolean(b) => Some(TableKey::Boolean(*b)),
+        Value::String(s) => Some(TableKey::String(s.clone())),
+        Value::Table(t) => Some(TableKey::Table(Rc::as_ptr(t) as usize)),
+        Value::Function(f) => Some(TableKey::Function(f.addr())),
+    }
+}
+
+// 和 Lua 的 tonumber 一样，允许前后空白和十六进制
+pub fn parse_number(s: &[u8]) -> Option<f64> {
+    let s = std::str::from_utf8(s).ok()?.trim();
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        let n = u64::from_str_radix(hex, 16).ok()? as f64;
+        return Some(if negative { -n } else { n });
+    }
+    // Rust 能解析 "inf" 和 "nan"，Lua 不能
+    if s.is_empty()
+        || !s
+            .bytes()
+            .all(|c| c.is_ascii_digit() || matches!(c, b'.' | b'e' | b'E' | b'+' | b'-'))
+    {
+        return None;
+    }
+    s.parse().ok()
+}
+
+// 按 Lua 5.1 的 "%.14g" 格式化
+pub fn format_number(n: f64) -> String {
+    if n.is_nan() {
+        return if n.is_sign_negative() { "-nan" } else { "nan" }.to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+    if n == n.trunc() && n.abs() < 1e15 {
+        return format!("{}", n as i64);
+    }
+    let exp = n.abs().log10().floor() as i32;
+    if !(-4..14).contains(&exp) {
+        let s = format!("{:.13e}", n);
+        let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+        let mantissa = trim_fraction(mantissa);
+        let exp: i32 = exp.parse().unwrap_or(0);
+        let sign = if exp < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", mantissa, sign, exp.abs())
+    } else {
+        let decimals = (13 - exp).max(0) as usize;
+        trim_fraction(&format!("{:.*}", decimals, n)).to_string()
+    }
+}
+
+fn trim_fraction(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(-0.5), "-0.5");
+        assert_eq!(format_number(0.1), "0.1");
+        assert_eq!(format_number(1e15), "1e+15");
+        assert_eq!(format_number(1.0 / 3.0), "0.33333333333333");
+        assert_eq!(format_number(1.5e-7), "1.5e-07");
+    }
+
+    #[test]
+    fn test_table_array_and_hash_parts() {
+        let mut table = Table::default();
+        table.set(Value::Number(2.0), Value::str("b"));
+        assert_eq!(table.len(), 0);
+        table.set(Value::Number(1.0), Value::str("a"));
+        // 2 从哈希部分移到了数组部分
+        assert_eq!(table.len(), 2);
+        table.set_str("name", Value::Number(1.0));
+        assert!(table.get_str("name").raw_equals(&Value::Number(1.0)));
+        assert_eq!(table.pairs().len(), 3);
+
+        table.set(Value::Number(2.0), Value::Nil);
+        assert_eq!(table.len(), 1);
+        assert!(matches!(table.get(&Value::Number(2.0)), Value::Nil));
+        assert_eq!(parse_number(b" 0x10 "), Some(16.0));
+        assert_eq!(parse_number(b"inf"), None);
+        assert_eq!(parse_number(b"1e3"), Some(1000.0));
+    }
+}
@@ -0,0 +1,380 @@
+use std::{net::SocketAddr, time::Duration};
+
+use anyhow::Result;
+use simple_redis::{network, replica, Backend, BulkString, Client, ServerConfig, SimpleError};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_util::sync::CancellationToken;
+
+// 在随机端口上启动完整的 accept 循环，返回监听地址和用于关闭服务的 token
+async fn spawn_test_server() -> (SocketAddr, CancellationToken) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let token = CancellationToken::new();
+    tokio::spawn(network::serve(listener, Backend::new(), token.clone()));
+    (addr, token)
+}
+
+// 读满 len 个字节，避免一次 read 只拿到部分回复
+async fn read_exact_len(stream: &mut TcpStream, len: usize) -> Result<Vec<u8>> {
+    let mut buf = vec![0; len];
+    tokio::time::timeout(Duration::from_secs(5), stream.read_exact(&mut buf)).await??;
+    Ok(buf)
+}
+
+async fn roundtrip(stream: &mut TcpStream, req: &[u8], expected: &[u8]) -> Result<()> {
+    stream.write_all(req).await?;
+    let buf = read_exact_len(stream, expected.len()).await?;
+    assert_eq!(
+        String::from_utf8_lossy(&buf),
+        String::from_utf8_lossy(expected)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_set_and_get() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    roundtrip(
+        &mut stream,
+        b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n",
+        b"+OK\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n",
+        b"$5\r\nvalue\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$3\r\nGET\r\n$7\r\nmissing\r\n",
+        b"$-1\r\n",
+    )
+    .await?;
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unrecognized_command_returns_error() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$4\r\nfoob\r\n$3\r\nkey\r\n",
+        b"-ERR unknown command 'foob', with args beginning with: 'key' \r\n",
+    )
+    .await?;
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_arguments_keep_connection_open() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    roundtrip(
+        &mut stream,
+        b"*1\r\n$3\r\nGET\r\n",
+        b"-ERR wrong number of arguments for 'get' command\r\n",
+    )
+    .await?;
+    roundtrip(&mut stream, b"*1\r\n$4\r\nPING\r\n", b"+PONG\r\n").await?;
+    roundtrip(
+        &mut stream,
+        b"*4\r\n$6\r\nSETBIT\r\n$1\r\nk\r\n$2\r\n-1\r\n$1\r\n1\r\n",
+        b"-ERR bit offset is not an integer or out of range\r\n",
+    )
+    .await?;
+    roundtrip(&mut stream, b"*1\r\n$4\r\nPING\r\n", b"+PONG\r\n").await?;
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_debug_unknown_subcommand_returns_error() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$5\r\nDEBUG\r\n$6\r\nNOSUCH\r\n",
+        b"-ERR Unknown subcommand or wrong number of arguments for 'nosuch'. Try DEBUG HELP.\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*4\r\n$5\r\nDEBUG\r\n$6\r\nOBJECT\r\n$1\r\na\r\n$1\r\nb\r\n",
+        b"-ERR Unknown subcommand or wrong number of arguments for 'object'. Try DEBUG HELP.\r\n",
+    )
+    .await?;
+    // 只有 DEBUG SLEEP 可用的构建才会解析参数
+    #[cfg(any(debug_assertions, feature = "debug-commands"))]
+    roundtrip(
+        &mut stream,
+        b"*3\r\n$5\r\nDEBUG\r\n$5\r\nSLEEP\r\n$3\r\nabc\r\n",
+        b"-ERR value is not a valid float\r\n",
+    )
+    .await?;
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_split_across_writes() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    let req = b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n";
+    // 在 CRLF 中间和 bulk string 内容中间拆开发送
+    for chunk in [&req[..3], &req[3..17], &req[17..27], &req[27..]] {
+        stream.write_all(chunk).await?;
+        stream.flush().await?;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(read_exact_len(&mut stream, 5).await?, b"+OK\r\n");
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pipelined_requests() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    roundtrip(
+        &mut stream,
+        b"*3\r\n$3\r\nSET\r\n$1\r\na\r\n$1\r\n1\r\n*3\r\n$3\r\nSET\r\n$1\r\nb\r\n$1\r\n2\r\n*2\r\n$3\r\nGET\r\n$1\r\nb\r\n",
+        b"+OK\r\n+OK\r\n$1\r\n2\r\n",
+    )
+    .await?;
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_binary_safe_values() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    // 值中包含 CRLF，必须按长度而不是按分隔符读取
+    roundtrip(
+        &mut stream,
+        b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$4\r\na\r\nb\r\n",
+        b"+OK\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n",
+        b"$4\r\na\r\nb\r\n",
+    )
+    .await?;
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_large_value_across_reads() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    // 1MB 的值远大于读缓冲区的初始容量，需要多次读取才能拼出完整的请求
+    let value: Vec<u8> = (0..1024 * 1024).map(|i| b'a' + (i % 26) as u8).collect();
+    let mut req = format!("*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n${}\r\n", value.len()).into_bytes();
+    req.extend_from_slice(&value);
+    req.extend_from_slice(b"\r\n");
+    roundtrip(&mut stream, &req, b"+OK\r\n").await?;
+
+    stream
+        .write_all(b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n")
+        .await?;
+    let header = format!("${}\r\n", value.len());
+    let reply = read_exact_len(&mut stream, header.len() + value.len() + 2).await?;
+    assert_eq!(&reply[..header.len()], header.as_bytes());
+    assert!(reply[header.len()..header.len() + value.len()] == value[..]);
+    assert!(reply.ends_with(b"\r\n"));
+    token.cancel();
+    Ok(())
+}
+
+// DEBUG SLEEP 只在 debug 构建或开启 debug-commands feature 时可用
+#[cfg(any(debug_assertions, feature = "debug-commands"))]
+#[tokio::test]
+async fn test_debug_sleep_does_not_block_other_connections() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut slow = TcpStream::connect(addr).await?;
+    let mut fast = TcpStream::connect(addr).await?;
+    let start = std::time::Instant::now();
+    slow.write_all(b"*3\r\n$5\r\nDEBUG\r\n$5\r\nSLEEP\r\n$3\r\n0.2\r\n")
+        .await?;
+    // 测试运行在单线程 runtime 上，DEBUG SLEEP 阻塞线程的话另一个连接也会被卡住
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    roundtrip(
+        &mut fast,
+        b"*2\r\n$3\r\nGET\r\n$7\r\nmissing\r\n",
+        b"$-1\r\n",
+    )
+    .await?;
+    assert!(start.elapsed() < Duration::from_millis(150));
+
+    assert_eq!(read_exact_len(&mut slow, 5).await?, b"+OK\r\n");
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    token.cancel();
+    Ok(())
+}
+
+// DEBUG SLEEP 只在 debug 构建或开启 debug-commands feature 时可用
+#[cfg(any(debug_assertions, feature = "debug-commands"))]
+#[tokio::test]
+async fn test_slowlog_records_slow_command() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    // 默认阈值是 10ms，GET 不会被记录，DEBUG SLEEP 0.02 一定会被记录
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$3\r\nGET\r\n$7\r\nmissing\r\n",
+        b"$-1\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*3\r\n$5\r\nDEBUG\r\n$5\r\nSLEEP\r\n$4\r\n0.02\r\n",
+        b"+OK\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$7\r\nSLOWLOG\r\n$3\r\nLEN\r\n",
+        b":+1\r\n",
+    )
+    .await?;
+
+    // [id, timestamp, duration, argv, client_addr, client_name]，只检查开头和参数
+    stream
+        .write_all(b"*2\r\n$7\r\nSLOWLOG\r\n$3\r\nGET\r\n")
+        .await?;
+    let prefix = b"*1\r\n*6\r\n:+0\r\n";
+    assert_eq!(read_exact_len(&mut stream, prefix.len()).await?, prefix);
+    let mut reply = Vec::new();
+    while !reply.ends_with(b"$0\r\n\r\n") {
+        let mut buf = [0; 256];
+        let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await??;
+        assert!(n > 0, "connection closed");
+        reply.extend_from_slice(&buf[..n]);
+    }
+    let reply = String::from_utf8_lossy(&reply);
+    assert!(
+        reply.contains("*3\r\n$5\r\nDEBUG\r\n$5\r\nSLEEP\r\n$4\r\n0.02\r\n"),
+        "{}",
+        reply
+    );
+
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$7\r\nSLOWLOG\r\n$5\r\nRESET\r\n",
+        b"+OK\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$7\r\nSLOWLOG\r\n$3\r\nLEN\r\n",
+        b":+0\r\n",
+    )
+    .await?;
+    token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shutdown_stops_accepting() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    token.cancel();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(TcpStream::connect(addr).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replica_serves_reads_and_rejects_writes() -> Result<()> {
+    let (primary, primary_token) = spawn_test_server().await;
+    let mut client = Client::connect(primary).await?;
+    client.set("key", "value").await?;
+
+    // 副本启动时先从主节点拉取一次全量数据
+    let config = ServerConfig {
+        replica_of: Some(primary.to_string()),
+        ..Default::default()
+    };
+    let backend = Backend::with_config(config);
+    replica::sync_from(&backend, primary).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let token = CancellationToken::new();
+    tokio::spawn(network::serve(listener, backend, token.clone()));
+
+    let mut replica = Client::connect(addr).await?;
+    assert_eq!(replica.get("key").await?, BulkString::new("value").into());
+    assert_eq!(
+        replica.set("key", "other").await?,
+        SimpleError::new("READONLY You can't write against a read only replica.").into()
+    );
+    assert_eq!(replica.get("key").await?, BulkString::new("value").into());
+    token.cancel();
+    primary_token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replica_authenticates_with_masterauth() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let primary = listener.local_addr()?;
+    let primary_backend = Backend::with_config(ServerConfig {
+        requirepass: Some("secret".to_string()),
+        ..Default::default()
+    });
+    primary_backend.set("key".to_string(), BulkString::new("value").into());
+    let primary_token = CancellationToken::new();
+    tokio::spawn(network::serve(
+        listener,
+        primary_backend,
+        primary_token.clone(),
+    ));
+
+    let backend = Backend::new();
+    let err = replica::sync_from(&backend, primary).await.unwrap_err();
+    assert!(err.to_string().starts_with("NOAUTH"), "{}", err);
+    backend.set_config("masterauth", "secret")?;
+    replica::sync_from(&backend, primary).await?;
+    assert_eq!(backend.dbsize(), 1);
+    primary_token.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscribed_connection_rejects_get() -> Result<()> {
+    let (addr, token) = spawn_test_server().await;
+    let mut stream = TcpStream::connect(addr).await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$10\r\npsubscribe\r\n$6\r\nnews.*\r\n",
+        b"*3\r\n$10\r\npsubscribe\r\n$6\r\nnews.*\r\n:+1\r\n",
+    )
+    .await?;
+    roundtrip(
+        &mut stream,
+        b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n",
+        b"-ERR Can't execute 'get': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context\r\n",
+    )
+    .await?;
+    // 取消所有模式订阅之后恢复正常
+    roundtrip(
+        &mut stream,
+        b"*1\r\n$12\r\npunsubscribe\r\n",
+        b"*3\r\n$12\r\npunsubscribe\r\n$6\r\nnews.*\r\n:+0\r\n",
+    )
+    .await?;
+    roundtrip(&mut stream, b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n", b"$-1\r\n").await?;
+    token.cancel();
+    Ok(())
+}
@@ -0,0 +1,158 @@
+// 随机生成 RespFrame，验证 decode(encode(frame)) == frame。
+// 生成器基于 crate 自带的 Rng，失败时输出 seed 以便复现。
+use bytes::BytesMut;
+use simple_redis::{
+    BulkString, RespArray, RespDecoder, RespEncoder, RespFrame, RespMap, RespNull, RespNullArray,
+    RespNullBulkString, RespPush, RespSet, Rng, SimpleError, SimpleString,
+};
+
+const CASES: u64 = 2000;
+// 嵌套类型的最大深度和每层的最大元素数
+const MAX_DEPTH: usize = 3;
+const MAX_WIDTH: usize = 5;
+
+// 特殊的浮点数，每种都要覆盖到
+const SPECIAL_DOUBLES: &[f64] = &[
+    0.0,
+    -0.0,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    f64::NAN,
+    f64::MIN,
+    f64::MAX,
+    f64::MIN_POSITIVE,
+    f64::EPSILON,
+    1e8,
+    1e-8,
+];
+
+// simple string / error 不能包含 CR 和 LF
+fn arbitrary_line(rng: &mut Rng) -> String {
+    const CHARSET: &[u8] =
+        b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 +-:$*_#,!%~>";
+    let len = rng.below(16);
+    (0..len)
+        .map(|_| CHARSET[rng.below(CHARSET.len())] as char)
+        .collect()
+}
+
+// bulk string 是二进制安全的，包括 CRLF 和非 UTF-8 字节
+fn arbitrary_bytes(rng: &mut Rng) -> Vec<u8> {
+    let len = match rng.below(10) {
+        0 => 0,
+        1 => rng.below(4096),
+        _ => rng.below(32),
+    };
+    (0..len).map(|_| rng.next_u64() as u8).collect()
+}
+
+fn arbitrary_integer(rng: &mut Rng) -> i64 {
+    match rng.below(4) {
+        0 => [0, -1, 1, i64::MIN, i64::MAX][rng.below(5)],
+        1 => rng.below(1000) as i64 - 500,
+        _ => rng.next_u64() as i64,
+    }
+}
+
+fn arbitrary_double(rng: &mut Rng) -> f64 {
+    match rng.below(3) {
+        0 => SPECIAL_DOUBLES[rng.below(SPECIAL_DOUBLES.len())],
+        1 => (rng.below(2_000_000) as f64 - 1_000_000.0) / 1000.0,
+        _ => loop {
+            // 任意的位模式，NaN 已经在特殊值中覆盖
+            let v = f64::from_bits(rng.next_u64());
+            if !v.is_nan() {
+                break v;
+            }
+        },
+    }
+}
+
+fn arbitrary_children(rng: &mut Rng, depth: usize) -> Vec<RespFrame> {
+    (0..rng.below(MAX_WIDTH + 1))
+        .map(|_| arbitrary_frame(rng, depth - 1))
+        .collect()
+}
+
+// depth 为 0 时只生成标量类型
+fn arbitrary_frame(rng: &mut Rng, depth: usize) -> RespFrame {
+    let kinds = if depth == 0 { 9 } else { 13 };
+    match rng.below(kinds) {
+        0 => SimpleString::new(arbitrary_line(rng)).into(),
+        1 => SimpleError::new(arbitrary_line(rng)).into(),
+        2 => RespFrame::Integer(arbitrary_integer(rng)),
+        3 => BulkString::new(arbitrary_bytes(rng)).into(),
+        4 => RespNull.into(),
+        5 => RespNullArray.into(),
+        6 => RespNullBulkString.into(),
+        7 => RespFrame::Boolean(rng.below(2) == 1),
+        8 => RespFrame::Double(arbitrary_double(rng)),
+        9 => RespArray::new(arbitrary_children(rng, depth)).into(),
+        10 => RespSet::new(arbitrary_children(rng, depth)).into(),
+        11 => RespPush::new(arbitrary_children(rng, depth)).into(),
+        _ => {
+            let mut map = RespMap::new();
+            for _ in 0..rng.below(MAX_WIDTH + 1) {
+                map.insert(arbitrary_line(rng), arbitrary_frame(rng, depth - 1));
+            }
+            map.into()
+        }
+    }
+}
+
+// NaN 不等于自身，逐层比较时把两个 NaN 视为相等
+fn frame_eq(a: &RespFrame, b: &RespFrame) -> bool {
+    let all_eq = |a: &[RespFrame], b: &[RespFrame]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| frame_eq(a, b))
+    };
+    match (a, b) {
+        (RespFrame::Double(a), RespFrame::Double(b)) => {
+            (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+        }
+        (RespFrame::Array(a), RespFrame::Array(b)) => all_eq(a, b),
+        (RespFrame::Set(a), RespFrame::Set(b)) => all_eq(a, b),
+        (RespFrame::Push(a), RespFrame::Push(b)) => all_eq(a, b),
+        (RespFrame::Map(a), RespFrame::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|((ka, va), (kb, vb))| ka == kb && frame_eq(va, vb))
+        }
+        (a, b) => a == b,
+    }
+}
+
+fn roundtrip(frame: &RespFrame) -> Result<RespFrame, String> {
+    let encoded = frame.clone().encode();
+    let mut buf = BytesMut::from(&encoded[..]);
+    let decoded = RespFrame::decode(&mut buf).map_err(|e| format!("decode error: {}", e))?;
+    if !buf.is_empty() {
+        return Err(format!("{} trailing bytes left", buf.len()));
+    }
+    Ok(decoded)
+}
+
+#[test]
+fn test_resp_frame_roundtrip() {
+    for seed in 1..=CASES {
+        let mut rng = Rng::new(seed);
+        let frame = arbitrary_frame(&mut rng, MAX_DEPTH);
+        match roundtrip(&frame) {
+            Ok(decoded) if frame_eq(&frame, &decoded) => {}
+            Ok(decoded) => panic!(
+                "seed {}: roundtrip mismatch\n  original: {:?}\n  decoded:  {:?}",
+                seed, frame, decoded
+            ),
+            Err(e) => panic!("seed {}: {} for {:?}", seed, e, frame),
+        }
+    }
+}
+
+#[test]
+fn test_special_doubles_roundtrip() {
+    for &v in SPECIAL_DOUBLES {
+        let frame = RespFrame::Double(v);
+        let decoded = roundtrip(&frame).unwrap_or_else(|e| panic!("{}: {}", v, e));
+        assert!(frame_eq(&frame, &decoded), "{} decoded as {:?}", v, decoded);
+    }
+}